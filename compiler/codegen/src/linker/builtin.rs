@@ -6,6 +6,8 @@ use std::os::raw::{c_char, c_int};
 use firefly_util::fs;
 
 extern "C" {
+    fn LLVMFireflyHasLinker() -> bool;
+
     #[cfg(windows)]
     pub fn LLVMFireflyLink(
         argc: c_int,
@@ -23,6 +25,11 @@ extern "C" {
     ) -> bool;
 }
 
+/// Returns true if this compiler was built with the embedded `lld` linker
+pub fn is_available() -> bool {
+    unsafe { LLVMFireflyHasLinker() }
+}
+
 /// Invoke the statically linked `lld` linker with the given arguments.
 ///
 /// NOTE: Assumes that the first value of the argument vector contains the
@@ -2,7 +2,7 @@ use std::ascii;
 use std::cell::OnceCell;
 use std::char;
 use std::env;
-use std::ffi::OsString;
+use std::ffi::{CString, OsString};
 use std::fmt;
 use std::fs;
 use std::io;
@@ -24,10 +24,12 @@ use firefly_session::{
 use firefly_target::crt_objects::CrtObjectsFallback;
 use firefly_target::{
     LinkOutputKind, LinkerFlavor, LldFlavor, PanicStrategy, RelocModel, RelroLevel, SplitDebugInfo,
+    Target,
 };
 use firefly_util::diagnostics::DiagnosticsHandler;
 use firefly_util::fs::{fix_windows_verbatim_for_gcc, NativeLibraryKind};
 
+use crate::linker::builtin;
 use crate::linker::command::Command;
 use crate::linker::rpath::{self, RPathConfig};
 use crate::linker::Linker;
@@ -55,6 +57,16 @@ pub fn link_binary(
         return Ok(());
     }
 
+    if needs_profiler_runtime(options, project_type) {
+        let path = profiler_runtime(options);
+        if !path.exists() {
+            return Err(anyhow!(
+                "`-C profile-generate` requires the profiler runtime, but it was not found at {}",
+                path.display()
+            ));
+        }
+    }
+
     for obj in codegen_results.modules.iter().filter_map(|m| m.object()) {
         check_file_is_writeable(obj)?;
     }
@@ -223,8 +235,13 @@ fn link_natively(
 ) -> anyhow::Result<()> {
     info!("preparing {:?} to {:?}", project_type, output_file);
     let (linker_path, flavor) = linker_and_flavor(options);
+    let builtin_flavor = builtin_linker_flavor(options, diagnostics, &linker_path, flavor);
+    let (linker_path, flavor) = match builtin_flavor {
+        Some(flavor) => (PathBuf::from("firefly-lld"), flavor),
+        None => (linker_path, flavor),
+    };
 
-    let (is_ld, mut cmd) = linker_with_args(
+    let mut cmd = linker_with_args(
         &linker_path,
         flavor,
         options,
@@ -249,8 +266,14 @@ fn link_natively(
     // May have not found libraries in the right formats.
     diagnostics.abort_if_errors();
 
-    if is_ld {
-        todo!("invoke firefly-lld");
+    if builtin_flavor.is_some() {
+        if let Err(e) = exec_builtin_linker(&cmd) {
+            let mut err = diagnostics.diagnostic(Severity::Error);
+            err.with_message(format!("linking with embedded lld failed: {}", e));
+            err.with_note(format!("{:?}", &cmd));
+            err.emit();
+        }
+        diagnostics.abort_if_errors();
     } else {
         match exec_linker(options, &mut cmd, output_file, tmpdir) {
             Ok(prog) => {
@@ -454,33 +477,49 @@ fn add_sanitizer_libraries(linker: &mut dyn Linker, options: &Options, project_t
     //}
 }
 
-fn link_sanitizer_runtime(linker: &mut dyn Linker, options: &Options, name: &str) {
-    fn find_sanitizer_runtime(options: &Options, filename: &str) -> PathBuf {
-        let tlib = filesearch::make_target_lib_path(&options.sysroot, options.target.triple());
-        let path = tlib.join(filename);
-        if path.exists() {
-            return tlib;
-        } else {
-            let default_sysroot = filesearch::get_or_default_sysroot();
-            let default_tlib =
-                filesearch::make_target_lib_path(&default_sysroot, options.target.triple());
-            return default_tlib;
-        }
+fn add_profiler_libraries(linker: &mut dyn Linker, options: &Options, project_type: ProjectType) {
+    if needs_profiler_runtime(options, project_type) {
+        linker.link_whole_rlib(&profiler_runtime(options));
     }
+}
+
+fn needs_profiler_runtime(options: &Options, project_type: ProjectType) -> bool {
+    options.codegen_opts.profile_generate.is_some() && project_type != ProjectType::Staticlib
+}
+
+fn profiler_runtime(options: &Options) -> PathBuf {
+    // Unlike the sanitizers, the profiler runtime is always distributed as a static library
+    let filename = "libfirefly_rt.profile.a";
+    find_runtime_library(options, filename).join(filename)
+}
 
+fn find_runtime_library(options: &Options, filename: &str) -> PathBuf {
+    let tlib = filesearch::make_target_lib_path(&options.sysroot, options.target.triple());
+    let path = tlib.join(filename);
+    if path.exists() {
+        return tlib;
+    } else {
+        let default_sysroot = filesearch::get_or_default_sysroot();
+        let default_tlib =
+            filesearch::make_target_lib_path(&default_sysroot, options.target.triple());
+        return default_tlib;
+    }
+}
+
+fn link_sanitizer_runtime(linker: &mut dyn Linker, options: &Options, name: &str) {
     if options.target.options.is_like_osx {
         // On Apple platforms, the sanitizer is always built as a dylib, and
         // LLVM will link to `@rpath/*.dylib`, so we need to specify an
         // rpath to the library as well (the rpath should be absolute, see
         // PR #41352 for details).
         let filename = format!("firefly_rt.{}", name);
-        let path = find_sanitizer_runtime(options, &filename);
+        let path = find_runtime_library(options, &filename);
         let rpath = path.to_str().expect("non-utf8 component in path");
         linker.args(&["-Wl,-rpath", "-Xlinker", rpath]);
         linker.link_dylib(&filename, false, true);
     } else {
         let filename = format!("libfirefly_rt.{}.a", name);
-        let path = find_sanitizer_runtime(options, &filename).join(&filename);
+        let path = find_runtime_library(options, &filename).join(&filename);
         linker.link_whole_rlib(&path);
     }
 }
@@ -641,6 +680,83 @@ fn get_object_file_path(options: &Options, name: &str, self_contained: bool) ->
     PathBuf::from(name)
 }
 
+/// Returns the flavor of the embedded lld to link with, or `None` if an external linker is used
+///
+/// The embedded linker is used when `-C link-self-contained` is set, or when the selected linker
+/// is `firefly-lld` (the default for wasm targets), if the target is supported by it. Otherwise,
+/// a warning is emitted and the external linker is used instead.
+fn builtin_linker_flavor(
+    options: &Options,
+    diagnostics: &DiagnosticsHandler,
+    linker: &Path,
+    flavor: LinkerFlavor,
+) -> Option<LinkerFlavor> {
+    let is_firefly_lld = linker.file_stem().and_then(|stem| stem.to_str()) == Some("firefly-lld");
+    if !is_firefly_lld && options.codegen_opts.link_self_contained != Some(true) {
+        return None;
+    }
+
+    if !builtin::is_available() {
+        diagnostics.warn(
+            "this compiler was built without the embedded linker, falling back to the system linker",
+        );
+        return None;
+    }
+
+    match embedded_linker_flavor(&options.target, flavor) {
+        Ok(flavor) => Some(flavor),
+        Err(reason) => {
+            diagnostics.warn(format!("{}, falling back to the system linker", reason));
+            None
+        }
+    }
+}
+
+/// Returns the flavor of the embedded lld with which to link for `target`
+///
+/// The embedded linker has no knowledge of the system toolchain, so it may only be used for
+/// targets whose CRT objects are bundled with the compiler, i.e. musl and wasm. Only the ELF and
+/// wasm drivers of lld are linked into the compiler, so Apple and Windows targets always use the
+/// system linker. If the target is not supported, the reason why is returned instead.
+fn embedded_linker_flavor(
+    target: &Target,
+    flavor: LinkerFlavor,
+) -> Result<LinkerFlavor, &'static str> {
+    let lld_flavor = match flavor {
+        LinkerFlavor::Lld(lld_flavor) => lld_flavor,
+        _ => target.options.lld_flavor,
+    };
+    if lld_flavor == LldFlavor::Link || target.options.is_like_windows {
+        return Err("the embedded linker does not support this target");
+    }
+    if lld_flavor == LldFlavor::Ld64 || target.options.is_like_osx {
+        return Err("the embedded linker does not support Apple targets");
+    }
+    match target.options.crt_objects_fallback {
+        Some(CrtObjectsFallback::Musl | CrtObjectsFallback::Wasm) => {
+            Ok(LinkerFlavor::Lld(lld_flavor))
+        }
+        _ => Err("the embedded linker can only be used for musl and wasm targets"),
+    }
+}
+
+/// Invokes the embedded lld in-process with the arguments from `cmd`
+fn exec_builtin_linker(cmd: &Command) -> anyhow::Result<()> {
+    // The leading `-flavor` argument tells lld which driver to run
+    let argv = cmd
+        .as_vec()
+        .into_iter()
+        .map(|arg| {
+            let arg = arg
+                .into_string()
+                .map_err(|arg| anyhow!("invalid linker argument {:?}", arg))?;
+            CString::new(arg).map_err(|e| anyhow!("invalid linker argument: {}", e))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    builtin::link(argv.as_slice()).map_err(|_| anyhow!("see linker output for details"))
+}
+
 fn exec_linker(
     options: &Options,
     cmd: &mut Command,
@@ -1084,7 +1200,7 @@ fn linker_with_args(
     tmpdir: &Path,
     out_filename: &Path,
     codegen_results: &CodegenResults,
-) -> (bool, Command) {
+) -> Command {
     let crt_objects_fallback = crt_objects_fallback(options, project_type);
     let cmd = &mut *super::get_linker(
         options,
@@ -1123,6 +1239,9 @@ fn linker_with_args(
     // Sanitizer libraries.
     add_sanitizer_libraries(cmd, options, project_type);
 
+    // Profiler runtime, when instrumenting for profile-guided optimization.
+    add_profiler_libraries(cmd, options, project_type);
+
     // Object code from the current project.
     // Take careful note of the ordering of the arguments we pass to the linker
     // here. Linkers will assume that things on the left depend on things to the
@@ -1234,9 +1353,7 @@ fn linker_with_args(
     // to it and remove the option.
     add_post_link_args(cmd, options, flavor);
 
-    let is_ld = cmd.is_ld();
-    let command = cmd.take_cmd();
-    (is_ld, command)
+    cmd.take_cmd()
 }
 
 fn add_order_independent_options(
@@ -1332,6 +1449,11 @@ fn add_order_independent_options(
     // Pass optimization flags down to the linker.
     cmd.optimize();
 
+    // Make sure the profiler runtime is initialized when instrumenting.
+    if options.codegen_opts.profile_generate.is_some() {
+        cmd.pgo_gen();
+    }
+
     // Pass debuginfo and strip flags down to the linker.
     cmd.debuginfo(options.codegen_opts.strip);
 
@@ -1723,3 +1845,55 @@ fn is_writeable(p: &Path) -> bool {
         Ok(m) => !m.permissions().readonly(),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use firefly_target::{LinkerFlavor, LldFlavor, Target};
+
+    use super::embedded_linker_flavor;
+
+    fn flavor_for(triple: &str) -> Result<LinkerFlavor, &'static str> {
+        let target = Target::search(triple).unwrap();
+        let flavor = target.options.linker_flavor;
+        embedded_linker_flavor(&target, flavor)
+    }
+
+    #[test]
+    fn embedded_linker_is_used_for_wasm_and_musl() {
+        assert_eq!(
+            flavor_for("wasm32-unknown-unknown"),
+            Ok(LinkerFlavor::Lld(LldFlavor::Wasm))
+        );
+        assert_eq!(
+            flavor_for("x86_64-unknown-linux-musl"),
+            Ok(LinkerFlavor::Lld(LldFlavor::Ld))
+        );
+    }
+
+    #[test]
+    fn embedded_linker_is_not_used_for_system_toolchains() {
+        assert!(flavor_for("x86_64-unknown-linux-gnu").is_err());
+        assert!(flavor_for("x86_64-apple-darwin").is_err());
+    }
+
+    #[test]
+    fn embedded_linker_does_not_support_apple_targets() {
+        let reason = Err("the embedded linker does not support Apple targets");
+        assert_eq!(flavor_for("x86_64-apple-darwin"), reason);
+        assert_eq!(flavor_for("aarch64-apple-darwin"), reason);
+        let target = Target::search("x86_64-apple-darwin").unwrap();
+        assert_eq!(
+            embedded_linker_flavor(&target, LinkerFlavor::Lld(LldFlavor::Ld64)),
+            reason
+        );
+    }
+
+    #[test]
+    fn explicit_lld_flavor_is_preserved() {
+        let target = Target::search("x86_64-unknown-linux-musl").unwrap();
+        assert_eq!(
+            embedded_linker_flavor(&target, LinkerFlavor::Lld(LldFlavor::Ld)),
+            Ok(LinkerFlavor::Lld(LldFlavor::Ld))
+        );
+    }
+}
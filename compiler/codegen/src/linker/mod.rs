@@ -1,4 +1,5 @@
 pub(crate) mod archive;
+mod builtin;
 mod command;
 pub(crate) mod link;
 mod rpath;
@@ -166,9 +167,6 @@ pub fn get_linker<'a>(
 /// MSVC linker (e.g., `link.exe`) is being used.
 pub trait Linker {
     fn cmd(&mut self) -> &mut Command;
-    fn is_ld(&self) -> bool {
-        false
-    }
     fn set_output_kind(&mut self, output_kind: LinkOutputKind, out_filename: &Path);
     fn link_dylib(&mut self, lib: &str, verbatim: bool, as_needed: bool);
     fn link_rust_dylib(&mut self, lib: &str, path: &Path);
@@ -374,10 +372,6 @@ impl<'a> Linker for GccLinker<'a> {
         &mut self.cmd
     }
 
-    fn is_ld(&self) -> bool {
-        self.is_ld
-    }
-
     fn set_output_kind(&mut self, output_kind: LinkOutputKind, output_file: &Path) {
         match output_kind {
             LinkOutputKind::DynamicNoPicExe => {
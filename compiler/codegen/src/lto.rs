@@ -0,0 +1,84 @@
+///! Link-time optimization across all modules of an application
+///!
+///! When LTO is enabled, each module is compiled to bitcode rather than an object file, and the
+///! bitcode for the whole application (optionally along with the runtime) is optimized here as a
+///! unit, permitting cross-module inlining of the small helper calls that are otherwise opaque.
+use std::fs;
+
+use anyhow::anyhow;
+use log::debug;
+
+use firefly_intern::Symbol;
+use firefly_llvm::lto::LinkTimeOptimizer;
+use firefly_llvm::target::TargetMachine;
+use firefly_session::{Lto, Options};
+use firefly_util::diagnostics::DiagnosticsHandler;
+
+use crate::meta::{self, CodegenResults, CompiledModule};
+
+/// Performs link-time optimization of the modules in `codegen_results`
+///
+/// Each module must have been compiled to bitcode. On success, the modules are replaced with the
+/// object file(s) produced by the optimizer, ready to be handed to the linker.
+pub fn optimize(
+    options: &Options,
+    diagnostics: &DiagnosticsHandler,
+    codegen_results: &mut CodegenResults,
+) -> anyhow::Result<()> {
+    assert_ne!(options.lto(), Lto::No);
+
+    let target_machine = TargetMachine::create(options)?;
+    let mut optimizer = LinkTimeOptimizer::new(options, target_machine.handle());
+    // ThinLTO backends share the thread count used to compile modules, 0 uses one per core
+    optimizer.threads(options.debugging_opts.threads.try_into().unwrap_or(0));
+
+    for module in codegen_results.modules.iter() {
+        let bytecode = module
+            .bytecode()
+            .ok_or_else(|| anyhow!("no bitcode was generated for module '{}'", module.name))?;
+        optimizer.add_module(bytecode);
+    }
+
+    if options.codegen_opts.lto_runtime && options.target.options.is_like_wasm {
+        diagnostics.warn("-C lto-runtime is not supported on this target, ignoring");
+    } else if options.codegen_opts.lto_runtime {
+        // The runtime archive is consumed entirely here, and is not passed to the linker: its
+        // bitcode is optimized along with the application, and its native objects are returned
+        // with the optimized objects, so all of it is linked in, as with --whole-archive
+        let runtime = meta::runtime_archive(options);
+        debug!("including runtime bitcode from {}", runtime.display());
+        optimizer.add_external(&runtime);
+    }
+
+    for symbol in codegen_results.project_info.exported_symbols.iter() {
+        optimizer.preserve_symbol(symbol.as_str());
+    }
+
+    let output_dir = options.output_dir();
+    fs::create_dir_all(&output_dir)?;
+    let app_name = codegen_results.app_name;
+    let objects = optimizer.run(&output_dir.join(&format!("{}.lto", app_name)))?;
+
+    diagnostics.success(
+        "Optimized",
+        format!(
+            "{} modules into {} object(s) with {:?} LTO",
+            codegen_results.modules.len(),
+            objects.len(),
+            options.lto()
+        ),
+    );
+
+    codegen_results.modules = objects
+        .into_iter()
+        .enumerate()
+        .map(|(i, object)| CompiledModule {
+            name: Symbol::intern(&format!("{}.lto.{}", app_name, i)),
+            object: Some(object),
+            dwarf_object: None,
+            bytecode: None,
+        })
+        .collect();
+
+    Ok(())
+}
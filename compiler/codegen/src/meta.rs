@@ -3,7 +3,7 @@ use std::path::{Path, PathBuf};
 
 use firefly_intern::Symbol;
 use firefly_llvm as llvm;
-use firefly_session::Options;
+use firefly_session::{Lto, Options};
 use firefly_target::spec::PanicStrategy;
 use firefly_util::fs::NativeLibraryKind;
 
@@ -57,11 +57,7 @@ impl ProjectInfo {
         info.exported_symbols = linker::exported_symbols(options);
 
         // We always add dependencies on our core runtime crates
-        let fireflylib_dir = options
-            .target_tlib_path
-            .as_ref()
-            .map(|t| t.dir.clone())
-            .unwrap_or_else(|| options.host_tlib_path.dir.clone());
+        let fireflylib_dir = fireflylib_dir(options);
         let prefix = &options.target.options.staticlib_prefix;
         info.used_deps
             .push(match options.target.options.panic_strategy {
@@ -92,14 +88,19 @@ impl ProjectInfo {
                 name: Symbol::intern("unwind"),
                 source: Some(fireflylib_dir.join(&format!("{}unwind.rlib", prefix))),
             });
-            info.used_libraries.push(NativeLibrary {
-                kind: NativeLibraryKind::Static {
-                    bundle: None,
-                    whole_archive: Some(true),
-                },
-                name: Some("firefly_rt_tiny".to_string()),
-                verbatim: None,
-            });
+            // When the runtime participates in link-time optimization, the whole archive is
+            // consumed by the optimizer, and is linked in via the objects it produces instead
+            let runtime_lto = options.codegen_opts.lto_runtime && options.lto() != Lto::No;
+            if !runtime_lto {
+                info.used_libraries.push(NativeLibrary {
+                    kind: NativeLibraryKind::Static {
+                        bundle: None,
+                        whole_archive: Some(true),
+                    },
+                    name: Some("firefly_rt_tiny".to_string()),
+                    verbatim: None,
+                });
+            }
             /*
             info.used_deps.push(Dependency {
                 name: Symbol::intern("firefly_otp"),
@@ -212,6 +213,23 @@ impl Default for ProjectInfo {
     }
 }
 
+/// Returns the directory containing the Firefly runtime libraries for the current target
+pub(crate) fn fireflylib_dir(options: &Options) -> PathBuf {
+    options
+        .target_tlib_path
+        .as_ref()
+        .map(|t| t.dir.clone())
+        .unwrap_or_else(|| options.host_tlib_path.dir.clone())
+}
+
+/// Returns the path of the runtime archive for the current target
+pub(crate) fn runtime_archive(options: &Options) -> PathBuf {
+    fireflylib_dir(options).join(&format!(
+        "{}firefly_rt_tiny{}",
+        &options.target.options.staticlib_prefix, &options.target.options.staticlib_suffix
+    ))
+}
+
 #[derive(Clone, Debug, Hash)]
 pub struct NativeLibrary {
     pub kind: NativeLibraryKind,
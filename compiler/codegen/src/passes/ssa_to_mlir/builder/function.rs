@@ -1213,8 +1213,9 @@ impl<'m> ModuleBuilder<'m> {
             }
             Opcode::RecvStart => builder.build_recv_start(loc, self.values[&args[0]]).base(),
             Opcode::RecvNext => builder.build_recv_next(loc, self.values[&args[0]]).base(),
-            Opcode::RecvPeek => builder.build_recv_next(loc, self.values[&args[0]]).base(),
+            Opcode::RecvPeek => builder.build_recv_peek(loc, self.values[&args[0]]).base(),
             Opcode::RecvPop => builder.build_recv_pop(loc, self.values[&args[0]]).base(),
+            Opcode::RecvDone => builder.build_recv_done(loc, self.values[&args[0]]).base(),
             Opcode::RecvWait => builder.build_yield(loc).base(),
             Opcode::Raise => {
                 let class = self.values[&args[0]];
@@ -210,8 +210,13 @@ impl<'m> ModuleBuilder<'m> {
                     self.builder
                         .get_flat_symbol_ref_attr_by_name("firefly_eh_personality"),
                 );
-                //TODO: Need to re-enable when garbage collector lowering is implemented
-                //func.set_attribute_by_name("garbageCollector", self.builder.get_string_attr("erlang"));
+                // Terms are lowered to pointers in addrspace(1), so using a statepoint-based
+                // strategy gets us stack maps describing the live terms at each call, which
+                // is what the runtime uses to find roots when collecting a process heap.
+                func.set_attribute_by_name(
+                    "garbageCollector",
+                    self.builder.get_string_attr("statepoint-example"),
+                );
             }
 
             // Register with the dispatch table for this module if public
@@ -12,6 +12,9 @@ pub struct CodeMap {
     files: DashMap<SourceId, Arc<SourceFile>>,
     names: DashMap<FileName, SourceId>,
     seen: DashMap<PathBuf, SourceId>,
+    // Maps a file to the `-file` directives it contains, as the index at which each directive
+    // ends, and the file which represents the remapped source locations following it
+    line_directives: DashMap<SourceId, Vec<(ByteIndex, SourceId)>>,
     next_file_id: AtomicU32,
 }
 impl CodeMap {
@@ -21,6 +24,7 @@ impl CodeMap {
             files: DashMap::new(),
             names: DashMap::new(),
             seen: DashMap::new(),
+            line_directives: DashMap::new(),
             next_file_id: AtomicU32::new(1),
         }
     }
@@ -71,6 +75,65 @@ impl CodeMap {
         file_id
     }
 
+    /// Records a `-file(Name, Line)` directive found at `directive`.
+    ///
+    /// Source locations in the same file following the directive are reported as being in
+    /// `name`, with the line following the directive numbered `line`. The returned id refers to
+    /// a file representing those remapped locations; it shares the source of the original file,
+    /// so any span in the original file is also valid in it.
+    pub fn add_line_directive(
+        &self,
+        directive: SourceSpan,
+        name: impl Into<FileName>,
+        line: u32,
+    ) -> Result<SourceId, Error> {
+        let parent = self.get(directive.source_id)?;
+        let mut directives = self.line_directives.entry(directive.source_id).or_default();
+        // The same file may be parsed more than once, in which case we reuse the mapping
+        if let Some((_, file_id)) = directives.iter().find(|(end, _)| *end == directive.end) {
+            return Ok(*file_id);
+        }
+
+        let line_index = parent.line_index(directive.end) + LineOffset::from(1);
+        let file_id = self.next_file_id();
+        let file = SourceFile::new(file_id, name.into(), parent.source().to_string(), None)
+            .with_line_mapping(line_index, line);
+        self.files.insert(file_id, Arc::new(file));
+
+        directives.push((directive.end, file_id));
+        directives.sort_by_key(|(end, _)| *end);
+        Ok(file_id)
+    }
+
+    /// Returns the given span adjusted for any `-file` directive which precedes it
+    pub fn remap_span(&self, span: SourceSpan) -> SourceSpan {
+        let directives = match self.line_directives.get(&span.source_id) {
+            None => return span,
+            Some(directives) => directives,
+        };
+        match directives.iter().rev().find(|(end, _)| *end <= span.start) {
+            None => span,
+            Some((_, file_id)) => SourceSpan {
+                source_id: *file_id,
+                ..span
+            },
+        }
+    }
+
+    /// Returns the given diagnostic with its labels adjusted for any `-file` directives
+    pub fn remap_diagnostic(&self, diagnostic: &Diagnostic) -> Diagnostic {
+        let mut diagnostic = diagnostic.clone();
+        for label in diagnostic.labels.iter_mut() {
+            let span = SourceSpan {
+                source_id: label.file_id,
+                start: ByteIndex(label.range.start as RawIndex),
+                end: ByteIndex(label.range.end as RawIndex),
+            };
+            label.file_id = self.remap_span(span).source_id;
+        }
+        diagnostic
+    }
+
     /// Get the file corresponding to the given id.
     pub fn get(&self, file_id: SourceId) -> Result<Arc<SourceFile>, Error> {
         if file_id == SourceId::UNKNOWN {
@@ -85,7 +148,7 @@ impl CodeMap {
 
     /// Get the file corresponding to the given SourceSpan
     pub fn get_with_span(&self, span: SourceSpan) -> Result<Arc<SourceFile>, Error> {
-        self.get(span.source_id)
+        self.get(self.remap_span(span).source_id)
     }
 
     pub fn parent(&self, file_id: SourceId) -> Option<SourceSpan> {
@@ -110,12 +173,12 @@ impl CodeMap {
 
     /// Get the filename associated with the given SourceSpan
     pub fn name_for_span(&self, span: SourceSpan) -> Result<FileName, Error> {
-        self.name(span.source_id)
+        self.name(self.remap_span(span).source_id)
     }
 
     /// Get the filename associated with the given Spanned item
     pub fn name_for_spanned(&self, spanned: &dyn Spanned) -> Result<FileName, Error> {
-        self.name(spanned.span().source_id)
+        self.name_for_span(spanned.span())
     }
 
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = Arc<SourceFile>> + 'a {
@@ -149,13 +212,13 @@ impl CodeMap {
 
     /// Get the Location associated with the given SourceSpan
     pub fn location_for_span(&self, span: SourceSpan) -> Result<Location, Error> {
+        let span = self.remap_span(span);
         self.location(span.source_id, span)
     }
 
     /// Get the Location associated with the given Spanned item
     pub fn location_for_spanned(&self, spanned: &dyn Spanned) -> Result<Location, Error> {
-        let span = spanned.span();
-        self.location(span.source_id, span)
+        self.location_for_span(spanned.span())
     }
 
     pub fn source_span(&self, file_id: SourceId) -> Result<SourceSpan, Error> {
@@ -209,6 +272,11 @@ impl<'a> Files<'a> for CodeMap {
         Ok(self.line_index(file_id, byte_index as u32)?.to_usize())
     }
 
+    fn line_number(&self, file_id: Self::FileId, line_index: usize) -> Result<usize, Error> {
+        let f = self.get(file_id)?;
+        Ok(f.line_number(LineIndex::from(line_index as RawIndex)))
+    }
+
     fn line_range(&self, file_id: Self::FileId, line_index: usize) -> Result<Range<usize>, Error> {
         let span = self.line_span(file_id, line_index as u32)?;
 
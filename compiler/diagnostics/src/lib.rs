@@ -194,6 +194,7 @@ impl ReporterImpl {
         let config = Config::default();
         let mut out = StandardStream::stderr(ColorChoice::Auto);
         for diag in &self.diagnostics {
+            let diag = codemap.remap_diagnostic(diag);
             term::emit(&mut out, &config, codemap, &diag).unwrap();
         }
     }
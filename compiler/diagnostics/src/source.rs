@@ -32,6 +32,10 @@ pub struct SourceFile {
     source: String,
     line_starts: Vec<ByteIndex>,
     parent: Option<SourceSpan>,
+    // Set when this file was produced by a `-file` directive, in which case the line at the
+    // given index is numbered as the given line, and the lines following it are numbered
+    // relative to that line
+    line_mapping: Option<(LineIndex, u32)>,
 }
 impl SourceFile {
     pub(crate) fn new(
@@ -50,9 +54,15 @@ impl SourceFile {
             source,
             line_starts,
             parent,
+            line_mapping: None,
         }
     }
 
+    pub(crate) fn with_line_mapping(mut self, line_index: LineIndex, line: u32) -> Self {
+        self.line_mapping = Some((line_index, line));
+        self
+    }
+
     pub fn name(&self) -> &FileName {
         &self.name
     }
@@ -97,6 +107,17 @@ impl SourceFile {
         }
     }
 
+    /// Returns the 1-based line number of the given line, taking into account any remapping of
+    /// lines due to a `-file` directive
+    pub fn line_number(&self, line_index: LineIndex) -> usize {
+        match self.line_mapping {
+            Some((first, line)) if line_index >= first => {
+                line as usize + (line_index.to_usize() - first.to_usize())
+            }
+            _ => line_index.to_usize() + 1,
+        }
+    }
+
     pub fn location<I: Into<ByteIndex>>(&self, byte_index: I) -> Result<Location, Error> {
         let byte_index = byte_index.into();
         let line_index = self.line_index(byte_index);
@@ -120,8 +141,9 @@ impl SourceFile {
                 }
             })?;
 
+        let line_number = self.line_number(line_index);
         Ok(Location {
-            line: line_index,
+            line: LineIndex::from(line_number.saturating_sub(1) as RawIndex),
             column: ColumnIndex::from(line_src.chars().count() as u32),
         })
     }
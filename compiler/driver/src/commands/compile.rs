@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::thread;
@@ -11,15 +11,20 @@ use salsa::{ParallelDatabase, Snapshot};
 use firefly_codegen as codegen;
 use firefly_codegen::linker;
 use firefly_codegen::meta::{CodegenResults, CompiledModule, ProjectInfo};
-use firefly_diagnostics::{CodeMap, Diagnostic, Label};
-use firefly_session::{CodegenOptions, DebuggingOptions, Options};
+use firefly_diagnostics::{CodeMap, Diagnostic, Label, Span, Spanned};
+use firefly_intern::Symbol;
+use firefly_session::{
+    CodegenOptions, DebuggingOptions, InputType, Lto, OptLevel, Options, OutputType,
+};
 use firefly_syntax_base::{ApplicationMetadata, Deprecation, FunctionName, ModuleMetadata};
-use firefly_util::diagnostics::Emitter;
+use firefly_syntax_erl::passes::sema;
+use firefly_util::diagnostics::{capture, Emitter};
 use firefly_util::time::HumanDuration;
 
 use crate::commands::*;
 use crate::compiler::prelude::{Compiler as CompilerQueryGroup, *};
 use crate::compiler::Compiler;
+use crate::incremental::IncrementalCache;
 use crate::parser::prelude::Parser as ParserQueryGroup;
 use crate::task;
 
@@ -32,10 +37,26 @@ pub fn handle_command<'a>(
 ) -> anyhow::Result<()> {
     // Extract options from provided arguments
     let options = Options::new(c_opts, z_opts, cwd, &matches)?;
+    // With LTO, modules are only compiled to bitcode, and the objects produced by optimizing them
+    // together are only meaningful as input to the linker
+    if options.lto() != Lto::No
+        && options.output_types.contains_key(&OutputType::Object)
+        && !options.should_link()
+    {
+        anyhow::bail!(
+            "`--emit=obj` cannot be used with `-C lto` unless linking, as no object is produced per module"
+        );
+    }
+    // Size the worker pool before any tasks are spawned
+    task::set_threads(options.debugging_opts.threads as usize);
     // Construct empty code map for use in compilation
     let codemap = Arc::new(CodeMap::new());
     // Set up diagnostics
     let diagnostics = create_diagnostics_handler(&options, codemap.clone(), emitter);
+    // Profile data only guides optimizations, so there is nothing for it to do without them
+    if options.codegen_opts.profile_use.is_some() && options.opt_level == OptLevel::No {
+        diagnostics.warn("`-C profile-use` has no effect at `-C opt-level=0`, and is ignored");
+    }
 
     // Initialize codegen backend
     codegen::init(&options)?;
@@ -62,7 +83,7 @@ pub fn handle_command<'a>(
         .copied()
         .map(|input| {
             let snapshot = db.snapshot();
-            task::spawn(async move { parse(snapshot, input) })
+            task::spawn(async move { capture(|| parse(snapshot, input)) })
         })
         .collect::<Vec<_>>();
 
@@ -72,13 +93,19 @@ pub fn handle_command<'a>(
     let diagnostics = db.diagnostics();
 
     let mut modules = BTreeMap::new();
+    let mut module_names = HashMap::new();
 
-    for task in tasks.drain(..) {
-        match task::join(task).unwrap() {
-            Ok(metadata) => {
+    // Tasks are joined in input order, and their output printed as they are joined, so that
+    // the output is the same regardless of the order in which the tasks actually completed
+    for (input, task) in inputs.iter().copied().zip(tasks.drain(..)) {
+        let (result, output) = task::join(task).unwrap();
+        diagnostics.print_captured(output);
+        match result {
+            Ok(Some(metadata)) => {
+                module_names.insert(input, metadata.name.name);
                 modules.insert(metadata.name.name, metadata);
             }
-            Err(_) => (),
+            Ok(None) | Err(_) => (),
         }
     }
 
@@ -97,14 +124,36 @@ pub fn handle_command<'a>(
         modules,
     });
 
+    // Warn about exports which are not referenced from elsewhere in the application, if requested
+    if options.debugging_opts.warn_unused_exports {
+        for export in app.unused_exports() {
+            let span = export.span();
+            let message = format!(
+                "{} is not referenced by any other module in this application",
+                export.item
+            );
+            let diagnostic = Diagnostic::warning()
+                .with_message("unused export")
+                .with_labels(vec![
+                    Label::primary(span.source_id(), span).with_message(message)
+                ]);
+            diagnostics.emit(&diagnostic);
+        }
+    }
+
+    // Reuse the artifacts of modules which are unchanged since they were last compiled
+    let cache = IncrementalCache::load(&options).map(Arc::new);
+
     // Spawn tasks for each input to be compiled
     let mut tasks = inputs
         .iter()
         .copied()
         .map(|input| {
             let app = app.clone();
+            let cache = cache.clone();
+            let module = module_names.get(&input).copied();
             let snapshot = db.snapshot();
-            task::spawn(async move { compile(snapshot, input, app) })
+            task::spawn(async move { capture(|| compile(snapshot, input, module, app, cache)) })
         })
         .collect::<Vec<_>>();
 
@@ -121,7 +170,9 @@ pub fn handle_command<'a>(
     };
 
     for task in tasks.drain(..) {
-        match task::join(task).unwrap() {
+        let (result, output) = task::join(task).unwrap();
+        diagnostics.print_captured(output);
+        match result {
             Ok(None) => continue,
             Ok(Some(module)) => {
                 codegen_results.modules.push(module);
@@ -130,6 +181,16 @@ pub fn handle_command<'a>(
         }
     }
 
+    // Persist the modules compiled successfully, even if others failed, so they are reused
+    if let Some(cache) = cache.as_ref() {
+        if let Err(err) = cache.save() {
+            diagnostics.notice(
+                "Incremental",
+                format!("unable to save compilation cache: {}", err),
+            );
+        }
+    }
+
     // Do not proceed to linking if there were compilation errors
     diagnostics.abort_if_errors();
 
@@ -143,6 +204,11 @@ pub fn handle_command<'a>(
     if codegen_results.modules.is_empty() {
         diagnostics.notice("Finished", "skipping link, no artifacts requested");
     } else {
+        // Replace the bitcode of each module with the result of optimizing them as a whole
+        if options.lto() != Lto::No {
+            codegen::lto::optimize(&options, &diagnostics, &mut codegen_results)?;
+        }
+
         // Link all compiled objects, if requested
         if !options.should_link() {
             if options.app_type.requires_link() {
@@ -172,12 +238,19 @@ pub fn handle_command<'a>(
     Ok(())
 }
 
-fn parse<C>(db: Snapshot<C>, input: InternedInput) -> Result<ModuleMetadata, ErrorReported>
+fn parse<C>(db: Snapshot<C>, input: InternedInput) -> Result<Option<ModuleMetadata>, ErrorReported>
 where
     C: ParserQueryGroup + ParallelDatabase,
 {
     debug!("spawning worker for {:?}", input);
 
+    // SSA and MLIR inputs have already been through the frontend, so there is no
+    // Erlang source to gather metadata from; they are parsed when compiled instead
+    match db.input_type(input) {
+        InputType::SSA | InputType::MLIR => return Ok(None),
+        _ => (),
+    }
+
     // Generate metadata about modules read from sources provided to the compiler
     let result = db.input_ast(input);
     match result {
@@ -187,10 +260,23 @@ where
             diagnostics.failed("Failed", format!("{}", &input_info.source_name()));
             Err(err)
         }
-        Ok(module) => {
+        Ok(mut module) => {
             let diagnostics = db.diagnostics();
             let name = module.name;
-            let exports = module.exports.iter().cloned().collect();
+            let export_all = module
+                .compile
+                .as_ref()
+                .map(|c| c.export_all)
+                .unwrap_or(false);
+            let exports = if export_all {
+                module
+                    .functions
+                    .iter()
+                    .map(|(name, function)| Span::new(function.name.span, *name))
+                    .collect()
+            } else {
+                module.exports.iter().cloned().collect()
+            };
             let mut deprecation = module.deprecation.clone();
             let mut deprecations: BTreeMap<FunctionName, Deprecation> = BTreeMap::new();
             for dep in module.deprecations.iter().copied() {
@@ -238,12 +324,18 @@ where
                     }
                 }
             }
-            Ok(ModuleMetadata {
+            let behaviour = module.behaviour();
+            let behaviours = module.behaviours.iter().map(|b| b.name).collect();
+            let references = sema::collect_references(&mut module);
+            Ok(Some(ModuleMetadata {
                 name,
                 exports,
                 deprecation,
                 deprecations,
-            })
+                behaviour,
+                behaviours,
+                references,
+            }))
         }
     }
 }
@@ -251,20 +343,44 @@ where
 fn compile<C>(
     db: Snapshot<C>,
     input: InternedInput,
+    module: Option<Symbol>,
     app: Arc<ApplicationMetadata>,
+    cache: Option<Arc<IncrementalCache>>,
 ) -> Result<Option<CompiledModule>, ErrorReported>
 where
     C: CompilerQueryGroup + ParallelDatabase,
 {
     debug!("spawning worker for {:?}", input);
 
+    let diagnostics = db.diagnostics();
+    let input_info = db.lookup_intern_input(input);
+    let source_name = format!("{}", &input_info.source_name());
+
+    // Check for artifacts from a previous compilation of the same sources
+    let cache = match cache {
+        Some(cache) => {
+            let source = db.input_fingerprint(input)?;
+            let fingerprint = cache.fingerprint(&source, module, &app);
+            if let Some(compiled) = cache.get(&source_name, fingerprint, &source) {
+                diagnostics.success("Fresh", source_name);
+                return Ok(Some(compiled));
+            }
+            Some((cache, fingerprint, source))
+        }
+        None => None,
+    };
+
     // Generate an LLVM IR module for this input, or None, if only earlier stages are requested
     let thread_id = thread::current().id();
     let result = db.compile(thread_id, input, app);
-    if result.is_err() {
-        let diagnostics = db.diagnostics();
-        let input_info = db.lookup_intern_input(input);
-        diagnostics.failed("Failed", format!("{}", &input_info.source_name()));
+    match result {
+        Ok(Some(ref compiled)) => {
+            if let Some((cache, fingerprint, source)) = cache {
+                cache.insert(&source_name, fingerprint, &source, compiled.clone());
+            }
+        }
+        Ok(None) => (),
+        Err(_) => diagnostics.failed("Failed", source_name),
     }
 
     result
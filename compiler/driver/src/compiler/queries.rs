@@ -1,11 +1,11 @@
-use std::sync::Arc;
+use std::sync::{Arc, Once};
 use std::thread::ThreadId;
 
 use log::debug;
 
 use firefly_codegen::meta::CompiledModule;
 use firefly_intern::Symbol;
-use firefly_session::OutputType;
+use firefly_session::{InputType, Lto, OutputType};
 use firefly_syntax_base::ApplicationMetadata;
 
 use super::prelude::*;
@@ -62,10 +62,25 @@ where
 
     // Bail early if we are just performing analysis or don't have artifacts to codegen
     if options.debugging_opts.analyze_only {
-        // We conduct analysis all the way up through Kernel, so run at least that much
-        db.input_kernel(input, app)?;
+        // We conduct analysis all the way up through Kernel, so run at least that much,
+        // unless the input is already SSA or MLIR, in which case parsing it is all there is to do
+        match db.input_type(input) {
+            InputType::SSA => {
+                db.input_ssa(input, app)?;
+            }
+            InputType::MLIR => {
+                db.input_mlir(thread_id, input, app)?;
+            }
+            _ => {
+                db.input_kernel(input, app)?;
+            }
+        }
         return Ok(None);
     } else if !options.output_types.should_generate_mlir() {
+        // MLIR inputs can't produce any of the earlier intermediate representations
+        if db.input_type(input) == InputType::MLIR {
+            return Ok(None);
+        }
         // Since production of Core/Kernel/SSA IR is driven by
         // queries for MLIR, we need to check if any of those
         // types were requested, and if so, execute the appropriate query
@@ -105,7 +120,9 @@ where
     let mut pm = PassManager::new(**mlir_context, &pm_opts);
     //let mpm = pm.nest("builtin.module");
     //mpm.add(firefly_mlir::conversions::ConvertCIRToLLVMPass::new());
-    firefly_mlir::conversions::ConvertCIRToLLVMPass::register();
+    // Pass registration is global and not thread-safe, so it must only happen once
+    static REGISTER_PASSES: Once = Once::new();
+    REGISTER_PASSES.call_once(|| firefly_mlir::conversions::ConvertCIRToLLVMPass::register());
     pm.parse_pipeline("convert-cir-to-llvm,reconcile-unrealized-casts")
         .unwrap();
 
@@ -136,7 +153,7 @@ where
     db.maybe_emit_file_with_opts(&options, input, &module)?;
 
     // Emit LLVM bitcode
-    db.maybe_emit_file_with_callback_and_opts(
+    let bc_path = db.maybe_emit_file_with_callback_and_opts(
         &options,
         input,
         OutputType::LLVMBitcode,
@@ -157,6 +174,35 @@ where
         module.emit_asm(outfile, target_machine.handle())
     })?;
 
+    // When performing link-time optimization, code generation is deferred until all modules
+    // have been compiled, so all we need from each module is its bitcode
+    if options.lto() != Lto::No {
+        let bc_path = match bc_path {
+            Some(path) => path,
+            None => {
+                let filename = db
+                    .output_dir()
+                    .join(&format!("{}.bc", input_info.file_stem()));
+                db.emit_file_with_callback(filename, |outfile| {
+                    debug!("emitting llvm bitcode for lto of {:?}", input);
+                    module.emit_bc(outfile)
+                })?
+            }
+        };
+
+        debug!(
+            "compilation finished for {:?}, deferring codegen to lto",
+            input
+        );
+        diagnostics.success("Compiled", format!("{}", &module_name));
+        return Ok(Some(CompiledModule {
+            name: module_sym,
+            object: None,
+            dwarf_object: None,
+            bytecode: Some(bc_path),
+        }));
+    }
+
     // Emit object file
     let obj_path = db.maybe_emit_file_with_callback_and_opts(
         &options,
@@ -169,11 +215,6 @@ where
     )?;
 
     // Gather compiled module metadata
-    let bc_path = options
-        .output_types
-        .maybe_emit(&input_info, OutputType::LLVMBitcode)
-        .map(|filename| db.output_dir().join(filename));
-
     let compiled = CompiledModule {
         name: module_sym,
         object: obj_path,
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use firefly_diagnostics::Reporter;
 use firefly_util::diagnostics::{CodeMap, Diagnostic, DiagnosticsHandler};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -29,5 +30,16 @@ pub trait CompilerDiagnostics {
         self.diagnostics().error(err);
     }
 
+    /// Emits the diagnostics gathered by `reporter`
+    ///
+    /// Unlike `Reporter::print`, which writes directly to stderr, this goes through the
+    /// diagnostics handler, so the output is ordered with the rest of the current task
+    fn report(&self, reporter: &Reporter) {
+        let diagnostics = self.diagnostics();
+        for diagnostic in reporter.diagnostics().iter() {
+            diagnostics.emit(diagnostic);
+        }
+    }
+
     fn codemap(&self) -> &Arc<CodeMap>;
 }
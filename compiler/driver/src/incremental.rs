@@ -0,0 +1,693 @@
+//! Persists the results of compiling each module across invocations of the compiler
+//!
+//! Every module is still parsed on each run, since the application metadata used to check
+//! each module is gathered from all of them, but the remainder of the pipeline is skipped for
+//! any module whose sources, referenced modules, compiler options and compiler version are
+//! unchanged since the artifacts recorded for it were produced.
+//!
+//! The cache is a manifest in `<output_dir>/incremental`, with an entry per input:
+//!
+//! ```text
+//! module <source name>
+//!   fingerprint <hash>
+//!   name <module name>
+//!   object <path>
+//!   bytecode <path>
+//!   dependency <hash> <path>
+//! ```
+//!
+//! NOTE: Warnings raised while compiling a module are not replayed when it is reused, which is
+//! why this is opt-in via `-C incremental`.
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use log::debug;
+use parking_lot::Mutex;
+
+use firefly_codegen::meta::CompiledModule;
+use firefly_intern::Symbol;
+use firefly_session::{Options, OutputType};
+use firefly_syntax_base::{ApplicationMetadata, Deprecation, ModuleMetadata};
+use firefly_util::diagnostics::{CodeMap, SourceId};
+
+const MANIFEST_HEADER: &'static str = "firefly incremental cache v1";
+
+/// A fingerprint of the sources a module was parsed from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFingerprint {
+    /// The hash of the source and all of the files it included
+    pub hash: u64,
+    /// The files included by the source along with the hash of their contents
+    ///
+    /// Records and macros shared between modules are defined in these files.
+    pub dependencies: Vec<(PathBuf, u64)>,
+}
+impl SourceFingerprint {
+    /// Fingerprints source code which cannot include other files, e.g. SSA or MLIR
+    pub fn of_source(source: &[u8]) -> Self {
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        Self {
+            hash: hasher.finish(),
+            dependencies: vec![],
+        }
+    }
+
+    /// Fingerprints the file containing `id` in `codemap`, and every file it included
+    pub fn of_file(codemap: &CodeMap, id: SourceId) -> Self {
+        let files = codemap.iter().collect::<Vec<_>>();
+        let parents = files
+            .iter()
+            .filter_map(|file| file.parent().map(|span| (file.id(), span.source_id())))
+            .collect::<HashMap<_, _>>();
+        let root_of = |mut id: SourceId| {
+            while let Some(parent) = parents.get(&id) {
+                id = *parent;
+            }
+            id
+        };
+        let root = root_of(id);
+
+        let mut hasher = DefaultHasher::new();
+        let mut dependencies = vec![];
+        for file in files.iter() {
+            if file.id() == root {
+                file.source().hash(&mut hasher);
+            } else if parents.contains_key(&file.id()) && root_of(file.id()) == root {
+                let path: &Path = file.name().as_ref();
+                dependencies.push((path.to_path_buf(), hash_of(file.source())));
+            }
+        }
+        // A file may be included more than once, and the order files are registered in
+        // depends on the order in which modules were parsed
+        dependencies.sort();
+        dependencies.dedup();
+        dependencies.hash(&mut hasher);
+
+        Self {
+            hash: hasher.finish(),
+            dependencies,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    fingerprint: u64,
+    module: CompiledModule,
+    dependencies: Vec<(PathBuf, u64)>,
+}
+
+/// The artifacts produced for each module by previous compilations of the current application
+pub struct IncrementalCache {
+    manifest: PathBuf,
+    options: u64,
+    previous: BTreeMap<String, CacheEntry>,
+    current: Mutex<BTreeMap<String, CacheEntry>>,
+}
+impl IncrementalCache {
+    /// Loads the cache from the output directory
+    ///
+    /// Returns `None` if incremental compilation is disabled, or if artifacts other than objects
+    /// and bitcode were requested, as those are only produced when a module is compiled.
+    pub fn load(options: &Options) -> Option<Self> {
+        if !options.codegen_opts.incremental || !options.output_types.should_codegen() {
+            return None;
+        }
+        let cacheable = options.output_types.keys().all(|ty| match ty {
+            OutputType::LLVMBitcode | OutputType::Object | OutputType::Link => true,
+            _ => false,
+        });
+        if !cacheable {
+            debug!("incremental compilation disabled, intermediate artifacts were requested");
+            return None;
+        }
+
+        let manifest = options.output_dir().join("incremental").join("manifest");
+        let previous = match fs::read_to_string(&manifest) {
+            Ok(contents) => parse_manifest(&contents).unwrap_or_else(|err| {
+                debug!("discarding incremental cache: {}", err);
+                BTreeMap::new()
+            }),
+            Err(_) => BTreeMap::new(),
+        };
+
+        Some(Self {
+            manifest,
+            options: hash_options(options),
+            previous,
+            current: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// Computes the fingerprint of `module`, which identifies the artifacts compiled from it
+    ///
+    /// Besides its sources, a module is checked against the modules it refers to during semantic
+    /// analysis, so their metadata is also part of the fingerprint.
+    pub fn fingerprint(
+        &self,
+        source: &SourceFingerprint,
+        module: Option<Symbol>,
+        app: &ApplicationMetadata,
+    ) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.options.hash(&mut hasher);
+        source.hash.hash(&mut hasher);
+
+        if let Some(metadata) = module.and_then(|name| app.modules.get(&name)) {
+            let referenced = metadata
+                .references
+                .iter()
+                .filter_map(|name| name.module)
+                .chain(metadata.behaviours.iter().copied())
+                .map(|name| (name.as_str().get().to_string(), name))
+                .collect::<BTreeMap<_, _>>();
+            for (name, symbol) in referenced.iter() {
+                name.hash(&mut hasher);
+                match app.modules.get(symbol) {
+                    Some(metadata) => describe_module(metadata).hash(&mut hasher),
+                    None => "<external>".hash(&mut hasher),
+                }
+            }
+        }
+
+        hasher.finish()
+    }
+
+    /// Returns the module compiled for the input named `key`, if it is still fresh
+    pub fn get(
+        &self,
+        key: &str,
+        fingerprint: u64,
+        source: &SourceFingerprint,
+    ) -> Option<CompiledModule> {
+        let entry = self.previous.get(key)?;
+        if entry.fingerprint != fingerprint {
+            match source
+                .dependencies
+                .iter()
+                .find(|dep| !entry.dependencies.contains(dep))
+            {
+                Some((path, _)) => debug!("{} is out of date, {} changed", key, path.display()),
+                None => debug!("{} is out of date", key),
+            }
+            return None;
+        }
+
+        let module = &entry.module;
+        let artifacts_exist = module
+            .object()
+            .into_iter()
+            .chain(module.bytecode())
+            .all(|path| path.exists());
+        if !artifacts_exist {
+            debug!("{} is out of date, its artifacts are missing", key);
+            return None;
+        }
+
+        self.insert(key, fingerprint, source, module.clone());
+        Some(module.clone())
+    }
+
+    /// Records the module compiled for the input named `key`
+    pub fn insert(
+        &self,
+        key: &str,
+        fingerprint: u64,
+        source: &SourceFingerprint,
+        module: CompiledModule,
+    ) {
+        let entry = CacheEntry {
+            fingerprint,
+            module,
+            dependencies: source.dependencies.clone(),
+        };
+        self.current.lock().insert(key.to_string(), entry);
+    }
+
+    /// Writes the modules recorded during this session to the output directory
+    ///
+    /// Entries for inputs which were not recorded, e.g. because they failed to compile or have
+    /// been removed from the application, are dropped.
+    pub fn save(&self) -> anyhow::Result<()> {
+        let mut contents = String::new();
+        contents.push_str(MANIFEST_HEADER);
+        contents.push('\n');
+        for (key, entry) in self.current.lock().iter() {
+            let module = &entry.module;
+            contents.push_str(&format!("module {}\n", key));
+            contents.push_str(&format!("  fingerprint {:016x}\n", entry.fingerprint));
+            contents.push_str(&format!("  name {}\n", module.name));
+            if let Some(object) = module.object() {
+                contents.push_str(&format!("  object {}\n", absolute(object).display()));
+            }
+            if let Some(bytecode) = module.bytecode() {
+                contents.push_str(&format!("  bytecode {}\n", absolute(bytecode).display()));
+            }
+            for (path, hash) in entry.dependencies.iter() {
+                contents.push_str(&format!("  dependency {:016x} {}\n", hash, path.display()));
+            }
+        }
+
+        let dir = self.manifest.parent().unwrap();
+        fs::create_dir_all(dir).with_context(|| format!("unable to create {}", dir.display()))?;
+        // Write to a temporary file first, so that an interrupted write doesn't corrupt the cache
+        let tmp = self.manifest.with_extension("tmp");
+        fs::write(&tmp, contents).with_context(|| format!("unable to write {}", tmp.display()))?;
+        fs::rename(&tmp, &self.manifest)
+            .with_context(|| format!("unable to write {}", self.manifest.display()))
+    }
+}
+
+fn parse_manifest(contents: &str) -> anyhow::Result<BTreeMap<String, CacheEntry>> {
+    let mut lines = contents.lines();
+    if lines.next() != Some(MANIFEST_HEADER) {
+        return Err(anyhow!("unrecognized manifest format"));
+    }
+
+    let mut entries = BTreeMap::new();
+    let mut current: Option<(String, CacheEntry)> = None;
+    for line in lines {
+        let (field, value) = line
+            .trim_start()
+            .split_once(' ')
+            .ok_or_else(|| anyhow!("invalid manifest entry '{}'", line))?;
+        if field == "module" {
+            if let Some((key, entry)) = current.take() {
+                entries.insert(key, entry);
+            }
+            let entry = CacheEntry {
+                fingerprint: 0,
+                module: CompiledModule {
+                    name: Symbol::intern(value),
+                    object: None,
+                    dwarf_object: None,
+                    bytecode: None,
+                },
+                dependencies: vec![],
+            };
+            current = Some((value.to_string(), entry));
+            continue;
+        }
+
+        let (_, entry) = current
+            .as_mut()
+            .ok_or_else(|| anyhow!("expected module entry, got '{}'", line))?;
+        match field {
+            "fingerprint" => entry.fingerprint = u64::from_str_radix(value, 16)?,
+            "name" => entry.module.name = Symbol::intern(value),
+            "object" => entry.module.object = Some(PathBuf::from(value)),
+            "bytecode" => entry.module.bytecode = Some(PathBuf::from(value)),
+            "dependency" => {
+                let (hash, path) = value
+                    .split_once(' ')
+                    .ok_or_else(|| anyhow!("invalid dependency '{}'", value))?;
+                let hash = u64::from_str_radix(hash, 16)?;
+                entry.dependencies.push((PathBuf::from(path), hash));
+            }
+            _ => return Err(anyhow!("unrecognized manifest field '{}'", field)),
+        }
+    }
+    if let Some((key, entry)) = current.take() {
+        entries.insert(key, entry);
+    }
+
+    Ok(entries)
+}
+
+/// Hashes the compiler version and the options which affect how an individual module is compiled
+///
+/// The set of inputs is deliberately excluded, so that adding a module to an application does
+/// not invalidate the others. Likewise, options which only affect linking, or what is printed
+/// while compiling, are excluded. When adding an option which changes the artifacts produced
+/// for a module, it must be hashed here.
+fn hash_options(options: &Options) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    crate::FIREFLY_RELEASE.hash(&mut hasher);
+    crate::FIREFLY_COMMIT_HASH.hash(&mut hasher);
+    crate::FIREFLY_COMMIT_DATE.hash(&mut hasher);
+    options.app.name.as_str().get().hash(&mut hasher);
+    options.target.triple().hash(&mut hasher);
+    options.output_types.hash(&mut hasher);
+    options.warnings_as_errors.hash(&mut hasher);
+    options.no_warn.hash(&mut hasher);
+    options.debug_assertions.hash(&mut hasher);
+    options.test.hash(&mut hasher);
+    options.include_path.hash(&mut hasher);
+    options.source_path_prefix.hash(&mut hasher);
+    options
+        .defines
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .hash(&mut hasher);
+    options.opt_level.hash(&mut hasher);
+    options.debug_info.hash(&mut hasher);
+
+    let codegen_opts = &options.codegen_opts;
+    codegen_opts.code_model.hash(&mut hasher);
+    codegen_opts.control_flow_guard.hash(&mut hasher);
+    codegen_opts.embed_bitcode.hash(&mut hasher);
+    codegen_opts.force_frame_pointers.hash(&mut hasher);
+    codegen_opts.force_unwind_tables.hash(&mut hasher);
+    codegen_opts.function_sections.hash(&mut hasher);
+    codegen_opts.inline_threshold.hash(&mut hasher);
+    codegen_opts.llvm_args.hash(&mut hasher);
+    codegen_opts.lto.hash(&mut hasher);
+    codegen_opts.thinlto.hash(&mut hasher);
+    codegen_opts.linker_plugin_lto.hash(&mut hasher);
+    codegen_opts.merge_functions.hash(&mut hasher);
+    codegen_opts.no_prepopulate_passes.hash(&mut hasher);
+    codegen_opts.no_unique_section_names.hash(&mut hasher);
+    codegen_opts.passes.hash(&mut hasher);
+    codegen_opts.profile_generate.hash(&mut hasher);
+    codegen_opts.profile_use.hash(&mut hasher);
+    codegen_opts.relocation_model.hash(&mut hasher);
+    codegen_opts.target_cpu.hash(&mut hasher);
+    codegen_opts.target_features.hash(&mut hasher);
+    codegen_opts.tls_model.hash(&mut hasher);
+
+    let debugging_opts = &options.debugging_opts;
+    debugging_opts.asm_comments.hash(&mut hasher);
+    debugging_opts.check_specs.hash(&mut hasher);
+    debugging_opts.emit_stack_sizes.hash(&mut hasher);
+    debugging_opts.llvm_plugins.hash(&mut hasher);
+    debugging_opts.mlir_print_debug_info.hash(&mut hasher);
+    debugging_opts.mlir_print_generic_ops.hash(&mut hasher);
+    debugging_opts.sanitizers.hash(&mut hasher);
+    debugging_opts
+        .sanitizer_memory_track_origins
+        .hash(&mut hasher);
+    debugging_opts
+        .split_debuginfo
+        .map(|split| split.to_string())
+        .hash(&mut hasher);
+    debugging_opts.split_dwarf_kind.hash(&mut hasher);
+    debugging_opts.split_dwarf_inlining.hash(&mut hasher);
+    debugging_opts.warn_unused_exports.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Describes the parts of `metadata` that other modules are checked against
+///
+/// Symbols are interned in whatever order modules happen to be parsed, so this is rendered
+/// to text and sorted, rather than hashed directly.
+fn describe_module(metadata: &ModuleMetadata) -> BTreeSet<String> {
+    let describe_deprecation = |deprecation: &Deprecation| match deprecation {
+        Deprecation::Module { flag, .. } => format!("deprecated {}", flag),
+        Deprecation::Function { function, flag, .. } => {
+            format!("deprecated {} {}", function.as_ref(), flag)
+        }
+    };
+
+    let mut description = BTreeSet::new();
+    for export in metadata.exports.iter() {
+        description.insert(format!("export {}", export.as_ref()));
+    }
+    description.extend(metadata.deprecation.iter().map(describe_deprecation));
+    description.extend(metadata.deprecations.values().map(describe_deprecation));
+    if let Some(behaviour) = metadata.behaviour.as_ref() {
+        for callback in behaviour.callbacks.iter() {
+            description.insert(format!("callback {}", callback));
+        }
+        for callback in behaviour.optional_callbacks.iter() {
+            description.insert(format!("optional_callback {}", callback));
+        }
+    }
+    description
+}
+
+fn hash_of(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+    use std::ffi::OsString;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use parking_lot::Mutex;
+
+    use firefly_codegen::meta::CompiledModule;
+    use firefly_diagnostics::{SourceSpan, Span};
+    use firefly_intern::{Ident, Symbol};
+    use firefly_session::{CodegenOptions, DebuggingOptions, OptLevel, OptionGroup, Options};
+    use firefly_syntax_base::{ApplicationMetadata, FunctionName, ModuleMetadata};
+    use firefly_util::diagnostics::CodeMap;
+
+    use super::{hash_of, hash_options, parse_manifest, IncrementalCache, SourceFingerprint};
+
+    fn options() -> Options {
+        let args = ["firefly", "compile", "--app-name", "test", "-"];
+        let matches = crate::argparser::parse(args.iter().map(OsString::from)).unwrap();
+        let c_opts = CodegenOptions::parse_option_group(&matches)
+            .unwrap()
+            .unwrap_or_default();
+        let z_opts = DebuggingOptions::parse_option_group(&matches)
+            .unwrap()
+            .unwrap_or_default();
+        let cwd = std::env::current_dir().unwrap();
+        let (_, compile) = matches.subcommand();
+        Options::new(c_opts, z_opts, cwd, compile.unwrap()).unwrap()
+    }
+
+    fn cache(name: &str, options: &Options) -> IncrementalCache {
+        let dir = std::env::temp_dir().join(format!(
+            "firefly-incremental-{}-{}",
+            std::process::id(),
+            name
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        IncrementalCache {
+            manifest: dir.join("manifest"),
+            options: hash_options(options),
+            previous: BTreeMap::new(),
+            current: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn metadata(name: &str, exports: &[&str], references: &[&str]) -> ModuleMetadata {
+        let function_name = |name: &&str| name.parse::<FunctionName>().unwrap();
+        ModuleMetadata {
+            name: Ident::from_str(name),
+            exports: exports
+                .iter()
+                .map(|name| Span::new(SourceSpan::default(), function_name(name)))
+                .collect(),
+            deprecation: None,
+            deprecations: BTreeMap::new(),
+            behaviour: None,
+            behaviours: BTreeSet::new(),
+            references: references.iter().map(function_name).collect(),
+        }
+    }
+
+    fn app(modules: Vec<ModuleMetadata>) -> ApplicationMetadata {
+        ApplicationMetadata {
+            name: Symbol::intern("test"),
+            modules: modules
+                .into_iter()
+                .map(|module| (module.name.name, module))
+                .collect(),
+        }
+    }
+
+    fn source_of(root: &str, include: &str) -> SourceFingerprint {
+        let codemap = CodeMap::new();
+        let id = codemap.add(PathBuf::from("/app/src/a.erl"), root.to_string());
+        let parent = codemap.source_span(id).unwrap();
+        codemap.add_child(
+            PathBuf::from("/app/include/a.hrl"),
+            include.to_string(),
+            parent,
+        );
+        SourceFingerprint::of_file(&codemap, id)
+    }
+
+    #[test]
+    fn manifest_round_trips() {
+        let options = options();
+        let cache = cache("round_trip", &options);
+        let dir = cache.manifest.parent().unwrap().to_path_buf();
+        let source = source_of("-module(a).", "-record(r, {}).");
+        let module = CompiledModule {
+            name: Symbol::intern("a"),
+            object: Some(dir.join("a.o")),
+            dwarf_object: None,
+            bytecode: Some(dir.join("a.bc")),
+        };
+        cache.insert("src/a.erl", 0xdeadbeef, &source, module.clone());
+        cache.save().unwrap();
+
+        let contents = fs::read_to_string(&cache.manifest).unwrap();
+        let entries = parse_manifest(&contents).unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries["src/a.erl"];
+        assert_eq!(entry.fingerprint, 0xdeadbeef);
+        assert_eq!(entry.module, module);
+        assert_eq!(entry.dependencies, source.dependencies);
+
+        assert!(parse_manifest("not a manifest\n").is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fresh_modules_are_reused() {
+        let options = options();
+        let cache = cache("reuse", &options);
+        let dir = cache.manifest.parent().unwrap().to_path_buf();
+        fs::create_dir_all(&dir).unwrap();
+        let object = dir.join("a.o");
+        fs::write(&object, b"").unwrap();
+
+        let source = source_of("-module(a).", "");
+        let app = app(vec![metadata("a", &[], &[])]);
+        let fingerprint = cache.fingerprint(&source, Some(Symbol::intern("a")), &app);
+        let module = CompiledModule {
+            name: Symbol::intern("a"),
+            object: Some(object.clone()),
+            dwarf_object: None,
+            bytecode: None,
+        };
+        cache.insert("a.erl", fingerprint, &source, module.clone());
+        cache.save().unwrap();
+
+        let contents = fs::read_to_string(&cache.manifest).unwrap();
+        let cache = IncrementalCache {
+            previous: parse_manifest(&contents).unwrap(),
+            ..cache
+        };
+        assert_eq!(cache.get("a.erl", fingerprint, &source), Some(module));
+        assert_eq!(
+            cache.get("a.erl", fingerprint.wrapping_add(1), &source),
+            None
+        );
+
+        // Artifacts which no longer exist can't be reused
+        fs::remove_file(&object).unwrap();
+        assert_eq!(cache.get("a.erl", fingerprint, &source), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn include_changes_invalidate() {
+        let source = source_of("-module(a).", "-record(r, {a}).");
+        assert_eq!(source, source_of("-module(a).", "-record(r, {a})."));
+        assert_eq!(
+            source.dependencies,
+            vec![(
+                PathBuf::from("/app/include/a.hrl"),
+                hash_of("-record(r, {a}).")
+            )]
+        );
+
+        let changed = source_of("-module(a).", "-record(r, {a, b}).");
+        assert_ne!(source.hash, changed.hash);
+
+        let options = options();
+        let cache = cache("include", &options);
+        let app = app(vec![metadata("a", &[], &[])]);
+        let a = Some(Symbol::intern("a"));
+        assert_ne!(
+            cache.fingerprint(&source, a, &app),
+            cache.fingerprint(&changed, a, &app)
+        );
+    }
+
+    #[test]
+    fn export_changes_invalidate_referencing_modules() {
+        let options = options();
+        let cache = cache("exports", &options);
+        let source = source_of("-module(a).", "");
+        let a = Some(Symbol::intern("a"));
+
+        let before = app(vec![
+            metadata("a", &["a:f/0"], &["b:g/0"]),
+            metadata("b", &["b:g/0"], &[]),
+            metadata("c", &["c:h/0"], &[]),
+        ]);
+        let fingerprint = cache.fingerprint(&source, a, &before);
+
+        // Changing the exports of a referenced module invalidates the referencing module
+        let after = app(vec![
+            metadata("a", &["a:f/0"], &["b:g/0"]),
+            metadata("b", &["b:g/1"], &[]),
+            metadata("c", &["c:h/0"], &[]),
+        ]);
+        assert_ne!(fingerprint, cache.fingerprint(&source, a, &after));
+
+        // While changes to unrelated modules do not
+        let after = app(vec![
+            metadata("a", &["a:f/0"], &["b:g/0"]),
+            metadata("b", &["b:g/0"], &[]),
+            metadata("c", &["c:h/1"], &[]),
+        ]);
+        assert_eq!(fingerprint, cache.fingerprint(&source, a, &after));
+    }
+
+    #[test]
+    fn option_changes_invalidate() {
+        let options = options();
+        assert_eq!(hash_options(&options), hash_options(&options.clone()));
+
+        let mut optimized = options.clone();
+        optimized.opt_level = OptLevel::Aggressive;
+        assert_ne!(hash_options(&options), hash_options(&optimized));
+
+        let mut defined = options.clone();
+        defined.defines.insert("TEST".to_string(), None);
+        assert_ne!(hash_options(&options), hash_options(&defined));
+
+        let mut native = options.clone();
+        native.codegen_opts.target_cpu = Some("native".to_string());
+        assert_ne!(hash_options(&options), hash_options(&native));
+
+        let mut stack_sizes = options.clone();
+        stack_sizes.debugging_opts.emit_stack_sizes = true;
+        assert_ne!(hash_options(&options), hash_options(&stack_sizes));
+
+        let source = source_of("-module(a).", "");
+        let app = app(vec![metadata("a", &[], &[])]);
+        let a = Some(Symbol::intern("a"));
+        assert_ne!(
+            cache("options", &options).fingerprint(&source, a, &app),
+            cache("options", &optimized).fingerprint(&source, a, &app)
+        );
+    }
+
+    #[test]
+    fn non_codegen_option_changes_are_ignored() {
+        let options = options();
+
+        let mut linker = options.clone();
+        linker.codegen_opts.linker = Some("ld.lld".into());
+        linker
+            .codegen_opts
+            .linker_arg
+            .push("--gc-sections".to_string());
+        assert_eq!(hash_options(&options), hash_options(&linker));
+
+        let mut threads = options.clone();
+        threads.debugging_opts.threads = 4;
+        threads.debugging_opts.time_passes = true;
+        threads.debugging_opts.mlir_print_passes_after = true;
+        assert_eq!(hash_options(&options), hash_options(&threads));
+    }
+}
@@ -4,10 +4,12 @@ mod argparser;
 mod commands;
 mod compiler;
 mod diagnostics;
+mod incremental;
 mod interner;
 mod output;
 mod parser;
 pub(crate) mod task;
+mod transforms;
 
 use std::ffi::OsString;
 use std::path::PathBuf;
@@ -19,6 +21,8 @@ use firefly_session::{CodegenOptions, DebuggingOptions, OptionGroup, ShowOptionG
 use firefly_util::diagnostics::Emitter;
 use firefly_util::error::HelpRequested;
 
+pub use self::transforms::register_parse_transform;
+
 pub const FIREFLY_RELEASE: &'static str = crate_version!();
 pub const FIREFLY_COMMIT_HASH: &'static str = env!("FIREFLY_COMMIT_HASH");
 pub const FIREFLY_COMMIT_DATE: &'static str = env!("FIREFLY_COMMIT_DATE");
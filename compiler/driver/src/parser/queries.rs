@@ -4,19 +4,22 @@ use std::thread::ThreadId;
 
 use log::debug;
 
+use firefly_beam::AbstractCode;
 use firefly_diagnostics::{Reporter, ToDiagnostic};
 use firefly_intern::symbols;
 use firefly_llvm as llvm;
 use firefly_mlir as mlir;
-use firefly_session::{Input, InputType};
+use firefly_session::{Input, InputType, OptLevel};
 use firefly_syntax_base::ApplicationMetadata;
 use firefly_syntax_core as syntax_core;
 use firefly_syntax_erl::{self as syntax_erl, ParseConfig};
 use firefly_syntax_kernel as syntax_kernel;
+use firefly_syntax_pp as syntax_pp;
 use firefly_syntax_ssa as syntax_ssa;
 use firefly_util::diagnostics::FileName;
 
 use super::prelude::*;
+use crate::incremental::SourceFingerprint;
 
 macro_rules! unwrap_or_bail {
     ($db:ident, $e:expr) => {
@@ -28,14 +31,14 @@ macro_rules! unwrap_or_bail {
         }
     };
 
-    ($db:ident, $reporter:expr, $codemap:expr, $e:expr) => {
+    ($db:ident, $reporter:expr, $e:expr) => {
         match $e {
             Ok(result) => {
-                $reporter.print($codemap);
+                $db.report(&$reporter);
                 result
             }
             Err(ref e) => {
-                $reporter.print($codemap);
+                $db.report(&$reporter);
                 bail!($db, "{}", e);
             }
         }
@@ -211,24 +214,77 @@ where
                     parser.parse_string::<syntax_erl::Module, _, _>(reporter.clone(), input)
                 }
             }
+            .map_err(|e| e.to_diagnostic())
+        }
+        InputType::AbstractErlang => {
+            let parser = parse::Parser::new((), codemap.clone());
+            match db.lookup_intern_input(input) {
+                Input::File(ref path) => {
+                    parser.parse_file::<syntax_pp::Root, &Path, _>(reporter.clone(), path)
+                }
+                Input::Str { ref input, .. } => {
+                    parser.parse_string::<syntax_pp::Root, _, _>(reporter.clone(), input)
+                }
+            }
+            .and_then(|root| syntax_pp::lower_module(&reporter, codemap.clone(), &root))
+            .map_err(|e| e.to_diagnostic())
+        }
+        InputType::BEAM => {
+            let path = match db.lookup_intern_input(input) {
+                Input::File(path) => path,
+                Input::Str { .. } => bail!(db, "beam inputs must be provided as files"),
+            };
+            let code = unwrap_or_bail!(db, AbstractCode::from_beam_file(&path));
+            // The forms have no source text, so the BEAM file is registered without any,
+            // giving diagnostics raised against the forms somewhere to point
+            let name = FileName::Virtual(path.display().to_string().into());
+            let id = codemap.add(name, String::new());
+            let span = codemap.get(id).unwrap().source_span();
+            syntax_pp::lower_abstract_code(&reporter, codemap.clone(), span, &code)
+                .map_err(|e| e.to_diagnostic())
         }
         ty => bail!(db, "invalid input type: {}", ty),
     };
 
     match result {
         Ok(module) => {
-            reporter.print(&codemap);
+            db.report(&reporter);
             db.maybe_emit_file_with_opts(&options, input, &module)?;
             Ok(module)
         }
-        Err(e) => {
-            reporter.diagnostic(e.to_diagnostic());
-            reporter.print(&codemap);
+        Err(diagnostic) => {
+            reporter.diagnostic(diagnostic);
+            db.report(&reporter);
             bail!(db, "parsing failed, see diagnostics for details");
         }
     }
 }
 
+pub(crate) fn input_fingerprint<P>(
+    db: &P,
+    input: InternedInput,
+) -> Result<SourceFingerprint, ErrorReported>
+where
+    P: Parser,
+{
+    match db.input_type(input) {
+        // These are not parsed from source text, or are parsed directly when compiled, and
+        // cannot include other files
+        InputType::BEAM | InputType::SSA | InputType::MLIR => match db.lookup_intern_input(input) {
+            Input::File(ref path) => {
+                let source = unwrap_or_bail!(db, std::fs::read(path));
+                Ok(SourceFingerprint::of_source(&source))
+            }
+            Input::Str { ref input, .. } => Ok(SourceFingerprint::of_source(input.as_bytes())),
+        },
+        _ => {
+            let module = db.input_ast(input)?;
+            let codemap = db.codemap();
+            Ok(SourceFingerprint::of_file(codemap, module.span.source_id()))
+        }
+    }
+}
+
 pub(crate) fn input_core<P>(
     db: &P,
     input: InternedInput,
@@ -238,7 +294,10 @@ where
     P: Parser,
 {
     use firefly_pass::Pass;
-    use firefly_syntax_erl::passes::{AstToCore, CanonicalizeSyntax, SemanticAnalysis};
+    use firefly_syntax_core::passes::{InlineFunctions, SimplifyCore};
+    use firefly_syntax_erl::passes::{
+        AstToCore, CanonicalizeSyntax, CheckTypeSpecs, SemanticAnalysis,
+    };
 
     // Get Erlang AST
     let ast = db.input_ast(input)?;
@@ -252,11 +311,50 @@ where
         Reporter::new()
     };
 
-    let mut passes = SemanticAnalysis::new(reporter.clone(), &app)
-        .chain(CanonicalizeSyntax::new(reporter.clone(), codemap.clone()))
+    // Apply any parse transforms requested by the module before it is analyzed
+    let ast = unwrap_or_bail!(
+        db,
+        reporter,
+        crate::transforms::apply_parse_transforms(ast, &reporter)
+    );
+
+    let ast = unwrap_or_bail!(
+        db,
+        reporter,
+        SemanticAnalysis::new(reporter.clone(), &app).run(ast)
+    );
+
+    // Check function bodies against their type specs, if requested
+    let ast = if options.debugging_opts.check_specs {
+        unwrap_or_bail!(db, reporter, CheckTypeSpecs::new(reporter.clone()).run(ast))
+    } else {
+        ast
+    };
+
+    let mut passes = CanonicalizeSyntax::new(reporter.clone(), codemap.clone())
         .chain(AstToCore::new(reporter.clone()));
 
-    let module = unwrap_or_bail!(db, reporter, &codemap, passes.run(ast));
+    let module = unwrap_or_bail!(db, reporter, passes.run(ast));
+
+    // Inline local functions requested via `-compile({inline, ...})`, or the `inline` option.
+    // The diagnostics of the passes above have already been reported, so use a fresh reporter.
+    let reporter = if options.warnings_as_errors {
+        Reporter::strict()
+    } else {
+        Reporter::new()
+    };
+    let module = unwrap_or_bail!(
+        db,
+        reporter,
+        InlineFunctions::new(reporter.clone()).run(module)
+    );
+
+    // Simplify the Core IR before it is lowered further, unless optimizations are disabled
+    let module = if options.opt_level != OptLevel::No {
+        unwrap_or_bail!(db, SimplifyCore::new().run(module))
+    } else {
+        module
+    };
 
     db.maybe_emit_file(input, &module)?;
 
@@ -279,14 +377,13 @@ where
 
     // Run lowering passes
     let options = db.options();
-    let codemap = db.codemap().clone();
     let reporter = if options.warnings_as_errors {
         Reporter::strict()
     } else {
         Reporter::new()
     };
     let mut passes = CoreToKernel::new(reporter.clone());
-    let module = unwrap_or_bail!(db, reporter, &codemap, passes.run(ast));
+    let module = unwrap_or_bail!(db, reporter, passes.run(ast));
 
     db.maybe_emit_file(input, &module)?;
 
@@ -301,13 +398,10 @@ pub(crate) fn input_ssa<P>(
 where
     P: Parser,
 {
+    use firefly_parser as parse;
     use firefly_pass::Pass;
     use firefly_syntax_kernel::passes::KernelToSsa;
 
-    // Get Kernel Erlang module
-    let cst = db.input_kernel(input, app)?;
-
-    // Run lowering passes
     let options = db.options();
     let codemap = db.codemap().clone();
     let reporter = if options.warnings_as_errors {
@@ -316,8 +410,36 @@ where
         Reporter::new()
     };
 
+    // SSA inputs are parsed directly, skipping the frontend entirely
+    if db.input_type(input) == InputType::SSA {
+        let parser = parse::Parser::new((), codemap.clone());
+        let result = match db.lookup_intern_input(input) {
+            Input::File(ref path) => {
+                parser.parse_file::<syntax_ssa::Module, &Path, _>(reporter.clone(), path)
+            }
+            Input::Str { ref input, .. } => {
+                parser.parse_string::<syntax_ssa::Module, _, _>(reporter.clone(), input)
+            }
+        };
+        return match result {
+            Ok(module) => {
+                db.report(&reporter);
+                Ok(module)
+            }
+            Err(err) => {
+                reporter.diagnostic(err.to_diagnostic());
+                db.report(&reporter);
+                bail!(db, "parsing failed, see diagnostics for details");
+            }
+        };
+    }
+
+    // Get Kernel Erlang module
+    let cst = db.input_kernel(input, app)?;
+
+    // Run lowering passes
     let mut passes = KernelToSsa::new(reporter.clone());
-    let module = unwrap_or_bail!(db, reporter, &codemap, passes.run(cst));
+    let module = unwrap_or_bail!(db, reporter, passes.run(cst));
 
     db.maybe_emit_file(input, &module)?;
 
@@ -354,7 +476,7 @@ where
                 }
             }
         }
-        InputType::Erlang | InputType::AbstractErlang | InputType::SSA => {
+        InputType::Erlang | InputType::AbstractErlang | InputType::BEAM | InputType::SSA => {
             debug!("generating mlir for {:?} on {:?}", input, thread_id);
             let module = db.input_ssa(input, app)?;
             let codemap = db.codemap();
@@ -13,6 +13,7 @@ use firefly_syntax_ssa as syntax_ssa;
 
 use super::queries;
 use crate::diagnostics::ErrorReported;
+use crate::incremental::SourceFingerprint;
 use crate::interner::*;
 use crate::output::CompilerOutput;
 
@@ -57,6 +58,12 @@ pub trait Parser: CompilerOutput {
     #[salsa::invoke(queries::input_ast)]
     fn input_ast(&self, input: InternedInput) -> Result<syntax_erl::Module, ErrorReported>;
 
+    /// Gets a fingerprint of the sources of the given input, including any files it includes
+    ///
+    /// This is used to determine whether artifacts from a previous compilation can be reused
+    #[salsa::invoke(queries::input_fingerprint)]
+    fn input_fingerprint(&self, input: InternedInput) -> Result<SourceFingerprint, ErrorReported>;
+
     /// Gets the syntax_core module associated with the given input, if it exists
     ///
     /// If the input is not compatible with producing a syntax_core module, or an
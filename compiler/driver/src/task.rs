@@ -1,17 +1,31 @@
 use std::future::Future;
+use std::iter;
 use std::panic::{resume_unwind, AssertUnwindSafe};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::thread;
 
-use crossbeam::channel;
+use crossbeam::deque::{Injector, Steal, Stealer, Worker};
 
 use futures::executor;
 use futures::future::FutureExt;
 
 use lazy_static::lazy_static;
 
+use parking_lot::{Condvar, Mutex};
+
+/// The number of worker threads in the pool, zero meaning one per core
+static THREADS: AtomicUsize = AtomicUsize::new(1);
+
+/// Sets the number of worker threads in the pool, with zero using one per core
+///
+/// This only has an effect if called before the first task is spawned.
+pub fn set_threads(threads: usize) {
+    THREADS.store(threads, Ordering::Relaxed);
+}
+
 /// Spawns a future on the thread pool
 ///
 /// The returned handle can be used to await the output of the future with `join`
@@ -21,7 +35,13 @@ where
     R: Send + 'static,
 {
     lazy_static! {
-        static ref SCHEDULER: Scheduler = Scheduler::new(1);
+        static ref SCHEDULER: Scheduler = {
+            let threads = match THREADS.load(Ordering::Relaxed) {
+                0 => num_cpus::get(),
+                n => n,
+            };
+            Scheduler::new(threads)
+        };
     }
 
     SCHEDULER.spawn(future)
@@ -36,44 +56,85 @@ where
     executor::block_on(handle)
 }
 
-enum Message {
-    Run(async_task::Task<()>),
-    Close,
-}
+type Task = async_task::Task<()>;
 
+/// A work-stealing scheduler
+///
+/// Newly scheduled tasks are pushed on a global queue, from which idle workers take batches
+/// into their own local queue. Workers which find both their local queue and the global queue
+/// empty steal from the other workers, and only go to sleep once there is nothing left to steal.
 struct Scheduler {
-    size: usize,
-    threads: Arc<Vec<ThreadState>>,
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    injector: Injector<Task>,
+    stealers: Vec<Stealer<Task>>,
+    sleep: Mutex<()>,
+    wakeup: Condvar,
+    shutdown: AtomicBool,
 }
+impl Shared {
+    fn push(&self, task: Task) {
+        self.injector.push(task);
+        // Acquiring the lock ensures a worker which found no work cannot miss this wakeup
+        let _guard = self.sleep.lock();
+        self.wakeup.notify_one();
+    }
+
+    fn find_task(&self, local: &Worker<Task>) -> Option<Task> {
+        local.pop().or_else(|| {
+            iter::repeat_with(|| {
+                self.injector
+                    .steal_batch_and_pop(local)
+                    .or_else(|| self.stealers.iter().map(|s| s.steal()).collect())
+            })
+            .find(|s| !s.is_retry())
+            .and_then(|s| s.success())
+        })
+    }
 
-struct ThreadState {
-    tx: Arc<channel::Sender<Message>>,
+    fn run(&self, local: Worker<Task>) {
+        let _scope = executor::enter().unwrap();
+        loop {
+            if let Some(task) = self.find_task(&local) {
+                task.run();
+                continue;
+            }
+
+            let mut guard = self.sleep.lock();
+            if self.shutdown.load(Ordering::Acquire) {
+                return;
+            }
+            // Check again while holding the lock, as a task may have been pushed in the meantime
+            if self.injector.is_empty() {
+                self.wakeup.wait(&mut guard);
+            }
+        }
+    }
 }
 
 impl Scheduler {
     pub fn new(size: usize) -> Self {
-        let mut threads = Vec::with_capacity(size);
-        for _ in 0..size {
-            let (tx, rx) = channel::unbounded();
-
-            thread::spawn(move || {
-                let _scope = executor::enter().unwrap();
-                // Keep taking the task from the channel and running it until completion.
-                for message in rx {
-                    match message {
-                        Message::Run(task) => task.run(),
-                        Message::Close => return,
-                    }
-                }
-            });
-
-            threads.push(ThreadState { tx: Arc::new(tx) });
+        let workers = (0..size).map(|_| Worker::new_fifo()).collect::<Vec<_>>();
+        let stealers = workers.iter().map(|w| w.stealer()).collect();
+        let shared = Arc::new(Shared {
+            injector: Injector::new(),
+            stealers,
+            sleep: Mutex::new(()),
+            wakeup: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+        });
+
+        for (index, local) in workers.into_iter().enumerate() {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name(format!("firefly-worker-{}", index))
+                .spawn(move || shared.run(local))
+                .unwrap();
         }
 
-        Self {
-            size,
-            threads: Arc::new(threads),
-        }
+        Self { shared }
     }
 
     /// Spawns a future on the thread pool
@@ -84,23 +145,19 @@ impl Scheduler {
         F: Future<Output = R> + Send + 'static,
         R: Send + 'static,
     {
-        use rand::distributions::{Distribution, Uniform};
-
-        // Select thread
-        let range = Uniform::from(0..self.size);
-        let mut rng = rand::thread_rng();
-        let index = range.sample(&mut rng);
-        let thread = &self.threads[index];
-
         // Wrap the future into one that disconnects the channel on completion.
         let future = AssertUnwindSafe(future).catch_unwind();
 
-        // Create a task that is scheduled by sending itself into the channel.
-        let tx = Arc::downgrade(&thread.tx);
-        let schedule = move |t| tx.upgrade().unwrap().send(Message::Run(t)).unwrap();
+        // Create a task that is scheduled by pushing itself on the global queue.
+        let shared = Arc::downgrade(&self.shared);
+        let schedule = move |t| {
+            if let Some(shared) = shared.upgrade() {
+                shared.push(t);
+            }
+        };
         let (task, handle) = async_task::spawn(future, schedule, ());
 
-        // Schedule the task by sending it into the channel.
+        // Schedule the task by pushing it on the global queue.
         task.schedule();
 
         // Wrap the handle in one that propagates panics
@@ -109,9 +166,9 @@ impl Scheduler {
 }
 impl Drop for Scheduler {
     fn drop(&mut self) {
-        for thread in self.threads.iter() {
-            thread.tx.send(Message::Close).unwrap();
-        }
+        let _guard = self.shared.sleep.lock();
+        self.shared.shutdown.store(true, Ordering::Release);
+        self.shared.wakeup.notify_all();
     }
 }
 
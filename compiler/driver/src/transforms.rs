@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use lazy_static::lazy_static;
+use parking_lot::RwLock;
+
+use firefly_diagnostics::{Reporter, Spanned};
+use firefly_intern::Symbol;
+use firefly_syntax_erl::passes::{MsTransform, ParseTransform};
+use firefly_syntax_erl::Module;
+
+lazy_static! {
+    static ref PARSE_TRANSFORMS: RwLock<HashMap<Symbol, Arc<dyn ParseTransform>>> = {
+        // These are the transforms built in to the compiler
+        let builtins: [Arc<dyn ParseTransform>; 1] = [Arc::new(MsTransform)];
+        let transforms = builtins
+            .into_iter()
+            .map(|transform| (transform.name(), transform))
+            .collect();
+        RwLock::new(transforms)
+    };
+}
+
+/// Registers a parse transform with the compiler, making it available to modules which
+/// request it via `-compile({parse_transform, Name})`.
+///
+/// If a transform with the same name was already registered, it is replaced.
+pub fn register_parse_transform(transform: Arc<dyn ParseTransform>) {
+    let mut transforms = PARSE_TRANSFORMS.write();
+    transforms.insert(transform.name(), transform);
+}
+
+/// Applies the parse transforms requested by `module`, in the order they were declared
+pub(crate) fn apply_parse_transforms(
+    mut module: Module,
+    reporter: &Reporter,
+) -> anyhow::Result<Module> {
+    let requested = match module.compile.as_ref() {
+        None => return Ok(module),
+        Some(options) => options.parse_transforms.clone(),
+    };
+
+    for name in requested.iter() {
+        let transform = PARSE_TRANSFORMS.read().get(&name.item).cloned();
+        match transform {
+            Some(transform) => {
+                module = transform.transform(module, reporter)?;
+            }
+            None => {
+                reporter.show_error(
+                    "undefined parse transform",
+                    &[(
+                        name.span(),
+                        "no parse transform with this name is registered with the compiler",
+                    )],
+                );
+                anyhow::bail!("undefined parse transform '{}'", name.item);
+            }
+        }
+    }
+
+    Ok(module)
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use parking_lot::Mutex;
+
+    use firefly_diagnostics::{CodeMap, Reporter, Severity};
+    use firefly_intern::Symbol;
+    use firefly_parser::Parser;
+    use firefly_syntax_erl::passes::ParseTransform;
+    use firefly_syntax_erl::{Module, ParseConfig};
+
+    /// A transform which records the order in which it was applied
+    struct Record {
+        name: Symbol,
+        applied: Arc<Mutex<Vec<Symbol>>>,
+    }
+    impl ParseTransform for Record {
+        fn name(&self) -> Symbol {
+            self.name
+        }
+
+        fn transform(&self, module: Module, _reporter: &Reporter) -> anyhow::Result<Module> {
+            self.applied.lock().push(self.name);
+            Ok(module)
+        }
+    }
+
+    fn parse(source: &str) -> Module {
+        let parser = Parser::new(ParseConfig::default(), Arc::new(CodeMap::new()));
+        match parser.parse_string::<Module, _, _>(Reporter::new(), source) {
+            Ok(module) => module,
+            Err(err) => panic!("failed to parse test module: {}", err),
+        }
+    }
+
+    #[test]
+    fn ms_transform_is_builtin() {
+        assert!(super::PARSE_TRANSFORMS
+            .read()
+            .contains_key(&Symbol::intern("ms_transform")));
+    }
+
+    #[test]
+    fn transforms_are_applied_in_declaration_order() {
+        // The registry is global, so these names must not be used by any other test
+        let applied = Arc::new(Mutex::new(Vec::new()));
+        for name in ["order_second", "order_first"] {
+            super::register_parse_transform(Arc::new(Record {
+                name: Symbol::intern(name),
+                applied: applied.clone(),
+            }));
+        }
+
+        let module = parse(
+            "-module(test).
+-compile({parse_transform, order_first}).
+-compile({parse_transform, order_second}).
+",
+        );
+        let reporter = Reporter::new();
+        super::apply_parse_transforms(module, &reporter).unwrap();
+
+        let expected = vec![
+            Symbol::intern("order_first"),
+            Symbol::intern("order_second"),
+        ];
+        assert_eq!(*applied.lock(), expected);
+        assert!(reporter.diagnostics().is_empty());
+    }
+
+    #[test]
+    fn undefined_parse_transform() {
+        let applied = Arc::new(Mutex::new(Vec::new()));
+        super::register_parse_transform(Arc::new(Record {
+            name: Symbol::intern("undefined_first"),
+            applied: applied.clone(),
+        }));
+
+        let module = parse(
+            "-module(test).
+-compile({parse_transform, undefined_first}).
+-compile({parse_transform, no_such_transform}).
+",
+        );
+        let reporter = Reporter::new();
+        let err = super::apply_parse_transforms(module, &reporter).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "undefined parse transform 'no_such_transform'"
+        );
+
+        // Transforms declared before the undefined one are still applied
+        assert_eq!(*applied.lock(), vec![Symbol::intern("undefined_first")]);
+        let errors = reporter
+            .diagnostics()
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+            .map(|diagnostic| diagnostic.message.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec!["undefined parse transform".to_string()]);
+    }
+}
@@ -11,6 +11,8 @@ const ENV_LLVM_LINK_LLVM_DYLIB: &'static str = "LLVM_LINK_LLVM_DYLIB";
 const ENV_FIREFLY_LLVM_LTO: &'static str = "FIREFLY_LLVM_LTO";
 const ENV_LLVM_USE_SANITIZER: &'static str = "LLVM_USE_SANITIZER";
 
+const LLD_LIBS: &[&str] = &["lldELF", "lldWasm", "lldCommon"];
+
 fn main() {
     let cwd = env::current_dir().unwrap();
     let llvm_prefix = detect_llvm_prefix();
@@ -57,6 +59,8 @@ fn main() {
         "asmparser",
         "lto",
         "instrumentation",
+        "option",
+        "textapi",
         //"orcjit",
     ];
 
@@ -125,6 +129,20 @@ fn main() {
         cfg.debug(false);
     }
 
+    // The embedded linker is optional, as not every LLVM distribution ships the lld libraries
+    let has_lld = LLD_LIBS.iter().all(|name| {
+        let lib = if target.contains("windows") {
+            format!("{}.lib", name)
+        } else {
+            format!("lib{}.a", name)
+        };
+        llvm_lib_dir.join(lib).exists()
+    });
+    println!("cargo:lld={}", has_lld);
+    if has_lld {
+        cfg.define("FIREFLY_HAS_LLD", None);
+    }
+
     let include_dir = cwd.join("c_src/include");
     println!("cargo:include={}", include_dir.display());
 
@@ -136,6 +154,8 @@ fn main() {
        .file("c_src/Diagnostics.cpp")
        .file("c_src/ErrorHandling.cpp")
        .file("c_src/IR.cpp")
+       .file("c_src/Linker.cpp")
+       .file("c_src/LTO.cpp")
        //.file("c_src/Orc.cpp")
        .file("c_src/Passes.cpp")
        .file("c_src/Target.cpp")
@@ -157,6 +177,16 @@ fn main() {
         llvm_lib_dir.as_path().display()
     );
 
+    // The embedded linker, these must precede the LLVM libraries they depend on
+    //
+    // lld is always built as a set of static libraries when LLVM is linked as a dylib
+    if has_lld {
+        let lld_kind = if link_llvm_dylib { "static" } else { llvm_kind };
+        for name in LLD_LIBS {
+            println!("cargo:rustc-link-lib={}={}", lld_kind, name);
+        }
+    }
+
     if !link_static && link_llvm_dylib {
         println!("cargo:rustc-link-lib=dylib=LLVM");
     } else {
@@ -0,0 +1,294 @@
+#include "mlir-c/Support.h"
+#include "mlir/CAPI/Support.h"
+#include "llvm-c/TargetMachine.h"
+#include "llvm/ADT/SmallVector.h"
+#include "llvm/ADT/StringSet.h"
+#include "llvm/Analysis/ModuleSummaryAnalysis.h"
+#include "llvm/Analysis/ProfileSummaryInfo.h"
+#include "llvm/BinaryFormat/Magic.h"
+#include "llvm/Bitcode/BitcodeReader.h"
+#include "llvm/Bitcode/BitcodeWriter.h"
+#include "llvm/IR/LLVMContext.h"
+#include "llvm/IR/Module.h"
+#include "llvm/LTO/Config.h"
+#include "llvm/LTO/LTO.h"
+#include "llvm/Object/Archive.h"
+#include "llvm/Object/IRObjectFile.h"
+#include "llvm/Support/CBindingWrapping.h"
+#include "llvm/Support/Caching.h"
+#include "llvm/Support/Error.h"
+#include "llvm/Support/FileSystem.h"
+#include "llvm/Support/MemoryBuffer.h"
+#include "llvm/Support/SmallVectorMemoryBuffer.h"
+#include "llvm/Support/Threading.h"
+#include "llvm/Support/raw_ostream.h"
+#include "llvm/Target/TargetMachine.h"
+#include "llvm/Transforms/Utils/ModuleUtils.h"
+
+#include <memory>
+#include <string>
+#include <vector>
+
+using namespace llvm;
+
+DEFINE_STDCXX_CONVERSION_FUNCTIONS(TargetMachine, LLVMTargetMachineRef);
+
+struct FireflyLTOConfig {
+  LLVMTargetMachineRef targetMachine;
+  unsigned optLevel;
+  unsigned threads;
+  bool thin;
+  bool debug;
+  bool verify;
+};
+
+struct FireflyLTOInput {
+  MlirStringRef path;
+  // When true, definitions in this input are internalized unless preserved
+  bool internalize;
+};
+
+typedef void (*FireflyLTOOutputCallback)(void *context, MlirStringRef path);
+
+namespace {
+/// Drives a link-time optimization session over a set of bitcode inputs
+class FireflyLTO {
+public:
+  FireflyLTO(const FireflyLTOConfig &config, lto::Config conf,
+             lto::ThinBackend backend)
+      : thin(config.thin), lto(std::move(conf), std::move(backend)) {}
+
+  void preserve(StringRef name) { preserved.insert(name); }
+
+  /// Adds a bitcode file, object file with embedded bitcode, or an archive
+  /// of either to this session.
+  ///
+  /// Archive members which do not contain bitcode are set aside, and written
+  /// out alongside the optimized objects, so that the archive as a whole is
+  /// accounted for and need not be given to the linker.
+  Error addFile(StringRef path, bool internalize) {
+    auto bufferOr = MemoryBuffer::getFile(path, /*IsText=*/false,
+                                          /*RequiresNullTerminator=*/false);
+    if (!bufferOr)
+      return errorCodeToError(bufferOr.getError());
+
+    MemoryBufferRef buffer = (*bufferOr)->getMemBufferRef();
+    buffers.push_back(std::move(*bufferOr));
+
+    if (identify_magic(buffer.getBuffer()) != file_magic::archive) {
+      auto bitcodeOr = object::IRObjectFile::findBitcodeInMemBuffer(buffer);
+      if (!bitcodeOr)
+        return bitcodeOr.takeError();
+      return addBitcode(*bitcodeOr, internalize);
+    }
+
+    auto archiveOr = object::Archive::create(buffer);
+    if (!archiveOr)
+      return archiveOr.takeError();
+
+    Error err = Error::success();
+    for (const object::Archive::Child &child : (*archiveOr)->children(err)) {
+      auto memberOr = child.getMemoryBufferRef();
+      if (!memberOr) {
+        consumeError(std::move(err));
+        return memberOr.takeError();
+      }
+      auto nameOr = child.getName();
+      if (!nameOr) {
+        consumeError(std::move(err));
+        return nameOr.takeError();
+      }
+      auto bitcodeOr = object::IRObjectFile::findBitcodeInMemBuffer(*memberOr);
+      if (!bitcodeOr) {
+        consumeError(bitcodeOr.takeError());
+        if (isNativeObject(*memberOr))
+          natives.push_back(*memberOr);
+        continue;
+      }
+      // Module identifiers must be unique across all inputs, so qualify
+      // members with the archive they came from
+      std::string id = (path + "(" + *nameOr + ")").str();
+      buffers.push_back(MemoryBuffer::getMemBuffer(
+          bitcodeOr->getBuffer(), id, /*RequiresNullTerminator=*/false));
+      if (auto addErr =
+              addBitcode(buffers.back()->getMemBufferRef(), internalize)) {
+        consumeError(std::move(err));
+        return addErr;
+      }
+    }
+    return err;
+  }
+
+  Error run(StringRef outputPrefix, FireflyLTOOutputCallback onOutput,
+            void *context) {
+    std::vector<std::string> outputs(lto.getMaxTasks());
+    auto addStream =
+        [&](size_t task) -> Expected<std::unique_ptr<CachedFileStream>> {
+      std::string path = (outputPrefix + "." + Twine(task) + ".o").str();
+      std::error_code ec;
+      auto os = std::make_unique<raw_fd_ostream>(path, ec, sys::fs::OF_None);
+      if (ec)
+        return errorCodeToError(ec);
+      outputs[task] = path;
+      return std::make_unique<CachedFileStream>(std::move(os), path);
+    };
+
+    if (auto err = lto.run(addStream))
+      return err;
+
+    for (size_t i = 0; i < natives.size(); ++i) {
+      std::string path = (outputPrefix + ".native." + Twine(i) + ".o").str();
+      std::error_code ec;
+      raw_fd_ostream os(path, ec, sys::fs::OF_None);
+      if (ec)
+        return errorCodeToError(ec);
+      os << natives[i].getBuffer();
+      outputs.push_back(path);
+    }
+
+    for (const std::string &path : outputs) {
+      if (!path.empty())
+        onOutput(context, wrap(StringRef(path)));
+    }
+    return Error::success();
+  }
+
+private:
+  static bool isNativeObject(MemoryBufferRef buffer) {
+    switch (identify_magic(buffer.getBuffer())) {
+    case file_magic::elf_relocatable:
+    case file_magic::macho_object:
+    case file_magic::coff_object:
+    case file_magic::wasm_object:
+      return true;
+    default:
+      return false;
+    }
+  }
+
+  Error addBitcode(MemoryBufferRef buffer, bool internalize) {
+    auto summarizedOr = ensureSummary(buffer);
+    if (!summarizedOr)
+      return summarizedOr.takeError();
+
+    auto inputOr = lto::InputFile::create(*summarizedOr);
+    if (!inputOr)
+      return inputOr.takeError();
+    std::unique_ptr<lto::InputFile> input = std::move(*inputOr);
+
+    std::vector<lto::SymbolResolution> resolutions;
+    for (const lto::InputFile::Symbol &sym : input->symbols()) {
+      lto::SymbolResolution res;
+      if (!sym.isUndefined()) {
+        StringRef name = sym.getName();
+        // The first definition of a symbol wins, which is what the linker
+        // does with linkonce_odr definitions shared between modules
+        res.Prevailing = prevailing.insert(name).second;
+        // Symbols placed in an explicit section (e.g. the atom and dispatch
+        // tables) are discovered by the runtime via the section bounds, so
+        // they must survive internalization
+        res.VisibleToRegularObj = !internalize || preserved.contains(name) ||
+                                  sym.isUsed() || !sym.getSectionName().empty();
+      }
+      resolutions.push_back(res);
+    }
+    return lto.add(std::move(input), resolutions);
+  }
+
+  /// ThinLTO requires a module summary index, which is only computed when
+  /// the bitcode was produced for ThinLTO, so build one if it is missing
+  Expected<MemoryBufferRef> ensureSummary(MemoryBufferRef buffer) {
+    if (!thin)
+      return buffer;
+
+    auto infoOr = getBitcodeLTOInfo(buffer);
+    if (!infoOr)
+      return infoOr.takeError();
+    if (infoOr->HasSummary)
+      return buffer;
+
+    LLVMContext context;
+    auto moduleOr = parseBitcodeFile(buffer, context);
+    if (!moduleOr)
+      return moduleOr.takeError();
+    Module &module = **moduleOr;
+    nameUnamedGlobals(module);
+
+    ProfileSummaryInfo psi(module);
+    ModuleSummaryIndex index = buildModuleSummaryIndex(module, nullptr, &psi);
+    SmallVector<char, 0> data;
+    raw_svector_ostream os(data);
+    WriteBitcodeToFile(module, os, /*ShouldPreserveUseListOrder=*/false,
+                       &index);
+
+    buffers.push_back(std::make_unique<SmallVectorMemoryBuffer>(
+        std::move(data), buffer.getBufferIdentifier(),
+        /*RequiresNullTerminator=*/false));
+    return buffers.back()->getMemBufferRef();
+  }
+
+  bool thin;
+  lto::LTO lto;
+  StringSet<> preserved;
+  StringSet<> prevailing;
+  // Archive members without bitcode, which are passed through to the linker
+  std::vector<MemoryBufferRef> natives;
+  // Inputs reference these buffers, so they must outlive the session
+  std::vector<std::unique_ptr<MemoryBuffer>> buffers;
+};
+} // namespace
+
+extern "C" bool
+LLVMFireflyRunLTO(const FireflyLTOConfig *config, const FireflyLTOInput *inputs,
+                  unsigned numInputs, const MlirStringRef *preserved,
+                  unsigned numPreserved, MlirStringRef outputPrefix,
+                  FireflyLTOOutputCallback onOutput, void *context,
+                  char **errorMessage) {
+  TargetMachine *targetMachine = unwrap(config->targetMachine);
+
+  // Mirror the configuration of the target machine used for each module
+  lto::Config conf;
+  conf.CPU = targetMachine->getTargetCPU().str();
+  SmallVector<StringRef, 8> features;
+  targetMachine->getTargetFeatureString().split(features, ',', -1, false);
+  for (StringRef feature : features)
+    conf.MAttrs.push_back(feature.str());
+  conf.Options = targetMachine->Options;
+  conf.RelocModel = targetMachine->getRelocationModel();
+  conf.CodeModel = targetMachine->getCodeModel();
+  conf.CGOptLevel = targetMachine->getOptLevel();
+  conf.OptLevel = config->optLevel;
+  conf.DisableVerify = !config->verify;
+  conf.DebugPassManager = config->debug;
+
+  lto::ThinBackend backend = nullptr;
+  if (config->thin)
+    backend = lto::createInProcessThinBackend(
+        heavyweight_hardware_concurrency(config->threads));
+
+  FireflyLTO session(*config, std::move(conf), std::move(backend));
+  for (unsigned i = 0; i < numPreserved; ++i)
+    session.preserve(unwrap(preserved[i]));
+
+  auto fail = [&](StringRef path, Error err) {
+    std::string message;
+    raw_string_ostream os(message);
+    if (!path.empty())
+      os << path << ": ";
+    os << toString(std::move(err));
+    *errorMessage = strdup(os.str().c_str());
+    return true;
+  };
+
+  for (unsigned i = 0; i < numInputs; ++i) {
+    StringRef path = unwrap(inputs[i].path);
+    if (auto err = session.addFile(path, inputs[i].internalize))
+      return fail(path, std::move(err));
+  }
+
+  if (auto err = session.run(unwrap(outputPrefix), onOutput, context))
+    return fail(StringRef(), std::move(err));
+
+  *errorMessage = nullptr;
+  return false;
+}
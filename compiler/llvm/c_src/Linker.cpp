@@ -0,0 +1,124 @@
+#if defined(FIREFLY_HAS_LLD)
+#include "lld/Common/CommonLinkerContext.h"
+#include "lld/Common/Driver.h"
+#endif
+#include "llvm/ADT/ArrayRef.h"
+#include "llvm/ADT/SmallVector.h"
+#include "llvm/ADT/StringRef.h"
+#include "llvm/ADT/StringSwitch.h"
+#include "llvm/Support/CrashRecoveryContext.h"
+#include "llvm/Support/Path.h"
+#include "llvm/Support/raw_ostream.h"
+
+#if defined(_WIN32)
+#include "firefly/llvm/raw_win32_handle_ostream.h"
+#endif
+
+#include <mutex>
+
+using namespace llvm;
+
+namespace {
+enum class Flavor { Invalid, Gnu, Darwin, Wasm, WinLink };
+
+Flavor getFlavor(StringRef s) {
+  return StringSwitch<Flavor>(s)
+      .CasesLower("ld", "ld.lld", "gnu", Flavor::Gnu)
+      .CasesLower("ld64", "ld64.lld", "darwin", Flavor::Darwin)
+      .CasesLower("wasm", "wasm-ld", Flavor::Wasm)
+      .CasesLower("link", "lld-link", Flavor::WinLink)
+      .Default(Flavor::Invalid);
+}
+
+/// Determines the linker flavor from either an explicit `-flavor` argument
+/// following the program name, or the name of the program itself
+Flavor parseFlavor(SmallVectorImpl<const char *> &args) {
+  if (args.size() > 2 && StringRef(args[1]) == "-flavor") {
+    Flavor f = getFlavor(args[2]);
+    args.erase(args.begin() + 1, args.begin() + 3);
+    return f;
+  }
+  return getFlavor(sys::path::stem(args[0]));
+}
+
+// lld keeps its state in globals, so only one link may be running at a time
+std::mutex linkerMutex;
+} // namespace
+
+/// Returns true if the lld libraries were found and linked in at build time
+extern "C" bool LLVMFireflyHasLinker() {
+#if defined(FIREFLY_HAS_LLD)
+  return true;
+#else
+  return false;
+#endif
+}
+
+#if defined(_WIN32)
+extern "C" bool LLVMFireflyLink(int argc, const char **argv, HANDLE outHandle,
+                                HANDLE errHandle) {
+  raw_win32_handle_ostream stdoutOS(outHandle, /*shouldClose=*/false);
+  raw_win32_handle_ostream stderrOS(errHandle, /*shouldClose=*/false);
+#else
+extern "C" bool LLVMFireflyLink(int argc, const char **argv, int outFd,
+                                int errFd) {
+  raw_fd_ostream stdoutOS(outFd, /*shouldClose=*/false);
+  raw_fd_ostream stderrOS(errFd, /*shouldClose=*/false);
+#endif
+#if !defined(FIREFLY_HAS_LLD)
+  stderrOS << "error: this compiler was built without the embedded linker\n";
+  return false;
+#else
+  SmallVector<const char *, 64> args(argv, argv + argc);
+  if (args.empty())
+    return false;
+
+  Flavor flavor = parseFlavor(args);
+  if (flavor == Flavor::Invalid) {
+    stderrOS << "error: unable to determine the lld flavor from '" << argv[0]
+             << "'\n";
+    return false;
+  }
+  // The COFF and Mach-O drivers are not linked in, MSVC and Apple targets use
+  // the system linker
+  if (flavor == Flavor::WinLink) {
+    stderrOS << "error: the embedded linker does not support COFF targets\n";
+    return false;
+  }
+  if (flavor == Flavor::Darwin) {
+    stderrOS << "error: the embedded linker does not support Mach-O targets\n";
+    return false;
+  }
+
+  std::lock_guard<std::mutex> guard(linkerMutex);
+
+  // A fatal error in lld exits the process, unless running inside a recovery
+  // context, in which case control returns here instead
+  bool ok = false;
+  CrashRecoveryContext crc;
+  bool completed = crc.RunSafely([&]() {
+    switch (flavor) {
+    case Flavor::Gnu:
+      ok = lld::elf::link(args, stdoutOS, stderrOS, /*exitEarly=*/false,
+                          /*disableOutput=*/false);
+      break;
+    case Flavor::Wasm:
+      ok = lld::wasm::link(args, stdoutOS, stderrOS, /*exitEarly=*/false,
+                           /*disableOutput=*/false);
+      break;
+    case Flavor::Darwin:
+    case Flavor::WinLink:
+    case Flavor::Invalid:
+      llvm_unreachable("invalid flavor");
+    }
+  });
+  stdoutOS.flush();
+  stderrOS.flush();
+
+  // Reset lld to a pristine state so that it may be invoked again
+  if (completed)
+    lld::CommonLinkerContext::destroy();
+
+  return completed && ok;
+#endif
+}
@@ -217,6 +217,8 @@ struct OptimizerConfig {
   bool emitSummaryIndex;
   bool emitModuleHash;
   bool preserveUseListOrder;
+  const char *pgoGenPath;
+  const char *pgoUsePath;
   void *profiler;
   LLVMFireflySelfProfileBeforePassCallback beforePass;
   LLVMFireflySelfProfileAfterPassCallback afterPass;
@@ -250,8 +252,18 @@ extern "C" bool LLVMFireflyOptimize(LLVMModuleRef m, LLVMTargetMachineRef tm,
                                        config.afterPass);
   }
 
+  // Configure profile-guided optimization, either instrumenting the module to
+  // generate profiles, or using previously generated profiles
+  llvm::Optional<PGOOptions> pgoOpt;
+  if (config.pgoGenPath) {
+    assert(!config.pgoUsePath);
+    pgoOpt = PGOOptions(config.pgoGenPath, "", "", PGOOptions::IRInstr);
+  } else if (config.pgoUsePath) {
+    pgoOpt = PGOOptions(config.pgoUsePath, "", "", PGOOptions::IRUse);
+  }
+
   // Populate the analysis managers with their respective passes
-  PassBuilder pb(targetMachine, tuningOpts, llvm::None, &pic);
+  PassBuilder pb(targetMachine, tuningOpts, pgoOpt, &pic);
 
   LoopAnalysisManager lam;
   FunctionAnalysisManager fam;
@@ -399,9 +411,11 @@ extern "C" bool LLVMFireflyOptimize(LLVMModuleRef m, LLVMTargetMachineRef tm,
       mpm.addPass(llvm::CanonicalizeAliasesPass());
       mpm.addPass(llvm::NameAnonGlobalPass());
     }
-
-    mpm.run(*mod, mam);
   }
 
+  // The pipeline must run even at O0, as the statepoints required by the
+  // garbage collector, and any profile instrumentation, are produced by it
+  mpm.run(*mod, mam);
+
   return false;
 }
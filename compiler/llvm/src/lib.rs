@@ -14,6 +14,7 @@ pub mod debuginfo;
 pub mod diagnostics;
 pub mod ir;
 //pub mod jit;
+pub mod lto;
 pub mod passes;
 pub mod profiling;
 pub mod support;
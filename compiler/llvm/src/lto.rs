@@ -0,0 +1,153 @@
+///! A wrapper around LLVM's link-time optimizer
+///!
+///! This drives the same machinery used by lld when linking bitcode: inputs are merged into a
+///! single module (or in the case of ThinLTO, optimized in parallel with cross-module imports),
+///! unexported definitions are internalized, and the result is emitted as one or more objects.
+use std::ffi::c_void;
+use std::mem::MaybeUninit;
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+
+use firefly_session::{Lto, Options};
+
+use crate::codegen::{self, CodeGenOptLevel};
+use crate::support::*;
+use crate::target::TargetMachine;
+
+#[repr(C)]
+struct LtoConfig {
+    target_machine: TargetMachine,
+    opt_level: u32,
+    threads: u32,
+    thin: bool,
+    debug: bool,
+    verify: bool,
+}
+
+#[repr(C)]
+struct LtoInput {
+    path: StringRef,
+    internalize: bool,
+}
+
+/// Represents a link-time optimization session over a set of bitcode inputs
+pub struct LinkTimeOptimizer {
+    config: LtoConfig,
+    inputs: Vec<(PathBuf, bool)>,
+    preserved: Vec<String>,
+}
+impl LinkTimeOptimizer {
+    /// Creates a new session using the LTO mode and optimization level from `options`
+    pub fn new(options: &Options, target_machine: TargetMachine) -> Self {
+        let (speed, _size) = codegen::to_llvm_opt_settings(options.opt_level);
+        let opt_level = match speed {
+            CodeGenOptLevel::Less => 1,
+            CodeGenOptLevel::Default => 2,
+            CodeGenOptLevel::Aggressive => 3,
+            _ => 0,
+        };
+        let thin = match options.lto() {
+            Lto::Thin | Lto::ThinLocal => true,
+            Lto::Fat | Lto::No => false,
+        };
+        Self {
+            config: LtoConfig {
+                target_machine,
+                opt_level,
+                threads: 0,
+                thin,
+                debug: options.debug_assertions,
+                verify: options.debugging_opts.verify_llvm_ir,
+            },
+            inputs: vec![],
+            preserved: vec![],
+        }
+    }
+
+    /// Sets the number of threads used for ThinLTO backends, 0 lets LLVM decide
+    pub fn threads(&mut self, threads: u32) {
+        self.config.threads = threads;
+    }
+
+    /// Adds bitcode for a module whose definitions may be internalized
+    pub fn add_module(&mut self, path: &Path) {
+        self.inputs.push((path.to_path_buf(), true));
+    }
+
+    /// Adds bitcode whose definitions must remain visible to native objects, e.g. the runtime
+    ///
+    /// The path may refer to a bitcode file, an object with embedded bitcode, or an archive of
+    /// them. Archive members without bitcode are extracted, and returned from `run` along with
+    /// the optimized objects, so the archive need not be passed to the linker.
+    pub fn add_external(&mut self, path: &Path) {
+        self.inputs.push((path.to_path_buf(), false));
+    }
+
+    /// Prevents the given symbol from being internalized
+    pub fn preserve_symbol<S: Into<String>>(&mut self, name: S) {
+        self.preserved.push(name.into());
+    }
+
+    /// Runs the optimizer, emitting objects to files named `<prefix>.<N>.o`
+    ///
+    /// Returns the paths of all objects which were produced, including any native objects
+    /// extracted from archives, which are named `<prefix>.native.<N>.o`
+    pub fn run(&self, output_prefix: &Path) -> anyhow::Result<Vec<PathBuf>> {
+        extern "C" {
+            fn LLVMFireflyRunLTO(
+                config: *const LtoConfig,
+                inputs: *const LtoInput,
+                num_inputs: u32,
+                preserved: *const StringRef,
+                num_preserved: u32,
+                output_prefix: StringRef,
+                on_output: unsafe extern "C" fn(*mut c_void, StringRef),
+                context: *mut c_void,
+                error: *mut *mut std::os::raw::c_char,
+            ) -> bool;
+        }
+
+        unsafe extern "C" fn on_output(context: *mut c_void, path: StringRef) {
+            let outputs = &mut *(context as *mut Vec<PathBuf>);
+            let path: &Path = path.try_into().unwrap();
+            outputs.push(path.to_path_buf());
+        }
+
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|(path, internalize)| LtoInput {
+                path: StringRef::from(path.as_path()),
+                internalize: *internalize,
+            })
+            .collect::<Vec<_>>();
+        let preserved = self
+            .preserved
+            .iter()
+            .map(|name| StringRef::from(name.as_str()))
+            .collect::<Vec<_>>();
+
+        let mut outputs: Vec<PathBuf> = vec![];
+        let mut error = MaybeUninit::<*mut std::os::raw::c_char>::uninit();
+        let failed = unsafe {
+            LLVMFireflyRunLTO(
+                &self.config,
+                inputs.as_ptr(),
+                inputs.len().try_into().unwrap(),
+                preserved.as_ptr(),
+                preserved.len().try_into().unwrap(),
+                StringRef::from(output_prefix),
+                on_output,
+                &mut outputs as *mut _ as *mut c_void,
+                error.as_mut_ptr(),
+            )
+        };
+        if failed {
+            let error = unsafe { OwnedStringRef::from_ptr(error.assume_init()) };
+            Err(anyhow!("link-time optimization failed: {}", &error))
+        } else {
+            Ok(outputs)
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use firefly_pass::Pass;
-use firefly_session::{Options, Sanitizer};
+use firefly_session::{Lto, Options, Sanitizer};
 
 use crate::codegen;
 use crate::target::TargetMachine;
@@ -22,6 +22,22 @@ impl PassManagerPass {
         manager.debug(options.debug_assertions);
         manager.optimize(opt_level);
 
+        // When performing link-time optimization, leave the heavy lifting to the LTO pipeline
+        match options.lto() {
+            Lto::No => (),
+            Lto::Thin | Lto::ThinLocal => manager.stage(OptStage::PreLinkThinLTO),
+            Lto::Fat => manager.stage(OptStage::PreLinkFatLTO),
+        }
+
+        if let Some(ref dir) = options.codegen_opts.profile_generate {
+            manager.profile_generate(&dir.join("default_%m.profraw"));
+        } else if let Some(ref path) = options.codegen_opts.profile_use {
+            // Profiles only guide optimizations, so are ignored when not optimizing
+            if opt_level != PassBuilderOptLevel::O0 {
+                manager.profile_use(path);
+            }
+        }
+
         for sanitizer in &options.debugging_opts.sanitizers {
             match sanitizer {
                 Sanitizer::Memory => {
@@ -1,4 +1,6 @@
+use std::ffi::CString;
 use std::mem::MaybeUninit;
+use std::path::{Path, PathBuf};
 
 use anyhow::anyhow;
 
@@ -16,6 +18,8 @@ use super::*;
 pub struct PassManager {
     config: OptimizerConfig,
     profiler: Option<SelfProfilerRef>,
+    pgo_gen_path: Option<PathBuf>,
+    pgo_use_path: Option<PathBuf>,
 }
 impl PassManager {
     /// Create a new default pass manager
@@ -44,6 +48,18 @@ impl PassManager {
         self.config.opt_stage = stage;
     }
 
+    /// Instrument the module to write profiles to the given file when run
+    ///
+    /// The path may contain the substitutions understood by the profiler runtime, e.g. `%m`
+    pub fn profile_generate(&mut self, path: &Path) {
+        self.pgo_gen_path = Some(path.to_path_buf());
+    }
+
+    /// Use the given indexed profile (i.e. `.profdata`) to guide optimization
+    pub fn profile_use(&mut self, path: &Path) {
+        self.pgo_use_path = Some(path.to_path_buf());
+    }
+
     /// Enable the MSan sanitizer
     pub fn sanitize_memory(&mut self, track_origins: bool) {
         self.config.sanitizer_opts.memory = true;
@@ -87,6 +103,10 @@ impl PassManager {
             ) -> bool;
         }
 
+        // These must outlive the call to the optimizer, which only borrows them
+        let pgo_gen_path = self.pgo_gen_path.as_deref().map(profile_path).transpose()?;
+        let pgo_use_path = self.pgo_use_path.as_deref().map(profile_path).transpose()?;
+
         let profiler = self.profiler.clone();
         let mut llvm_profiler = profiler
             .as_ref()
@@ -98,6 +118,14 @@ impl PassManager {
                 .map(|p| p as *mut _ as *mut std::ffi::c_void)
                 .unwrap_or(std::ptr::null_mut());
             config.profiler = profiler;
+            config.pgo_gen_path = pgo_gen_path
+                .as_ref()
+                .map(|p| p.as_ptr())
+                .unwrap_or(std::ptr::null());
+            config.pgo_use_path = pgo_use_path
+                .as_ref()
+                .map(|p| p.as_ptr())
+                .unwrap_or(std::ptr::null());
             config
         };
 
@@ -114,6 +142,14 @@ impl PassManager {
     }
 }
 
+/// Converts a profile path to the form expected by the optimizer
+fn profile_path(path: &Path) -> anyhow::Result<CString> {
+    let utf8 = path
+        .to_str()
+        .ok_or_else(|| anyhow!("profile path `{}` is not valid UTF-8", path.display()))?;
+    CString::new(utf8).map_err(|_| anyhow!("profile path `{}` contains a nul byte", utf8))
+}
+
 /// Represents the set of options passed to the optimizer during codegen
 #[repr(C)]
 #[derive(Debug, Clone)]
@@ -129,6 +165,8 @@ pub struct OptimizerConfig {
     emit_summary_index: bool,
     emit_module_hash: bool,
     preserve_use_list_order: bool,
+    pgo_gen_path: *const std::os::raw::c_char,
+    pgo_use_path: *const std::os::raw::c_char,
     profiler: *mut std::ffi::c_void,
     before_pass: SelfProfileBeforePassCallback,
     after_pass: SelfProfileAfterPassCallback,
@@ -147,6 +185,8 @@ impl Default for OptimizerConfig {
             emit_summary_index: false,
             emit_module_hash: false,
             preserve_use_list_order: false,
+            pgo_gen_path: std::ptr::null(),
+            pgo_use_path: std::ptr::null(),
             profiler: std::ptr::null_mut(),
             before_pass: profiling::selfprofile_before_pass_callback,
             after_pass: profiling::selfprofile_after_pass_callback,
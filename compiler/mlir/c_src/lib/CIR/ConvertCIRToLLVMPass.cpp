@@ -153,12 +153,11 @@ public:
 
   Type getI64Type() { return IntegerType::get(&getContext(), 64); }
 
-  // NOTE: This is likely to change, so do not depend on this representation.
-  // To get stackmaps working for garbage collection, we're likely going to
-  // switch to a pointer type in a non-zero address space, and when that
-  // happens, we can't have terms somtimes represented as integers and sometimes
-  // as pointers, or it will cause gc roots to be missed.
-  Type getTermType() { return getI64Type(); }
+  // Terms are represented as pointers in address space 1, which is how the
+  // statepoint rewriting pass identifies gc roots. Terms must never be kept
+  // as integers across a call, or the root will be missed, so any integer
+  // manipulation of a term must round-trip through its raw bits.
+  Type getTermType() { return LLVM::LLVMPointerType::get(getI8Type(), 1); }
 
   // Floats are immediates on nanboxed platforms, boxed types everywhere else
   Type getFloatType() { return Float64Type::get(&getContext()); }
@@ -357,7 +356,7 @@ public:
     return traceTy;
   }
 
-  // Corresponds to Message in firefly_alloc
+  // Corresponds to Message in firefly_rt
   Type getMessageType() {
     MLIRContext *context = &getContext();
     auto messageTy =
@@ -407,7 +406,7 @@ public:
     return matchResultTy;
   }
 
-  // Corresponds to ReceiveContext in firefly_rt_minimal
+  // Corresponds to ReceiveContext in firefly_rt_tiny
   Type getRecvContextType() {
     MLIRContext *context = &getContext();
     auto recvCtxTy =
@@ -649,9 +648,27 @@ protected:
   // This function builds an LLVM::ConstantOp with a value of term type
   Value createTermConstant(OpBuilder &builder, Location loc,
                            uint64_t value) const {
-    auto termTy = getTermType();
+    Value bits = createTermBitsConstant(builder, loc, value);
+    return bitsToTerm(builder, loc, bits);
+  }
+
+  // This function builds an LLVM::ConstantOp containing the raw bits of a term,
+  // i.e. a value suitable for use with the integer ops used to (de)tag terms
+  Value createTermBitsConstant(OpBuilder &builder, Location loc,
+                               uint64_t value) const {
+    auto i64Ty = getI64Type();
     return builder.create<LLVM::ConstantOp>(
-        loc, termTy, builder.getIntegerAttr(termTy, value));
+        loc, i64Ty, builder.getIntegerAttr(i64Ty, value));
+  }
+
+  // This function converts a term to its raw bits
+  Value termToBits(OpBuilder &builder, Location loc, Value term) const {
+    return builder.create<LLVM::PtrToIntOp>(loc, getI64Type(), term);
+  }
+
+  // This function converts raw bits back to a term
+  Value bitsToTerm(OpBuilder &builder, Location loc, Value bits) const {
+    return builder.create<LLVM::IntToPtrOp>(loc, getTermType(), bits);
   }
 
   // This function builds an LLVM::GlobalOp representing a constnat value of the
@@ -988,11 +1005,12 @@ protected:
   // that.
   Value encodeLiteralPtr(OpBuilder &builder, Location loc, Value value) const {
     // TODO: Possibly use ptrmask intrinsic
-    auto termTy = getTermType();
-    Value valueAsInt = builder.create<LLVM::PtrToIntOp>(loc, termTy, value);
+    auto i64Ty = getI64Type();
+    Value valueAsInt = builder.create<LLVM::PtrToIntOp>(loc, i64Ty, value);
     Value tag =
-        createTermConstant(builder, loc, NANBOX_INFINITY | (uint64_t)0x01);
-    return builder.create<LLVM::OrOp>(loc, valueAsInt, tag);
+        createTermBitsConstant(builder, loc, NANBOX_INFINITY | (uint64_t)0x01);
+    return bitsToTerm(builder, loc,
+                      builder.create<LLVM::OrOp>(loc, valueAsInt, tag));
   }
 
   // This function encodes a pointer to an Erlang list (i.e. cons cell) as an
@@ -1011,11 +1029,12 @@ protected:
   Value encodeListPtr(OpBuilder &builder, Location loc, Value value,
                       bool isLiteral = false) const {
     // TODO: Possibly use ptrmask intrinsic
-    auto termTy = getTermType();
-    Value tag = createTermConstant(
+    auto i64Ty = getI64Type();
+    Value tag = createTermBitsConstant(
         builder, loc, NANBOX_INFINITY | (uint64_t)(isLiteral ? 0x05 : 0x04));
-    Value valueAsInt = builder.create<LLVM::PtrToIntOp>(loc, termTy, value);
-    return builder.create<LLVM::OrOp>(loc, valueAsInt, tag);
+    Value valueAsInt = builder.create<LLVM::PtrToIntOp>(loc, i64Ty, value);
+    return bitsToTerm(builder, loc,
+                      builder.create<LLVM::OrOp>(loc, valueAsInt, tag));
   }
 
   /// This function encodes a pointer as a GcBox<T>, which requires that it was
@@ -1023,63 +1042,69 @@ protected:
   /// guarantee these properties
   Value encodeGcBoxPtr(OpBuilder &builder, Location loc, Value value) const {
     // TODO: Possibly use ptrmask intrinsic
-    auto termTy = getTermType();
-    Value tag = createTermConstant(builder, loc, NANBOX_INFINITY);
-    Value valueAsInt = builder.create<LLVM::PtrToIntOp>(loc, termTy, value);
-    return builder.create<LLVM::OrOp>(loc, valueAsInt, tag);
+    auto i64Ty = getI64Type();
+    Value tag = createTermBitsConstant(builder, loc, NANBOX_INFINITY);
+    Value valueAsInt = builder.create<LLVM::PtrToIntOp>(loc, i64Ty, value);
+    return bitsToTerm(builder, loc,
+                      builder.create<LLVM::OrOp>(loc, valueAsInt, tag));
   }
 
   /// Same as encodeListPtr, but for tuples
   Value encodeTuplePtr(OpBuilder &builder, Location loc, Value value,
                        bool isLiteral = false) const {
     // TODO: Possibly use ptrmask intrinsic
-    auto termTy = getTermType();
-    Value tag = createTermConstant(
+    auto i64Ty = getI64Type();
+    Value tag = createTermBitsConstant(
         builder, loc, NANBOX_INFINITY | (uint64_t)(isLiteral ? 0x07 : 0x06));
-    Value valueAsInt = builder.create<LLVM::PtrToIntOp>(loc, termTy, value);
-    return builder.create<LLVM::OrOp>(loc, valueAsInt, tag);
+    Value valueAsInt = builder.create<LLVM::PtrToIntOp>(loc, i64Ty, value);
+    return bitsToTerm(builder, loc,
+                      builder.create<LLVM::OrOp>(loc, valueAsInt, tag));
   }
 
   /// This function handles encoding a pointer to AtomData as an atom immediate
   Value encodeAtomPtr(OpBuilder &builder, Location loc, Value value) const {
-    auto termTy = getTermType();
-    Value valueAsInt = builder.create<LLVM::PtrToIntOp>(loc, termTy, value);
-    Value tag =
-        createTermConstant(builder, loc, NANBOX_CANONICAL_NAN | (uint64_t)0x02);
-    return builder.create<LLVM::OrOp>(loc, valueAsInt, tag);
+    auto i64Ty = getI64Type();
+    Value valueAsInt = builder.create<LLVM::PtrToIntOp>(loc, i64Ty, value);
+    Value tag = createTermBitsConstant(builder, loc,
+                                       NANBOX_CANONICAL_NAN | (uint64_t)0x02);
+    return bitsToTerm(builder, loc,
+                      builder.create<LLVM::OrOp>(loc, valueAsInt, tag));
   }
 
   /// Floats are always encoded by a simple bitcast
   ///
   /// NOTE: This is only valid if the float is not NaN, or one of the infinities
   Value encodeFloat(OpBuilder &builder, Location loc, Value value) const {
-    auto termTy = getTermType();
-    return builder.create<LLVM::BitcastOp>(loc, termTy, value);
+    auto i64Ty = getI64Type();
+    Value bits = builder.create<LLVM::BitcastOp>(loc, i64Ty, value);
+    return bitsToTerm(builder, loc, bits);
   }
 
   /// Integers must fit in the mantissa bits of a 64-bit float and are tagged
   /// by setting the sign + exponent bits to 1
   Value encodeInteger(OpBuilder &builder, Location loc, Value value) const {
-    Value zero = createTermConstant(builder, loc, 0);
+    Value zero = createTermBitsConstant(builder, loc, 0);
     Value isNeg = builder.create<LLVM::ICmpOp>(loc, LLVM::ICmpPredicate::slt,
                                                value, zero);
-    Value negTag = createTermConstant(builder, loc, NANBOX_INTEGER_NEG);
-    Value posTag = createTermConstant(builder, loc, NANBOX_NEG_INFINITY);
+    Value negTag = createTermBitsConstant(builder, loc, NANBOX_INTEGER_NEG);
+    Value posTag = createTermBitsConstant(builder, loc, NANBOX_NEG_INFINITY);
     Value tag = builder.create<LLVM::SelectOp>(loc, isNeg, negTag, posTag);
-    Value mask = createTermConstant(builder, loc, !NANBOX_INTEGER_NEG);
+    Value mask = createTermBitsConstant(builder, loc, !NANBOX_INTEGER_NEG);
     Value masked = builder.create<LLVM::AndOp>(loc, value, mask);
-    return builder.create<LLVM::OrOp>(loc, masked, tag);
+    return bitsToTerm(builder, loc,
+                      builder.create<LLVM::OrOp>(loc, masked, tag));
   }
 
   Value decodeInteger(OpBuilder &builder, Location loc, Value value) const {
     // See opaque.rs in firefly_rt
-    auto mask = createTermConstant(builder, loc, NANBOX_INTEGER_MASK);
-    Value raw = builder.create<LLVM::AndOp>(loc, value, mask);
-    auto signal = createTermConstant(builder, loc, NANBOX_SIGNAL_BIT);
+    auto mask = createTermBitsConstant(builder, loc, NANBOX_INTEGER_MASK);
+    Value bits = termToBits(builder, loc, value);
+    Value raw = builder.create<LLVM::AndOp>(loc, bits, mask);
+    auto signal = createTermBitsConstant(builder, loc, NANBOX_SIGNAL_BIT);
     Value signExtract = builder.create<LLVM::AndOp>(loc, raw, signal);
     Value isNeg = builder.create<LLVM::ICmpOp>(loc, LLVM::ICmpPredicate::eq,
                                                signExtract, signal);
-    Value neg = createTermConstant(builder, loc, NANBOX_INTEGER_NEG);
+    Value neg = createTermBitsConstant(builder, loc, NANBOX_INTEGER_NEG);
     Value sign = builder.create<LLVM::MulOp>(loc, isNeg, neg);
 
     return builder.create<LLVM::OrOp>(loc, raw, sign);
@@ -1102,8 +1127,9 @@ protected:
     // NOTE: Possibly use ptrmask intrinsic
     auto ptrTy = LLVM::LLVMPointerType::get(pointee);
     // Need to untag the pointer first
-    auto mask = createTermConstant(builder, loc, NANBOX_PTR_MASK);
-    Value untagged = builder.create<LLVM::AndOp>(loc, box, mask);
+    auto mask = createTermBitsConstant(builder, loc, NANBOX_PTR_MASK);
+    Value bits = termToBits(builder, loc, box);
+    Value untagged = builder.create<LLVM::AndOp>(loc, bits, mask);
     return builder.create<LLVM::IntToPtrOp>(loc, ptrTy, untagged);
   }
 
@@ -1276,8 +1302,9 @@ struct ConstantOpLowering : public ConvertCIROpToLLVMPattern<cir::ConstantOp> {
                     StringRef str = attr.cast<StringAttr>().getValue();
                     bool isUtf8 =
                         op->getAttrOfType<BoolAttr>("utf8").getValue();
-                    return createBinaryDataConstant(rewriter, loc, str, isUtf8,
-                                                    module);
+                    Value ptr = createBinaryDataConstant(rewriter, loc, str,
+                                                         isUtf8, module);
+                    return encodeLiteralPtr(rewriter, loc, ptr);
                   })
                   .Case<CIRBigIntType>([&](CIRBigIntType) {
                     auto bigIntAttr = attr.cast<BigIntAttr>();
@@ -1481,20 +1508,20 @@ struct CastOpLowering : public ConvertCIROpToLLVMPattern<cir::CastOp> {
       // are simple direct bitcasts, as the value is not actually a term
       if (inputType.isa<CIROpaqueTermType>()) {
         // Casts from opaque term type to i64 are intended as bitcasts to allow
-        // working with terms as native integers, but are no-ops since terms are
-        // actually integers at the LLVM level
+        // working with terms as native integers
         if (outputType.isInteger(64)) {
-          results.push_back(input);
+          results.push_back(termToBits(rewriter, loc, input));
           continue;
         }
 
         if (auto ptrTy = outputType.dyn_cast_or_null<PtrType>()) {
           auto innerTy = ptrTy.getElementType();
+          Value bits = termToBits(rewriter, loc, input);
           if (innerTy.isa<CIRBinaryBuilderType>()) {
             auto bvTy = getBinaryBuilderType();
             auto bvPtrTy = LLVM::LLVMPointerType::get(bvTy);
             results.push_back(
-                rewriter.create<LLVM::IntToPtrOp>(loc, bvPtrTy, input));
+                rewriter.create<LLVM::IntToPtrOp>(loc, bvPtrTy, bits));
             continue;
           }
 
@@ -1502,7 +1529,7 @@ struct CastOpLowering : public ConvertCIROpToLLVMPattern<cir::CastOp> {
             auto ctxTy = getMatchContextType();
             auto ctxPtrTy = LLVM::LLVMPointerType::get(ctxTy);
             results.push_back(
-                rewriter.create<LLVM::IntToPtrOp>(loc, ctxPtrTy, input));
+                rewriter.create<LLVM::IntToPtrOp>(loc, ctxPtrTy, bits));
             continue;
           }
 
@@ -1510,7 +1537,7 @@ struct CastOpLowering : public ConvertCIROpToLLVMPattern<cir::CastOp> {
             auto excTy = getExceptionType();
             auto excPtrTy = LLVM::LLVMPointerType::get(excTy);
             results.push_back(
-                rewriter.create<LLVM::IntToPtrOp>(loc, excPtrTy, input));
+                rewriter.create<LLVM::IntToPtrOp>(loc, excPtrTy, bits));
             continue;
           }
         }
@@ -1520,8 +1547,9 @@ struct CastOpLowering : public ConvertCIROpToLLVMPattern<cir::CastOp> {
       if (inputType.isa<CIRBoolType>() && outputType.isInteger(1)) {
         // To cast from a boolean term to its value, we know that we can
         // truncate to i1 due to how true/false terms are encoded
+        Value bits = termToBits(rewriter, loc, input);
         results.push_back(
-            rewriter.create<LLVM::TruncOp>(loc, getI1Type(), input));
+            rewriter.create<LLVM::TruncOp>(loc, getI1Type(), bits));
         continue;
       }
 
@@ -1534,7 +1562,7 @@ struct CastOpLowering : public ConvertCIROpToLLVMPattern<cir::CastOp> {
           results.push_back(integer);
         } else {
           results.push_back(
-              rewriter.create<LLVM::TruncOp>(loc, outputType, input));
+              rewriter.create<LLVM::TruncOp>(loc, outputType, integer));
         }
         continue;
       }
@@ -1544,10 +1572,11 @@ struct CastOpLowering : public ConvertCIROpToLLVMPattern<cir::CastOp> {
           outputType.isa<CIRBoolType, CIROpaqueTermType>()) {
         // To cast from i1 to a boolean term, we treat the value as the symbol
         // id, zext and encode as an atom
-        Value symbol = rewriter.create<LLVM::ZExtOp>(loc, getTermType(), input);
+        Value symbol = rewriter.create<LLVM::ZExtOp>(loc, getI64Type(), input);
         Value tag =
-            createTermConstant(rewriter, loc, NANBOX_CANONICAL_NAN | 0x02);
-        results.push_back(rewriter.create<LLVM::OrOp>(loc, symbol, tag));
+            createTermBitsConstant(rewriter, loc, NANBOX_CANONICAL_NAN | 0x02);
+        Value bits = rewriter.create<LLVM::OrOp>(loc, symbol, tag);
+        results.push_back(bitsToTerm(rewriter, loc, bits));
         continue;
       }
 
@@ -1593,8 +1622,19 @@ struct TruncOpLowering : public ConvertCIROpToLLVMPattern<cir::TruncOp> {
   LogicalResult
   matchAndRewrite(cir::TruncOp op, OpAdaptor adaptor,
                   ConversionPatternRewriter &rewriter) const override {
+    auto loc = op.getLoc();
     auto ty = convertType(op.result().getType());
     auto value = adaptor.value();
+
+    // Terms are pointers, so truncating one operates on its raw bits
+    if (value.getType() == getTermType()) {
+      value = termToBits(rewriter, loc, value);
+      if (ty.cast<IntegerType>().getWidth() == 64) {
+        rewriter.replaceOp(op, {value});
+        return success();
+      }
+    }
+
     rewriter.replaceOpWithNewOp<LLVM::TruncOp>(op, ty, value);
     return success();
   }
@@ -1609,8 +1649,19 @@ struct ZExtOpLowering : public ConvertCIROpToLLVMPattern<cir::ZExtOp> {
   LogicalResult
   matchAndRewrite(cir::ZExtOp op, OpAdaptor adaptor,
                   ConversionPatternRewriter &rewriter) const override {
+    auto loc = op.getLoc();
     auto ty = convertType(op.result().getType());
     auto value = adaptor.value();
+
+    // Terms are pointers, so extending one operates on its raw bits
+    if (value.getType() == getTermType()) {
+      value = termToBits(rewriter, loc, value);
+      if (ty.cast<IntegerType>().getWidth() == 64) {
+        rewriter.replaceOp(op, {value});
+        return success();
+      }
+    }
+
     rewriter.replaceOpWithNewOp<LLVM::ZExtOp>(op, ty, value);
     return success();
   }
@@ -1799,20 +1850,22 @@ struct IsListOpLowering : public ConvertCIROpToLLVMPattern<cir::IsListOp> {
     //    }
     //}
 
-    auto mask = createTermConstant(rewriter, loc, TAG_MASK);
-    Value masked = rewriter.create<LLVM::AndOp>(loc, input, mask);
+    Value bits = termToBits(rewriter, loc, input);
+    auto mask = createTermBitsConstant(rewriter, loc, TAG_MASK);
+    Value masked = rewriter.create<LLVM::AndOp>(loc, bits, mask);
     // Is the masked value a boxed cons cell
-    Value consTag = createTermConstant(rewriter, loc, IS_CONS);
+    Value consTag = createTermBitsConstant(rewriter, loc, IS_CONS);
     Value isCons = rewriter.create<LLVM::ICmpOp>(loc, LLVM::ICmpPredicate::eq,
                                                  masked, consTag);
     // Is the masked value a literal cons cell
-    Value consLiteralTag = createTermConstant(rewriter, loc, IS_CONS_LITERAL);
+    Value consLiteralTag =
+        createTermBitsConstant(rewriter, loc, IS_CONS_LITERAL);
     Value isConsLiteral = rewriter.create<LLVM::ICmpOp>(
         loc, LLVM::ICmpPredicate::eq, masked, consLiteralTag);
     // Is the untagged value nil
-    Value nilValue = createTermConstant(rewriter, loc, NANBOX_INFINITY);
+    Value nilValue = createTermBitsConstant(rewriter, loc, NANBOX_INFINITY);
     Value isNil = rewriter.create<LLVM::ICmpOp>(loc, LLVM::ICmpPredicate::eq,
-                                                input, nilValue);
+                                                bits, nilValue);
     Value isNonEmpty = rewriter.create<LLVM::OrOp>(loc, isCons, isConsLiteral);
     Value isList = rewriter.create<LLVM::OrOp>(loc, isNonEmpty, isNil);
 
@@ -1835,14 +1888,16 @@ struct IsNonEmptyListOpLowering
     auto loc = op.getLoc();
 
     // Same as IsListOp, but without the check for nil
-    auto mask = createTermConstant(rewriter, loc, TAG_MASK);
-    Value masked = rewriter.create<LLVM::AndOp>(loc, adaptor.value(), mask);
+    Value bits = termToBits(rewriter, loc, adaptor.value());
+    auto mask = createTermBitsConstant(rewriter, loc, TAG_MASK);
+    Value masked = rewriter.create<LLVM::AndOp>(loc, bits, mask);
     // Is the tag value a boxed cons cell
-    Value consTag = createTermConstant(rewriter, loc, IS_CONS);
+    Value consTag = createTermBitsConstant(rewriter, loc, IS_CONS);
     Value isCons = rewriter.create<LLVM::ICmpOp>(loc, LLVM::ICmpPredicate::eq,
                                                  masked, consTag);
     // Is the tag value a literal cons cell
-    Value consLiteralTag = createTermConstant(rewriter, loc, IS_CONS_LITERAL);
+    Value consLiteralTag =
+        createTermBitsConstant(rewriter, loc, IS_CONS_LITERAL);
     Value isConsLiteral = rewriter.create<LLVM::ICmpOp>(
         loc, LLVM::ICmpPredicate::eq, masked, consLiteralTag);
 
@@ -1941,8 +1996,9 @@ struct IsTaggedTupleOpLowering
               l, termPtrTy, tuplePtr, ValueRange({zero, first, first}));
           Value elem = builder.create<LLVM::LoadOp>(l, elemPtr);
           Value expectedAtom = createAtom(builder, l, atom.getName(), module);
-          Value isEq = builder.create<LLVM::ICmpOp>(l, LLVM::ICmpPredicate::eq,
-                                                    elem, expectedAtom);
+          Value isEq = builder.create<LLVM::ICmpOp>(
+              l, LLVM::ICmpPredicate::eq, termToBits(builder, l, elem),
+              termToBits(builder, l, expectedAtom));
           builder.create<scf::YieldOp>(l, ValueRange({isEq}));
         },
         // checkTag==false, i.e. this tuple can't possibly match
@@ -1995,7 +2051,7 @@ struct IsFloatOpLowering : public ConvertCIROpToLLVMPattern<cir::IsFloatOp> {
   matchAndRewrite(cir::IsFloatOp op, OpAdaptor adaptor,
                   ConversionPatternRewriter &rewriter) const override {
     auto loc = op.getLoc();
-    auto value = adaptor.value();
+    auto value = termToBits(rewriter, loc, adaptor.value());
 
     // The value is a float if its NaN bits (ignoring the quiet bit) are not set
     Value nan = createIsizeConstant(rewriter, loc, NANBOX_NAN);
@@ -2038,7 +2094,7 @@ struct IsIsizeOpLowering : public ConvertCIROpToLLVMPattern<cir::IsIsizeOp> {
   matchAndRewrite(cir::IsIsizeOp op, OpAdaptor adaptor,
                   ConversionPatternRewriter &rewriter) const override {
     auto loc = op.getLoc();
-    auto value = adaptor.value();
+    auto value = termToBits(rewriter, loc, adaptor.value());
 
     // value & NEG_INFINITY == NEG_INFINITY
     Value mask = createIsizeConstant(rewriter, loc, NANBOX_NEG_INFINITY);
@@ -2068,7 +2124,8 @@ struct IsBigIntOpLowering : public ConvertCIROpToLLVMPattern<cir::IsBigIntOp> {
     Value isInt = rewriter.create<LLVM::ICmpOp>(loc, LLVM::ICmpPredicate::eq,
                                                 ty, expected);
     Value mask = createIsizeConstant(rewriter, loc, NANBOX_NEG_INFINITY);
-    Value masked = rewriter.create<LLVM::AndOp>(loc, value, mask);
+    Value bits = termToBits(rewriter, loc, value);
+    Value masked = rewriter.create<LLVM::AndOp>(loc, bits, mask);
     Value notSmallInt = rewriter.create<LLVM::ICmpOp>(
         loc, LLVM::ICmpPredicate::ne, masked, mask);
     rewriter.replaceOpWithNewOp<LLVM::AndOp>(op, isInt, notSmallInt);
@@ -2087,10 +2144,10 @@ struct IsAtomOpLowering : public ConvertCIROpToLLVMPattern<cir::IsAtomOp> {
                   ConversionPatternRewriter &rewriter) const override {
 
     auto loc = op.getLoc();
-    auto input = adaptor.value();
+    auto input = termToBits(rewriter, loc, adaptor.value());
 
     // Extract the tag bits
-    Value tagMask = createTermConstant(rewriter, loc, TAG_MASK);
+    Value tagMask = createTermBitsConstant(rewriter, loc, TAG_MASK);
     Value masked = rewriter.create<LLVM::AndOp>(loc, input, tagMask);
     // The masked value must meet one of two criteria:
     //
@@ -2100,11 +2157,11 @@ struct IsAtomOpLowering : public ConvertCIROpToLLVMPattern<cir::IsAtomOp> {
     // bits, as 'true' overlaps with the tag scheme for Rc<T>)
     //
     Value atomTag =
-        createTermConstant(rewriter, loc, NANBOX_CANONICAL_NAN | 0x02);
+        createTermBitsConstant(rewriter, loc, NANBOX_CANONICAL_NAN | 0x02);
     Value isAtom = rewriter.create<LLVM::ICmpOp>(loc, LLVM::ICmpPredicate::eq,
                                                  masked, atomTag);
     Value trueTag =
-        createTermConstant(rewriter, loc, NANBOX_CANONICAL_NAN | 0x03);
+        createTermBitsConstant(rewriter, loc, NANBOX_CANONICAL_NAN | 0x03);
     Value isTrue = rewriter.create<LLVM::ICmpOp>(loc, LLVM::ICmpPredicate::eq,
                                                  input, trueTag);
     // Combine the two checks to give us our result
@@ -2124,7 +2181,7 @@ struct IsBoolOpLowering : public ConvertCIROpToLLVMPattern<cir::IsBoolOp> {
                   ConversionPatternRewriter &rewriter) const override {
 
     auto loc = op.getLoc();
-    auto value = adaptor.value();
+    auto value = termToBits(rewriter, loc, adaptor.value());
 
     Value constFalse =
         createIsizeConstant(rewriter, loc, NANBOX_CANONICAL_NAN | 0x02);
@@ -2655,8 +2712,9 @@ struct RaiseOpLowering : public ConvertCIROpToLLVMPattern<cir::RaiseOp> {
 
     // Lastly, convert this op to our multi-value return convention
     Value isError = createBoolConstant(rewriter, loc, true);
-    Value exceptionTerm =
-        rewriter.create<LLVM::PtrToIntOp>(loc, getTermType(), exceptionPtr);
+    Value exceptionBits =
+        rewriter.create<LLVM::PtrToIntOp>(loc, getI64Type(), exceptionPtr);
+    Value exceptionTerm = bitsToTerm(rewriter, loc, exceptionBits);
     rewriter.replaceOpWithNewOp<func::ReturnOp>(
         op, ValueRange({isError, exceptionTerm}));
     return success();
@@ -2791,7 +2849,7 @@ struct RecvNextOpLowering : public ConvertCIROpToLLVMPattern<cir::RecvNextOp> {
     auto context = adaptor.context();
 
     rewriter.replaceOpWithNewOp<LLVM::CallOp>(op, TypeRange({i8Ty}),
-                                              "__firefly_builtin_receive_start",
+                                              "__firefly_builtin_receive_next",
                                               ValueRange({context}));
     return success();
   }
@@ -2808,24 +2866,21 @@ struct RecvPeekOpLowering : public ConvertCIROpToLLVMPattern<cir::RecvPeekOp> {
                   ConversionPatternRewriter &rewriter) const override {
     auto loc = op.getLoc();
     auto i32Ty = getI32Type();
-    auto recvCtxTy = getRecvContextType();
     auto termPtrTy = LLVM::LLVMPointerType::get(getTermType());
+    auto msgPtrTy = LLVM::LLVMPointerType::get(getMessageType());
+    auto msgPtrPtrTy = LLVM::LLVMPointerType::get(msgPtrTy);
     auto context = adaptor.context();
 
     Value zero = createIsizeConstant(rewriter, loc, 0);
-    Value zero32 = createIndexAttrConstant(rewriter, loc, i32Ty, 0);
     Value one = createIndexAttrConstant(rewriter, loc, i32Ty, 1);
-    auto two = rewriter.getI32ArrayAttr({2});
-    Value messagePtr =
-        rewriter.create<LLVM::ExtractValueOp>(loc, recvCtxTy, context, two);
-    Value dataAddr;
-    if (getPointerBitwidth() == 64) {
-      dataAddr = rewriter.create<LLVM::GEPOp>(
-          loc, termPtrTy, messagePtr, ValueRange({zero, one, one, one}));
-    } else {
-      dataAddr = rewriter.create<LLVM::GEPOp>(
-          loc, termPtrTy, messagePtr, ValueRange({zero, one, one, zero32}));
-    }
+    Value two = createIndexAttrConstant(rewriter, loc, i32Ty, 2);
+    // The context is a pointer to the ReceiveContext, load the message pointer from it
+    Value messagePtrAddr = rewriter.create<LLVM::GEPOp>(
+        loc, msgPtrPtrTy, context, ValueRange({zero, two}));
+    Value messagePtr = rewriter.create<LLVM::LoadOp>(loc, messagePtrAddr);
+    // Then load the term from the message data, i.e. Message.data.1
+    Value dataAddr = rewriter.create<LLVM::GEPOp>(
+        loc, termPtrTy, messagePtr, ValueRange({zero, one, one}));
     rewriter.replaceOpWithNewOp<LLVM::LoadOp>(op, dataAddr);
     return success();
   }
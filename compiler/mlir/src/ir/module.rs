@@ -153,7 +153,7 @@ impl OwnedModule {
     pub fn parse_file<S: Into<StringRef>>(context: Context, path: S) -> anyhow::Result<Self> {
         let base = unsafe { mlir_parse_file(context, path.into()) };
         if base.is_null() {
-            Err(anyhow!("failed to parse module from the given string"))
+            Err(anyhow!("failed to parse module from the given file"))
         } else {
             Ok(Self(base))
         }
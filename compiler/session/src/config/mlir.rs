@@ -6,7 +6,7 @@ use crate::config::options::invalid_value;
 use crate::config::options::{OptionInfo, ParseOption};
 
 /// Represents how MLIR debug info is printed
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum MlirDebugPrinting {
     /// Disables debug info printing
     None,
@@ -205,6 +205,33 @@ impl Options {
 
         let debug_assertions = codegen_opts.debug_assertions.unwrap_or(false);
 
+        if codegen_opts.profile_generate.is_some() && codegen_opts.profile_use.is_some() {
+            bail!("options `-C profile-generate` and `-C profile-use` are exclusive");
+        }
+        // Profile paths are handed to LLVM, which requires them to be valid UTF-8
+        for (option, path) in [
+            ("profile-generate", codegen_opts.profile_generate.as_ref()),
+            ("profile-use", codegen_opts.profile_use.as_ref()),
+        ] {
+            if let Some(path) = path {
+                if path.to_str().is_none() {
+                    bail!(
+                        "path `{}` passed to `-C {}` is not valid UTF-8",
+                        path.display(),
+                        option
+                    );
+                }
+            }
+        }
+        if let Some(ref path) = codegen_opts.profile_use {
+            if !path.exists() {
+                bail!(
+                    "file `{}` passed to `-C profile-use` does not exist",
+                    path.display()
+                );
+            }
+        }
+
         if debug_assertions {
             defines.insert("DEBUG".to_string(), None);
         }
@@ -389,7 +416,10 @@ impl Options {
             LtoCli::Yes => Lto::Fat,
             LtoCli::Thin => Lto::Thin,
             LtoCli::Fat => Lto::Fat,
-            LtoCli::Unspecified => Lto::No,
+            LtoCli::Unspecified => match self.codegen_opts.thinlto {
+                Some(true) if !self.cli_forced_thinlto_off => Lto::Thin,
+                _ => Lto::No,
+            },
         }
     }
 
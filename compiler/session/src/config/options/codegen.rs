@@ -52,6 +52,10 @@ pub struct CodegenOptions {
     pub function_sections: Option<bool>,
     #[option(hidden(true))]
     pub gcc_ld: Option<LdImpl>,
+    #[option(default_value("false"))]
+    /// Reuse the artifacts of unchanged modules from previous compilations,
+    /// note that warnings are not reported again for the modules reused
+    pub incremental: bool,
     #[option(value_name("N"), takes_value(true), hidden(true))]
     /// Set the threshold for inlining a function
     pub inline_threshold: Option<u64>,
@@ -67,9 +71,9 @@ pub struct CodegenOptions {
     #[option(default_value("true"))]
     /// Link native libraries in the linker invocation
     pub link_native_libraries: bool,
-    #[option(hidden(true))]
-    /// Control whether to link Rust provided C objects/libraries or rely on
-    /// C toolchain installed on the system
+    #[option]
+    /// Link in-process with the embedded lld, using the CRT objects/libraries
+    /// bundled with the compiler rather than a C toolchain installed on the system
     pub link_self_contained: Option<bool>,
     #[option(value_name("PATH"), takes_value(true))]
     /// The system linker to link with
@@ -101,6 +105,9 @@ pub struct CodegenOptions {
     )]
     /// Perform link-time optimization
     pub lto: LtoCli,
+    #[option(hidden(true))]
+    /// Include the runtime's embedded bitcode in link-time optimization
+    pub lto_runtime: bool,
     #[option(
         takes_value(true),
         possible_values("disabled", "trampolines", "aliases"),
@@ -156,6 +163,12 @@ pub struct CodegenOptions {
     #[option]
     /// Prefer dynamic linking to static linking
     pub prefer_dynamic: bool,
+    #[option(value_name("DIR"), takes_value(true))]
+    /// Instrument the generated code to write execution profiles to the given directory
+    pub profile_generate: Option<PathBuf>,
+    #[option(value_name("PATH"), takes_value(true))]
+    /// Use the given profile data file (i.e. `.profdata`) to guide optimization
+    pub profile_use: Option<PathBuf>,
     #[option(value_name("MODEL"), takes_value(true), hidden(true))]
     /// Choose the relocation model to use
     pub relocation_model: Option<RelocModel>,
@@ -11,6 +11,9 @@ pub struct DebuggingOptions {
     #[option]
     /// Generate comments into the assembly (may change behavior)
     pub asm_comments: bool,
+    #[option]
+    /// Check function bodies and call sites against their type specs, warning on discrepancies
+    pub check_specs: bool,
     /**
      * Debug info emission level
      *     0 = no debug info
@@ -128,7 +131,7 @@ pub struct DebuggingOptions {
     /// symbolication/stack traces in the absence of .dwo/.dwp files when using split DWARF
     pub split_dwarf_inlining: bool,
     #[option(default_value("1"), takes_value(true), value_name("N"))]
-    /// Use a thread pool with N threads
+    /// Use a thread pool with N threads to compile modules in parallel, or one per core if 0
     pub threads: u64,
     /// Measure the time spent on tasks
     #[option]
@@ -145,4 +148,7 @@ pub struct DebuggingOptions {
     #[option]
     /// Verify LLVM IR
     pub verify_llvm_ir: bool,
+    #[option]
+    /// Warn about exported functions which are not referenced by any other module in the application
+    pub warn_unused_exports: bool,
 }
@@ -1,3 +1,4 @@
+#![feature(linkage)]
 ///! This library provides the means to access the LLVM-generated stack maps
 ///! included in a binary when the use of LLVM statepoints or patchpoints are
 ///! present in the IR used to generate objects in that binary.
@@ -41,8 +42,12 @@ extern "C" {
     // The section name on macOS is `__llvm_stackmaps`, but
     // on Linux it is `.llvm_stackmaps`, however the segment
     // name is the same on both.
+    //
+    // This is weakly linked, as the section is only present when
+    // the code in the binary actually contains statepoints.
     #[link_name = "__LLVM_STACKMAPS"]
-    static STACK_MAP_HEADER: StackMapHeader;
+    #[linkage = "extern_weak"]
+    static STACK_MAP_HEADER: *const StackMapHeader;
 }
 
 lazy_static! {
@@ -70,6 +75,14 @@ impl StackMap {
         self.frame_infos.get(&addr)
     }
 
+    /// Returns true if there are no call sites in this stack map
+    ///
+    /// This is the case when the binary contains no statepoints at all
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.frame_infos.is_empty()
+    }
+
     #[inline(always)]
     pub fn functions(&self) -> &'static [FunctionInfo] {
         self.functions
@@ -88,7 +101,16 @@ impl StackMap {
 
     fn build() -> Self {
         // Obtain reference to header and validate it before proceeding
-        let header = unsafe { &STACK_MAP_HEADER };
+        let header = unsafe { STACK_MAP_HEADER };
+        if header.is_null() {
+            return Self {
+                version: 3,
+                functions: &[],
+                constants: &[],
+                frame_infos: HashMap::new(),
+            };
+        }
+        let header = unsafe { &*header };
         let version = header.version();
         assert_eq!(version, 3, "unsupported version of LLVM StackMaps");
         assert_eq!(header.reserved1(), 0, "expected zero");
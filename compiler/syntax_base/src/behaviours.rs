@@ -0,0 +1,119 @@
+///! This module contains the callbacks of the behaviours provided by OTP
+///!
+///! Modules implementing a behaviour are verified against the callbacks it requires. When the
+///! behaviour module is part of the application being compiled, its `-callback` attributes are
+///! used, otherwise we fall back to the definitions here, which mirror `Module:behaviour_info/1`
+///! of the corresponding OTP module.
+use std::collections::{BTreeMap, BTreeSet};
+
+use firefly_intern::Symbol;
+use lazy_static::lazy_static;
+
+use crate::FunctionName;
+
+/// The set of callbacks declared by a behaviour module
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Behaviour {
+    /// Callbacks which must be exported by modules implementing the behaviour
+    pub callbacks: BTreeSet<FunctionName>,
+    /// Callbacks which may be omitted by modules implementing the behaviour
+    pub optional_callbacks: BTreeSet<FunctionName>,
+}
+impl Behaviour {
+    fn new(callbacks: &[(&str, u8)], optional_callbacks: &[(&str, u8)]) -> Self {
+        let to_names = |names: &[(&str, u8)]| {
+            names
+                .iter()
+                .map(|(f, a)| FunctionName::new_local(Symbol::intern(f), *a))
+                .collect()
+        };
+        Self {
+            callbacks: to_names(callbacks),
+            optional_callbacks: to_names(optional_callbacks),
+        }
+    }
+
+    /// Returns true if `name` is declared as a callback, optional or otherwise
+    pub fn is_callback(&self, name: &FunctionName) -> bool {
+        self.callbacks.contains(name) || self.optional_callbacks.contains(name)
+    }
+}
+
+lazy_static! {
+    static ref OTP_BEHAVIOURS: BTreeMap<Symbol, Behaviour> = {
+        let mut behaviours = BTreeMap::new();
+        behaviours.insert(
+            Symbol::intern("application"),
+            Behaviour::new(
+                &[("start", 2), ("stop", 1)],
+                &[("prep_stop", 1), ("start_phase", 3), ("config_change", 3)],
+            ),
+        );
+        behaviours.insert(
+            Symbol::intern("gen_event"),
+            Behaviour::new(
+                &[("init", 1), ("handle_event", 2), ("handle_call", 2)],
+                &[
+                    ("handle_info", 2),
+                    ("terminate", 2),
+                    ("code_change", 3),
+                    ("format_status", 1),
+                    ("format_status", 2),
+                ],
+            ),
+        );
+        behaviours.insert(
+            Symbol::intern("gen_fsm"),
+            Behaviour::new(
+                &[("init", 1), ("handle_event", 3), ("handle_sync_event", 4)],
+                &[
+                    ("handle_info", 3),
+                    ("terminate", 3),
+                    ("code_change", 4),
+                    ("format_status", 2),
+                ],
+            ),
+        );
+        behaviours.insert(
+            Symbol::intern("gen_server"),
+            Behaviour::new(
+                &[("init", 1), ("handle_call", 3), ("handle_cast", 2)],
+                &[
+                    ("handle_info", 2),
+                    ("handle_continue", 2),
+                    ("terminate", 2),
+                    ("code_change", 3),
+                    ("format_status", 1),
+                    ("format_status", 2),
+                ],
+            ),
+        );
+        behaviours.insert(
+            Symbol::intern("gen_statem"),
+            Behaviour::new(
+                &[("init", 1), ("callback_mode", 0)],
+                &[
+                    ("handle_event", 4),
+                    ("terminate", 3),
+                    ("code_change", 4),
+                    ("format_status", 1),
+                    ("format_status", 2),
+                ],
+            ),
+        );
+        behaviours.insert(
+            Symbol::intern("supervisor"),
+            Behaviour::new(&[("init", 1)], &[]),
+        );
+        behaviours.insert(
+            Symbol::intern("supervisor_bridge"),
+            Behaviour::new(&[("init", 1), ("terminate", 2)], &[]),
+        );
+        behaviours
+    };
+}
+
+/// Returns the callbacks of the given OTP behaviour, if known
+pub fn get(module: Symbol) -> Option<&'static Behaviour> {
+    OTP_BEHAVIOURS.get(&module)
+}
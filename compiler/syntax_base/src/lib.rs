@@ -4,6 +4,7 @@ mod macros;
 pub use self::macros::*;
 
 mod annotations;
+pub mod behaviours;
 pub mod bifs;
 mod deprecations;
 mod functions;
@@ -16,6 +17,7 @@ mod types;
 mod var;
 
 pub use self::annotations::*;
+pub use self::behaviours::Behaviour;
 pub use self::deprecations::*;
 pub use self::functions::*;
 pub use self::literals::{Lit, Literal};
@@ -26,7 +28,7 @@ pub use self::var::Var;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 
 use firefly_diagnostics::{SourceSpan, Span};
-use firefly_intern::{Ident, Symbol};
+use firefly_intern::{symbols, Ident, Symbol};
 
 /// This structure contains metadata representing an OTP application gathered during parsing and semantic analysis.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -54,6 +56,46 @@ impl ApplicationMetadata {
             self.get_module_deprecation(&module_name)
         }
     }
+
+    /// Returns the exports of modules in this application which are not referenced by any of them
+    ///
+    /// Exports which implement a callback of a behaviour of their module are not considered unused
+    pub fn unused_exports(&self) -> Vec<Span<FunctionName>> {
+        let referenced = self
+            .modules
+            .values()
+            .flat_map(|m| m.references.iter().copied())
+            .collect::<BTreeSet<_>>();
+
+        let mut unused = vec![];
+        for (module_name, module) in self.modules.iter() {
+            for export in module.exports.iter() {
+                let name = export.resolve(*module_name);
+                if referenced.contains(&name) {
+                    continue;
+                }
+                let is_callback = module
+                    .behaviours
+                    .iter()
+                    .filter_map(|b| self.get_behaviour(b))
+                    .any(|b| b.is_callback(export.as_ref()));
+                if !is_callback {
+                    unused.push(Span::new(export.span(), name));
+                }
+            }
+        }
+        unused
+    }
+
+    /// Returns the callbacks of the given behaviour module, if known
+    ///
+    /// Behaviours defined in this application take precedence over the built-in OTP behaviours
+    pub fn get_behaviour(&self, name: &Symbol) -> Option<&Behaviour> {
+        match self.modules.get(name).and_then(|m| m.behaviour.as_ref()) {
+            None => behaviours::get(*name),
+            behaviour => behaviour,
+        }
+    }
 }
 
 /// This structure contains metadata about a module gathered during initial parsing and semantic analysis,
@@ -65,6 +107,28 @@ pub struct ModuleMetadata {
     pub exports: BTreeSet<Span<FunctionName>>,
     pub deprecation: Option<Deprecation>,
     pub deprecations: BTreeMap<FunctionName, Deprecation>,
+    /// The callbacks declared by this module via `-callback`, if it defines a behaviour
+    pub behaviour: Option<Behaviour>,
+    /// The behaviours implemented by this module
+    pub behaviours: BTreeSet<Symbol>,
+    /// The remote functions referenced by this module, either by calls or captures
+    pub references: BTreeSet<FunctionName>,
+}
+impl ModuleMetadata {
+    /// Returns true if the given local function name is exported from this module
+    ///
+    /// This accounts for the functions which are defined implicitly by the compiler
+    pub fn is_exported(&self, name: &FunctionName) -> bool {
+        let key = Span::new(SourceSpan::default(), *name);
+        if self.exports.contains(&key) {
+            return true;
+        }
+        match (name.function, name.arity) {
+            (symbols::ModuleInfo, 0 | 1) => true,
+            (symbols::BehaviourInfo, 1) => self.behaviour.is_some(),
+            _ => false,
+        }
+    }
 }
 
 /// This structure holds module-specific compiler options and configuration; it is passed through all phases of
@@ -118,6 +182,8 @@ pub struct CompileOptions {
     pub inline: bool,
     // Inlines the given functions
     pub inline_functions: HashSet<Span<FunctionName>>,
+    // Parse transforms to apply to the module, in the order given
+    pub parse_transforms: Vec<Span<Symbol>>,
 }
 impl Default for CompileOptions {
     fn default() -> Self {
@@ -133,6 +199,7 @@ impl Default for CompileOptions {
             no_auto_imports: HashSet::new(),
             inline: false,
             inline_functions: HashSet::new(),
+            parse_transforms: Vec::new(),
 
             // Warning toggles
             warn_export_all: true,
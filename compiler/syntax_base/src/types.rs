@@ -32,7 +32,7 @@ impl fmt::Display for FunctionType {
                 write!(f, "{}", ty)?;
             }
         }
-        f.write_str(" -> (")?;
+        f.write_str(") -> (")?;
         for (i, ty) in self.results.iter().enumerate() {
             if i > 0 {
                 write!(f, ", {}", ty)?;
@@ -0,0 +1,835 @@
+///! This pass inlines calls to local functions in Core IR, similar to `sys_core_inline` in erlc.
+///!
+///! A function is inlined at each of its call sites when it is listed in a
+///! `-compile({inline, [Name/Arity]}).` attribute, or, when the `inline` option is set,
+///! if it is small enough to be worth inlining. In both cases the size of the function is
+///! limited, so that inlining can't cause code size to explode.
+///!
+///! Only functions which are not recursive, and whose body contains no closures or `letrec`
+///! expressions are inlined, as the definitions of those must be unique within a module.
+///!
+///! A call is replaced with a `let` which binds the arguments to the parameters of the callee,
+///! followed by a copy of the body of the callee in which every variable has been renamed, so
+///! that the bindings it introduces don't clash with those of the caller. The copy retains the
+///! source spans of the original definition, so debug info refers to the inlined function.
+use std::collections::{BTreeMap, HashMap};
+
+use firefly_diagnostics::{Reporter, SourceSpan, Span, Spanned};
+use firefly_intern::{Ident, Symbol};
+use firefly_pass::Pass;
+use firefly_syntax_base::*;
+
+use crate::*;
+
+/// The largest function which will be inlined when the `inline` option is set
+///
+/// This is the same as the default `inline_size` of erlc
+const INLINE_SIZE: usize = 24;
+
+/// The largest function which will be inlined when explicitly requested
+const MAX_INLINE_SIZE: usize = 240;
+
+/// Inlines local functions in a module, see the module documentation for details
+///
+/// A warning is reported for each function explicitly requested to be inlined which can't be.
+pub struct InlineFunctions {
+    reporter: Reporter,
+}
+impl InlineFunctions {
+    pub fn new(reporter: Reporter) -> Self {
+        Self { reporter }
+    }
+}
+impl Pass for InlineFunctions {
+    type Input<'a> = Module;
+    type Output<'a> = Module;
+
+    fn run<'a>(&mut self, mut module: Self::Input<'a>) -> anyhow::Result<Self::Output<'a>> {
+        if !module.compile.inline && module.compile.inline_functions.is_empty() {
+            return Ok(module);
+        }
+
+        // Functions of other modules, i.e. those requested by `inline_list_funcs`, can only be
+        // inlined by the compiler of those modules
+        let requested = module
+            .compile
+            .inline_functions
+            .iter()
+            .filter(|name| name.module.map(|m| m == module.name.name).unwrap_or(true))
+            .map(|name| (name.to_local(), name.span()))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut candidates = HashMap::new();
+        for (name, function) in module.functions.iter() {
+            let limit = if requested.contains_key(name) {
+                MAX_INLINE_SIZE
+            } else if module.compile.inline {
+                INLINE_SIZE
+            } else {
+                continue;
+            };
+            let is_nif = module.nifs.contains(&Span::new(SourceSpan::UNKNOWN, *name));
+            let rejected = if is_nif {
+                Some("it is a nif".to_string())
+            } else if !is_inlinable(&function.fun.body) {
+                Some("it contains a fun, receive or letrec".to_string())
+            } else if size(&function.fun.body) > limit {
+                Some(format!(
+                    "it is larger than the limit of {} for inlining",
+                    limit
+                ))
+            } else if calls(&function.fun.body, module.name.name, name) {
+                Some("it is recursive".to_string())
+            } else {
+                None
+            };
+            match rejected {
+                None => {
+                    candidates.insert(*name, function.fun.clone());
+                }
+                Some(reason) => {
+                    if let Some(span) = requested.get(name) {
+                        self.not_inlined(name, *span, &reason);
+                    }
+                }
+            }
+        }
+        for (name, span) in requested.iter() {
+            if !module.functions.contains_key(name) {
+                self.not_inlined(name, *span, "it is not defined in this module");
+            }
+        }
+
+        if candidates.is_empty() {
+            return Ok(module);
+        }
+
+        for (name, function) in module.functions.iter_mut() {
+            let mut inliner = Inliner {
+                module: module.name.name,
+                candidates: &candidates,
+                var_counter: &mut function.var_counter,
+                stack: vec![*name],
+            };
+            inliner.inline(function.fun.body.as_mut());
+        }
+
+        Ok(module)
+    }
+}
+
+impl InlineFunctions {
+    fn not_inlined(&self, name: &FunctionName, span: SourceSpan, reason: &str) {
+        let message = format!("{} will not be inlined", name);
+        let label = format!("requested to be inlined here, but {}", reason);
+        self.reporter
+            .show_warning(&message, &[(span, label.as_str())]);
+    }
+}
+
+struct Inliner<'a> {
+    module: Symbol,
+    candidates: &'a HashMap<FunctionName, Fun>,
+    var_counter: &'a mut usize,
+    /// The functions currently being inlined into, used to avoid inlining mutually recursive
+    /// functions into one another forever
+    stack: Vec<FunctionName>,
+}
+impl<'a> Inliner<'a> {
+    fn inline(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Call(call) => {
+                self.inline_all(call.args.as_mut_slice());
+                let Some(name) = self.local_callee(call) else { return; };
+                let candidates = self.candidates;
+                let Some(callee) = candidates.get(&name) else { return; };
+                let span = call.span;
+                let args = core::mem::take(&mut call.args);
+                let mut inlined = self.instantiate(span, callee, args);
+                // The inlined body may itself contain calls to functions which can be inlined
+                self.stack.push(name);
+                self.inline(&mut inlined);
+                self.stack.pop();
+                *expr = inlined;
+            }
+            Expr::Alias(alias) => self.inline(alias.pattern.as_mut()),
+            Expr::Apply(apply) => {
+                self.inline(apply.callee.as_mut());
+                self.inline_all(apply.args.as_mut_slice());
+            }
+            Expr::Binary(bin) => {
+                for segment in bin.segments.iter_mut() {
+                    self.inline(segment.value.as_mut());
+                    if let Some(size) = segment.size.as_mut() {
+                        self.inline(size.as_mut());
+                    }
+                }
+            }
+            Expr::Case(case) => {
+                self.inline(case.arg.as_mut());
+                self.inline_clauses(case.clauses.as_mut_slice());
+            }
+            Expr::Catch(catch) => self.inline(catch.body.as_mut()),
+            Expr::Cons(cons) => {
+                self.inline(cons.head.as_mut());
+                self.inline(cons.tail.as_mut());
+            }
+            Expr::Fun(fun) => self.inline(fun.body.as_mut()),
+            Expr::If(expr) => {
+                self.inline(expr.guard.as_mut());
+                self.inline(expr.then_body.as_mut());
+                self.inline(expr.else_body.as_mut());
+            }
+            Expr::Let(expr) => {
+                self.inline(expr.arg.as_mut());
+                self.inline(expr.body.as_mut());
+            }
+            Expr::LetRec(letrec) => {
+                for (_, def) in letrec.defs.iter_mut() {
+                    self.inline(def);
+                }
+                self.inline(letrec.body.as_mut());
+            }
+            Expr::Map(map) => {
+                self.inline(map.arg.as_mut());
+                for pair in map.pairs.iter_mut() {
+                    self.inline(pair.key.as_mut());
+                    self.inline(pair.value.as_mut());
+                }
+            }
+            Expr::PrimOp(op) => self.inline_all(op.args.as_mut_slice()),
+            Expr::Receive(recv) => {
+                self.inline_clauses(recv.clauses.as_mut_slice());
+                self.inline(recv.timeout.as_mut());
+                self.inline(recv.action.as_mut());
+            }
+            Expr::Seq(seq) => {
+                self.inline(seq.arg.as_mut());
+                self.inline(seq.body.as_mut());
+            }
+            Expr::Try(expr) => {
+                self.inline(expr.arg.as_mut());
+                self.inline(expr.body.as_mut());
+                self.inline(expr.handler.as_mut());
+            }
+            Expr::Tuple(tuple) => self.inline_all(tuple.elements.as_mut_slice()),
+            Expr::Values(values) => self.inline_all(values.values.as_mut_slice()),
+            Expr::Literal(_) | Expr::Var(_) => (),
+        }
+    }
+
+    fn inline_all(&mut self, exprs: &mut [Expr]) {
+        for expr in exprs.iter_mut() {
+            self.inline(expr);
+        }
+    }
+
+    fn inline_clauses(&mut self, clauses: &mut [Clause]) {
+        // Patterns can't contain calls, so only guards and bodies need to be visited
+        for clause in clauses.iter_mut() {
+            if let Some(guard) = clause.guard.as_mut() {
+                self.inline(guard.as_mut());
+            }
+            self.inline(clause.body.as_mut());
+        }
+    }
+
+    /// Returns the name of the function called by `call`, if it is a candidate for inlining
+    /// defined in the current module, and is not already being inlined
+    fn local_callee(&self, call: &Call) -> Option<FunctionName> {
+        if !call.module.is_atom_value(self.module) {
+            return None;
+        }
+        let function = call.function.as_atom()?;
+        let name = FunctionName::new_local(function, call.args.len() as u8);
+        if self.stack.contains(&name) || !self.candidates.contains_key(&name) {
+            return None;
+        }
+        Some(name)
+    }
+
+    /// Creates a copy of the body of `callee`, with its parameters bound to `args`
+    fn instantiate(&mut self, span: SourceSpan, callee: &Fun, args: Vec<Expr>) -> Expr {
+        let mut renamer = Renamer {
+            vars: HashMap::new(),
+            var_counter: &mut *self.var_counter,
+        };
+        let vars = callee.vars.iter().map(|v| renamer.bind(v)).collect();
+        let mut body = callee.body.as_ref().clone();
+        renamer.rename(&mut body);
+
+        let mut annotations = Annotations::default();
+        annotations.set(symbols::Inlined);
+        Expr::Let(Let {
+            span,
+            annotations,
+            vars,
+            arg: Box::new(Values::new(span, args)),
+            body: Box::new(body),
+        })
+    }
+}
+
+/// Renames all variables bound in an expression to fresh names
+struct Renamer<'a> {
+    vars: HashMap<Symbol, Symbol>,
+    var_counter: &'a mut usize,
+}
+impl<'a> Renamer<'a> {
+    /// Binds a fresh name for `var`, returning the renamed variable
+    fn bind(&mut self, var: &Var) -> Var {
+        let name = Symbol::intern(&format!("${}", *self.var_counter));
+        *self.var_counter += 1;
+        self.vars.insert(var.name(), name);
+        Var {
+            annotations: var.annotations.clone(),
+            name: Ident::new(name, var.name.span),
+            arity: var.arity,
+        }
+    }
+
+    fn rename_var(&self, var: &mut Var) {
+        // Function references are left alone, as they refer to functions rather than bindings
+        if var.arity.is_some() {
+            return;
+        }
+        if let Some(name) = self.vars.get(&var.name()) {
+            var.name.name = *name;
+        }
+    }
+
+    fn bind_all(&mut self, vars: &mut [Var]) {
+        for var in vars.iter_mut() {
+            *var = self.bind(var);
+        }
+    }
+
+    fn rename(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Var(var) => self.rename_var(var),
+            Expr::Literal(_) => (),
+            Expr::Alias(alias) => {
+                alias.var = self.bind(&alias.var);
+                self.rename_pattern(alias.pattern.as_mut());
+            }
+            Expr::Apply(apply) => {
+                self.rename(apply.callee.as_mut());
+                self.rename_all(apply.args.as_mut_slice());
+            }
+            Expr::Binary(bin) => {
+                for segment in bin.segments.iter_mut() {
+                    self.rename(segment.value.as_mut());
+                    if let Some(size) = segment.size.as_mut() {
+                        self.rename(size.as_mut());
+                    }
+                }
+            }
+            Expr::Call(call) => {
+                self.rename(call.module.as_mut());
+                self.rename(call.function.as_mut());
+                self.rename_all(call.args.as_mut_slice());
+            }
+            Expr::Case(case) => {
+                self.rename(case.arg.as_mut());
+                for clause in case.clauses.iter_mut() {
+                    self.rename_clause(clause);
+                }
+            }
+            Expr::Catch(catch) => self.rename(catch.body.as_mut()),
+            Expr::Cons(cons) => {
+                self.rename(cons.head.as_mut());
+                self.rename(cons.tail.as_mut());
+            }
+            Expr::Fun(fun) => {
+                let saved = self.vars.clone();
+                self.bind_all(fun.vars.as_mut_slice());
+                self.rename(fun.body.as_mut());
+                self.vars = saved;
+            }
+            Expr::If(expr) => {
+                self.rename(expr.guard.as_mut());
+                self.rename(expr.then_body.as_mut());
+                self.rename(expr.else_body.as_mut());
+            }
+            Expr::Let(expr) => {
+                self.rename(expr.arg.as_mut());
+                let saved = self.vars.clone();
+                self.bind_all(expr.vars.as_mut_slice());
+                self.rename(expr.body.as_mut());
+                self.vars = saved;
+            }
+            Expr::LetRec(letrec) => {
+                for (_, def) in letrec.defs.iter_mut() {
+                    self.rename(def);
+                }
+                self.rename(letrec.body.as_mut());
+            }
+            Expr::Map(map) => {
+                self.rename(map.arg.as_mut());
+                for pair in map.pairs.iter_mut() {
+                    self.rename(pair.key.as_mut());
+                    self.rename(pair.value.as_mut());
+                }
+            }
+            Expr::PrimOp(op) => self.rename_all(op.args.as_mut_slice()),
+            Expr::Receive(recv) => {
+                for clause in recv.clauses.iter_mut() {
+                    self.rename_clause(clause);
+                }
+                self.rename(recv.timeout.as_mut());
+                self.rename(recv.action.as_mut());
+            }
+            Expr::Seq(seq) => {
+                self.rename(seq.arg.as_mut());
+                self.rename(seq.body.as_mut());
+            }
+            Expr::Try(expr) => {
+                self.rename(expr.arg.as_mut());
+                let saved = self.vars.clone();
+                self.bind_all(expr.vars.as_mut_slice());
+                self.rename(expr.body.as_mut());
+                self.vars = saved.clone();
+                self.bind_all(expr.evars.as_mut_slice());
+                self.rename(expr.handler.as_mut());
+                self.vars = saved;
+            }
+            Expr::Tuple(tuple) => self.rename_all(tuple.elements.as_mut_slice()),
+            Expr::Values(values) => self.rename_all(values.values.as_mut_slice()),
+        }
+    }
+
+    fn rename_all(&mut self, exprs: &mut [Expr]) {
+        for expr in exprs.iter_mut() {
+            self.rename(expr);
+        }
+    }
+
+    fn rename_clause(&mut self, clause: &mut Clause) {
+        let saved = self.vars.clone();
+        for pattern in clause.patterns.iter_mut() {
+            self.rename_pattern(pattern);
+        }
+        if let Some(guard) = clause.guard.as_mut() {
+            self.rename(guard.as_mut());
+        }
+        self.rename(clause.body.as_mut());
+        self.vars = saved;
+    }
+
+    /// Renames the variables of a pattern, every one of which is a new binding
+    ///
+    /// The exceptions to this are map keys and binary segment sizes, which refer to
+    /// variables bound outside the pattern, or earlier in the same pattern.
+    fn rename_pattern(&mut self, pattern: &mut Expr) {
+        match pattern {
+            Expr::Var(var) => *var = self.bind(var),
+            Expr::Alias(alias) => {
+                alias.var = self.bind(&alias.var);
+                self.rename_pattern(alias.pattern.as_mut());
+            }
+            Expr::Cons(cons) => {
+                self.rename_pattern(cons.head.as_mut());
+                self.rename_pattern(cons.tail.as_mut());
+            }
+            Expr::Tuple(tuple) => {
+                for element in tuple.elements.iter_mut() {
+                    self.rename_pattern(element);
+                }
+            }
+            Expr::Map(map) => {
+                for pair in map.pairs.iter_mut() {
+                    self.rename(pair.key.as_mut());
+                    self.rename_pattern(pair.value.as_mut());
+                }
+            }
+            Expr::Binary(bin) => {
+                for segment in bin.segments.iter_mut() {
+                    if let Some(size) = segment.size.as_mut() {
+                        self.rename(size.as_mut());
+                    }
+                    self.rename_pattern(segment.value.as_mut());
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Returns true if `expr` can be safely duplicated by inlining
+fn is_inlinable(expr: &Expr) -> bool {
+    match expr {
+        Expr::Fun(_) | Expr::LetRec(_) | Expr::Receive(_) => false,
+        Expr::Var(_) | Expr::Literal(_) => true,
+        Expr::Alias(alias) => is_inlinable(&alias.pattern),
+        Expr::Apply(apply) => is_inlinable(&apply.callee) && apply.args.iter().all(is_inlinable),
+        Expr::Binary(bin) => bin.segments.iter().all(|segment| {
+            is_inlinable(&segment.value) && segment.size.as_deref().map_or(true, is_inlinable)
+        }),
+        Expr::Call(call) => call.args.iter().all(is_inlinable),
+        Expr::Case(case) => is_inlinable(&case.arg) && case.clauses.iter().all(is_clause_inlinable),
+        Expr::Catch(catch) => is_inlinable(&catch.body),
+        Expr::Cons(cons) => is_inlinable(&cons.head) && is_inlinable(&cons.tail),
+        Expr::If(expr) => {
+            is_inlinable(&expr.guard)
+                && is_inlinable(&expr.then_body)
+                && is_inlinable(&expr.else_body)
+        }
+        Expr::Let(expr) => is_inlinable(&expr.arg) && is_inlinable(&expr.body),
+        Expr::Map(map) => {
+            is_inlinable(&map.arg)
+                && map
+                    .pairs
+                    .iter()
+                    .all(|pair| is_inlinable(&pair.key) && is_inlinable(&pair.value))
+        }
+        Expr::PrimOp(op) => op.args.iter().all(is_inlinable),
+        Expr::Seq(seq) => is_inlinable(&seq.arg) && is_inlinable(&seq.body),
+        Expr::Try(expr) => {
+            is_inlinable(&expr.arg) && is_inlinable(&expr.body) && is_inlinable(&expr.handler)
+        }
+        Expr::Tuple(tuple) => tuple.elements.iter().all(is_inlinable),
+        Expr::Values(values) => values.values.iter().all(is_inlinable),
+    }
+}
+
+fn is_clause_inlinable(clause: &Clause) -> bool {
+    clause.guard.as_deref().map_or(true, is_inlinable) && is_inlinable(&clause.body)
+}
+
+/// Returns true if `expr` contains a call to the local function `name`
+fn calls(expr: &Expr, module: Symbol, name: &FunctionName) -> bool {
+    let calls_all = |exprs: &[Expr]| exprs.iter().any(|e| calls(e, module, name));
+    match expr {
+        Expr::Call(call) => {
+            let is_call = call.module.is_atom_value(module)
+                && call.function.is_atom_value(name.function)
+                && call.args.len() == name.arity as usize;
+            is_call || calls_all(call.args.as_slice())
+        }
+        Expr::Apply(apply) => {
+            let is_call = match apply.callee.as_ref() {
+                Expr::Var(var) => {
+                    var.name() == name.function && var.arity == Some(name.arity as usize)
+                }
+                callee => calls(callee, module, name),
+            };
+            is_call || calls_all(apply.args.as_slice())
+        }
+        Expr::Var(var) => var.name() == name.function && var.arity == Some(name.arity as usize),
+        Expr::Literal(_) => false,
+        Expr::Alias(alias) => calls(&alias.pattern, module, name),
+        Expr::Binary(bin) => bin.segments.iter().any(|segment| {
+            calls(&segment.value, module, name)
+                || segment
+                    .size
+                    .as_deref()
+                    .map_or(false, |size| calls(size, module, name))
+        }),
+        Expr::Case(case) => {
+            calls(&case.arg, module, name)
+                || case.clauses.iter().any(|clause| {
+                    clause
+                        .guard
+                        .as_deref()
+                        .map_or(false, |guard| calls(guard, module, name))
+                        || calls(&clause.body, module, name)
+                })
+        }
+        Expr::Catch(catch) => calls(&catch.body, module, name),
+        Expr::Cons(cons) => calls(&cons.head, module, name) || calls(&cons.tail, module, name),
+        Expr::Fun(fun) => calls(&fun.body, module, name),
+        Expr::If(expr) => {
+            calls(&expr.guard, module, name)
+                || calls(&expr.then_body, module, name)
+                || calls(&expr.else_body, module, name)
+        }
+        Expr::Let(expr) => calls(&expr.arg, module, name) || calls(&expr.body, module, name),
+        Expr::LetRec(letrec) => {
+            letrec.defs.iter().any(|(_, def)| calls(def, module, name))
+                || calls(&letrec.body, module, name)
+        }
+        Expr::Map(map) => {
+            calls(&map.arg, module, name)
+                || map
+                    .pairs
+                    .iter()
+                    .any(|pair| calls(&pair.key, module, name) || calls(&pair.value, module, name))
+        }
+        Expr::PrimOp(op) => calls_all(op.args.as_slice()),
+        Expr::Receive(recv) => {
+            recv.clauses
+                .iter()
+                .any(|clause| calls(&clause.body, module, name))
+                || calls(&recv.timeout, module, name)
+                || calls(&recv.action, module, name)
+        }
+        Expr::Seq(seq) => calls(&seq.arg, module, name) || calls(&seq.body, module, name),
+        Expr::Try(expr) => {
+            calls(&expr.arg, module, name)
+                || calls(&expr.body, module, name)
+                || calls(&expr.handler, module, name)
+        }
+        Expr::Tuple(tuple) => calls_all(tuple.elements.as_slice()),
+        Expr::Values(values) => calls_all(values.values.as_slice()),
+    }
+}
+
+/// Returns the size of `expr`, as the number of expressions it contains
+fn size(expr: &Expr) -> usize {
+    let size_all = |exprs: &[Expr]| exprs.iter().map(size).sum::<usize>();
+    let size_clause = |clause: &Clause| {
+        size_all(clause.patterns.as_slice())
+            + clause.guard.as_deref().map_or(0, size)
+            + size(&clause.body)
+    };
+    1 + match expr {
+        Expr::Var(_) | Expr::Literal(_) => 0,
+        Expr::Alias(alias) => size(&alias.pattern),
+        Expr::Apply(apply) => size(&apply.callee) + size_all(apply.args.as_slice()),
+        Expr::Binary(bin) => bin
+            .segments
+            .iter()
+            .map(|segment| size(&segment.value) + segment.size.as_deref().map_or(0, size))
+            .sum(),
+        Expr::Call(call) => size_all(call.args.as_slice()),
+        Expr::Case(case) => size(&case.arg) + case.clauses.iter().map(size_clause).sum::<usize>(),
+        Expr::Catch(catch) => size(&catch.body),
+        Expr::Cons(cons) => size(&cons.head) + size(&cons.tail),
+        Expr::Fun(fun) => size(&fun.body),
+        Expr::If(expr) => size(&expr.guard) + size(&expr.then_body) + size(&expr.else_body),
+        Expr::Let(expr) => size(&expr.arg) + size(&expr.body),
+        Expr::LetRec(letrec) => {
+            letrec.defs.iter().map(|(_, def)| size(def)).sum::<usize>() + size(&letrec.body)
+        }
+        Expr::Map(map) => {
+            size(&map.arg)
+                + map
+                    .pairs
+                    .iter()
+                    .map(|pair| size(&pair.key) + size(&pair.value))
+                    .sum::<usize>()
+        }
+        Expr::PrimOp(op) => size_all(op.args.as_slice()),
+        Expr::Receive(recv) => {
+            recv.clauses.iter().map(size_clause).sum::<usize>()
+                + size(&recv.timeout)
+                + size(&recv.action)
+        }
+        Expr::Seq(seq) => size(&seq.arg) + size(&seq.body),
+        Expr::Try(expr) => size(&expr.arg) + size(&expr.body) + size(&expr.handler),
+        Expr::Tuple(tuple) => size_all(tuple.elements.as_slice()),
+        Expr::Values(values) => size_all(values.values.as_slice()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+    use std::ops::Range;
+
+    use firefly_diagnostics::{CodeMap, FileName, Severity};
+
+    use super::*;
+
+    const SPAN: SourceSpan = SourceSpan::UNKNOWN;
+
+    fn var(name: &str) -> Var {
+        Var::new(Ident::new(Symbol::intern(name), SPAN))
+    }
+
+    fn call(span: SourceSpan, function: &str, args: Vec<Expr>) -> Expr {
+        let module = Symbol::intern("test");
+        Expr::Call(Call::new(span, module, Symbol::intern(function), args))
+    }
+
+    fn function(name: &str, params: &[&str], body: Expr) -> (FunctionName, Function) {
+        let vars = params.iter().map(|p| var(p)).collect::<Vec<_>>();
+        let name = Symbol::intern(name);
+        let fun = Fun {
+            span: SPAN,
+            annotations: Annotations::default(),
+            name,
+            vars,
+            body: Box::new(body),
+        };
+        let function = Function {
+            var_counter: 0,
+            fun,
+        };
+        (FunctionName::new_local(name, params.len() as u8), function)
+    }
+
+    /// Builds a module from `functions`, requesting those in `inline` to be inlined, as if by
+    /// a `-compile({inline, ...})` attribute at the given span
+    fn module(
+        functions: Vec<(FunctionName, Function)>,
+        inline: &[(&str, u8, SourceSpan)],
+    ) -> Module {
+        let name = Ident::new(Symbol::intern("test"), SPAN);
+        let mut compile = CompileOptions::default();
+        for (function, arity, span) in inline.iter() {
+            let function = FunctionName::new(name.name, Symbol::intern(function), *arity);
+            compile.inline_functions.insert(Span::new(*span, function));
+        }
+        Module {
+            span: SPAN,
+            annotations: Annotations::default(),
+            name,
+            compile,
+            on_load: None,
+            exports: HashSet::new(),
+            nifs: HashSet::new(),
+            functions: functions.into_iter().collect(),
+        }
+    }
+
+    fn run(module: Module) -> (Module, Reporter) {
+        let reporter = Reporter::new();
+        let module = InlineFunctions::new(reporter.clone()).run(module).unwrap();
+        (module, reporter)
+    }
+
+    fn body<'a>(module: &'a Module, name: &str, arity: u8) -> &'a Expr {
+        let name = FunctionName::new_local(Symbol::intern(name), arity);
+        module.functions[&name].fun.body.as_ref()
+    }
+
+    /// Returns a unique span in a file of its own
+    fn span(codemap: &CodeMap, name: &str) -> SourceSpan {
+        let id = codemap.add(FileName::Virtual(name.to_string().into()), name.to_string());
+        codemap.get(id).unwrap().source_span()
+    }
+
+    #[test]
+    fn inlined_bindings_are_renamed() {
+        // g(A) -> case A of {Y} -> Y end.
+        let clause = Clause::new(
+            SPAN,
+            vec![Expr::Tuple(Tuple::new(SPAN, vec![Expr::Var(var("Y"))]))],
+            Expr::Var(var("Y")),
+        );
+        let case = Expr::Case(Case {
+            span: SPAN,
+            annotations: Annotations::default(),
+            arg: Box::new(Expr::Var(var("A"))),
+            clauses: vec![clause],
+        });
+        let g = function("g", &["A"], case);
+        // f(Y) -> {g(Y), Y}.
+        let tuple = Tuple::new(
+            SPAN,
+            vec![
+                call(SPAN, "g", vec![Expr::Var(var("Y"))]),
+                Expr::Var(var("Y")),
+            ],
+        );
+        let f = function("f", &["Y"], Expr::Tuple(tuple));
+
+        let (module, _) = run(module(vec![f, g], &[("g", 1, SPAN)]));
+
+        let Expr::Tuple(tuple) = body(&module, "f", 1) else {
+            panic!("expected tuple");
+        };
+        let Expr::Let(inlined) = &tuple.elements[0] else {
+            panic!("expected inlined call");
+        };
+        assert!(inlined.annotations.contains(symbols::Inlined));
+        // The parameter of the callee is bound to the argument under a fresh name
+        let param = inlined.vars[0].name();
+        assert!(param != Symbol::intern("A") && param != Symbol::intern("Y"));
+        assert_eq!(inlined.arg.as_ref(), &Expr::Var(var("Y")));
+
+        let Expr::Case(case) = inlined.body.as_ref() else {
+            panic!("expected case");
+        };
+        assert_eq!(
+            case.arg.as_ref(),
+            &Expr::Var(Var::new(Ident::with_empty_span(param)))
+        );
+        // The pattern variable shadowing the caller's `Y` is renamed too, along with its uses
+        let Expr::Tuple(pattern) = &case.clauses[0].patterns[0] else {
+            panic!("expected tuple");
+        };
+        let Expr::Var(bound) = &pattern.elements[0] else {
+            panic!("expected var");
+        };
+        assert!(bound.name() != Symbol::intern("Y") && bound.name() != param);
+        assert_eq!(case.clauses[0].body.as_ref(), &Expr::Var(bound.clone()));
+
+        // The caller's own use of `Y` is untouched
+        assert_eq!(&tuple.elements[1], &Expr::Var(var("Y")));
+    }
+
+    #[test]
+    fn mutually_recursive_functions_are_inlined_once() {
+        // even(N) -> odd(N). odd(N) -> even(N). f(X) -> even(X).
+        let even = function("even", &["N"], call(SPAN, "odd", vec![Expr::Var(var("N"))]));
+        let odd = function("odd", &["N"], call(SPAN, "even", vec![Expr::Var(var("N"))]));
+        let f = function("f", &["X"], call(SPAN, "even", vec![Expr::Var(var("X"))]));
+
+        let inline = [("even", 1, SPAN), ("odd", 1, SPAN)];
+        let (module, reporter) = run(module(vec![f, even, odd], &inline));
+        assert!(reporter.diagnostics().is_empty());
+
+        // even/1 is inlined into f/1, and odd/1 into that, but the call back to even/1 is kept
+        let Expr::Let(even) = body(&module, "f", 1) else {
+            panic!("expected inlined even/1");
+        };
+        let Expr::Let(odd) = even.body.as_ref() else {
+            panic!("expected inlined odd/1");
+        };
+        let Expr::Call(call) = odd.body.as_ref() else {
+            panic!("expected call to even/1");
+        };
+        assert!(call.is_static(Symbol::intern("test"), Symbol::intern("even"), 1));
+    }
+
+    #[test]
+    fn functions_over_the_size_limit_are_not_inlined() {
+        let codemap = CodeMap::new();
+        let attribute = span(&codemap, "attribute");
+
+        let elements = (0..MAX_INLINE_SIZE).map(|_| Expr::Var(var("X"))).collect();
+        let big = function("big", &["X"], Expr::Tuple(Tuple::new(SPAN, elements)));
+        let caller = call(SPAN, "big", vec![Expr::Literal(Literal::nil(SPAN))]);
+        let f = function("f", &[], caller.clone());
+
+        let (module, reporter) = run(module(vec![f, big], &[("big", 1, attribute)]));
+        assert_eq!(body(&module, "f", 0), &caller);
+
+        let diagnostics = reporter.diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        let warning = &diagnostics[0];
+        assert_eq!(warning.severity, Severity::Warning);
+        assert_eq!(warning.message, "big/1 will not be inlined");
+        // The warning points at the function in the attribute which requested it be inlined
+        let label = &warning.labels[0];
+        assert_eq!(label.file_id, attribute.source_id());
+        assert_eq!(label.range, Range::<usize>::from(attribute));
+        assert!(label.message.contains("larger than the limit"));
+    }
+
+    #[test]
+    fn inlined_bodies_keep_their_spans() {
+        let codemap = CodeMap::new();
+        let definition = span(&codemap, "definition");
+        let call_site = span(&codemap, "call_site");
+
+        // g() -> ok. f() -> g().
+        let g = function(
+            "g",
+            &[],
+            Expr::Literal(Literal::atom(definition, symbols::Ok)),
+        );
+        let f = function("f", &[], call(call_site, "g", vec![]));
+
+        let (module, _) = run(module(vec![f, g], &[("g", 0, SPAN)]));
+
+        // The binding of the arguments belongs to the call, the body to the definition
+        let Expr::Let(inlined) = body(&module, "f", 0) else {
+            panic!("expected inlined call");
+        };
+        assert_eq!(inlined.span, call_site);
+        assert_eq!(inlined.body.span(), definition);
+    }
+}
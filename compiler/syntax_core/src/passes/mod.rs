@@ -3,12 +3,16 @@ use firefly_intern::{Ident, Symbol};
 use firefly_syntax_base::*;
 
 mod annotate;
+mod inline;
 mod known;
 mod rewrites;
+mod simplify;
 
 pub use self::annotate::AnnotateVariableUsage;
+pub use self::inline::InlineFunctions;
 pub(self) use self::known::Known;
 pub use self::rewrites::*;
+pub use self::simplify::SimplifyCore;
 
 #[derive(Debug, PartialEq)]
 pub struct FunctionContext {
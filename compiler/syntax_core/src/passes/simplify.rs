@@ -0,0 +1,1110 @@
+///! This pass performs simple optimizations on Core IR, similar to `sys_core_fold` in erlc.
+///!
+///! The following transformations are applied bottom-up to every function in the module:
+///!
+///! * Calls to guard BIFs with only literal arguments are evaluated at compile-time
+///! * Variables bound to literals by `let` are substituted in the body of the `let`
+///! * `let` and `do` expressions whose value is unused are removed when their argument
+///!   is free of side effects
+///! * `case` expressions with a literal argument are reduced to the body of the first
+///!   clause known to match, and `if` expressions with a literal guard to the branch taken
+///! * Clauses whose guard is known to be `false` are removed
+///!
+///! When evaluating a BIF would raise an exception, the call is left as-is so that the
+///! exception is raised at runtime.
+use std::cmp::Ordering;
+
+use firefly_binary::Bitstring as _;
+use firefly_diagnostics::Spanned;
+use firefly_intern::{symbols, Symbol};
+use firefly_number::{Integer, Number};
+use firefly_pass::Pass;
+use firefly_syntax_base::*;
+
+use crate::*;
+
+/// The largest shift amount which will be evaluated at compile-time
+///
+/// Larger shifts are left to the runtime, rather than risk producing enormous literals
+const MAX_FOLDED_SHIFT: i64 = 1024;
+
+/// Simplifies the Core IR of a module, see the module documentation for details
+pub struct SimplifyCore;
+impl SimplifyCore {
+    pub fn new() -> Self {
+        Self
+    }
+}
+impl Pass for SimplifyCore {
+    type Input<'a> = Module;
+    type Output<'a> = Module;
+
+    fn run<'a>(&mut self, mut module: Self::Input<'a>) -> anyhow::Result<Self::Output<'a>> {
+        for function in module.functions.values_mut() {
+            let body =
+                std::mem::replace(function.fun.body.as_mut(), Expr::Values(Values::default()));
+            *function.fun.body = self.simplify(body);
+        }
+
+        Ok(module)
+    }
+}
+
+impl SimplifyCore {
+    fn simplify(&mut self, expr: Expr) -> Expr {
+        match expr {
+            Expr::Call(call) => self.simplify_call(call),
+            Expr::Case(case) => self.simplify_case(case),
+            Expr::If(expr) => self.simplify_if(expr),
+            Expr::Let(expr) => self.simplify_let(expr),
+            Expr::Seq(seq) => {
+                let arg = self.simplify(*seq.arg);
+                let body = self.simplify(*seq.body);
+                if is_pure(&arg) {
+                    body
+                } else {
+                    Expr::Seq(Seq {
+                        arg: Box::new(arg),
+                        body: Box::new(body),
+                        ..seq
+                    })
+                }
+            }
+            Expr::Cons(cons) => {
+                let head = self.simplify(*cons.head);
+                let tail = self.simplify(*cons.tail);
+                match (head, tail) {
+                    (Expr::Literal(head), Expr::Literal(tail)) => Expr::Literal(Literal {
+                        span: cons.span,
+                        annotations: cons.annotations,
+                        value: Lit::Cons(Box::new(head), Box::new(tail)),
+                    }),
+                    (head, tail) => Expr::Cons(Cons {
+                        head: Box::new(head),
+                        tail: Box::new(tail),
+                        ..cons
+                    }),
+                }
+            }
+            Expr::Tuple(tuple) => {
+                let elements = self.simplify_all(tuple.elements);
+                if elements.iter().all(|e| e.is_literal()) {
+                    let elements = elements
+                        .into_iter()
+                        .map(|e| match e {
+                            Expr::Literal(lit) => lit,
+                            _ => unreachable!(),
+                        })
+                        .collect();
+                    Expr::Literal(Literal {
+                        span: tuple.span,
+                        annotations: tuple.annotations,
+                        value: Lit::Tuple(elements),
+                    })
+                } else {
+                    Expr::Tuple(Tuple { elements, ..tuple })
+                }
+            }
+            Expr::Values(expr) => {
+                let values = self.simplify_all(expr.values);
+                Expr::Values(Values { values, ..expr })
+            }
+            Expr::Fun(fun) => Expr::Fun(self.simplify_fun(fun)),
+            Expr::LetRec(letrec) => {
+                let defs = letrec
+                    .defs
+                    .into_iter()
+                    .map(|(var, def)| (var, self.simplify(def)))
+                    .collect();
+                let body = Box::new(self.simplify(*letrec.body));
+                Expr::LetRec(LetRec {
+                    defs,
+                    body,
+                    ..letrec
+                })
+            }
+            Expr::Apply(apply) => {
+                let callee = Box::new(self.simplify(*apply.callee));
+                let args = self.simplify_all(apply.args);
+                Expr::Apply(Apply {
+                    callee,
+                    args,
+                    ..apply
+                })
+            }
+            Expr::PrimOp(op) => {
+                let args = self.simplify_all(op.args);
+                Expr::PrimOp(PrimOp { args, ..op })
+            }
+            Expr::Catch(catch) => {
+                let body = Box::new(self.simplify(*catch.body));
+                Expr::Catch(Catch { body, ..catch })
+            }
+            Expr::Try(expr) => {
+                let arg = Box::new(self.simplify(*expr.arg));
+                let body = Box::new(self.simplify(*expr.body));
+                let handler = Box::new(self.simplify(*expr.handler));
+                Expr::Try(Try {
+                    arg,
+                    body,
+                    handler,
+                    ..expr
+                })
+            }
+            Expr::Receive(recv) => {
+                let clauses = recv
+                    .clauses
+                    .into_iter()
+                    .map(|clause| self.simplify_clause(clause))
+                    .collect();
+                let timeout = Box::new(self.simplify(*recv.timeout));
+                let action = Box::new(self.simplify(*recv.action));
+                Expr::Receive(Receive {
+                    clauses,
+                    timeout,
+                    action,
+                    ..recv
+                })
+            }
+            Expr::Map(map) if !map.is_pattern => {
+                let arg = Box::new(self.simplify(*map.arg));
+                let pairs = map
+                    .pairs
+                    .into_iter()
+                    .map(|pair| MapPair {
+                        op: pair.op,
+                        key: Box::new(self.simplify(*pair.key)),
+                        value: Box::new(self.simplify(*pair.value)),
+                    })
+                    .collect();
+                Expr::Map(Map { arg, pairs, ..map })
+            }
+            Expr::Binary(bin) => {
+                let segments = bin
+                    .segments
+                    .into_iter()
+                    .map(|segment| Bitstring {
+                        value: Box::new(self.simplify(*segment.value)),
+                        size: segment.size.map(|sz| Box::new(self.simplify(*sz))),
+                        ..segment
+                    })
+                    .collect();
+                Expr::Binary(Binary { segments, ..bin })
+            }
+            expr => expr,
+        }
+    }
+
+    fn simplify_all(&mut self, exprs: Vec<Expr>) -> Vec<Expr> {
+        exprs.into_iter().map(|e| self.simplify(e)).collect()
+    }
+
+    fn simplify_fun(&mut self, fun: Fun) -> Fun {
+        let body = Box::new(self.simplify(*fun.body));
+        Fun { body, ..fun }
+    }
+
+    fn simplify_clause(&mut self, clause: Clause) -> Clause {
+        let guard = clause.guard.map(|g| Box::new(self.simplify(*g)));
+        let body = Box::new(self.simplify(*clause.body));
+        Clause {
+            guard,
+            body,
+            ..clause
+        }
+    }
+
+    fn simplify_call(&mut self, call: Call) -> Expr {
+        let module = self.simplify(*call.module);
+        let function = self.simplify(*call.function);
+        let args = self.simplify_all(call.args);
+
+        if module.is_atom_value(symbols::Erlang) && args.iter().all(|arg| arg.is_literal()) {
+            if let Some(function) = function.as_atom() {
+                let name = FunctionName::new(symbols::Erlang, function, args.len() as u8);
+                if name.is_guard_bif() || name.is_arith_op() {
+                    let literals = args
+                        .iter()
+                        .map(|arg| match arg {
+                            Expr::Literal(lit) => lit,
+                            _ => unreachable!(),
+                        })
+                        .collect::<Vec<_>>();
+                    if let Some(value) = eval_bif(function, literals.as_slice()) {
+                        return Expr::Literal(Literal {
+                            span: call.span,
+                            annotations: call.annotations,
+                            value,
+                        });
+                    }
+                }
+            }
+        }
+
+        Expr::Call(Call {
+            module: Box::new(module),
+            function: Box::new(function),
+            args,
+            ..call
+        })
+    }
+
+    fn simplify_if(&mut self, expr: If) -> Expr {
+        let guard = self.simplify(*expr.guard);
+        match guard.as_boolean() {
+            Some(true) => self.simplify(*expr.then_body),
+            Some(false) => self.simplify(*expr.else_body),
+            None => {
+                let then_body = Box::new(self.simplify(*expr.then_body));
+                let else_body = Box::new(self.simplify(*expr.else_body));
+                Expr::If(If {
+                    guard: Box::new(guard),
+                    then_body,
+                    else_body,
+                    ..expr
+                })
+            }
+        }
+    }
+
+    fn simplify_let(&mut self, expr: Let) -> Expr {
+        let arg = self.simplify(*expr.arg);
+
+        // Propagate literals into the body
+        let mut body = *expr.body;
+        let literals = match &arg {
+            Expr::Literal(lit) if expr.vars.len() == 1 => vec![lit.clone()],
+            Expr::Values(Values { values, .. }) if values.len() == expr.vars.len() => values
+                .iter()
+                .filter_map(|v| match v {
+                    Expr::Literal(lit) => Some(lit.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        };
+        if literals.len() == expr.vars.len() {
+            for (var, lit) in expr.vars.iter().zip(literals.iter()) {
+                body = substitute(body, var, lit);
+            }
+        }
+        let body = self.simplify(body);
+
+        if expr.vars.iter().any(|v| body.is_var_used(v)) {
+            return Expr::Let(Let {
+                arg: Box::new(arg),
+                body: Box::new(body),
+                ..expr
+            });
+        }
+
+        // None of the bound variables are used, so the binding can be removed
+        if is_pure(&arg) {
+            body
+        } else if expr.vars.len() == 1 {
+            Expr::Seq(Seq {
+                span: expr.span,
+                annotations: expr.annotations,
+                arg: Box::new(arg),
+                body: Box::new(body),
+            })
+        } else {
+            Expr::Let(Let {
+                arg: Box::new(arg),
+                body: Box::new(body),
+                ..expr
+            })
+        }
+    }
+
+    fn simplify_case(&mut self, case: Case) -> Expr {
+        let arg = self.simplify(*case.arg);
+        let mut clauses = case.clauses;
+
+        let values = match &arg {
+            Expr::Literal(lit) => Some(vec![lit.clone()]),
+            Expr::Values(Values { values, .. }) => values
+                .iter()
+                .map(|v| match v {
+                    Expr::Literal(lit) => Some(lit.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => None,
+        };
+        if let Some(values) = values {
+            match self.select_clause(values.as_slice(), clauses) {
+                Ok(expr) => return expr,
+                Err(remaining) => clauses = remaining,
+            }
+        }
+
+        let clauses = clauses
+            .into_iter()
+            .map(|clause| self.simplify_clause(clause))
+            .collect::<Vec<_>>();
+        let is_unreachable = |clause: &Clause| {
+            clause
+                .guard
+                .as_ref()
+                .map(|g| g.is_atom_value(symbols::False))
+                .unwrap_or_default()
+        };
+        let clauses = if clauses.iter().all(is_unreachable) {
+            clauses
+        } else {
+            clauses.into_iter().filter(|c| !is_unreachable(c)).collect()
+        };
+
+        Expr::Case(Case {
+            arg: Box::new(arg),
+            clauses,
+            ..case
+        })
+    }
+
+    /// Selects the first clause which is known to match `values`, returning its body with the
+    /// pattern variables bound to the corresponding parts of `values`.
+    ///
+    /// If no clause is known to match, the clauses are returned, less those which are known
+    /// not to match.
+    fn select_clause(
+        &mut self,
+        values: &[Literal],
+        mut clauses: Vec<Clause>,
+    ) -> Result<Expr, Vec<Clause>> {
+        let mut unmatched = vec![];
+        let mut selected = None;
+        for (i, clause) in clauses.iter().enumerate() {
+            let mut bindings = vec![];
+            match match_patterns(clause.patterns.as_slice(), values, &mut bindings) {
+                Match::No => {
+                    unmatched.push(i);
+                    continue;
+                }
+                Match::Unknown => break,
+                Match::Yes => (),
+            }
+
+            let guard = match clause.guard.as_deref() {
+                None => Some(true),
+                Some(guard) => {
+                    let guard = bindings
+                        .iter()
+                        .fold(guard.clone(), |g, (var, lit)| substitute(g, var, lit));
+                    self.simplify(guard).as_boolean()
+                }
+            };
+            match guard {
+                Some(true) => {
+                    selected = Some((i, bindings));
+                    break;
+                }
+                Some(false) => {
+                    unmatched.push(i);
+                    continue;
+                }
+                None => break,
+            }
+        }
+
+        if let Some((i, bindings)) = selected {
+            let clause = clauses.swap_remove(i);
+            let span = clause.span;
+            let body = bindings
+                .into_iter()
+                .rev()
+                .fold(*clause.body, |body, (var, lit)| {
+                    Expr::Let(Let::new(span, vec![var], Expr::Literal(lit), body))
+                });
+            return Ok(self.simplify(body));
+        }
+
+        if unmatched.len() < clauses.len() {
+            for i in unmatched.into_iter().rev() {
+                clauses.remove(i);
+            }
+        }
+        Err(clauses)
+    }
+}
+
+/// The result of matching a pattern against a literal
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Match {
+    Yes,
+    No,
+    Unknown,
+}
+impl Match {
+    fn and(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::No, _) | (_, Self::No) => Self::No,
+            (Self::Unknown, _) | (_, Self::Unknown) => Self::Unknown,
+            _ => Self::Yes,
+        }
+    }
+}
+
+fn match_patterns(
+    patterns: &[Expr],
+    values: &[Literal],
+    bindings: &mut Vec<(Var, Literal)>,
+) -> Match {
+    if patterns.len() != values.len() {
+        return Match::Unknown;
+    }
+    patterns
+        .iter()
+        .zip(values.iter())
+        .fold(Match::Yes, |acc, (pattern, value)| {
+            acc.and(match_pattern(pattern, value, bindings))
+        })
+}
+
+fn match_pattern(pattern: &Expr, value: &Literal, bindings: &mut Vec<(Var, Literal)>) -> Match {
+    match (pattern, &value.value) {
+        (Expr::Var(var), _) => {
+            bindings.push((var.clone(), value.clone()));
+            Match::Yes
+        }
+        (Expr::Alias(alias), _) => {
+            bindings.push((alias.var.clone(), value.clone()));
+            match_pattern(alias.pattern.as_ref(), value, bindings)
+        }
+        (Expr::Literal(lit), _) => {
+            if lit.value == value.value {
+                Match::Yes
+            } else {
+                Match::No
+            }
+        }
+        (Expr::Cons(cons), Lit::Cons(head, tail)) => match_pattern(
+            cons.head.as_ref(),
+            head,
+            bindings,
+        )
+        .and(match_pattern(cons.tail.as_ref(), tail, bindings)),
+        (Expr::Cons(_), _) => Match::No,
+        (Expr::Tuple(tuple), Lit::Tuple(elements)) => {
+            if tuple.elements.len() == elements.len() {
+                match_patterns(tuple.elements.as_slice(), elements.as_slice(), bindings)
+            } else {
+                Match::No
+            }
+        }
+        (Expr::Tuple(_), _) => Match::No,
+        (Expr::Map(_), Lit::Map(_)) => Match::Unknown,
+        (Expr::Map(_), _) => Match::No,
+        (Expr::Binary(_), Lit::Binary(_)) => Match::Unknown,
+        (Expr::Binary(_), _) => Match::No,
+        _ => Match::Unknown,
+    }
+}
+
+/// Returns true if evaluating `expr` cannot have side effects or raise an exception
+fn is_pure(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(_) | Expr::Var(_) | Expr::Fun(_) => true,
+        Expr::Cons(Cons { head, tail, .. }) => is_pure(head) && is_pure(tail),
+        Expr::Tuple(Tuple { elements, .. }) => elements.iter().all(is_pure),
+        Expr::Values(Values { values, .. }) => values.iter().all(is_pure),
+        Expr::Call(call) => {
+            let Some(function) = call.function.as_atom() else { return false; };
+            if !call.module.is_atom_value(symbols::Erlang) {
+                return false;
+            }
+            let name = FunctionName::new(symbols::Erlang, function, call.args.len() as u8);
+            name.is_safe() && call.args.iter().all(is_pure)
+        }
+        _ => false,
+    }
+}
+
+/// Replaces all references to `var` in `expr` with `lit`, taking care to respect shadowing
+///
+/// References which cannot hold a literal, i.e. the callee of an `apply`, are left as-is
+fn substitute(expr: Expr, var: &Var, lit: &Literal) -> Expr {
+    let subst = |e: Expr| substitute(e, var, lit);
+    let subst_all = |es: Vec<Expr>| -> Vec<Expr> { es.into_iter().map(subst).collect() };
+    match expr {
+        Expr::Var(v) if v.arity.is_none() && v.name() == var.name() => Expr::Literal(Literal {
+            span: v.span(),
+            annotations: Annotations::default(),
+            value: lit.value.clone(),
+        }),
+        Expr::Apply(apply) => {
+            let callee = match *apply.callee {
+                callee @ Expr::Var(_) => callee,
+                callee => subst(callee),
+            };
+            Expr::Apply(Apply {
+                callee: Box::new(callee),
+                args: subst_all(apply.args),
+                ..apply
+            })
+        }
+        Expr::Binary(bin) => {
+            let segments = bin
+                .segments
+                .into_iter()
+                .map(|segment| Bitstring {
+                    value: Box::new(subst(*segment.value)),
+                    size: segment.size.map(|sz| Box::new(subst(*sz))),
+                    ..segment
+                })
+                .collect();
+            Expr::Binary(Binary { segments, ..bin })
+        }
+        Expr::Call(call) => Expr::Call(Call {
+            module: Box::new(subst(*call.module)),
+            function: Box::new(subst(*call.function)),
+            args: subst_all(call.args),
+            ..call
+        }),
+        Expr::Case(case) => Expr::Case(Case {
+            arg: Box::new(subst(*case.arg)),
+            clauses: substitute_clauses(case.clauses, var, lit),
+            ..case
+        }),
+        Expr::Catch(catch) => Expr::Catch(Catch {
+            body: Box::new(subst(*catch.body)),
+            ..catch
+        }),
+        Expr::Cons(cons) => Expr::Cons(Cons {
+            head: Box::new(subst(*cons.head)),
+            tail: Box::new(subst(*cons.tail)),
+            ..cons
+        }),
+        Expr::Fun(fun) if !fun.vars.contains(var) => Expr::Fun(Fun {
+            body: Box::new(subst(*fun.body)),
+            ..fun
+        }),
+        Expr::If(expr) => Expr::If(If {
+            guard: Box::new(subst(*expr.guard)),
+            then_body: Box::new(subst(*expr.then_body)),
+            else_body: Box::new(subst(*expr.else_body)),
+            ..expr
+        }),
+        Expr::Let(expr) => {
+            let arg = Box::new(subst(*expr.arg));
+            let body = if expr.vars.contains(var) {
+                expr.body
+            } else {
+                Box::new(subst(*expr.body))
+            };
+            Expr::Let(Let { arg, body, ..expr })
+        }
+        Expr::LetRec(letrec) => Expr::LetRec(LetRec {
+            defs: letrec
+                .defs
+                .into_iter()
+                .map(|(name, def)| (name, subst(def)))
+                .collect(),
+            body: Box::new(subst(*letrec.body)),
+            ..letrec
+        }),
+        Expr::Map(map) if !map.is_pattern => Expr::Map(Map {
+            arg: Box::new(subst(*map.arg)),
+            pairs: map
+                .pairs
+                .into_iter()
+                .map(|pair| MapPair {
+                    op: pair.op,
+                    key: Box::new(subst(*pair.key)),
+                    value: Box::new(subst(*pair.value)),
+                })
+                .collect(),
+            ..map
+        }),
+        Expr::PrimOp(op) => Expr::PrimOp(PrimOp {
+            args: subst_all(op.args),
+            ..op
+        }),
+        Expr::Receive(recv) => Expr::Receive(Receive {
+            clauses: substitute_clauses(recv.clauses, var, lit),
+            timeout: Box::new(subst(*recv.timeout)),
+            action: Box::new(subst(*recv.action)),
+            ..recv
+        }),
+        Expr::Seq(seq) => Expr::Seq(Seq {
+            arg: Box::new(subst(*seq.arg)),
+            body: Box::new(subst(*seq.body)),
+            ..seq
+        }),
+        Expr::Try(expr) => {
+            let arg = Box::new(subst(*expr.arg));
+            let body = if expr.vars.contains(var) {
+                expr.body
+            } else {
+                Box::new(subst(*expr.body))
+            };
+            let handler = if expr.evars.contains(var) {
+                expr.handler
+            } else {
+                Box::new(subst(*expr.handler))
+            };
+            Expr::Try(Try {
+                arg,
+                body,
+                handler,
+                ..expr
+            })
+        }
+        Expr::Tuple(tuple) => Expr::Tuple(Tuple {
+            elements: subst_all(tuple.elements),
+            ..tuple
+        }),
+        Expr::Values(values) => Expr::Values(Values {
+            values: subst_all(values.values),
+            ..values
+        }),
+        expr => expr,
+    }
+}
+
+fn substitute_clauses(clauses: Vec<Clause>, var: &Var, lit: &Literal) -> Vec<Clause> {
+    clauses
+        .into_iter()
+        .map(|clause| {
+            // Pattern variables shadow previous bindings
+            if clause.patterns.iter().any(|p| binds_var(p, var)) {
+                return clause;
+            }
+            Clause {
+                patterns: clause
+                    .patterns
+                    .into_iter()
+                    .map(|p| substitute_pattern(p, var, lit))
+                    .collect(),
+                guard: clause.guard.map(|g| Box::new(substitute(*g, var, lit))),
+                body: Box::new(substitute(*clause.body, var, lit)),
+                ..clause
+            }
+        })
+        .collect()
+}
+
+/// Patterns may only refer to bound variables in map keys and binary segment sizes
+fn substitute_pattern(pattern: Expr, var: &Var, lit: &Literal) -> Expr {
+    match pattern {
+        Expr::Alias(alias) => Expr::Alias(Alias {
+            pattern: Box::new(substitute_pattern(*alias.pattern, var, lit)),
+            ..alias
+        }),
+        Expr::Cons(cons) => Expr::Cons(Cons {
+            head: Box::new(substitute_pattern(*cons.head, var, lit)),
+            tail: Box::new(substitute_pattern(*cons.tail, var, lit)),
+            ..cons
+        }),
+        Expr::Tuple(tuple) => Expr::Tuple(Tuple {
+            elements: tuple
+                .elements
+                .into_iter()
+                .map(|e| substitute_pattern(e, var, lit))
+                .collect(),
+            ..tuple
+        }),
+        Expr::Map(map) => Expr::Map(Map {
+            pairs: map
+                .pairs
+                .into_iter()
+                .map(|pair| MapPair {
+                    op: pair.op,
+                    key: Box::new(substitute(*pair.key, var, lit)),
+                    value: Box::new(substitute_pattern(*pair.value, var, lit)),
+                })
+                .collect(),
+            ..map
+        }),
+        Expr::Binary(bin) => Expr::Binary(Binary {
+            segments: bin
+                .segments
+                .into_iter()
+                .map(|segment| Bitstring {
+                    value: Box::new(substitute_pattern(*segment.value, var, lit)),
+                    size: segment.size.map(|sz| Box::new(substitute(*sz, var, lit))),
+                    ..segment
+                })
+                .collect(),
+            ..bin
+        }),
+        pattern => pattern,
+    }
+}
+
+/// Returns true if `var` is bound by `pattern`
+fn binds_var(pattern: &Expr, var: &Var) -> bool {
+    match pattern {
+        Expr::Var(v) => v.name() == var.name(),
+        Expr::Alias(alias) => alias.var.name() == var.name() || binds_var(&alias.pattern, var),
+        Expr::Cons(Cons { head, tail, .. }) => binds_var(head, var) || binds_var(tail, var),
+        Expr::Tuple(Tuple { elements, .. }) => elements.iter().any(|e| binds_var(e, var)),
+        Expr::Map(Map { pairs, .. }) => pairs.iter().any(|p| binds_var(&p.value, var)),
+        Expr::Binary(Binary { segments, .. }) => segments.iter().any(|s| binds_var(&s.value, var)),
+        _ => false,
+    }
+}
+
+/// Evaluates a call to the BIF `erlang:<function>/<args.len()>`
+///
+/// Returns `None` if the BIF is not supported, or if the call would raise an exception
+fn eval_bif(function: Symbol, args: &[&Literal]) -> Option<Lit> {
+    match (function, args) {
+        // Type tests
+        (symbols::IsAtom, [arg]) => Some(bool_lit(matches!(arg.value, Lit::Atom(_)))),
+        (symbols::IsBoolean, [arg]) => Some(bool_lit(
+            arg.as_atom().map(|a| a.is_boolean()).unwrap_or_default(),
+        )),
+        (symbols::IsInteger, [arg]) => Some(bool_lit(matches!(arg.value, Lit::Integer(_)))),
+        (symbols::IsFloat, [arg]) => Some(bool_lit(matches!(arg.value, Lit::Float(_)))),
+        (symbols::IsNumber, [arg]) => Some(bool_lit(arg.value.is_number())),
+        (symbols::IsList, [arg]) => Some(bool_lit(matches!(arg.value, Lit::Nil | Lit::Cons(_, _)))),
+        (symbols::IsTuple, [arg]) => Some(bool_lit(matches!(arg.value, Lit::Tuple(_)))),
+        (symbols::IsMap, [arg]) => Some(bool_lit(matches!(arg.value, Lit::Map(_)))),
+        (symbols::IsBitstring, [arg]) => Some(bool_lit(matches!(arg.value, Lit::Binary(_)))),
+        (symbols::IsBinary, [arg]) => Some(bool_lit(match &arg.value {
+            Lit::Binary(bin) => bin.is_binary(),
+            _ => false,
+        })),
+        // There are no literal pids, ports, references or funs
+        (symbols::IsPid | symbols::IsPort | symbols::IsReference | symbols::IsFunction, [_]) => {
+            Some(bool_lit(false))
+        }
+        // Comparisons
+        (symbols::EqualStrict, [lhs, rhs]) => exact_eq(lhs, rhs).map(bool_lit),
+        (symbols::NotEqualStrict, [lhs, rhs]) => exact_eq(lhs, rhs).map(|eq| bool_lit(!eq)),
+        (symbols::Equal, [lhs, rhs]) => loose_eq(lhs, rhs).map(bool_lit),
+        (symbols::NotEqual, [lhs, rhs]) => loose_eq(lhs, rhs).map(|eq| bool_lit(!eq)),
+        (symbols::Lt, [lhs, rhs]) => compare(lhs, rhs).map(|o| bool_lit(o.is_lt())),
+        (symbols::Lte, [lhs, rhs]) => compare(lhs, rhs).map(|o| bool_lit(o.is_le())),
+        (symbols::Gt, [lhs, rhs]) => compare(lhs, rhs).map(|o| bool_lit(o.is_gt())),
+        (symbols::Gte, [lhs, rhs]) => compare(lhs, rhs).map(|o| bool_lit(o.is_ge())),
+        // Boolean operators
+        (symbols::Not, [arg]) => Some(bool_lit(!as_boolean(arg)?)),
+        (symbols::And, [lhs, rhs]) => Some(bool_lit(as_boolean(lhs)? & as_boolean(rhs)?)),
+        (symbols::Or, [lhs, rhs]) => Some(bool_lit(as_boolean(lhs)? | as_boolean(rhs)?)),
+        (symbols::Xor, [lhs, rhs]) => Some(bool_lit(as_boolean(lhs)? ^ as_boolean(rhs)?)),
+        // Arithmetic
+        (symbols::Plus, [arg]) => as_number(arg).map(number_lit),
+        (symbols::Minus, [arg]) => as_number(arg).map(|n| number_lit(-n)),
+        (symbols::Abs, [arg]) => match &arg.value {
+            Lit::Integer(i) => Some(Lit::Integer(i.abs())),
+            Lit::Float(f) => Some(Lit::Float(f.abs())),
+            _ => None,
+        },
+        (symbols::Plus, [lhs, rhs]) => (as_number(lhs)? + as_number(rhs)?).ok().map(number_lit),
+        (symbols::Minus, [lhs, rhs]) => (as_number(lhs)? - as_number(rhs)?).ok().map(number_lit),
+        (symbols::Star, [lhs, rhs]) => (as_number(lhs)? * as_number(rhs)?).ok().map(number_lit),
+        (symbols::Slash, [lhs, rhs]) => {
+            let lhs = as_float(lhs)?;
+            let rhs = as_float(rhs)?;
+            (lhs / rhs).ok().map(Lit::Float)
+        }
+        (symbols::Div, [lhs, rhs]) => (as_integer(lhs)? / as_integer(rhs)?).ok().map(Lit::Integer),
+        (symbols::Rem, [lhs, rhs]) => (as_integer(lhs)? % as_integer(rhs)?).ok().map(Lit::Integer),
+        (symbols::Band, [lhs, rhs]) => Some(Lit::Integer(as_integer(lhs)? & as_integer(rhs)?)),
+        (symbols::Bor, [lhs, rhs]) => Some(Lit::Integer(as_integer(lhs)? | as_integer(rhs)?)),
+        (symbols::Bxor, [lhs, rhs]) => Some(Lit::Integer(as_integer(lhs)? ^ as_integer(rhs)?)),
+        (symbols::Bnot, [arg]) => Some(Lit::Integer(!as_integer(arg)?)),
+        (symbols::Bsl | symbols::Bsr, [lhs, rhs]) => {
+            let value = as_integer(lhs)?;
+            let shift = as_integer(rhs)?;
+            if shift < 0i64 || shift > MAX_FOLDED_SHIFT {
+                return None;
+            }
+            let result = if function == symbols::Bsl {
+                value << shift
+            } else {
+                value >> shift
+            };
+            result.ok().map(Lit::Integer)
+        }
+        // Data structures
+        (symbols::TupleSize, [arg]) => match &arg.value {
+            Lit::Tuple(elements) => Some(Lit::Integer(elements.len().into())),
+            _ => None,
+        },
+        (symbols::Element, [index, tuple]) => match (&index.value, &tuple.value) {
+            (Lit::Integer(i), Lit::Tuple(elements)) => {
+                let index = i.to_usize()?.checked_sub(1)?;
+                elements.get(index).map(|e| e.value.clone())
+            }
+            _ => None,
+        },
+        (symbols::Hd, [arg]) => match &arg.value {
+            Lit::Cons(head, _) => Some(head.value.clone()),
+            _ => None,
+        },
+        (symbols::Tl, [arg]) => match &arg.value {
+            Lit::Cons(_, tail) => Some(tail.value.clone()),
+            _ => None,
+        },
+        (symbols::Length, [arg]) => {
+            let mut len = 0usize;
+            let mut list = &arg.value;
+            loop {
+                match list {
+                    Lit::Nil => break Some(Lit::Integer(len.into())),
+                    Lit::Cons(_, tail) => {
+                        len += 1;
+                        list = &tail.value;
+                    }
+                    _ => break None,
+                }
+            }
+        }
+        (symbols::ByteSize, [arg]) => match &arg.value {
+            Lit::Binary(bin) => Some(Lit::Integer(bin.byte_size().into())),
+            _ => None,
+        },
+        (symbols::BitSize, [arg]) => match &arg.value {
+            Lit::Binary(bin) => Some(Lit::Integer(bin.bit_size().into())),
+            _ => None,
+        },
+        (symbols::Float, [arg]) => Some(Lit::Float(as_float(arg)?)),
+        _ => None,
+    }
+}
+
+fn bool_lit(value: bool) -> Lit {
+    Lit::Atom(if value { symbols::True } else { symbols::False })
+}
+
+fn number_lit(n: Number) -> Lit {
+    match n {
+        Number::Integer(i) => Lit::Integer(i),
+        Number::Float(f) => Lit::Float(f),
+    }
+}
+
+fn as_boolean(lit: &Literal) -> Option<bool> {
+    match lit.value {
+        Lit::Atom(symbols::True) => Some(true),
+        Lit::Atom(symbols::False) => Some(false),
+        _ => None,
+    }
+}
+
+fn as_integer(lit: &Literal) -> Option<Integer> {
+    match &lit.value {
+        Lit::Integer(i) => Some(i.clone()),
+        _ => None,
+    }
+}
+
+fn as_number(lit: &Literal) -> Option<Number> {
+    match &lit.value {
+        Lit::Integer(i) => Some(Number::Integer(i.clone())),
+        Lit::Float(f) => Some(Number::Float(*f)),
+        _ => None,
+    }
+}
+
+fn as_float(lit: &Literal) -> Option<firefly_number::Float> {
+    match &lit.value {
+        Lit::Integer(i) => i.to_efloat().ok(),
+        Lit::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+/// Returns true if `lit` is, or contains, a float
+fn contains_float(lit: &Lit) -> bool {
+    match lit {
+        Lit::Float(_) => true,
+        Lit::Cons(head, tail) => contains_float(&head.value) || contains_float(&tail.value),
+        Lit::Tuple(elements) => elements.iter().any(|e| contains_float(&e.value)),
+        Lit::Map(map) => map
+            .iter()
+            .any(|(k, v)| contains_float(&k.value) || contains_float(&v.value)),
+        _ => false,
+    }
+}
+
+/// Compares two literals for exact equality, i.e. `=:=`
+///
+/// Floats are never compared, as the exact semantics of float equality are left to the runtime
+fn exact_eq(lhs: &Literal, rhs: &Literal) -> Option<bool> {
+    if contains_float(&lhs.value) || contains_float(&rhs.value) {
+        return None;
+    }
+    Some(lhs.value == rhs.value)
+}
+
+/// Compares two literals for equality, i.e. `==`
+///
+/// This only differs from exact equality when integers are compared with floats
+fn loose_eq(lhs: &Literal, rhs: &Literal) -> Option<bool> {
+    if lhs.value.is_number() && rhs.value.is_number() {
+        return Some(lhs.value.cmp(&rhs.value).is_eq());
+    }
+    if type_order(&lhs.value) != type_order(&rhs.value) {
+        return Some(false);
+    }
+    exact_eq(lhs, rhs)
+}
+
+/// Compares two literals in term order, i.e. as `<` does
+///
+/// Only numbers, and terms of different types, are compared, anything else returns `None`.
+fn compare(lhs: &Literal, rhs: &Literal) -> Option<Ordering> {
+    if lhs.value.is_number() && rhs.value.is_number() {
+        return Some(lhs.value.cmp(&rhs.value));
+    }
+    let lhs_order = type_order(&lhs.value);
+    let rhs_order = type_order(&rhs.value);
+    if lhs_order != rhs_order {
+        return Some(lhs_order.cmp(&rhs_order));
+    }
+    None
+}
+
+/// number < atom < reference < fun < port < pid < tuple < map < nil < list < bit string
+fn type_order(lit: &Lit) -> u8 {
+    match lit {
+        Lit::Integer(_) | Lit::Float(_) => 0,
+        Lit::Atom(_) => 1,
+        Lit::Tuple(_) => 2,
+        Lit::Map(_) => 3,
+        Lit::Nil => 4,
+        Lit::Cons(_, _) => 5,
+        Lit::Binary(_) => 6,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use firefly_diagnostics::SourceSpan;
+    use firefly_intern::Ident;
+
+    use super::*;
+
+    const SPAN: SourceSpan = SourceSpan::UNKNOWN;
+
+    fn simplify(expr: Expr) -> Expr {
+        SimplifyCore::new().simplify(expr)
+    }
+
+    fn atom(name: &str) -> Expr {
+        Expr::Literal(Literal::atom(SPAN, Symbol::intern(name)))
+    }
+
+    fn int(i: i64) -> Expr {
+        Expr::Literal(Literal::integer(SPAN, i))
+    }
+
+    fn var(name: &str) -> Var {
+        Var::new(Ident::new(Symbol::intern(name), SPAN))
+    }
+
+    fn tuple(elements: Vec<Expr>) -> Expr {
+        Expr::Tuple(Tuple::new(SPAN, elements))
+    }
+
+    fn erlang(function: Symbol, args: Vec<Expr>) -> Expr {
+        Expr::Call(Call::new(SPAN, symbols::Erlang, function, args))
+    }
+
+    fn case(arg: Expr, clauses: Vec<Clause>) -> Expr {
+        Expr::Case(Case {
+            span: SPAN,
+            annotations: Annotations::default(),
+            arg: Box::new(arg),
+            clauses,
+        })
+    }
+
+    fn bind(name: &str, arg: Expr, body: Expr) -> Expr {
+        Expr::Let(Let::new(SPAN, vec![var(name)], arg, body))
+    }
+
+    #[test]
+    fn constant_folding() {
+        // erlang:'*'(erlang:'+'(1, 2), 4)
+        let sum = erlang(symbols::Plus, vec![int(1), int(2)]);
+        let expr = erlang(symbols::Star, vec![sum, int(4)]);
+        assert_eq!(simplify(expr), int(12));
+
+        let expr = erlang(symbols::IsAtom, vec![atom("foo")]);
+        assert_eq!(simplify(expr), atom("true"));
+
+        // let <X> = 2 in erlang:'+'(X, 1)
+        let sum = erlang(symbols::Plus, vec![Expr::Var(var("X")), int(1)]);
+        let expr = bind("X", int(2), sum);
+        assert_eq!(simplify(expr), int(3));
+    }
+
+    #[test]
+    fn constant_folding_leaves_exceptions_to_runtime() {
+        let expr = erlang(symbols::Div, vec![int(1), int(0)]);
+        assert_eq!(simplify(expr.clone()), expr);
+
+        let expr = erlang(symbols::Plus, vec![atom("a"), int(1)]);
+        assert_eq!(simplify(expr.clone()), expr);
+    }
+
+    #[test]
+    fn case_of_known_constructor() {
+        // case {ok, 1} of {error, _} -> error; {ok, X} -> X end
+        let expr = case(
+            tuple(vec![atom("ok"), int(1)]),
+            vec![
+                Clause::new(
+                    SPAN,
+                    vec![tuple(vec![atom("error"), Expr::Var(var("_"))])],
+                    atom("error"),
+                ),
+                Clause::new(
+                    SPAN,
+                    vec![tuple(vec![atom("ok"), Expr::Var(var("X"))])],
+                    Expr::Var(var("X")),
+                ),
+            ],
+        );
+        assert_eq!(simplify(expr), int(1));
+    }
+
+    #[test]
+    fn case_with_unknown_argument_drops_unreachable_clauses() {
+        let unreachable = Clause {
+            guard: Some(Box::new(erlang(symbols::IsAtom, vec![int(1)]))),
+            ..Clause::new(SPAN, vec![Expr::Var(var("Y"))], atom("a"))
+        };
+        let reachable = Clause::new(SPAN, vec![Expr::Var(var("Z"))], atom("b"));
+        let expr = case(Expr::Var(var("X")), vec![unreachable, reachable.clone()]);
+        assert_eq!(simplify(expr), case(Expr::Var(var("X")), vec![reachable]));
+    }
+
+    #[test]
+    fn dead_let_removal() {
+        // let <X> = {a, Y} in ok
+        let expr = bind("X", tuple(vec![atom("a"), Expr::Var(var("Y"))]), atom("ok"));
+        assert_eq!(simplify(expr), atom("ok"));
+
+        // do {a} ok
+        let expr = Expr::Seq(Seq::new(SPAN, tuple(vec![atom("a")]), atom("ok")));
+        assert_eq!(simplify(expr), atom("ok"));
+    }
+
+    #[test]
+    fn side_effecting_arguments_are_kept() {
+        let io = Symbol::intern("io");
+        let format = Symbol::intern("format");
+        let effect = Expr::Call(Call::new(SPAN, io, format, vec![atom("hello")]));
+
+        // let <X> = io:format(hello) in ok
+        let expr = bind("X", effect.clone(), atom("ok"));
+        let expected = Expr::Seq(Seq::new(SPAN, effect.clone(), atom("ok")));
+        assert_eq!(simplify(expr), expected);
+
+        let expr = Expr::Seq(Seq::new(SPAN, effect, atom("ok")));
+        assert_eq!(simplify(expr.clone()), expr);
+
+        // Arithmetic may raise, so it must be kept even when its result is unused
+        let arith = erlang(symbols::Plus, vec![Expr::Var(var("Y")), int(1)]);
+        let expr = bind("X", arith.clone(), atom("ok"));
+        let expected = Expr::Seq(Seq::new(SPAN, arith, atom("ok")));
+        assert_eq!(simplify(expr), expected);
+    }
+}
@@ -38,6 +38,7 @@ pub enum Expr {
     // Comprehensions
     ListComprehension(ListComprehension),
     BinaryComprehension(BinaryComprehension),
+    MapComprehension(MapComprehension),
     Generator(Generator),
     // Complex expressions
     Begin(Begin),
@@ -51,6 +52,8 @@ pub enum Expr {
     Case(Case),
     Receive(Receive),
     Try(Try),
+    Maybe(Maybe),
+    MaybeMatch(MaybeMatch),
     Fun(Fun),
     Protect(Protect),
 }
@@ -978,6 +981,20 @@ impl PartialEq for BinaryComprehension {
     }
 }
 
+#[derive(Debug, Clone, Spanned)]
+pub struct MapComprehension {
+    #[span]
+    pub span: SourceSpan,
+    pub key: Box<Expr>,
+    pub value: Box<Expr>,
+    pub qualifiers: Vec<Expr>,
+}
+impl PartialEq for MapComprehension {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.value == other.value && self.qualifiers == other.qualifiers
+    }
+}
+
 /// A generator is one of two types of expressions that act as qualifiers in a commprehension, the other is a filter
 #[derive(Debug, Clone, Spanned)]
 pub struct Generator {
@@ -997,6 +1014,10 @@ impl PartialEq for Generator {
 pub enum GeneratorType {
     Default,
     Bitstring,
+    /// A map generator, i.e. `K := V <- Map`
+    ///
+    /// The pattern of the generator is a 2-tuple of the key and value patterns
+    Map,
 }
 impl Default for GeneratorType {
     fn default() -> Self {
@@ -1234,6 +1255,40 @@ impl PartialEq for Try {
     }
 }
 
+/// Represents a `maybe` expression, as described in EEP-49
+///
+/// The body is evaluated in sequence until a conditional match (`?=`) fails, in which case
+/// the unmatched value is either the result of the `maybe`, or is matched against the `else`
+/// clauses, if present.
+#[derive(Debug, Clone, Spanned)]
+pub struct Maybe {
+    #[span]
+    pub span: SourceSpan,
+    pub body: Vec<Expr>,
+    pub else_clauses: Option<Vec<Clause>>,
+}
+impl PartialEq for Maybe {
+    fn eq(&self, other: &Self) -> bool {
+        self.body == other.body && self.else_clauses == other.else_clauses
+    }
+}
+
+/// Represents a conditional match, i.e. `Pattern ?= Expr`
+///
+/// This is only valid as a top-level expression in the body of a `maybe`.
+#[derive(Debug, Clone, Spanned)]
+pub struct MaybeMatch {
+    #[span]
+    pub span: SourceSpan,
+    pub pattern: Box<Expr>,
+    pub expr: Box<Expr>,
+}
+impl PartialEq for MaybeMatch {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern && self.expr == other.expr
+    }
+}
+
 /// Represents the `after` clause of a `receive` expression
 #[derive(Debug, Clone, Spanned)]
 pub struct After {
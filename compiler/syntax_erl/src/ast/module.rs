@@ -77,6 +77,8 @@ pub struct Module {
     pub specs: HashMap<FunctionName, TypeSpec>,
     pub behaviours: HashSet<Ident>,
     pub callbacks: HashMap<FunctionName, Callback>,
+    // Callbacks named by `-optional_callbacks`
+    pub optional_callbacks: HashSet<Span<FunctionName>>,
     pub records: HashMap<Symbol, Record>,
     pub attributes: HashMap<Ident, ast::Literal>,
     pub functions: BTreeMap<FunctionName, Function>,
@@ -115,6 +117,22 @@ impl Module {
         !self.is_local(&local_name) && self.imports.contains_key(&local_name)
     }
 
+    /// Returns the behaviour defined by the `-callback` attributes of this module, if any
+    pub fn behaviour(&self) -> Option<Behaviour> {
+        if self.callbacks.is_empty() {
+            return None;
+        }
+        let mut behaviour = Behaviour::default();
+        for (name, callback) in self.callbacks.iter() {
+            if callback.optional {
+                behaviour.optional_callbacks.insert(*name);
+            } else {
+                behaviour.callbacks.insert(*name);
+            }
+        }
+        Some(behaviour)
+    }
+
     /// Creates a new, empty module with the given name and span
     pub fn new(name: Ident, span: SourceSpan) -> Self {
         Self {
@@ -133,6 +151,7 @@ impl Module {
             specs: HashMap::new(),
             behaviours: HashSet::new(),
             callbacks: HashMap::new(),
+            optional_callbacks: HashSet::new(),
             records: HashMap::new(),
             attributes: HashMap::new(),
             functions: BTreeMap::new(),
@@ -185,6 +204,7 @@ impl Module {
 
             behaviours: HashSet::new(),
             callbacks: HashMap::new(),
+            optional_callbacks: HashSet::new(),
             records: HashMap::new(),
             attributes: HashMap::new(),
             functions: BTreeMap::new(),
@@ -239,6 +259,9 @@ impl PartialEq for Module {
         if self.callbacks != other.callbacks {
             return false;
         }
+        if self.optional_callbacks != other.optional_callbacks {
+            return false;
+        }
         if self.records != other.records {
             return false;
         }
@@ -1,9 +1,10 @@
+use std::cell::Cell;
 use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 
 use firefly_binary::{BinaryEntrySpecifier, BitVec, Bitstring};
 use firefly_diagnostics::{Diagnostic, Label, SourceSpan, Spanned, ToDiagnostic};
-use firefly_intern::{symbols, Ident};
+use firefly_intern::{symbols, Ident, Symbol};
 use firefly_number::{f16, Integer, Number, ToPrimitive};
 use firefly_syntax_base::{BinaryOp, UnaryOp};
 
@@ -60,6 +61,12 @@ pub enum EvalError {
         span: SourceSpan,
     },
 
+    #[error("invalid binary segment")]
+    InvalidBinarySegment {
+        #[span]
+        span: SourceSpan,
+    },
+
     #[error("map does not contain key `{key}`")]
     InvalidMapKey {
         #[span]
@@ -96,6 +103,10 @@ impl ToDiagnostic for EvalError {
             EvalError::NoRecordField { span } => Diagnostic::error()
                 .with_message(msg)
                 .with_labels(vec![Label::primary(span.source_id(), *span)]),
+            EvalError::InvalidBinarySegment { span } => Diagnostic::error()
+                .with_message(msg)
+                .with_labels(vec![Label::primary(span.source_id(), *span)
+                    .with_message("this segment cannot be evaluated to a constant")]),
             EvalError::InvalidMapKey { span, .. } => Diagnostic::error()
                 .with_message(msg)
                 .with_labels(vec![Label::primary(span.source_id(), *span)]),
@@ -103,12 +114,6 @@ impl ToDiagnostic for EvalError {
     }
 }
 
-#[allow(dead_code)]
-pub enum ResolveRecordIndexError {
-    NoRecord,
-    NoField,
-}
-
 #[derive(Debug, Clone, Default)]
 pub struct Bindings(BTreeMap<Ident, Expr>);
 impl Bindings {
@@ -154,24 +159,27 @@ impl Bindings {
     }
 }
 
+/// Evaluates `expr` to a constant
+///
+/// If provided, `records` is used to resolve record expressions to their tuple representation
 pub fn eval_expr(
     expr: &Expr,
-    resolve_record_index: Option<&dyn Fn(Ident, Ident) -> Result<usize, ResolveRecordIndexError>>,
+    records: Option<&HashMap<Symbol, ast::Record>>,
 ) -> Result<Literal, EvalError> {
     let span = expr.span();
 
     let res = match expr {
         Expr::Literal(lit) => lit.clone(),
         Expr::Cons(cons) => {
-            let head = eval_expr(&cons.head, resolve_record_index)?;
-            let tail = eval_expr(&cons.tail, resolve_record_index)?;
+            let head = eval_expr(&cons.head, records)?;
+            let tail = eval_expr(&cons.tail, records)?;
             Literal::Cons(cons.span, Box::new(head), Box::new(tail))
         }
         Expr::Tuple(tup) => Literal::Tuple(
             tup.span,
             tup.elements
                 .iter()
-                .map(|e| eval_expr(e, resolve_record_index))
+                .map(|e| eval_expr(e, records))
                 .collect::<Result<Vec<_>, _>>()?,
         ),
         Expr::Map(ast::Map { span, fields }) => {
@@ -181,8 +189,8 @@ pub fn eval_expr(
                     ast::MapField::Assoc {
                         ref key, ref value, ..
                     } => {
-                        let key = eval_expr(key, resolve_record_index)?;
-                        let value = eval_expr(value, resolve_record_index)?;
+                        let key = eval_expr(key, records)?;
+                        let value = eval_expr(value, records)?;
                         stored.insert(key, value);
                     }
                     ast::MapField::Exact {
@@ -190,8 +198,8 @@ pub fn eval_expr(
                         ref key,
                         ref value,
                     } => {
-                        let key = eval_expr(key, resolve_record_index)?;
-                        let value = eval_expr(value, resolve_record_index)?;
+                        let key = eval_expr(key, records)?;
+                        let value = eval_expr(value, records)?;
                         if let Err(err) = stored.try_insert(key, value) {
                             return Err(EvalError::InvalidMapKey {
                                 span: *span,
@@ -203,14 +211,75 @@ pub fn eval_expr(
             }
             Literal::Map(*span, stored)
         }
-        Expr::Binary(_) => unimplemented!(),
-        Expr::Record(_) => unimplemented!(),
-        Expr::RecordIndex(rec_idx) if resolve_record_index.is_some() => {
+        Expr::Binary(ast::Binary { span, elements }) => {
+            // Segments are evaluated via `expr_grp`, which does not propagate errors, so we
+            // stash the first evaluation error to report it rather than a generic one
+            let error = Cell::new(None);
+            let mut bindings = Bindings::default();
+            let result = expr_grp(elements, &mut bindings, |expr, _bindings| {
+                match eval_expr(&expr, records) {
+                    Ok(lit) => Ok(Expr::Literal(lit)),
+                    Err(err) => {
+                        error.set(Some(err));
+                        Err(())
+                    }
+                }
+            });
+            match result {
+                Ok(bin) => Literal::Binary(*span, bin),
+                Err(_) => {
+                    return Err(error
+                        .take()
+                        .unwrap_or(EvalError::InvalidBinarySegment { span: *span }))
+                }
+            }
+        }
+        Expr::Record(ast::Record {
+            span,
+            name,
+            fields,
+            default,
+        }) => {
+            let definition = records
+                .and_then(|records| records.get(&name.name))
+                .ok_or(EvalError::NoRecord { span: *span })?;
+            if let Some(field) = fields
+                .iter()
+                .find(|f| !definition.fields.iter().any(|d| d.name.name == f.name.name))
+            {
+                return Err(EvalError::NoRecordField { span: field.span });
+            }
+
+            let mut elements = Vec::with_capacity(definition.fields.len() + 1);
+            elements.push(Literal::Atom(*name));
+            for field in definition.fields.iter() {
+                let value = fields
+                    .iter()
+                    .find(|f| f.name.name == field.name.name)
+                    .and_then(|f| f.value.as_ref())
+                    .or(default.as_deref())
+                    .or(field.value.as_ref());
+                let value = match value {
+                    Some(value) => eval_expr(value, records)?,
+                    None => Literal::Atom(Ident::new(symbols::Undefined, field.span)),
+                };
+                elements.push(value);
+            }
+            Literal::Tuple(*span, elements)
+        }
+        Expr::RecordIndex(rec_idx) => {
             let span = rec_idx.span;
-            match resolve_record_index.unwrap()(rec_idx.name, rec_idx.field) {
-                Ok(index) => Literal::Integer(span, index.into()),
-                Err(ResolveRecordIndexError::NoRecord) => Err(EvalError::NoRecord { span })?,
-                Err(ResolveRecordIndexError::NoField) => Err(EvalError::NoRecordField { span })?,
+            let definition = records
+                .and_then(|records| records.get(&rec_idx.name.name))
+                .ok_or(EvalError::NoRecord { span })?;
+            // The first element of a record tuple is its name, and tuple indices are 1-based
+            match definition
+                .fields
+                .iter()
+                .position(|f| f.name.name == rec_idx.field.name)
+            {
+                Some(index) => Literal::Integer(span, (index + 2).into()),
+                None => Err(EvalError::NoRecordField { span })?,
             }
         }
 
@@ -218,34 +287,50 @@ pub fn eval_expr(
             use BinaryOp as B;
 
             let span = bin_expr.span;
-            let lhs = eval_expr(&bin_expr.lhs, resolve_record_index)?;
-            let rhs = eval_expr(&bin_expr.rhs, resolve_record_index)?;
+            let lhs = eval_expr(&bin_expr.lhs, records)?;
+            let rhs = eval_expr(&bin_expr.rhs, records)?;
 
             match (bin_expr.op, lhs, rhs) {
                 (B::Add, lhs, rhs) => {
-                    let lhs: Number = lhs.try_into().unwrap();
-                    let rhs: Number = rhs.try_into().unwrap();
+                    let lhs: Number = lhs
+                        .try_into()
+                        .map_err(|_| EvalError::InvalidConstExpression { span })?;
+                    let rhs: Number = rhs
+                        .try_into()
+                        .map_err(|_| EvalError::InvalidConstExpression { span })?;
                     (lhs + rhs)
                         .map_err(|ty| EvalError::FloatError { span, ty })?
                         .into()
                 }
                 (B::Sub, lhs, rhs) => {
-                    let lhs: Number = lhs.try_into().unwrap();
-                    let rhs: Number = rhs.try_into().unwrap();
+                    let lhs: Number = lhs
+                        .try_into()
+                        .map_err(|_| EvalError::InvalidConstExpression { span })?;
+                    let rhs: Number = rhs
+                        .try_into()
+                        .map_err(|_| EvalError::InvalidConstExpression { span })?;
                     (lhs - rhs)
                         .map_err(|ty| EvalError::FloatError { span, ty })?
                         .into()
                 }
                 (B::Multiply, lhs, rhs) => {
-                    let lhs: Number = lhs.try_into().unwrap();
-                    let rhs: Number = rhs.try_into().unwrap();
+                    let lhs: Number = lhs
+                        .try_into()
+                        .map_err(|_| EvalError::InvalidConstExpression { span })?;
+                    let rhs: Number = rhs
+                        .try_into()
+                        .map_err(|_| EvalError::InvalidConstExpression { span })?;
                     (lhs * rhs)
                         .map_err(|ty| EvalError::FloatError { span, ty })?
                         .into()
                 }
                 (B::Divide, lhs, rhs) => {
-                    let rhs: Number = rhs.try_into().unwrap();
-                    let lhs: Number = lhs.try_into().unwrap();
+                    let rhs: Number = rhs
+                        .try_into()
+                        .map_err(|_| EvalError::InvalidConstExpression { span })?;
+                    let lhs: Number = lhs
+                        .try_into()
+                        .map_err(|_| EvalError::InvalidConstExpression { span })?;
                     (lhs / rhs)
                         .map_err(|_| EvalError::DivisionByZero { span })?
                         .into()
@@ -360,7 +445,7 @@ pub fn eval_expr(
                         lhs: Box::new(Expr::Literal(l)),
                         rhs: Box::new(Expr::Literal(r)),
                     });
-                    eval_expr(&expr, resolve_record_index)?
+                    eval_expr(&expr, records)?
                 }
                 (B::Append | B::Remove, _, _) => {
                     return Err(EvalError::InvalidConstExpression { span })
@@ -373,7 +458,7 @@ pub fn eval_expr(
         Expr::UnaryExpr(un_expr) => {
             use UnaryOp as U;
 
-            let operand = eval_expr(&un_expr.operand, resolve_record_index)?;
+            let operand = eval_expr(&un_expr.operand, records)?;
 
             match (un_expr.op, operand) {
                 (U::Plus, lit) => {
@@ -571,7 +656,9 @@ where
             }
         }
         BinaryEntrySpecifier::Utf8 => {
-            assert!(size.is_none());
+            if size.is_some() {
+                return Err(());
+            }
             let codepoint = match value {
                 Expr::Literal(Literal::Char(_, c)) => c,
                 Expr::Literal(Literal::Integer(_, Integer::Small(i))) => {
@@ -583,7 +670,9 @@ where
             bin.push_utf8(codepoint);
         }
         BinaryEntrySpecifier::Utf16 { endianness } => {
-            assert!(size.is_none());
+            if size.is_some() {
+                return Err(());
+            }
             match value {
                 Expr::Literal(Literal::Char(_, c)) => bin.push_utf16(c, endianness),
                 Expr::Literal(Literal::Integer(_, Integer::Small(i))) => {
@@ -597,7 +686,9 @@ where
             }
         }
         BinaryEntrySpecifier::Utf32 { endianness } => {
-            assert!(size.is_none());
+            if size.is_some() {
+                return Err(());
+            }
             let codepoint = match value {
                 Expr::Literal(Literal::Char(_, c)) => c,
                 Expr::Literal(Literal::Integer(_, Integer::Small(i))) => {
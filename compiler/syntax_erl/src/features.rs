@@ -72,3 +72,11 @@ impl Feature {
 pub fn get(op: &Symbol) -> Option<&'static Feature> {
     FEATURE_MAP.get(op).copied()
 }
+
+/// Get the names of all features which are enabled unless a module explicitly disables them
+pub fn enabled_by_default() -> impl Iterator<Item = Symbol> {
+    FEATURES
+        .iter()
+        .filter(|feat| feat.enabled)
+        .map(|feat| feat.name)
+}
@@ -276,6 +276,7 @@ where
             '}' => pop!(self, Token::RBrace),
             '?' => match self.peek() {
                 '?' => pop2!(self, Token::DoubleQuestion),
+                '=' => pop2!(self, Token::QuestionEqual),
                 _ => pop!(self, Token::Question),
             },
             '-' => match self.peek() {
@@ -861,6 +862,7 @@ mod test {
         assert_lex!(":", |_| vec![Ok(Token::Colon)]);
         assert_lex!(",", |_| vec![Ok(Token::Comma)]);
         assert_lex!("=", |_| vec![Ok(Token::Equals)]);
+        assert_lex!("?=", |_| vec![Ok(Token::QuestionEqual)]);
     }
 
     #[test]
@@ -932,6 +934,8 @@ mod test {
         assert_lex!("i_d@e_t0123", |_| vec![Ok(Token::Atom(symbol!(
             "i_d@e_t0123"
         )))]);
+        assert_lex!("maybe", |_| vec![Ok(Token::Maybe)]);
+        assert_lex!("'maybe'", |_| vec![Ok(Token::Atom(symbol!("maybe")))]);
     }
 
     #[test]
@@ -168,6 +168,7 @@ mod test {
     use firefly_diagnostics::*;
     use firefly_intern::{Ident, Symbol};
 
+    use crate::lexer::Token;
     use crate::preprocessor::PreprocessorError;
 
     fn fail_with(reporter: Reporter, codemap: &CodeMap, message: &'static str) -> ! {
@@ -792,6 +793,272 @@ exw(File) ->
         );
     }
 
+    #[test]
+    fn parse_maybe_expressions() {
+        let codemap = Arc::new(CodeMap::default());
+        let config = ParseConfig::default();
+        let result: Module = parse(
+            config,
+            codemap.clone(),
+            "-module(foo).
+-feature(maybe_expr, enable).
+
+example(File) ->
+    maybe
+        {ok, Fd} ?= open(File),
+        Bin = read(Fd),
+        {ok, Term} ?= decode(Bin),
+        Term
+    else
+        {error, _} = Err ->
+            Err
+    end.
+",
+        );
+
+        let mut clauses = Vec::new();
+        clauses.push((
+            ident_opt!(example).map(Name::Atom),
+            Clause {
+                span: SourceSpan::UNKNOWN,
+                patterns: vec![var!(File)],
+                guards: vec![],
+                compiler_generated: false,
+                body: vec![Expr::Maybe(Maybe {
+                    span: SourceSpan::UNKNOWN,
+                    body: vec![
+                        Expr::MaybeMatch(MaybeMatch {
+                            span: SourceSpan::UNKNOWN,
+                            pattern: Box::new(tuple!(atom!(ok), var!(Fd))),
+                            expr: Box::new(apply!(SourceSpan::UNKNOWN, atom!(open), (var!(File)))),
+                        }),
+                        Expr::Match(Match {
+                            span: SourceSpan::UNKNOWN,
+                            pattern: Box::new(var!(Bin)),
+                            expr: Box::new(apply!(SourceSpan::UNKNOWN, atom!(read), (var!(Fd)))),
+                        }),
+                        Expr::MaybeMatch(MaybeMatch {
+                            span: SourceSpan::UNKNOWN,
+                            pattern: Box::new(tuple!(atom!(ok), var!(Term))),
+                            expr: Box::new(apply!(SourceSpan::UNKNOWN, atom!(decode), (var!(Bin)))),
+                        }),
+                        var!(Term),
+                    ],
+                    else_clauses: Some(vec![Clause {
+                        span: SourceSpan::UNKNOWN,
+                        patterns: vec![Expr::Match(Match {
+                            span: SourceSpan::UNKNOWN,
+                            pattern: Box::new(tuple!(atom!(error), var!(_))),
+                            expr: Box::new(var!(Err)),
+                        })],
+                        guards: vec![],
+                        body: vec![var!(Err)],
+                        compiler_generated: false,
+                    }]),
+                })],
+            },
+        ));
+        let mut body = Vec::new();
+        body.push(TopLevel::Function(NamedFunction {
+            span: SourceSpan::UNKNOWN,
+            name: Name::Atom(ident!(example)),
+            arity: 1,
+            clauses,
+            spec: None,
+        }));
+        let expected = module!(&codemap, ident!(foo), body);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn parse_maybe_keywords_as_atoms_when_disabled() {
+        let codemap = Arc::new(CodeMap::default());
+        let config = ParseConfig::default();
+        let result: Module = parse(
+            config,
+            codemap.clone(),
+            "-module(foo).
+
+example() ->
+    {maybe, else}.
+",
+        );
+
+        let mut clauses = Vec::new();
+        clauses.push((
+            ident_opt!(example).map(Name::Atom),
+            Clause {
+                span: SourceSpan::UNKNOWN,
+                patterns: vec![],
+                guards: vec![],
+                compiler_generated: false,
+                body: vec![tuple!(
+                    atom!(firefly_intern::symbols::Maybe),
+                    atom!(firefly_intern::symbols::Else)
+                )],
+            },
+        ));
+        let mut body = Vec::new();
+        body.push(TopLevel::Function(NamedFunction {
+            span: SourceSpan::UNKNOWN,
+            name: Name::Atom(ident!(example)),
+            arity: 0,
+            clauses,
+            spec: None,
+        }));
+        let expected = module!(&codemap, ident!(foo), body);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn preprocess_maybe_match_operator_when_disabled() {
+        let codemap = Arc::new(CodeMap::default());
+        let id = codemap.add("nofile", "A?=B".to_string());
+        let file = codemap.get(id).unwrap();
+        let parser = Parser::new(ParseConfig::default(), codemap);
+        let lexer = Lexer::new(Scanner::new(file));
+        let tokens = Preprocessor::new(&parser, lexer, Reporter::new())
+            .map(|result| result.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Ident(Symbol::intern("A")),
+                Token::Question,
+                Token::Equals,
+                Token::Ident(Symbol::intern("B")),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_map_comprehensions() {
+        let codemap = Arc::new(CodeMap::default());
+        let config = ParseConfig::default();
+        let _result: Module = parse(
+            config,
+            codemap.clone(),
+            "-module(foo).
+
+invert(Map) ->
+    #{V => K || K := V <- Map}.
+
+squares(List) ->
+    #{X => X * X || X <- List, is_integer(X)}.
+",
+        );
+    }
+
+    #[test]
+    fn parse_transform_fun2ms() {
+        use crate::passes::{MsTransform, ParseTransform};
+
+        // Each `Name_ms` function returns the match specification expected from `Name`
+        let codemap = Arc::new(CodeMap::default());
+        let config = ParseConfig::default();
+        let module: Module = parse(
+            config,
+            codemap.clone(),
+            "-module(foo).
+-record(person, {name, age}).
+
+positive() ->
+    ets:fun2ms(fun({K, V}) when V > 0 -> K end).
+positive_ms() ->
+    [{{'$1', '$2'}, [{'>', '$2', 0}], ['$1']}].
+
+adults() ->
+    ets:fun2ms(fun(#person{name = N, age = A}) when A >= 18 -> N end).
+adults_ms() ->
+    [{{person, '$1', '$2'}, [{'>=', '$2', 18}], ['$1']}].
+
+ages() ->
+    ets:fun2ms(fun(#person{age = A}) -> A end).
+ages_ms() ->
+    [{{person, '_', '$1'}, [], ['$1']}].
+
+older_than(Age) ->
+    ets:fun2ms(fun({K, V} = Obj) when V > Age; K =:= root -> {K, Obj} end).
+older_than_ms(Age) ->
+    [{{'$1', '$2'},
+      [{'orelse', {'>', '$2', {const, Age}}, {'=:=', '$1', root}}],
+      [{{'$1', '$_'}}]}].
+
+traced() ->
+    dbg:fun2ms(fun([X, _]) when is_integer(X) -> return_trace() end).
+traced_ms() ->
+    [{['$1', '_'], [{is_integer, '$1'}], [{return_trace}]}].
+",
+        );
+        let reporter = Reporter::new();
+        let module = MsTransform.transform(module, &reporter).unwrap();
+
+        let body = |name: &str| {
+            let function = module
+                .functions
+                .values()
+                .find(|f| f.name.name.as_str().get() == name)
+                .unwrap();
+            let (_, clause) = &function.clauses[0];
+            clause.body[0].clone()
+        };
+        for name in ["positive", "adults", "ages", "older_than", "traced"] {
+            assert_eq!(body(name), body(&format!("{}_ms", name)), "in {}", name);
+        }
+
+        let module: Module = parse(
+            ParseConfig::default(),
+            codemap.clone(),
+            "-module(foo).
+
+invalid() ->
+    ets:fun2ms(fun({K, V}) -> lists:reverse(V) end).
+",
+        );
+        let reporter = Reporter::new();
+        assert!(MsTransform.transform(module, &reporter).is_err());
+    }
+
+    #[test]
+    fn parse_file_directive() {
+        let codemap = Arc::new(CodeMap::default());
+        let config = ParseConfig::default();
+        let module: Module = parse(
+            config,
+            codemap.clone(),
+            "-module(foo).
+-file(\"foo.yrl\", 10).
+bar() -> ok.
+",
+        );
+        let (_, function) = module.functions.iter().next().unwrap();
+        let name = codemap.name_for_span(function.span).unwrap();
+        assert_eq!(name.to_string(), "foo.yrl");
+        let location = codemap.location_for_span(function.span).unwrap();
+        assert_eq!(location.line.number().to_usize(), 10);
+    }
+
+    #[test]
+    fn parse_constant_attributes() {
+        let module: Module = parse(
+            ParseConfig::default(),
+            Arc::new(CodeMap::new()),
+            "-module(foo).
+-record(rec, {a, b = 2}).
+-bin(<<\"ab\", 1:16>>).
+-rec(#rec{a = 1}).
+",
+        );
+        let attribute = |name| {
+            module
+                .attributes
+                .get(&Ident::with_empty_span(Symbol::intern(name)))
+                .unwrap()
+        };
+        assert!(matches!(attribute("bin"), Literal::Binary(_, _)));
+        assert_eq!(attribute("rec").to_string(), "{'rec', 1, 2}");
+    }
+
     #[test]
     fn parse_numbers() {
         let _result: Module = parse(
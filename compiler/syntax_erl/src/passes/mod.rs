@@ -1,7 +1,9 @@
+mod parse_transform;
 pub mod sema;
 mod transforms;
 mod translate;
 
+pub use self::parse_transform::ParseTransform;
 pub use self::sema::*;
 pub use self::transforms::*;
 pub use self::translate::*;
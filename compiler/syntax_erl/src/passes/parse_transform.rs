@@ -0,0 +1,24 @@
+use firefly_diagnostics::Reporter;
+use firefly_intern::Symbol;
+
+use crate::ast;
+
+/// A parse transform rewrites the AST of a module before it is analyzed and lowered.
+///
+/// In erlc, parse transforms are Erlang modules which are called with the abstract forms of the
+/// module being compiled, selected via `-compile({parse_transform, Module})`. Since we can't run
+/// Erlang code at compile-time, transforms are instead implemented in Rust, and are registered
+/// with the compiler under the name of the Erlang module they replace.
+///
+/// Transforms are applied in the order in which they are declared in the module, after the
+/// module has been constructed, but before semantic analysis.
+pub trait ParseTransform: Send + Sync {
+    /// The name by which this transform is referenced, e.g. `ms_transform`
+    fn name(&self) -> Symbol;
+
+    /// Applies this transform to `module`
+    ///
+    /// Errors should be reported via `reporter` with appropriate source locations, in addition
+    /// to being returned.
+    fn transform(&self, module: ast::Module, reporter: &Reporter) -> anyhow::Result<ast::Module>;
+}
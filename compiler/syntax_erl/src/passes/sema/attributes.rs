@@ -3,6 +3,7 @@ use firefly_intern::Symbol;
 use firefly_syntax_base::{bifs, CompileOptions, Deprecation, FunctionName, Signature};
 
 use crate::ast::*;
+use crate::evaluator;
 
 use super::*;
 
@@ -241,6 +242,11 @@ pub fn analyze_attribute(reporter: &Reporter, module: &mut Module, attr: Attribu
             let local_cb_name = cb_name.to_local();
             match module.callbacks.get(&local_cb_name) {
                 None => {
+                    let mut callback = callback;
+                    let optional_name = Span::new(callback.span, local_cb_name);
+                    if module.optional_callbacks.contains(&optional_name) {
+                        callback.optional = true;
+                    }
                     module.callbacks.insert(local_cb_name, callback);
                     return;
                 }
@@ -353,6 +359,7 @@ pub fn analyze_attribute(reporter: &Reporter, module: &mut Module, attr: Attribu
                     return;
                 }
                 "optional_callbacks" => {
+                    optional_callbacks(reporter, module, &attr.value);
                     return;
                 }
                 // Drop dialyzer attributes as they are unused
@@ -361,7 +368,7 @@ pub fn analyze_attribute(reporter: &Reporter, module: &mut Module, attr: Attribu
                 }
                 _ => (),
             }
-            let attr_value: Result<ast::Literal, _> = attr.value.try_into();
+            let attr_value = evaluator::eval_expr(&attr.value, Some(&module.records));
             if attr_value.is_err() {
                 reporter.show_warning(
                     "invalid attribute value",
@@ -393,6 +400,29 @@ pub fn analyze_attribute(reporter: &Reporter, module: &mut Module, attr: Attribu
     }
 }
 
+/// Handles `-optional_callbacks([f/N, ..])`, which may appear before or after the callbacks it names
+fn optional_callbacks(reporter: &Reporter, module: &mut Module, value: &Expr) {
+    for callback in to_list_simple(value) {
+        match callback {
+            Expr::FunctionVar(FunctionVar::PartiallyResolved(name)) => {
+                if let Some(cb) = module.callbacks.get_mut(name.as_ref()) {
+                    cb.optional = true;
+                }
+                module.optional_callbacks.insert(name);
+            }
+            other => {
+                reporter.show_warning(
+                    "invalid -optional_callbacks attribute",
+                    &[(
+                        other.span(),
+                        "expected a callback name of the form name/arity",
+                    )],
+                );
+            }
+        }
+    }
+}
+
 fn compile_opts_from_expr(
     module: Ident,
     expr: &Expr,
@@ -539,6 +569,7 @@ fn set_compile_option(
                         no_warn_deprecated_functions(options, module, &list, reporter)
                     }
                     "inline" => inline_functions(options, module, &list, reporter),
+                    "parse_transform" => parse_transform(options, &elements[1], reporter),
                     // Ignored
                     "hipe" => {}
                     _name => {
@@ -758,6 +789,28 @@ fn inline_functions(
     }
 }
 
+fn parse_transform(options: &mut CompileOptions, transform: &Expr, reporter: &Reporter) {
+    match transform {
+        Expr::Literal(Literal::Atom(name)) => {
+            options
+                .parse_transforms
+                .push(Span::new(name.span, name.name));
+        }
+        _ => {
+            let transform_span = transform.span();
+            reporter.diagnostic(
+                Diagnostic::warning()
+                    .with_message("invalid compile option")
+                    .with_labels(vec![Label::primary(
+                        transform_span.source_id(),
+                        transform_span,
+                    )
+                    .with_message("expected module name for parse_transform")]),
+            );
+        }
+    }
+}
+
 fn to_list_simple(mut expr: &Expr) -> Vec<Expr> {
     let mut list = Vec::new();
     loop {
@@ -1,12 +1,13 @@
 use core::ops::ControlFlow;
 use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
 
 use firefly_diagnostics::*;
-use firefly_intern::symbols;
+use firefly_intern::{symbols, Symbol};
 use firefly_syntax_base::*;
 
 use crate::ast::*;
-use crate::visit::VisitMut;
+use crate::visit::{self, VisitMut};
 
 pub fn analyze_function(reporter: &Reporter, module: &mut Module, mut function: Function) {
     let resolved_name = FunctionName::new(module.name(), function.name.name, function.arity);
@@ -62,6 +63,75 @@ pub fn analyze_function(reporter: &Reporter, module: &mut Module, mut function:
     }
 }
 
+/// Returns the functions of other modules which are referenced by `module`, either by calls or captures
+///
+/// This is gathered for each module prior to compilation, to support application-wide analyses
+pub fn collect_references(module: &mut Module) -> BTreeSet<FunctionName> {
+    // Calls to functions imported via -import are written as local calls
+    let imports = module
+        .imports
+        .iter()
+        .filter(|(name, _)| !module.functions.contains_key(name))
+        .map(|(name, sig)| (*name, sig.mfa()))
+        .collect();
+    let mut visitor = CollectReferencesVisitor {
+        module: module.name(),
+        imports,
+        references: BTreeSet::new(),
+    };
+    for function in module.functions.values_mut() {
+        let _ = visitor.visit_mut_function(function);
+    }
+    visitor.references
+}
+
+struct CollectReferencesVisitor {
+    module: Symbol,
+    imports: BTreeMap<FunctionName, FunctionName>,
+    references: BTreeSet<FunctionName>,
+}
+impl CollectReferencesVisitor {
+    fn insert(&mut self, name: FunctionName) {
+        if name.module != Some(self.module) {
+            self.references.insert(name);
+        }
+    }
+}
+impl VisitMut<()> for CollectReferencesVisitor {
+    fn visit_mut_apply(&mut self, apply: &mut Apply) -> ControlFlow<()> {
+        let arity = apply.args.len().try_into().unwrap();
+        match apply.callee.as_ref() {
+            Expr::Remote(Remote {
+                module, function, ..
+            }) => {
+                if let (Some(m), Some(f)) = (module.as_atom_symbol(), function.as_atom_symbol()) {
+                    self.insert(FunctionName::new(m, f, arity));
+                }
+            }
+            Expr::Literal(Literal::Atom(f)) => {
+                let local_name = FunctionName::new_local(f.name, arity);
+                if let Some(imported) = self.imports.get(&local_name).copied() {
+                    self.insert(imported);
+                }
+            }
+            Expr::FunctionVar(FunctionVar::PartiallyResolved(name)) => {
+                if let Some(imported) = self.imports.get(&name.to_local()).copied() {
+                    self.insert(imported);
+                }
+            }
+            _ => (),
+        }
+        visit::visit_mut_apply(self, apply)
+    }
+
+    fn visit_mut_function_var(&mut self, var: &mut FunctionVar) -> ControlFlow<()> {
+        if let FunctionVar::Resolved(name) = var {
+            self.insert(name.item);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
 struct IsNifVisitor;
 impl VisitMut<bool> for IsNifVisitor {
     fn visit_mut_apply(&mut self, apply: &mut Apply) -> ControlFlow<bool> {
@@ -111,3 +181,94 @@ impl VisitMut<bool> for IsNifVisitor {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use firefly_diagnostics::{CodeMap, Reporter};
+    use firefly_intern::Symbol;
+    use firefly_syntax_base::{ApplicationMetadata, ModuleMetadata};
+
+    use crate::ast;
+    use crate::parser::{ParseConfig, Parser};
+
+    fn parse(source: &str) -> ast::Module {
+        let parser = Parser::new(ParseConfig::default(), Arc::new(CodeMap::new()));
+        parser
+            .parse_string::<ast::Module, _, _>(Reporter::new(), source)
+            .expect("failed to parse test module")
+    }
+
+    #[test]
+    fn collect_references() {
+        let mut module = parse(
+            "-module(foo).
+-import(lists, [reverse/1]).
+-export([f/1, reverse/2]).
+
+f(X) ->
+    Y = reverse(X),
+    bar:g(Y, fun baz:h/0),
+    ?MODULE:reverse(X, []).
+
+reverse(X, Acc) -> lists:reverse(X, Acc).
+",
+        );
+        let references = super::collect_references(&mut module);
+        assert_eq!(
+            references
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>(),
+            vec!["bar:g/2", "baz:h/0", "lists:reverse/1", "lists:reverse/2"]
+        );
+    }
+
+    #[test]
+    fn unused_exports() {
+        let sources = [
+            "-module(a).
+-export([f/0, g/0, unused/0]).
+
+f() -> ok.
+g() -> ok.
+unused() -> ok.
+",
+            "-module(b).
+-behaviour(gen_server).
+-import(a, [g/0]).
+-export([init/1, unused/0]).
+
+init(_) -> {ok, a:f()}.
+unused() -> g().
+",
+        ];
+        let mut app = ApplicationMetadata {
+            name: Symbol::intern("test"),
+            modules: BTreeMap::new(),
+        };
+        for source in sources.iter() {
+            let mut module = parse(source);
+            let metadata = ModuleMetadata {
+                name: module.name,
+                exports: module.exports.iter().cloned().collect(),
+                deprecation: None,
+                deprecations: BTreeMap::new(),
+                behaviour: module.behaviour(),
+                behaviours: module.behaviours.iter().map(|b| b.name).collect(),
+                references: super::collect_references(&mut module),
+            };
+            app.modules.insert(module.name(), metadata);
+        }
+        let unused = app.unused_exports();
+        assert_eq!(
+            unused
+                .iter()
+                .map(|name| name.item.to_string())
+                .collect::<Vec<_>>(),
+            vec!["a:unused/0", "b:unused/0"]
+        );
+    }
+}
@@ -2,6 +2,7 @@ mod attributes;
 mod functions;
 mod inject;
 mod records;
+mod specs;
 mod verify;
 
 use firefly_diagnostics::*;
@@ -12,8 +13,9 @@ use firefly_syntax_base::ApplicationMetadata;
 use crate::ast;
 
 pub use self::attributes::analyze_attribute;
-pub use self::functions::analyze_function;
+pub use self::functions::{analyze_function, collect_references};
 pub use self::records::analyze_record;
+pub use self::specs::CheckTypeSpecs;
 
 /// This pass is responsible for taking a set of top-level forms and
 /// analyzing them in the context of a new module to produce a fully
@@ -23,6 +25,7 @@ pub use self::records::analyze_record;
 ///
 /// * If configured to do so, warns if functions are missing type specs
 /// * Warns about type specs for undefined functions
+/// * Warns about missing behaviour callbacks
 /// * Warns about redefined attributes
 /// * Errors on invalid nif declarations
 /// * Errors on invalid syntax in built-in attributes (e.g. -import(..))
@@ -47,6 +50,7 @@ impl<'app> Pass for SemanticAnalysis<'app> {
     fn run<'a>(&mut self, mut module: Self::Input<'a>) -> anyhow::Result<Self::Output<'a>> {
         let mut passes = inject::AddAutoImports
             .chain(verify::VerifyExports::new(self.reporter.clone()))
+            .chain(verify::VerifyBehaviours::new(self.reporter.clone(), self.app))
             .chain(verify::VerifyOnLoadFunctions::new(self.reporter.clone()))
             .chain(verify::VerifyTypeSpecs::new(self.reporter.clone()))
             .chain(verify::VerifyNifs::new(self.reporter.clone()))
@@ -0,0 +1,732 @@
+use core::ops::ControlFlow;
+use std::collections::{BTreeMap, HashMap};
+
+use firefly_diagnostics::*;
+use firefly_intern::Symbol;
+use firefly_pass::Pass;
+use firefly_syntax_base::{BinaryOp, FunctionName, TermType, UnaryOp};
+
+use crate::ast::*;
+use crate::visit::{self, VisitMut};
+
+/// The maximum depth to which user-defined types are expanded when lowering a spec
+const MAX_TYPE_DEPTH: usize = 8;
+
+/// Performs a lightweight, success-typing style check of function bodies against
+/// the `-spec` declared for them, using `-type`/`-opaque` definitions in the module
+/// to resolve user-defined types.
+///
+/// Types are approximated using the coarse `TermType` lattice, and a discrepancy is
+/// only reported when the inferred type of an expression can never overlap with the
+/// declared type, i.e. the code is known to violate the spec if it is ever reached.
+///
+/// The following are checked:
+///
+/// * Values returned from each clause of a function are compatible with its spec
+/// * Arguments of calls to local functions are compatible with the callee's spec
+///
+/// All discrepancies are reported as warnings.
+pub struct CheckTypeSpecs {
+    reporter: Reporter,
+}
+impl CheckTypeSpecs {
+    pub fn new(reporter: Reporter) -> Self {
+        Self { reporter }
+    }
+}
+impl Pass for CheckTypeSpecs {
+    type Input<'a> = Module;
+    type Output<'a> = Module;
+
+    fn run<'a>(&mut self, mut module: Self::Input<'a>) -> anyhow::Result<Self::Output<'a>> {
+        let lowering = SpecLowering {
+            types: &module.types,
+        };
+        let specs = module
+            .functions
+            .iter()
+            .filter_map(|(name, function)| {
+                function
+                    .spec
+                    .as_ref()
+                    .map(|spec| (*name, lowering.lower_spec(spec, function.arity)))
+            })
+            .collect::<BTreeMap<FunctionName, Spec>>();
+
+        let mut checker = SpecChecker {
+            reporter: self.reporter.clone(),
+            module: module.name(),
+            specs: &specs,
+        };
+        for (name, function) in module.functions.iter_mut() {
+            if let Some(spec) = specs.get(name) {
+                checker.check_returns(name, spec, function);
+            }
+            let _ = checker.visit_mut_function(function);
+        }
+
+        Ok(module)
+    }
+}
+
+/// A function spec, lowered to term types
+struct Spec {
+    span: SourceSpan,
+    sigs: Vec<SpecSig>,
+}
+impl Spec {
+    /// Returns the union of the return types of all signatures in this spec
+    fn returns(&self) -> Vec<TermType> {
+        let mut types = vec![];
+        for sig in self.sigs.iter() {
+            for ty in sig.ret.iter() {
+                push_type(&mut types, ty.clone());
+            }
+        }
+        types
+    }
+}
+
+/// A single signature of a function spec, where each type is represented as a union of term types
+struct SpecSig {
+    params: Vec<Vec<TermType>>,
+    ret: Vec<TermType>,
+}
+impl SpecSig {
+    /// Returns the index of the first argument incompatible with this signature, if any
+    fn mismatch(&self, args: &[TermType]) -> Option<usize> {
+        args.iter()
+            .zip(self.params.iter())
+            .position(|(arg, param)| !overlaps_any(arg, param.as_slice()))
+    }
+}
+
+struct SpecLowering<'a> {
+    types: &'a HashMap<FunctionName, TypeDef>,
+}
+impl<'a> SpecLowering<'a> {
+    fn lower_spec(&self, spec: &TypeSpec, arity: u8) -> Spec {
+        let sigs = spec
+            .sigs
+            .iter()
+            .filter(|sig| sig.params.len() == arity as usize)
+            .map(|sig| self.lower_sig(sig))
+            .collect();
+        Spec {
+            span: spec.span,
+            sigs,
+        }
+    }
+
+    fn lower_sig(&self, sig: &TypeSig) -> SpecSig {
+        let mut vars = HashMap::new();
+        if let Some(guards) = sig.guards.as_ref() {
+            for guard in guards.iter() {
+                let ty = self.lower(&guard.ty, &vars, 0);
+                vars.insert(guard.var.symbol(), ty);
+            }
+        }
+        SpecSig {
+            params: sig
+                .params
+                .iter()
+                .map(|param| self.lower(param, &vars, 0))
+                .collect(),
+            ret: self.lower(sig.ret.as_ref(), &vars, 0),
+        }
+    }
+
+    /// Lowers a type to the union of term types it represents
+    ///
+    /// Types which cannot be represented precisely are approximated by a supertype.
+    fn lower(
+        &self,
+        ty: &Type,
+        vars: &HashMap<Symbol, Vec<TermType>>,
+        depth: usize,
+    ) -> Vec<TermType> {
+        match ty {
+            Type::Name(Name::Atom(a)) => match a.as_str().get() {
+                "true" | "false" => vec![TermType::Bool],
+                _ => vec![TermType::Atom],
+            },
+            Type::Name(Name::Var(v)) => vars
+                .get(&v.name)
+                .cloned()
+                .unwrap_or_else(|| vec![TermType::Any]),
+            Type::Annotated { ty, .. } => self.lower(ty.as_ref(), vars, depth),
+            Type::Union { types, .. } => {
+                let mut union = vec![];
+                for ty in types.iter() {
+                    for t in self.lower(ty, vars, depth) {
+                        push_type(&mut union, t);
+                    }
+                }
+                union
+            }
+            Type::Range { .. }
+            | Type::BinaryOp { .. }
+            | Type::UnaryOp { .. }
+            | Type::Integer(_, _)
+            | Type::Char(_, _) => vec![TermType::Integer],
+            Type::Generic { fun, params, .. } => self.lower_generic(*fun, params, depth),
+            Type::Remote { .. } => vec![TermType::Any],
+            Type::Nil(_) => vec![TermType::Nil],
+            Type::List(_, _) => vec![TermType::List(None)],
+            Type::NonEmptyList(_, _) => vec![TermType::Cons],
+            Type::Map(_, _) => vec![TermType::Map],
+            Type::Tuple(_, elements) => {
+                let elements = elements
+                    .iter()
+                    .map(|element| single(self.lower(element, vars, depth)))
+                    .collect();
+                vec![TermType::Tuple(Some(elements))]
+            }
+            Type::Record(_, _, _) => vec![TermType::Tuple(None)],
+            Type::Binary(_, _, _) => vec![TermType::Bitstring],
+            Type::AnyFun { .. } | Type::Fun { .. } => vec![TermType::Fun(None)],
+            Type::KeyValuePair(_, _, _) | Type::Field(_, _, _) => vec![TermType::Any],
+        }
+    }
+
+    fn lower_generic(&self, fun: Ident, params: &[Type], depth: usize) -> Vec<TermType> {
+        let ty = match fun.as_str().get() {
+            "any" | "term" | "dynamic" => TermType::Any,
+            "none" | "no_return" => return vec![],
+            "atom" | "module" | "node" => TermType::Atom,
+            "boolean" | "bool" => TermType::Bool,
+            "integer" | "non_neg_integer" | "pos_integer" | "neg_integer" | "byte" | "char"
+            | "arity" => TermType::Integer,
+            "float" => TermType::Float,
+            "number" => TermType::Number,
+            "binary" | "nonempty_binary" => TermType::Binary,
+            "bitstring" | "nonempty_bitstring" => TermType::Bitstring,
+            "nil" => TermType::Nil,
+            "list" | "string" => TermType::List(None),
+            "nonempty_list" | "nonempty_string" => TermType::Cons,
+            "maybe_improper_list"
+            | "nonempty_maybe_improper_list"
+            | "nonempty_improper_list"
+            | "iolist" => TermType::MaybeImproperList,
+            "iodata" => return vec![TermType::MaybeImproperList, TermType::Binary],
+            "identifier" => return vec![TermType::Pid, TermType::Port, TermType::Reference],
+            "timeout" => return vec![TermType::Atom, TermType::Integer],
+            "map" => TermType::Map,
+            "tuple" => TermType::Tuple(None),
+            "mfa" => TermType::Tuple(Some(vec![
+                TermType::Atom,
+                TermType::Atom,
+                TermType::Integer,
+            ])),
+            "pid" => TermType::Pid,
+            "port" => TermType::Port,
+            "reference" => TermType::Reference,
+            "function" | "fun" => TermType::Fun(None),
+            _ => {
+                let name = FunctionName::new_local(fun.name, params.len().try_into().unwrap());
+                return match self.types.get(&name) {
+                    // Type parameters are not substituted, and so are treated as any()
+                    Some(def) if depth < MAX_TYPE_DEPTH => {
+                        self.lower(&def.ty, &HashMap::new(), depth + 1)
+                    }
+                    _ => vec![TermType::Any],
+                };
+            }
+        };
+        vec![ty]
+    }
+}
+
+struct SpecChecker<'a> {
+    reporter: Reporter,
+    module: Symbol,
+    specs: &'a BTreeMap<FunctionName, Spec>,
+}
+impl<'a> SpecChecker<'a> {
+    /// Checks that the value returned by each clause of `function` is allowed by its spec
+    fn check_returns(&self, name: &FunctionName, spec: &Spec, function: &Function) {
+        if spec.sigs.is_empty() {
+            return;
+        }
+        let returns = spec.returns();
+        let mut exprs = vec![];
+        for (_, clause) in function.clauses.iter() {
+            if let Some(last) = clause.body.last() {
+                return_exprs(last, &mut exprs);
+            }
+        }
+        for expr in exprs.drain(..) {
+            let ty = self.infer(expr);
+            // Nothing is known about the value, e.g. a call which may never return, and it
+            // is not a discrepancy even if the function is specified to never return
+            if ty == TermType::Any || ty.is_opaque() || overlaps_any(&ty, returns.as_slice()) {
+                continue;
+            }
+            let message = format!(
+                "this expression has type {}, but {} is specified to return {}",
+                &ty,
+                name,
+                DisplayUnion(returns.as_slice())
+            );
+            self.reporter.show_warning(
+                "return value does not match spec",
+                &[
+                    (expr.span(), message.as_str()),
+                    (spec.span, "spec declared here"),
+                ],
+            );
+        }
+    }
+
+    /// Resolves the callee of `apply` to a local function name, if possible
+    fn local_callee(&self, apply: &Apply) -> Option<FunctionName> {
+        let arity = apply.args.len().try_into().unwrap();
+        match apply.callee.as_ref() {
+            Expr::Literal(Literal::Atom(f)) => Some(FunctionName::new_local(f.name, arity)),
+            Expr::FunctionVar(FunctionVar::PartiallyResolved(name)) => Some(name.to_local()),
+            Expr::FunctionVar(FunctionVar::Resolved(name)) if name.module == Some(self.module) => {
+                Some(name.to_local())
+            }
+            Expr::Remote(Remote {
+                module, function, ..
+            }) => match (module.as_atom(), function.as_atom()) {
+                (Some(m), Some(f)) if m.name == self.module => {
+                    Some(FunctionName::new_local(f.name, arity))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Infers the type of the given expression
+    ///
+    /// This is purely local, no attempt is made to track the types of variables.
+    fn infer(&self, expr: &Expr) -> TermType {
+        match expr {
+            Expr::Literal(lit) => literal_type(lit),
+            Expr::FunctionVar(_) | Expr::Fun(_) => TermType::Fun(None),
+            Expr::Cons(_) => TermType::Cons,
+            Expr::Tuple(Tuple { elements, .. }) => {
+                TermType::Tuple(Some(elements.iter().map(|e| self.infer(e)).collect()))
+            }
+            Expr::Map(_) | Expr::MapUpdate(_) | Expr::MapComprehension(_) => TermType::Map,
+            Expr::Binary(_) | Expr::BinaryComprehension(_) => TermType::Bitstring,
+            Expr::Record(_) | Expr::RecordUpdate(_) => TermType::Tuple(None),
+            Expr::RecordIndex(_) => TermType::Integer,
+            Expr::ListComprehension(_) => TermType::List(None),
+            Expr::Begin(Begin { body, .. }) => {
+                body.last().map(|e| self.infer(e)).unwrap_or(TermType::Any)
+            }
+            Expr::Match(Match { expr, .. }) => self.infer(expr.as_ref()),
+            Expr::BinaryExpr(BinaryExpr { lhs, op, rhs, .. }) => match op {
+                BinaryOp::Equal
+                | BinaryOp::NotEqual
+                | BinaryOp::Lte
+                | BinaryOp::Lt
+                | BinaryOp::Gte
+                | BinaryOp::Gt
+                | BinaryOp::StrictEqual
+                | BinaryOp::StrictNotEqual
+                | BinaryOp::And
+                | BinaryOp::Or
+                | BinaryOp::Xor => TermType::Bool,
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Multiply => {
+                    match (self.infer(lhs.as_ref()), self.infer(rhs.as_ref())) {
+                        (TermType::Integer, TermType::Integer) => TermType::Integer,
+                        (TermType::Float, _) | (_, TermType::Float) => TermType::Float,
+                        _ => TermType::Number,
+                    }
+                }
+                BinaryOp::Divide => TermType::Float,
+                BinaryOp::Div
+                | BinaryOp::Rem
+                | BinaryOp::Band
+                | BinaryOp::Bor
+                | BinaryOp::Bxor
+                | BinaryOp::Bsl
+                | BinaryOp::Bsr => TermType::Integer,
+                BinaryOp::Append => TermType::MaybeImproperList,
+                BinaryOp::Remove => TermType::List(None),
+                BinaryOp::Send | BinaryOp::AndAlso | BinaryOp::OrElse => TermType::Any,
+            },
+            Expr::UnaryExpr(UnaryExpr { op, operand, .. }) => match op {
+                UnaryOp::Not => TermType::Bool,
+                UnaryOp::Bnot => TermType::Integer,
+                UnaryOp::Plus | UnaryOp::Minus => match self.infer(operand.as_ref()) {
+                    ty @ (TermType::Integer | TermType::Float) => ty,
+                    _ => TermType::Number,
+                },
+            },
+            Expr::Apply(apply) => match self
+                .local_callee(apply)
+                .and_then(|name| self.specs.get(&name))
+            {
+                Some(spec) => single(spec.returns()),
+                None => TermType::Any,
+            },
+            _ => TermType::Any,
+        }
+    }
+}
+impl<'a> VisitMut<()> for SpecChecker<'a> {
+    fn visit_mut_apply(&mut self, apply: &mut Apply) -> ControlFlow<()> {
+        for arg in apply.args.iter_mut() {
+            let _ = visit::visit_mut_expr(self, arg);
+        }
+
+        let (name, spec) = match self
+            .local_callee(apply)
+            .and_then(|name| self.specs.get(&name).map(|spec| (name, spec)))
+        {
+            Some((_, spec)) if spec.sigs.is_empty() => return ControlFlow::Continue(()),
+            Some(found) => found,
+            None => return ControlFlow::Continue(()),
+        };
+
+        let args = apply
+            .args
+            .iter()
+            .map(|arg| self.infer(arg))
+            .collect::<Vec<_>>();
+        let mismatches = spec
+            .sigs
+            .iter()
+            .map(|sig| sig.mismatch(args.as_slice()))
+            .collect::<Vec<_>>();
+        if mismatches.iter().any(|m| m.is_none()) {
+            return ControlFlow::Continue(());
+        }
+
+        if let [Some(index)] = mismatches.as_slice() {
+            let message = format!(
+                "this argument has type {}, but {} expects {}",
+                &args[*index],
+                &name,
+                DisplayUnion(spec.sigs[0].params[*index].as_slice())
+            );
+            self.reporter.show_warning(
+                "call does not match spec",
+                &[
+                    (apply.args[*index].span(), message.as_str()),
+                    (spec.span, "spec declared here"),
+                ],
+            );
+        } else {
+            let message = format!(
+                "no signature in the spec for {} accepts arguments of type ({})",
+                &name,
+                args.iter()
+                    .map(|ty| ty.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            self.reporter.show_warning(
+                "call does not match spec",
+                &[
+                    (apply.span, message.as_str()),
+                    (spec.span, "spec declared here"),
+                ],
+            );
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+/// Collects the expressions which may produce the return value of `expr`
+fn return_exprs<'e>(expr: &'e Expr, exprs: &mut Vec<&'e Expr>) {
+    fn clauses<'e>(clauses: &'e [Clause], exprs: &mut Vec<&'e Expr>) {
+        for clause in clauses.iter() {
+            if let Some(last) = clause.body.last() {
+                return_exprs(last, exprs);
+            }
+        }
+    }
+
+    match expr {
+        Expr::Begin(Begin { body, .. }) => {
+            if let Some(last) = body.last() {
+                return_exprs(last, exprs);
+            }
+        }
+        Expr::Match(Match { expr, .. }) => return_exprs(expr.as_ref(), exprs),
+        Expr::Case(Case { clauses: cs, .. }) | Expr::If(If { clauses: cs, .. }) => {
+            clauses(cs.as_slice(), exprs)
+        }
+        Expr::Receive(Receive {
+            clauses: cs, after, ..
+        }) => {
+            if let Some(cs) = cs.as_ref() {
+                clauses(cs.as_slice(), exprs);
+            }
+            if let Some(last) = after.as_ref().and_then(|after| after.body.last()) {
+                return_exprs(last, exprs);
+            }
+        }
+        Expr::Try(Try {
+            exprs: body,
+            clauses: cs,
+            catch_clauses,
+            ..
+        }) => {
+            match cs.as_ref() {
+                Some(cs) => clauses(cs.as_slice(), exprs),
+                None => {
+                    if let Some(last) = body.last() {
+                        return_exprs(last, exprs);
+                    }
+                }
+            }
+            if let Some(cs) = catch_clauses.as_ref() {
+                clauses(cs.as_slice(), exprs);
+            }
+        }
+        expr => exprs.push(expr),
+    }
+}
+
+fn literal_type(lit: &Literal) -> TermType {
+    match lit {
+        Literal::Atom(a) => match a.as_str().get() {
+            "true" | "false" => TermType::Bool,
+            _ => TermType::Atom,
+        },
+        Literal::String(s) if s.as_str().get().is_empty() => TermType::Nil,
+        Literal::String(_) | Literal::Cons(_, _, _) => TermType::Cons,
+        Literal::Char(_, _) | Literal::Integer(_, _) => TermType::Integer,
+        Literal::Float(_, _) => TermType::Float,
+        Literal::Nil(_) => TermType::Nil,
+        Literal::Tuple(_, elements) => {
+            TermType::Tuple(Some(elements.iter().map(literal_type).collect()))
+        }
+        Literal::Map(_, _) => TermType::Map,
+        Literal::Binary(_, _) => TermType::Bitstring,
+    }
+}
+
+/// Adds `ty` to the given union, collapsing the union to `any()` if `ty` is `any()`
+fn push_type(union: &mut Vec<TermType>, ty: TermType) {
+    if union.first() == Some(&TermType::Any) {
+        return;
+    }
+    if ty.is_opaque() {
+        union.clear();
+        union.push(ty);
+    } else if !union.contains(&ty) {
+        union.push(ty);
+    }
+}
+
+/// Converts a union to a single type, approximating it with `any()` if necessary
+fn single(mut union: Vec<TermType>) -> TermType {
+    if union.len() == 1 {
+        union.pop().unwrap()
+    } else {
+        TermType::Any
+    }
+}
+
+fn overlaps_any(ty: &TermType, union: &[TermType]) -> bool {
+    union.iter().any(|t| overlaps(ty, t))
+}
+
+/// Returns true if there exist values which are members of both types
+fn overlaps(a: &TermType, b: &TermType) -> bool {
+    match (a, b) {
+        (TermType::Any, _) | (_, TermType::Any) => true,
+        (TermType::Number, t) | (t, TermType::Number) => t.is_numeric(),
+        (TermType::Atom, t) | (t, TermType::Atom) => t.is_atom(),
+        (TermType::Bitstring, t) | (t, TermType::Bitstring) => t.is_bitstring(),
+        (TermType::MaybeImproperList, t) | (t, TermType::MaybeImproperList) => t.is_list(),
+        (TermType::List(_), t) | (t, TermType::List(_)) => t.is_list(),
+        (TermType::Tuple(Some(xs)), TermType::Tuple(Some(ys))) => {
+            xs.len() == ys.len() && xs.iter().zip(ys.iter()).all(|(x, y)| overlaps(x, y))
+        }
+        (TermType::Tuple(_), TermType::Tuple(_)) | (TermType::Fun(_), TermType::Fun(_)) => true,
+        (a, b) => a == b,
+    }
+}
+
+struct DisplayUnion<'a>(&'a [TermType]);
+impl<'a> core::fmt::Display for DisplayUnion<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        if self.0.is_empty() {
+            return f.write_str("none");
+        }
+        for (i, ty) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" | ")?;
+            }
+            write!(f, "{}", ty)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use firefly_diagnostics::{CodeMap, Reporter, Severity};
+    use firefly_intern::Symbol;
+    use firefly_pass::Pass;
+    use firefly_syntax_base::ApplicationMetadata;
+
+    use crate::ast;
+    use crate::parser::{ParseConfig, Parser};
+    use crate::passes::SemanticAnalysis;
+
+    use super::CheckTypeSpecs;
+
+    /// Checks the specs of `source`, returning the reporter the discrepancies were reported to
+    fn check(source: &str) -> Reporter {
+        let parser = Parser::new(ParseConfig::default(), Arc::new(CodeMap::new()));
+        let module = parser
+            .parse_string::<ast::Module, _, _>(Reporter::new(), source)
+            .expect("failed to parse test module");
+        let app = ApplicationMetadata {
+            name: Symbol::intern("test"),
+            modules: BTreeMap::new(),
+        };
+        let module = SemanticAnalysis::new(Reporter::new(), &app)
+            .run(module)
+            .expect("failed to analyze test module");
+        let reporter = Reporter::new();
+        CheckTypeSpecs::new(reporter.clone())
+            .run(module)
+            .expect("spec checking failed");
+        reporter
+    }
+
+    /// Returns the messages of the warnings reported to `reporter`
+    fn warnings(reporter: &Reporter) -> Vec<String> {
+        reporter
+            .diagnostics()
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+            .map(|diagnostic| diagnostic.message.clone())
+            .collect()
+    }
+
+    /// Returns the messages of the labels of the warnings reported to `reporter`
+    fn labels(reporter: &Reporter) -> Vec<String> {
+        reporter
+            .diagnostics()
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+            .flat_map(|diagnostic| diagnostic.labels.iter())
+            .map(|label| label.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn return_mismatch() {
+        let reporter = check(
+            "-module(foo).
+-export([f/0]).
+
+-spec f() -> integer().
+f() -> ok.
+",
+        );
+        assert_eq!(
+            warnings(&reporter),
+            vec!["return value does not match spec"]
+        );
+        assert!(labels(&reporter)
+            .iter()
+            .any(|label| label.starts_with("this expression has type atom")));
+    }
+
+    #[test]
+    fn local_call_argument_mismatch() {
+        let reporter = check(
+            "-module(foo).
+-export([g/0]).
+
+-spec double(integer()) -> integer().
+double(X) -> X * 2.
+
+g() -> double(foo).
+",
+        );
+        assert_eq!(warnings(&reporter), vec!["call does not match spec"]);
+        assert!(labels(&reporter)
+            .iter()
+            .any(|label| label.starts_with("this argument has type atom")));
+    }
+
+    #[test]
+    fn unions() {
+        let reporter = check(
+            "-module(foo).
+-export([g/0, h/0]).
+
+-spec f(atom() | integer()) -> ok | error.
+f(_) -> ok.
+
+g() -> {f(a), f(1)}.
+h() -> f(1.0).
+",
+        );
+        assert_eq!(warnings(&reporter), vec!["call does not match spec"]);
+    }
+
+    #[test]
+    fn when_constraints() {
+        let reporter = check(
+            "-module(foo).
+-export([g/0]).
+
+-spec id(T) -> T when T :: integer().
+id(X) -> X.
+
+g() -> id(ok).
+",
+        );
+        assert_eq!(warnings(&reporter), vec!["call does not match spec"]);
+    }
+
+    #[test]
+    fn user_types() {
+        let reporter = check(
+            "-module(foo).
+-export([f/0, g/0]).
+
+-type name() :: atom().
+-type names() :: [name()].
+
+-spec f() -> name().
+f() -> 42.
+
+-spec g() -> names().
+g() -> [a, b].
+",
+        );
+        assert_eq!(
+            warnings(&reporter),
+            vec!["return value does not match spec"]
+        );
+    }
+
+    #[test]
+    fn no_false_positives() {
+        let reporter = check(
+            "-module(foo).
+-export([f/1, g/0, h/0]).
+
+-spec f(any()) -> term().
+f(X) -> {X, ok}.
+
+-spec g() -> no_return().
+g() -> erlang:error(badarg).
+
+-spec h() -> no_return().
+h() -> exit(normal).
+",
+        );
+        assert_eq!(warnings(&reporter), Vec::<String>::new());
+    }
+}
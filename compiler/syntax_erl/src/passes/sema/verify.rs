@@ -2,9 +2,9 @@ use core::ops::ControlFlow;
 use std::collections::{BTreeMap, BTreeSet};
 
 use firefly_diagnostics::*;
-use firefly_intern::Symbol;
+use firefly_intern::{symbols, Ident, Symbol};
 use firefly_pass::Pass;
-use firefly_syntax_base::{ApplicationMetadata, Deprecation, FunctionName};
+use firefly_syntax_base::{bifs, ApplicationMetadata, Deprecation, FunctionName};
 
 use crate::ast::*;
 use crate::visit::{self, VisitMut};
@@ -182,6 +182,140 @@ impl Pass for VerifyTypeSpecs {
     }
 }
 
+/// Verifies that modules implementing a behaviour export the callbacks it requires
+///
+/// Behaviours are resolved using the `-callback` attributes of other modules in the application,
+/// falling back to the built-in definitions of the OTP behaviours. Like `erlc`, this warns about
+/// undefined behaviours, missing callbacks, callbacks required by more than one behaviour, and
+/// `-optional_callbacks` which name an undeclared callback.
+pub struct VerifyBehaviours<'app> {
+    reporter: Reporter,
+    app: &'app ApplicationMetadata,
+}
+impl<'app> VerifyBehaviours<'app> {
+    pub fn new(reporter: Reporter, app: &'app ApplicationMetadata) -> Self {
+        Self { reporter, app }
+    }
+}
+impl<'app> Pass for VerifyBehaviours<'app> {
+    type Input<'a> = &'a mut Module;
+    type Output<'a> = &'a mut Module;
+
+    fn run<'a>(&mut self, module: Self::Input<'a>) -> anyhow::Result<Self::Output<'a>> {
+        for optional in module.optional_callbacks.iter() {
+            if !module.callbacks.contains_key(optional.as_ref()) {
+                self.reporter.show_warning(
+                    "undefined callback",
+                    &[(
+                        optional.span(),
+                        "this callback is not declared by a -callback attribute in this module",
+                    )],
+                );
+            }
+        }
+
+        let export_all = module
+            .compile
+            .as_ref()
+            .map(|c| c.export_all)
+            .unwrap_or(false);
+
+        // Visit behaviours in the order they were declared
+        let mut behaviours = module.behaviours.iter().copied().collect::<Vec<_>>();
+        behaviours.sort_by_key(|b| b.span);
+
+        // Tracks the behaviour which first required each callback, to detect conflicts
+        let mut required: BTreeMap<FunctionName, Ident> = BTreeMap::new();
+        for behaviour_name in behaviours {
+            let behaviour = match self.app.get_behaviour(&behaviour_name.name) {
+                Some(behaviour) => behaviour,
+                None => {
+                    let message = format!(
+                        "the behaviour module '{}' could not be found",
+                        &behaviour_name
+                    );
+                    self.reporter.show_warning(
+                        "undefined behaviour",
+                        &[(behaviour_name.span, message.as_str())],
+                    );
+                    continue;
+                }
+            };
+
+            for callback in behaviour.callbacks.iter() {
+                if let Some(prev) = required.get(callback) {
+                    let message =
+                        format!("the callback {} is also required by '{}'", callback, prev);
+                    self.reporter.show_warning(
+                        "conflicting behaviours",
+                        &[
+                            (behaviour_name.span, message.as_str()),
+                            (prev.span, "first required by this behaviour"),
+                        ],
+                    );
+                    continue;
+                }
+                required.insert(*callback, behaviour_name);
+
+                let defined = module.functions.get(callback);
+                let exported = module
+                    .exports
+                    .contains(&Span::new(SourceSpan::UNKNOWN, *callback));
+                if exported || (export_all && defined.is_some()) {
+                    continue;
+                }
+
+                let message = format!(
+                    "this behaviour requires the callback {} to be exported",
+                    callback
+                );
+                if let Some(function) = defined {
+                    self.reporter.show_warning(
+                        "undefined callback function",
+                        &[
+                            (behaviour_name.span, message.as_str()),
+                            (function.span, "this function is defined, but not exported"),
+                        ],
+                    );
+                    continue;
+                }
+
+                // Look for a function which may be a misnamed implementation of this callback
+                let name = callback.to_string();
+                let most_similar = module
+                    .functions
+                    .iter()
+                    .filter(|(f, _)| !behaviour.is_callback(f))
+                    .map(|(f, function)| (strsim::jaro_winkler(&name, &f.to_string()), function))
+                    .max_by(|(x_score, _), (ref y_score, _)| x_score.total_cmp(y_score))
+                    .and_then(|(score, f)| if score < 0.85 { None } else { Some(f) });
+                match most_similar {
+                    None => {
+                        self.reporter.show_warning(
+                            "undefined callback function",
+                            &[(behaviour_name.span, message.as_str())],
+                        );
+                    }
+                    Some(function) => {
+                        self.reporter.show_warning(
+                            "undefined callback function",
+                            &[
+                                (behaviour_name.span, message.as_str()),
+                                (
+                                    function.span,
+                                    "maybe this function was meant to implement it?",
+                                ),
+                            ],
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(module)
+    }
+}
+
 /// Verifies that the callee of local function calls is defined or imported, or is dynamic and thus not statically analyzable
 ///
 /// Additionally, checks if the callee is known to be deprecated and raises appropriate diagnostics.
@@ -233,7 +367,50 @@ struct VerifyCallsVisitor<'a> {
     locals: &'a BTreeSet<FunctionName>,
     imports: &'a BTreeMap<FunctionName, FunctionName>,
 }
+impl<'a> VerifyCallsVisitor<'a> {
+    /// Verifies that the given remote function exists, where that can be determined statically
+    ///
+    /// Functions of other modules in this application are checked against the exports of that module.
+    /// The runtime implements more of the `erlang` module than the compiler knows about, so calls to it
+    /// are only reported when there is a known BIF of the same name, but none with the given arity.
+    fn verify_remote_function(&self, name: &FunctionName, span: SourceSpan) {
+        let module = name.module.unwrap();
+        if module == self.module {
+            return;
+        }
+        if let Some(metadata) = self.app.modules.get(&module) {
+            if !metadata.is_exported(&name.to_local()) {
+                let message = format!("the function {} is not exported by its module", name);
+                self.reporter
+                    .show_warning("call to undefined function", &[(span, message.as_str())]);
+            }
+        } else if module == symbols::Erlang && !name.is_bif() {
+            let arities = bifs::all()
+                .iter()
+                .filter(|sig| sig.module == module && sig.name == name.function)
+                .map(|sig| sig.arity().to_string())
+                .collect::<Vec<_>>();
+            if !arities.is_empty() {
+                let message = format!(
+                    "{} is not a known built-in function, it is only defined with arity {}",
+                    name,
+                    arities.join(", ")
+                );
+                self.reporter
+                    .show_warning("call to undefined function", &[(span, message.as_str())]);
+            }
+        }
+    }
+}
 impl<'a> VisitMut<()> for VerifyCallsVisitor<'a> {
+    fn visit_mut_function_var(&mut self, var: &mut FunctionVar) -> ControlFlow<()> {
+        // Calls are handled in visit_mut_apply, so this is only reached for captures, e.g. `fun m:f/1`
+        if let FunctionVar::Resolved(name) = var {
+            self.verify_remote_function(name, name.span());
+        }
+        ControlFlow::Continue(())
+    }
+
     fn visit_mut_apply(&mut self, apply: &mut Apply) -> ControlFlow<()> {
         for arg in apply.args.iter_mut() {
             let _ = visit::visit_mut_expr(self, arg);
@@ -261,6 +438,7 @@ impl<'a> VisitMut<()> for VerifyCallsVisitor<'a> {
                 }
                 (Some(m), Some(f)) => {
                     let name = FunctionName::new(m.name, f.name, arity);
+                    self.verify_remote_function(&name, *rspan);
                     match self.app.get_function_deprecation(&name) {
                         None => ControlFlow::Continue(()),
                         Some(Deprecation::Module { span: dspan, flag }) => {
@@ -348,6 +526,7 @@ impl<'a> VisitMut<()> for VerifyCallsVisitor<'a> {
                         );
                     }
                 } else {
+                    self.verify_remote_function(&name, name.span());
                     match self.app.get_function_deprecation(&name) {
                         None => (),
                         Some(Deprecation::Module { span: dspan, flag }) => {
@@ -567,3 +746,214 @@ impl<'a> VisitMut<()> for VerifyCallsVisitor<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use firefly_diagnostics::{CodeMap, Reporter, Severity};
+    use firefly_intern::Symbol;
+    use firefly_pass::Pass;
+    use firefly_syntax_base::{ApplicationMetadata, FunctionName, ModuleMetadata};
+
+    use crate::ast;
+    use crate::parser::{ParseConfig, Parser};
+    use crate::passes::{sema, SemanticAnalysis};
+
+    fn parse(source: &str) -> ast::Module {
+        let parser = Parser::new(ParseConfig::default(), Arc::new(CodeMap::new()));
+        parser
+            .parse_string::<ast::Module, _, _>(Reporter::new(), source)
+            .expect("failed to parse test module")
+    }
+
+    /// Returns an application consisting of the given modules, in addition to the module under test
+    fn app_with(sources: &[&str]) -> ApplicationMetadata {
+        let mut app = ApplicationMetadata {
+            name: Symbol::intern("test"),
+            modules: BTreeMap::new(),
+        };
+        for source in sources.iter() {
+            let mut module = parse(source);
+            let metadata = ModuleMetadata {
+                name: module.name,
+                exports: module.exports.iter().cloned().collect(),
+                deprecation: None,
+                deprecations: BTreeMap::new(),
+                behaviour: module.behaviour(),
+                behaviours: module.behaviours.iter().map(|b| b.name).collect(),
+                references: sema::collect_references(&mut module),
+            };
+            app.modules.insert(module.name(), metadata);
+        }
+        app
+    }
+
+    /// Analyzes `source` in the context of `app`, returning the reporter warnings were reported to
+    fn analyze_in(
+        app: &ApplicationMetadata,
+        source: &str,
+    ) -> (anyhow::Result<ast::Module>, Reporter) {
+        let reporter = Reporter::new();
+        let result = SemanticAnalysis::new(reporter.clone(), app).run(parse(source));
+        (result, reporter)
+    }
+
+    fn analyze(source: &str) -> (anyhow::Result<ast::Module>, Reporter) {
+        analyze_in(&app_with(&[]), source)
+    }
+
+    /// Returns the messages of the warnings reported to `reporter`
+    fn warnings(reporter: &Reporter) -> Vec<String> {
+        reporter
+            .diagnostics()
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+            .map(|diagnostic| diagnostic.message.clone())
+            .collect()
+    }
+
+    /// Returns the messages of the labels of the warnings reported to `reporter`
+    fn labels(reporter: &Reporter) -> Vec<String> {
+        reporter
+            .diagnostics()
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+            .flat_map(|diagnostic| diagnostic.labels.iter())
+            .map(|label| label.message.clone())
+            .collect()
+    }
+
+    const BEHAVIOUR: &str = "-module(my_behaviour).
+-optional_callbacks([early/0]).
+-callback early() -> ok.
+-callback late() -> ok.
+-callback required(term()) -> ok.
+-optional_callbacks([late/0]).
+";
+
+    #[test]
+    fn missing_callback() {
+        let app = app_with(&[BEHAVIOUR]);
+        let (_, reporter) = analyze_in(
+            &app,
+            "-module(foo).
+-behaviour(my_behaviour).
+-export([early/0]).
+
+early() -> ok.
+",
+        );
+        assert_eq!(warnings(&reporter), vec!["undefined callback function"]);
+        assert_eq!(
+            labels(&reporter),
+            vec!["this behaviour requires the callback required/1 to be exported"]
+        );
+    }
+
+    #[test]
+    fn optional_callbacks() {
+        // -optional_callbacks may precede or follow the callbacks it names
+        let module = parse(BEHAVIOUR);
+        let behaviour = module.behaviour().unwrap();
+        let callback = |name, arity| FunctionName::new_local(Symbol::intern(name), arity);
+        assert!(behaviour.optional_callbacks.contains(&callback("early", 0)));
+        assert!(behaviour.optional_callbacks.contains(&callback("late", 0)));
+        assert_eq!(
+            behaviour.callbacks.iter().copied().collect::<Vec<_>>(),
+            vec![callback("required", 1)]
+        );
+
+        let app = app_with(&[BEHAVIOUR]);
+        let (result, reporter) = analyze_in(
+            &app,
+            "-module(foo).
+-behaviour(my_behaviour).
+-export([required/1]).
+
+required(_) -> ok.
+",
+        );
+        assert!(result.is_ok());
+        assert_eq!(warnings(&reporter), Vec::<String>::new());
+    }
+
+    #[test]
+    fn undefined_optional_callback() {
+        let (_, reporter) = analyze(
+            "-module(my_behaviour).
+-callback init() -> ok.
+-optional_callbacks([init/0, terminate/0]).
+",
+        );
+        assert_eq!(warnings(&reporter), vec!["undefined callback"]);
+    }
+
+    #[test]
+    fn undefined_behaviour() {
+        let (_, reporter) = analyze(
+            "-module(foo).
+-behaviour(no_such_behaviour).
+",
+        );
+        assert_eq!(warnings(&reporter), vec!["undefined behaviour"]);
+        assert_eq!(
+            labels(&reporter),
+            vec!["the behaviour module 'no_such_behaviour' could not be found"]
+        );
+    }
+
+    #[test]
+    fn undefined_remote_calls() {
+        let app = app_with(&["-module(bar).
+-export([f/1]).
+
+f(X) -> X.
+"]);
+        let (_, reporter) = analyze_in(
+            &app,
+            "-module(foo).
+-export([f/1]).
+
+f(X) ->
+    bar:f(X),
+    bar:module_info(),
+    bar:g(X),
+    erlang:length(X, X),
+    fun bar:f/2.
+",
+        );
+        assert_eq!(warnings(&reporter), vec!["call to undefined function"; 3]);
+        assert_eq!(
+            labels(&reporter),
+            vec![
+                "the function bar:g/1 is not exported by its module",
+                "erlang:length/2 is not a known built-in function, it is only defined with arity 1",
+                "the function bar:f/2 is not exported by its module",
+            ]
+        );
+    }
+
+    #[test]
+    fn misnamed_callback() {
+        let (_, reporter) = analyze(
+            "-module(foo).
+-behaviour(gen_server).
+-export([init/1, handle_call/3, handle_casts/2]).
+
+init(State) -> {ok, State}.
+handle_call(_, _, State) -> {reply, ok, State}.
+handle_casts(_, State) -> {noreply, State}.
+",
+        );
+        assert_eq!(warnings(&reporter), vec!["undefined callback function"]);
+        assert_eq!(
+            labels(&reporter),
+            vec![
+                "this behaviour requires the callback handle_cast/2 to be exported",
+                "maybe this function was meant to implement it?",
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 mod expand_records;
 mod expand_substitutions;
 mod expand_unqualified_calls;
+mod ms_transform;
 
 use std::collections::BTreeMap;
 use std::sync::Arc;
@@ -14,6 +15,8 @@ use self::expand_records::ExpandRecords;
 use self::expand_substitutions::ExpandSubstitutions;
 use self::expand_unqualified_calls::ExpandUnqualifiedCalls;
 
+pub use self::ms_transform::MsTransform;
+
 pub struct CanonicalizeSyntax {
     #[allow(dead_code)]
     reporter: Reporter,
@@ -0,0 +1,498 @@
+use core::ops::ControlFlow;
+use std::collections::HashMap;
+
+use firefly_diagnostics::*;
+use firefly_intern::{symbols, Ident, Symbol};
+use firefly_syntax_base::FunctionName;
+
+use crate::ast::*;
+use crate::passes::ParseTransform;
+use crate::visit::{self as visit, VisitMut};
+
+/// These functions may only be called in the body of a match specification used for tracing
+const ACTION_FUNCTIONS: &[&str] = &[
+    "caller",
+    "disable_trace",
+    "display",
+    "enable_trace",
+    "exception_trace",
+    "get_seq_token",
+    "get_tcw",
+    "message",
+    "process_dump",
+    "return_trace",
+    "set_seq_token",
+    "set_tcw",
+    "silent",
+    "trace",
+];
+
+/// This transform is the equivalent of `ms_transform` from stdlib, it rewrites calls to
+/// `ets:fun2ms/1` and `dbg:fun2ms/1` with a literal fun argument into the match specification
+/// described by that fun, e.g.:
+///
+/// ```erlang
+/// ets:fun2ms(fun({K, V}) when V > 0 -> K end)
+/// ```
+///
+/// Becomes:
+///
+/// ```erlang
+/// [{{'$1', '$2'}, [{'>', '$2', 0}], ['$1']}]
+/// ```
+///
+/// Variables bound outside of the fun are captured as `{const, Var}`, so the result is not
+/// necessarily a literal. Calls to `fun2ms` with any other argument are left untouched, and
+/// will raise at runtime, as they do in erlc.
+pub struct MsTransform;
+impl ParseTransform for MsTransform {
+    fn name(&self) -> Symbol {
+        symbols::MsTransform
+    }
+
+    fn transform(&self, mut module: Module, reporter: &Reporter) -> anyhow::Result<Module> {
+        let mut functions = core::mem::take(&mut module.functions);
+        let failed = {
+            let mut visitor = Fun2Ms {
+                module: &module,
+                reporter,
+                failed: false,
+            };
+            for function in functions.values_mut() {
+                let _ = visitor.visit_mut_function(function);
+            }
+            visitor.failed
+        };
+        module.functions = functions;
+
+        if failed {
+            anyhow::bail!("ms_transform failed, see diagnostics for details");
+        }
+
+        Ok(module)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Fun2MsKind {
+    /// `ets:fun2ms/1`, the fun parameter must be a variable or a tuple
+    Ets,
+    /// `dbg:fun2ms/1`, the fun parameter must be a variable or a list, and action functions may
+    /// be called in the body
+    Dbg,
+}
+
+struct Fun2Ms<'m> {
+    module: &'m Module,
+    reporter: &'m Reporter,
+    failed: bool,
+}
+impl<'m> VisitMut<()> for Fun2Ms<'m> {
+    fn visit_mut_expr(&mut self, expr: &mut Expr) -> ControlFlow<()> {
+        visit::visit_mut_expr(self, expr)?;
+
+        if let Expr::Apply(apply) = expr {
+            let Some(kind) = fun2ms_kind(apply) else { return ControlFlow::Continue(()); };
+            let Expr::Fun(fun) = &apply.args[0] else { return ControlFlow::Continue(()); };
+            match self.translate_fun(kind, fun) {
+                Ok(ms) => *expr = ms,
+                Err(()) => self.failed = true,
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+/// Tracks the variables bound in the head of a single clause
+#[derive(Default)]
+struct Bindings {
+    vars: HashMap<Symbol, Symbol>,
+}
+impl Bindings {
+    fn bind(&mut self, var: Symbol) -> Symbol {
+        let next = self.vars.len() + 1;
+        *self
+            .vars
+            .entry(var)
+            .or_insert_with(|| Symbol::intern(&format!("${}", next)))
+    }
+
+    fn get(&self, var: Symbol) -> Option<Symbol> {
+        self.vars.get(&var).copied()
+    }
+}
+
+impl<'m> Fun2Ms<'m> {
+    fn unsupported<T>(&self, span: SourceSpan, message: &str) -> Result<T, ()> {
+        self.reporter
+            .show_error("unsupported match specification", &[(span, message)]);
+        Err(())
+    }
+
+    fn translate_fun(&self, kind: Fun2MsKind, fun: &Fun) -> Result<Expr, ()> {
+        let fun = match fun {
+            Fun::Anonymous(fun) => fun,
+            Fun::Recursive(fun) => {
+                return self.unsupported(fun.span, "named funs cannot be translated by fun2ms");
+            }
+        };
+        if fun.arity != 1 {
+            return self.unsupported(fun.span, "fun2ms requires a fun with a single parameter");
+        }
+
+        let mut clauses = Vec::with_capacity(fun.clauses.len());
+        for clause in fun.clauses.iter() {
+            clauses.push(self.translate_clause(kind, clause)?);
+        }
+
+        Ok(list(fun.span, clauses))
+    }
+
+    fn translate_clause(&self, kind: Fun2MsKind, clause: &Clause) -> Result<Expr, ()> {
+        let span = clause.span;
+        let mut bindings = Bindings::default();
+
+        let head = self.translate_head(kind, &mut bindings, &clause.patterns[0])?;
+
+        let mut guards = Vec::with_capacity(clause.guards.len());
+        for guard in clause.guards.iter() {
+            let mut conditions = Vec::with_capacity(guard.conditions.len());
+            for condition in guard.conditions.iter() {
+                conditions.push(self.translate_expr(kind, &bindings, condition, false)?);
+            }
+            guards.push(conditions);
+        }
+        // A guard sequence (i.e. `G1; G2`) must be expressed as a single `orelse`, as the
+        // conditions of a match specification are all required to hold
+        let conditions = match guards.len() {
+            0 => vec![],
+            1 => guards.pop().unwrap(),
+            _ => {
+                let mut alternatives = vec![atom(span, symbols::OrElse)];
+                for mut conditions in guards.drain(..) {
+                    if conditions.len() == 1 {
+                        alternatives.push(conditions.pop().unwrap());
+                    } else {
+                        let mut elements = vec![atom(span, symbols::AndAlso)];
+                        elements.append(&mut conditions);
+                        alternatives.push(tuple(span, elements));
+                    }
+                }
+                vec![tuple(span, alternatives)]
+            }
+        };
+
+        let mut body = Vec::with_capacity(clause.body.len());
+        for expr in clause.body.iter() {
+            body.push(self.translate_expr(kind, &bindings, expr, true)?);
+        }
+
+        Ok(tuple(
+            span,
+            vec![head, list(span, conditions), list(span, body)],
+        ))
+    }
+
+    fn translate_head(
+        &self,
+        kind: Fun2MsKind,
+        bindings: &mut Bindings,
+        head: &Expr,
+    ) -> Result<Expr, ()> {
+        match (kind, head) {
+            (_, Expr::Var(_)) => self.translate_pattern(bindings, head),
+            // The variable in a top-level alias refers to the whole object, i.e. `'$_'`
+            (_, Expr::Match(Match { pattern, expr, .. })) => {
+                let (var, pattern) = match (pattern.as_ref(), expr.as_ref()) {
+                    (Expr::Var(var), pattern) | (pattern, Expr::Var(var)) => (var, pattern),
+                    _ => {
+                        return self.unsupported(
+                            head.span(),
+                            "only a variable may be bound to the head of a match specification",
+                        );
+                    }
+                };
+                let pattern = self.translate_head(kind, bindings, pattern)?;
+                if !var.is_wildcard() {
+                    bindings.vars.insert(var.sym(), Symbol::intern("$_"));
+                }
+                Ok(pattern)
+            }
+            (Fun2MsKind::Ets, Expr::Tuple(_) | Expr::Record(_)) => {
+                self.translate_pattern(bindings, head)
+            }
+            (Fun2MsKind::Dbg, Expr::Cons(_) | Expr::Literal(Literal::Nil(_))) => {
+                self.translate_pattern(bindings, head)
+            }
+            (Fun2MsKind::Ets, _) => self.unsupported(
+                head.span(),
+                "the parameter of the fun given to ets:fun2ms must be a variable or a tuple",
+            ),
+            (Fun2MsKind::Dbg, _) => self.unsupported(
+                head.span(),
+                "the parameter of the fun given to dbg:fun2ms must be a variable or a list",
+            ),
+        }
+    }
+
+    fn translate_pattern(&self, bindings: &mut Bindings, pattern: &Expr) -> Result<Expr, ()> {
+        match pattern {
+            Expr::Var(var) if var.is_wildcard() => Ok(atom(var.span(), symbols::Underscore)),
+            Expr::Var(var) => Ok(atom(var.span(), bindings.bind(var.sym()))),
+            Expr::Literal(_) => Ok(pattern.clone()),
+            Expr::Tuple(Tuple { span, elements }) => {
+                let mut translated = Vec::with_capacity(elements.len());
+                for element in elements.iter() {
+                    translated.push(self.translate_pattern(bindings, element)?);
+                }
+                Ok(tuple(*span, translated))
+            }
+            Expr::Cons(Cons { span, head, tail }) => {
+                let head = self.translate_pattern(bindings, head)?;
+                let tail = self.translate_pattern(bindings, tail)?;
+                Ok(cons(*span, head, tail))
+            }
+            Expr::Record(record) => {
+                let span = record.span;
+                let definition = self.record(record.name)?;
+                let mut elements = Vec::with_capacity(definition.fields.len() + 1);
+                elements.push(atom(span, record.name.name));
+                for defined in definition.fields.iter() {
+                    let field = record.fields.iter().find(|f| f.name == defined.name);
+                    match field.and_then(|f| f.value.as_ref()) {
+                        Some(value) => elements.push(self.translate_pattern(bindings, value)?),
+                        None => match record.default.as_deref() {
+                            Some(default) => {
+                                elements.push(self.translate_pattern(bindings, default)?)
+                            }
+                            None => elements.push(atom(span, symbols::Underscore)),
+                        },
+                    }
+                }
+                self.check_record_fields(record, definition)?;
+                Ok(tuple(span, elements))
+            }
+            Expr::Match(_) => self.unsupported(
+                pattern.span(),
+                "aliases are only supported at the top level of a match specification head",
+            ),
+            _ => self.unsupported(
+                pattern.span(),
+                "this pattern is not supported in a match specification head",
+            ),
+        }
+    }
+
+    fn translate_expr(
+        &self,
+        kind: Fun2MsKind,
+        bindings: &Bindings,
+        expr: &Expr,
+        in_body: bool,
+    ) -> Result<Expr, ()> {
+        match expr {
+            Expr::Var(var) if var.is_wildcard() => self.unsupported(
+                var.span(),
+                "wildcards are not allowed in match specification guards or bodies",
+            ),
+            Expr::Var(var) => match bindings.get(var.sym()) {
+                Some(bound) => Ok(atom(var.span(), bound)),
+                // Variables from the enclosing scope are captured by value
+                None => Ok(tuple(
+                    var.span(),
+                    vec![atom(var.span(), symbols::Const), expr.clone()],
+                )),
+            },
+            // Tuples must be wrapped in another tuple, to distinguish them from calls
+            Expr::Literal(Literal::Tuple(span, _)) => Ok(tuple(*span, vec![expr.clone()])),
+            Expr::Literal(_) => Ok(expr.clone()),
+            Expr::Tuple(Tuple { span, elements }) => {
+                let mut translated = Vec::with_capacity(elements.len());
+                for element in elements.iter() {
+                    translated.push(self.translate_expr(kind, bindings, element, in_body)?);
+                }
+                Ok(tuple(*span, vec![tuple(*span, translated)]))
+            }
+            Expr::Cons(Cons { span, head, tail }) => {
+                let head = self.translate_expr(kind, bindings, head, in_body)?;
+                let tail = self.translate_expr(kind, bindings, tail, in_body)?;
+                Ok(cons(*span, head, tail))
+            }
+            Expr::BinaryExpr(BinaryExpr { span, lhs, op, rhs }) if op.is_guard_op() => {
+                let lhs = self.translate_expr(kind, bindings, lhs, in_body)?;
+                let rhs = self.translate_expr(kind, bindings, rhs, in_body)?;
+                Ok(tuple(*span, vec![atom(*span, op.to_symbol()), lhs, rhs]))
+            }
+            Expr::UnaryExpr(UnaryExpr { span, op, operand }) if op.is_guard_op() => {
+                let operand = self.translate_expr(kind, bindings, operand, in_body)?;
+                Ok(tuple(*span, vec![atom(*span, op.to_symbol()), operand]))
+            }
+            Expr::Apply(apply) => self.translate_call(kind, bindings, apply, in_body),
+            Expr::RecordIndex(RecordIndex { span, name, field }) => {
+                let index = self.record_field_index(*name, *field)?;
+                Ok(Expr::Literal(Literal::Integer(*span, index.into())))
+            }
+            Expr::RecordAccess(RecordAccess {
+                span,
+                record,
+                name,
+                field,
+            }) => {
+                let index = self.record_field_index(*name, *field)?;
+                let record = self.translate_expr(kind, bindings, record, in_body)?;
+                Ok(tuple(
+                    *span,
+                    vec![
+                        atom(*span, symbols::Element),
+                        Expr::Literal(Literal::Integer(*span, index.into())),
+                        record,
+                    ],
+                ))
+            }
+            Expr::Record(record) => {
+                let span = record.span;
+                let definition = self.record(record.name)?;
+                let mut elements = Vec::with_capacity(definition.fields.len() + 1);
+                elements.push(atom(span, record.name.name));
+                for defined in definition.fields.iter() {
+                    let field = record.fields.iter().find(|f| f.name == defined.name);
+                    let value = field
+                        .and_then(|f| f.value.as_ref())
+                        .or(record.default.as_deref())
+                        .or(defined.value.as_ref());
+                    match value {
+                        Some(value) => {
+                            elements.push(self.translate_expr(kind, bindings, value, in_body)?)
+                        }
+                        None => elements.push(atom(span, symbols::Undefined)),
+                    }
+                }
+                self.check_record_fields(record, definition)?;
+                Ok(tuple(span, vec![tuple(span, elements)]))
+            }
+            _ => self.unsupported(
+                expr.span(),
+                "this expression is not supported in a match specification",
+            ),
+        }
+    }
+
+    fn translate_call(
+        &self,
+        kind: Fun2MsKind,
+        bindings: &Bindings,
+        apply: &Apply,
+        in_body: bool,
+    ) -> Result<Expr, ()> {
+        let span = apply.span;
+        let function = match apply.callee.as_ref() {
+            Expr::Literal(Literal::Atom(name)) => Some(name.name),
+            Expr::FunctionVar(FunctionVar::Resolved(name))
+                if name.module == Some(symbols::Erlang) =>
+            {
+                Some(name.function)
+            }
+            Expr::FunctionVar(FunctionVar::PartiallyResolved(name)) => Some(name.function),
+            _ => None,
+        };
+        let Some(function) = function else {
+            return self.unsupported(
+                apply.callee.span(),
+                "only calls to guard functions are allowed in a match specification",
+            );
+        };
+
+        let arity = apply.args.len();
+        match (function, arity) {
+            (symbols::Object, 0) => return Ok(atom(span, Symbol::intern("$_"))),
+            (symbols::Bindings, 0) => return Ok(atom(span, Symbol::intern("$*"))),
+            _ => (),
+        }
+
+        let is_guard_bif = FunctionName::new(symbols::Erlang, function, arity as u8).is_guard_bif();
+        let is_action = kind == Fun2MsKind::Dbg
+            && in_body
+            && ACTION_FUNCTIONS.contains(&function.as_str().get());
+        if !is_guard_bif && !is_action {
+            return self.unsupported(
+                span,
+                "only calls to guard functions are allowed in a match specification",
+            );
+        }
+
+        let mut elements = Vec::with_capacity(arity + 1);
+        elements.push(atom(apply.callee.span(), function));
+        for arg in apply.args.iter() {
+            elements.push(self.translate_expr(kind, bindings, arg, in_body)?);
+        }
+        Ok(tuple(span, elements))
+    }
+
+    fn record(&self, name: Ident) -> Result<&'m Record, ()> {
+        match self.module.record(name.name) {
+            Some(definition) => Ok(definition),
+            None => self.unsupported(name.span, "this record is not defined"),
+        }
+    }
+
+    /// Returns the 1-based index of `field` in the tuple representation of the record `name`
+    fn record_field_index(&self, name: Ident, field: Ident) -> Result<usize, ()> {
+        let definition = self.record(name)?;
+        match definition.fields.iter().position(|f| f.name == field) {
+            // The first element of the tuple is the record name
+            Some(index) => Ok(index + 2),
+            None => self.unsupported(field.span, "this field is not defined in the record"),
+        }
+    }
+
+    fn check_record_fields(&self, record: &Record, definition: &Record) -> Result<(), ()> {
+        for field in record.fields.iter() {
+            if !definition.fields.iter().any(|f| f.name == field.name) {
+                return self.unsupported(field.span, "this field is not defined in the record");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the kind of `fun2ms` being called by `apply`, if it is one
+fn fun2ms_kind(apply: &Apply) -> Option<Fun2MsKind> {
+    let name = match apply.callee.as_ref() {
+        Expr::FunctionVar(FunctionVar::Resolved(name)) => name,
+        _ => return None,
+    };
+    if name.function != symbols::Fun2ms || name.arity != 1 {
+        return None;
+    }
+    match name.module {
+        Some(symbols::Ets) => Some(Fun2MsKind::Ets),
+        Some(symbols::Dbg) => Some(Fun2MsKind::Dbg),
+        _ => None,
+    }
+}
+
+fn atom(span: SourceSpan, name: Symbol) -> Expr {
+    Expr::Literal(Literal::Atom(Ident::new(name, span)))
+}
+
+fn tuple(span: SourceSpan, elements: Vec<Expr>) -> Expr {
+    Expr::Tuple(Tuple { span, elements })
+}
+
+fn cons(span: SourceSpan, head: Expr, tail: Expr) -> Expr {
+    Expr::Cons(Cons {
+        span,
+        head: Box::new(head),
+        tail: Box::new(tail),
+    })
+}
+
+fn list(span: SourceSpan, elements: Vec<Expr>) -> Expr {
+    elements
+        .into_iter()
+        .rev()
+        .fold(Expr::Literal(Literal::Nil(span)), |tail, head| {
+            cons(span, head, tail)
+        })
+}
@@ -1093,6 +1093,29 @@ impl TranslateAst {
                 let qualifiers = self.preprocess_quals(qualifiers)?;
                 self.bc_tq(span, *body, qualifiers)
             }
+            ast::Expr::MapComprehension(ast::MapComprehension {
+                span,
+                key,
+                value,
+                qualifiers,
+            }) => {
+                // A map comprehension is equivalent to `maps:from_list([{K, V} || Qualifiers])`
+                let body = ast::Expr::Tuple(ast::Tuple {
+                    span,
+                    elements: vec![*key, *value],
+                });
+                let lc = ast::Expr::ListComprehension(ast::ListComprehension {
+                    span,
+                    body: Box::new(body),
+                    qualifiers,
+                });
+                let from_list = ast::FunctionVar::new(span, symbols::Maps, symbols::FromList, 1);
+                self.expr(ast::Expr::Apply(ast::Apply {
+                    span,
+                    callee: Box::new(ast::Expr::FunctionVar(from_list)),
+                    args: vec![lc],
+                }))
+            }
             ast::Expr::Tuple(ast::Tuple { span, elements }) => {
                 let (elements, pre) = self.safe_list(elements)?;
                 Ok((IExpr::Tuple(ITuple::new(span, elements)), pre))
@@ -1281,6 +1304,21 @@ impl TranslateAst {
                 });
                 self.expr(outer)
             }
+            ast::Expr::Maybe(ast::Maybe {
+                span,
+                body,
+                else_clauses,
+            }) => self.maybe(span, body, else_clauses),
+            ast::Expr::MaybeMatch(ast::MaybeMatch { span, .. }) => {
+                self.reporter.show_error(
+                    "invalid conditional match",
+                    &[(
+                        span,
+                        "'?=' is only permitted at the top level of a maybe expression",
+                    )],
+                );
+                bail!("invalid conditional match")
+            }
             ast::Expr::Catch(ast::Catch { span, expr }) => {
                 let (expr, mut pre) = self.expr(*expr)?;
                 pre.push(expr);
@@ -2159,9 +2197,60 @@ impl TranslateAst {
             ast::GeneratorType::Bitstring => {
                 self.bit_generator(gen.span, *gen.pattern, *gen.expr, guards)
             }
+            ast::GeneratorType::Map => {
+                let expr = self.map_generator_input(gen.span, *gen.expr);
+                self.list_generator(gen.span, *gen.pattern, expr, guards)
+            }
         }
     }
 
+    /// Map generators are translated to list generators over the key/value pairs of the map,
+    /// i.e. `K := V <- Map` becomes `{K, V} <- maps:to_list(Map)`, except that an input which
+    /// is not a map raises `{bad_generator, Input}`, as it would for the other generator types.
+    fn map_generator_input(&mut self, span: SourceSpan, expr: ast::Expr) -> ast::Expr {
+        let map = ast::Expr::Var(ast::Var(self.context_mut().next_var(Some(span)).name));
+        let other = ast::Expr::Var(ast::Var(self.context_mut().next_var(Some(span)).name));
+
+        let is_map = ast::FunctionVar::new(span, symbols::Erlang, symbols::IsMap, 1);
+        let guard = ast::Guard {
+            span,
+            conditions: vec![ast::Expr::Apply(ast::Apply {
+                span,
+                callee: Box::new(ast::Expr::FunctionVar(is_map)),
+                args: vec![map.clone()],
+            })],
+        };
+        let to_list = ast::FunctionVar::new(span, symbols::Maps, symbols::ToList, 1);
+        let to_list = ast::Expr::Apply(ast::Apply {
+            span,
+            callee: Box::new(ast::Expr::FunctionVar(to_list)),
+            args: vec![map.clone()],
+        });
+
+        let reason = ast::Expr::Tuple(ast::Tuple {
+            span,
+            elements: vec![
+                ast::Expr::Literal(ast::Literal::Atom(Ident::new(symbols::BadGenerator, span))),
+                other.clone(),
+            ],
+        });
+        let error = ast::FunctionVar::new(span, symbols::Erlang, symbols::Error, 1);
+        let error = ast::Expr::Apply(ast::Apply {
+            span,
+            callee: Box::new(ast::Expr::FunctionVar(error)),
+            args: vec![reason],
+        });
+
+        ast::Expr::Case(ast::Case {
+            span,
+            expr: Box::new(expr),
+            clauses: vec![
+                ast::Clause::new(span, vec![map], vec![guard], vec![to_list], true),
+                ast::Clause::new(span, vec![other], vec![], vec![error], true),
+            ],
+        })
+    }
+
     fn list_generator(
         &mut self,
         span: SourceSpan,
@@ -2898,6 +2987,148 @@ impl TranslateAst {
         Ok((vec![tag, value, info], handler))
     }
 
+    /// Translates a `maybe` expression into nested case expressions.
+    ///
+    /// Each conditional match `P ?= E` becomes a case on `E`, where the clause for `P`
+    /// continues with the remainder of the body, and any other value is either the result
+    /// of the `maybe`, or is passed to the `else` clauses, raising `{else_clause, Value}`
+    /// if none of them match. The `else` clauses are lowered once, as a local fun bound
+    /// around the body, which every failed conditional match applies.
+    fn maybe(
+        &mut self,
+        span: SourceSpan,
+        body: Vec<ast::Expr>,
+        else_clauses: Option<Vec<ast::Clause>>,
+    ) -> anyhow::Result<(IExpr, Vec<IExpr>)> {
+        let else_clauses = match else_clauses {
+            Some(clauses) => clauses,
+            None => return self.maybe_body(span, body, None),
+        };
+
+        let name = self.context_mut().new_fun_name(Some("maybe_else"));
+        let f = Var::new_with_arity(Ident::new(name, span), 1);
+        let var = self.context_mut().next_var(Some(span));
+        let clauses = self.clauses(else_clauses)?;
+        let fpat = self.context_mut().next_var(Some(span));
+        let reason = ituple!(
+            span,
+            iatom!(span, symbols::ElseClause),
+            IExpr::Var(fpat.clone())
+        );
+        let fail = fail_clause(span, vec![IExpr::Var(fpat)], reason);
+        let fun = IExpr::Fun(IFun {
+            span,
+            annotations: Annotations::default(),
+            id: Some(Ident::new(name, span)),
+            name: Some(Ident::new(name, span)),
+            vars: vec![var],
+            clauses,
+            fail,
+        });
+
+        let (expr, mut body) = self.maybe_body(span, body, Some(&f))?;
+        body.push(expr);
+        let letr = IExpr::LetRec(ILetRec {
+            span,
+            annotations: Annotations::default(),
+            defs: vec![(f, fun)],
+            body,
+        });
+        Ok((letr, vec![]))
+    }
+
+    /// Translates the body of a `maybe` expression, see `maybe`.
+    ///
+    /// Values which fail to match are passed to `else_fun` if given, otherwise they are the result.
+    fn maybe_body(
+        &mut self,
+        span: SourceSpan,
+        mut body: Vec<ast::Expr>,
+        else_fun: Option<&Var>,
+    ) -> anyhow::Result<(IExpr, Vec<IExpr>)> {
+        let is_maybe_match = |expr: &ast::Expr| matches!(expr, ast::Expr::MaybeMatch(_));
+        let index = match body.iter().position(is_maybe_match) {
+            Some(index) => index,
+            None => return self.expr(ast::Expr::Begin(ast::Begin { span, body })),
+        };
+        let rest = body.split_off(index + 1);
+        let (match_span, pattern, expr) = match body.pop() {
+            Some(ast::Expr::MaybeMatch(ast::MaybeMatch {
+                span,
+                pattern,
+                expr,
+            })) => (span, pattern, expr),
+            _ => unreachable!(),
+        };
+
+        let mut pre = self.exprs(body)?;
+        let (arg, mut pre2) = self.novars(*expr)?;
+        pre.append(&mut pre2);
+
+        // If there is nothing left in the body, the result is the matched value
+        let success = if rest.is_empty() {
+            let v = self.context_mut().next_var(Some(match_span));
+            let pattern = ast::Expr::Match(ast::Match {
+                span: match_span,
+                pattern,
+                expr: Box::new(ast::Expr::Var(ast::Var(v.name))),
+            });
+            let body = vec![ast::Expr::Var(ast::Var(v.name))];
+            self.clause(ast::Clause::new(
+                match_span,
+                vec![pattern],
+                vec![],
+                body,
+                false,
+            ))?
+        } else {
+            let mut success = self.clause(ast::Clause::new(
+                match_span,
+                vec![*pattern],
+                vec![],
+                vec![],
+                false,
+            ))?;
+            let (expr, mut body) = self.maybe_body(span, rest, else_fun)?;
+            body.push(expr);
+            success.body = body;
+            success
+        };
+
+        let other = self.context_mut().next_var(Some(match_span));
+        let fallthrough = match else_fun {
+            None => IExpr::Var(other.clone()),
+            Some(f) => IExpr::Apply(IApply::new(
+                match_span,
+                IExpr::Var(f.clone()),
+                vec![IExpr::Var(other.clone())],
+            )),
+        };
+        let fallthrough = IClause {
+            span: match_span,
+            annotations: Annotations::default_compiler_generated(),
+            patterns: vec![IExpr::Var(other)],
+            guards: vec![],
+            body: vec![fallthrough],
+        };
+
+        let fpat = self.context_mut().next_var(Some(match_span));
+        let reason = ituple!(
+            match_span,
+            iatom!(match_span, symbols::CaseClause),
+            IExpr::Var(fpat.clone())
+        );
+        let fail = fail_clause(match_span, vec![IExpr::Var(fpat)], reason);
+        let case = IExpr::Case(ICase {
+            span: match_span,
+            annotations: Annotations::default(),
+            args: vec![arg],
+            clauses: vec![success, fallthrough],
+            fail,
+        });
+        Ok((case, pre))
+    }
+
     fn try_after(
         &mut self,
         span: SourceSpan,
@@ -3864,3 +4095,119 @@ fn force_safe(context: &mut FunctionContext, expr: IExpr) -> (IExpr, Vec<IExpr>)
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use firefly_diagnostics::{CodeMap, Reporter};
+    use firefly_intern::Symbol;
+    use firefly_pass::Pass;
+    use firefly_syntax_base::ApplicationMetadata;
+
+    use crate::ast;
+    use crate::parser::{ParseConfig, Parser};
+    use crate::passes::{AstToCore, CanonicalizeSyntax, SemanticAnalysis};
+
+    /// Lowers `source` to Core, panicking if it is not valid
+    fn lower(source: &str) -> String {
+        let codemap = Arc::new(CodeMap::new());
+        let reporter = Reporter::new();
+        let parser = Parser::new(ParseConfig::default(), codemap.clone());
+        let module = parser
+            .parse_string::<ast::Module, _, _>(reporter.clone(), source)
+            .expect("failed to parse test module");
+        let app = ApplicationMetadata {
+            name: Symbol::intern("test"),
+            modules: BTreeMap::new(),
+        };
+        let module = SemanticAnalysis::new(reporter.clone(), &app)
+            .run(module)
+            .expect("failed to analyze test module");
+        let mut passes =
+            CanonicalizeSyntax::new(reporter.clone(), codemap).chain(AstToCore::new(reporter));
+        passes
+            .run(module)
+            .expect("failed to lower test module")
+            .to_string()
+    }
+
+    #[test]
+    fn lower_maybe_else_once() {
+        let module = lower(
+            "-module(foo).
+-feature(maybe_expr, enable).
+-export([example/1]).
+
+example(File) ->
+    maybe
+        {ok, Fd} ?= open(File),
+        {ok, Term} ?= read(Fd),
+        Term
+    else
+        {error, Reason} ->
+            erlang:display(Reason)
+    end.
+
+open(File) -> {ok, File}.
+
+read(Fd) -> {ok, Fd}.
+",
+        );
+
+        // The else clauses are lowered once, as a local fun applied by each failed match
+        assert_eq!(
+            module.matches("call erlang:display/1").count(),
+            1,
+            "{}",
+            module
+        );
+        assert!(module.contains("letrec"), "{}", module);
+        // And a value matching none of them raises {else_clause, Value}
+        assert!(module.contains("else_clause"), "{}", module);
+    }
+
+    #[test]
+    fn lower_maybe_without_else() {
+        let module = lower(
+            "-module(foo).
+-feature(maybe_expr, enable).
+-export([example/1]).
+
+example(File) ->
+    maybe
+        {ok, Fd} ?= open(File),
+        Fd
+    end.
+
+open(File) -> {ok, File}.
+",
+        );
+
+        // Without else clauses, a value which fails to match is the result of the maybe
+        assert!(!module.contains("letrec"), "{}", module);
+        assert!(!module.contains("else_clause"), "{}", module);
+    }
+
+    #[test]
+    fn lower_map_comprehension() {
+        let module = lower(
+            "-module(foo).
+-export([invert/1]).
+
+invert(Map) ->
+    #{V => K || K := V <- Map}.
+",
+        );
+
+        // The comprehension builds the map from the list of pairs it produces
+        assert!(module.contains("call maps:from_list/1"), "{}", module);
+        // The generator iterates over the pairs of the input map
+        assert!(module.contains("call erlang:is_map/1"), "{}", module);
+        assert!(module.contains("call maps:to_list/1"), "{}", module);
+        // And an input which is not a map raises {bad_generator, Input}
+        assert!(module.contains("bad_generator"), "{}", module);
+        assert!(module.contains("call erlang:error/1"), "{}", module);
+    }
+}
@@ -978,3 +978,56 @@ impl ReadFrom for File {
         })
     }
 }
+
+/// `feature` directive.
+///
+/// Enables or disables an optional language feature for the remainder of the module.
+#[derive(Debug, Clone)]
+pub struct Feature {
+    pub _hyphen: SymbolToken,
+    pub _feature: AtomToken,
+    pub _open_paren: SymbolToken,
+    pub name: AtomToken,
+    pub _comma: SymbolToken,
+    pub action: AtomToken,
+    pub _close_paren: SymbolToken,
+    pub _dot: SymbolToken,
+}
+impl Feature {
+    pub fn span(&self) -> SourceSpan {
+        let start = self._hyphen.0;
+        let end = self._dot.2;
+        SourceSpan::new(start, end)
+    }
+    pub fn name(&self) -> Symbol {
+        self.name.symbol()
+    }
+}
+impl Eq for Feature {}
+impl PartialEq for Feature {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name() && self.action.symbol() == other.action.symbol()
+    }
+}
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "-feature({}, {}).", self.name, self.action)
+    }
+}
+impl ReadFrom for Feature {
+    fn read_from<R, S>(reader: &mut R) -> Result<Self>
+    where
+        R: TokenReader<Source = S>,
+    {
+        Ok(Feature {
+            _hyphen: reader.read_expected(&Token::Minus)?,
+            _feature: reader.read_expected(&symbols::Feature)?,
+            _open_paren: reader.read_expected(&Token::LParen)?,
+            name: reader.read()?,
+            _comma: reader.read_expected(&Token::Comma)?,
+            action: reader.read()?,
+            _close_paren: reader.read_expected(&Token::RParen)?,
+            _dot: reader.read_expected(&Token::Dot)?,
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::convert::TryFrom;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -30,6 +30,7 @@ pub struct Preprocessor<Reader: TokenReader> {
     macros: MacroContainer,
     macro_calls: BTreeMap<SourceIndex, MacroCall>,
     expanded_tokens: VecDeque<LexicalToken>,
+    features: BTreeSet<Symbol>,
     warnings_as_errors: bool,
     no_warn: bool,
 }
@@ -75,6 +76,7 @@ where
             macros,
             macro_calls: BTreeMap::new(),
             expanded_tokens: VecDeque::new(),
+            features: crate::features::enabled_by_default().collect(),
             warnings_as_errors: parser.config.warnings_as_errors,
             no_warn: parser.config.no_warn,
         }
@@ -99,6 +101,7 @@ where
             macros: self.macros.clone(),
             macro_calls: BTreeMap::new(),
             expanded_tokens: VecDeque::new(),
+            features: self.features.clone(),
             warnings_as_errors: self.warnings_as_errors,
             no_warn: self.no_warn,
         }
@@ -111,7 +114,7 @@ where
     fn next_token(&mut self) -> Result<Option<LexicalToken>, ParserError> {
         loop {
             if let Some(token) = self.expanded_tokens.pop_front() {
-                return Ok(Some(token));
+                return Ok(Some(self.apply_features(token)));
             }
             if self.can_directive_start {
                 match self.try_read_directive().map_err(ParserError::from)? {
@@ -150,7 +153,7 @@ where
                 } else {
                     self.can_directive_start = false;
                 }
-                return Ok(Some(token));
+                return Ok(Some(self.apply_features(token)));
             } else {
                 break;
             }
@@ -158,6 +161,29 @@ where
         Ok(None)
     }
 
+    /// The keywords introduced by a feature are plain atoms unless that feature is enabled, and
+    /// likewise its operators are lexed as the separate tokens they consist of
+    fn apply_features(&mut self, token: LexicalToken) -> LexicalToken {
+        if self.features.contains(&symbols::MaybeExpr) {
+            return token;
+        }
+        match token {
+            LexicalToken(start, Token::Maybe, end) => {
+                LexicalToken(start, Token::Atom(symbols::Maybe), end)
+            }
+            LexicalToken(start, Token::Else, end) => {
+                LexicalToken(start, Token::Atom(symbols::Else), end)
+            }
+            LexicalToken(start, Token::QuestionEqual, end) => {
+                let equals = start + ByteOffset(1);
+                self.expanded_tokens
+                    .push_front(LexicalToken(equals, Token::Equals, end));
+                LexicalToken(start, Token::Question, equals)
+            }
+            token => token,
+        }
+    }
+
     fn expand_macro(&mut self, call: MacroCall) -> PResult<VecDeque<LexicalToken>> {
         if let Some(expanded) = self.try_expand_predefined_macro(&call)? {
             Ok(vec![expanded].into())
@@ -170,10 +196,8 @@ where
         let expanded = match call.name().as_str().get() {
             "FILE" => {
                 let span = call.span();
-                let source_id = span.source_id();
                 let current = span.start();
-                let file = self.codemap.get(source_id).unwrap();
-                let filename = file.name().to_string();
+                let filename = self.codemap.name_for_span(span).unwrap().to_string();
                 LexicalToken(
                     current,
                     Token::String(Symbol::intern(&filename)),
@@ -182,10 +206,9 @@ where
             }
             "LINE" => {
                 let span = call.span();
-                let source_id = span.source_id();
                 let current = span.start();
-                let file = self.codemap.get(source_id).unwrap();
-                let line = file.line_index(current.index()).to_usize() as i64;
+                let location = self.codemap.location_for_span(span).unwrap();
+                let line = location.line.number().to_usize() as i64;
                 LexicalToken(current, Token::Integer(line.into()), span.end())
             }
             "MACHINE" => {
@@ -244,7 +267,7 @@ where
                         match arg.tokens.as_slice() {
                             [LexicalToken(_, Token::Atom(feature), _)] => {
                                 match crate::features::get(feature) {
-                                    Some(feat) if feat.enabled => LexicalToken(
+                                    Some(_) if self.features.contains(feature) => LexicalToken(
                                         span.start(),
                                         Token::Atom(symbols::True),
                                         span.end(),
@@ -508,16 +531,57 @@ where
                     });
                 }
             }
+            Directive::Feature(ref d) if !ignore => {
+                let span = d.span();
+                let name = d.name();
+                if crate::features::get(&name).is_none() {
+                    return Err(PreprocessorError::ShowDiagnostic {
+                        diagnostic: Diagnostic::error()
+                            .with_message("unknown feature")
+                            .with_labels(vec![Label::primary(span.source_id(), d.name.span())
+                                .with_message(format!("there is no feature named '{}'", name))]),
+                    });
+                }
+                match d.action.symbol() {
+                    symbols::Enable => {
+                        self.features.insert(name);
+                    }
+                    symbols::Disable => {
+                        self.features.remove(&name);
+                    }
+                    _ => {
+                        return Err(PreprocessorError::ShowDiagnostic {
+                            diagnostic: Diagnostic::error()
+                                .with_message("invalid feature directive")
+                                .with_labels(vec![Label::primary(
+                                    span.source_id(),
+                                    d.action.span(),
+                                )
+                                .with_message("expected either 'enable' or 'disable'")]),
+                        });
+                    }
+                }
+            }
             Directive::File(ref f) if !ignore => {
-                // TODO
+                // Source locations following this directive are remapped to the given file/line
                 let span = f.span();
-                self.reporter.diagnostic(
-                    Diagnostic::warning()
-                        .with_message("-file directive ignored")
-                        .with_labels(vec![Label::primary(span.source_id(), span).with_message(
-                            "support for the -file directive has not been implemented yet",
-                        )]),
-                );
+                let line = f.line.small_integer().and_then(|l| u32::try_from(l).ok());
+                let path = PathBuf::from(f.path.symbol().as_str().get());
+                let remapped = match line {
+                    Some(line) if line > 0 => {
+                        self.codemap.add_line_directive(span, path, line).ok()
+                    }
+                    _ => None,
+                };
+                if remapped.is_none() {
+                    let line_span = f.line.span();
+                    return Err(PreprocessorError::ShowDiagnostic {
+                        diagnostic: Diagnostic::error()
+                            .with_message("invalid -file directive")
+                            .with_labels(vec![Label::primary(line_span.source_id(), line_span)
+                                .with_message("expected a positive line number")]),
+                    });
+                }
             }
             _ => {}
         }
@@ -1175,7 +1175,8 @@ impl<'m> LowerFunctionToSsa<'m> {
                 //
                 // If the timeout was invalid, then the second result is an exception, which should then be raised based on
                 // the current failure context
-                let inst = builder.ins().call(callee, &[], span);
+                let args = self.ssa_values(builder, bif.args)?;
+                let inst = builder.ins().call(callee, args.as_slice(), span);
                 let (is_err, result) = {
                     let results = builder.inst_results(inst);
                     (results[0], results[1])
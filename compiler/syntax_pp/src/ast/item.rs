@@ -1,30 +1,34 @@
-use firefly_diagnostics::{Spanned, SourceSpan};
-use firefly_intern::Ident;
-use firefly_number::{Integer, Float};
+use firefly_binary::BitVec;
+use firefly_diagnostics::{SourceSpan, Spanned};
+use firefly_intern::{symbols, Ident};
+use firefly_number::{Float, Integer};
 
-#[derive(Debug)]
+/// The root of a file in Abstract Format, i.e. a sequence of `.`-terminated terms
+#[derive(Debug, Clone)]
 pub struct Root {
     pub items: Vec<Item>,
 }
 impl Root {
     pub fn span(&self) -> SourceSpan {
-        self.items
-            .first()
-            .map(|i| i.span())
-            .unwrap_or(SourceSpan::UNKNOWN)
+        match (self.items.first(), self.items.last()) {
+            (Some(first), Some(last)) => SourceSpan::new(first.span().start(), last.span().end()),
+            _ => SourceSpan::UNKNOWN,
+        }
     }
 }
 
-#[derive(Debug)]
+/// A single Erlang term
+#[derive(Debug, Clone, Spanned)]
 pub enum Item {
-    Lit(Literal),
-    Attribute(Spanned<Attr>),
-    Function(Spanned<Func>),
-    Tuple(Spanned<Tuple>),
-    List(Spanned<List>),
-    Eof(SourceSpan),
+    Atom(Ident),
+    String(Ident),
+    Int(#[span] SourceSpan, Integer),
+    Float(#[span] SourceSpan, Float),
+    Binary(#[span] SourceSpan, BitVec),
+    Tuple(Tuple),
+    List(List),
+    Map(Map),
 }
-
 impl Item {
     pub fn tuple(&self) -> Option<&Tuple> {
         match self {
@@ -47,16 +51,16 @@ impl Item {
         }
     }
 
-    pub fn integer(&self) -> Option<&Int> {
+    pub fn integer(&self) -> Option<&Integer> {
         match self {
-            Item::Int(inner) => Some(inner),
+            Item::Int(_, inner) => Some(inner),
             _ => None,
         }
     }
 
-    pub fn float(&self) -> Option<&Float> {
+    pub fn float(&self) -> Option<Float> {
         match self {
-            Item::Float(inner) => Some(inner),
+            Item::Float(_, inner) => Some(*inner),
             _ => None,
         }
     }
@@ -68,71 +72,103 @@ impl Item {
         }
     }
 
-    pub fn list_iter(&self) -> Option<impl Iterator<Item = &Item>> {
-        self.list().map(|v| ListIterator {
-            curr: Some(v),
-            idx: 0,
-        })
+    /// Returns true if this term is the empty list, `[]` or `""`
+    pub fn is_nil(&self) -> bool {
+        match self {
+            Item::List(list) => list.head.is_empty() && list.tail.is_none(),
+            Item::String(s) => s.name == symbols::Empty,
+            _ => false,
+        }
     }
 
-    pub fn span(&self) -> SourceSpan {
+    /// Returns true if this term is an atom with the given name
+    pub fn is_atom(&self, name: &str) -> bool {
+        match self {
+            Item::Atom(a) => a.as_str().get() == name,
+            _ => false,
+        }
+    }
+
+    /// Returns the elements of this term if it is a proper list.
+    ///
+    /// Strings are treated as lists of character codes, since the printer will
+    /// render lists of printable integers using string syntax.
+    pub fn as_proper_list(&self) -> Option<Vec<Item>> {
         match self {
-            Item::Atom(ident) => ident.span,
-            Item::String(ident) => ident.span,
-            Item::Int(int) => int.span,
-            Item::Tuple(tup) => tup.span,
-            Item::List(list) => list.span,
-            Item::Float(float) => float.span,
+            Item::String(s) => Some(string_to_chars(s)),
+            Item::List(list) => {
+                let mut elements = list.head.clone();
+                let mut tail = list.tail.as_deref();
+                while let Some(next) = tail.take() {
+                    match next {
+                        Item::List(list) => {
+                            elements.extend(list.head.iter().cloned());
+                            tail = list.tail.as_deref();
+                        }
+                        Item::String(s) => elements.append(&mut string_to_chars(s)),
+                        _ => return None,
+                    }
+                }
+                Some(elements)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the contents of this term as a string, if it is a list of character codes
+    pub fn as_string(&self) -> Option<String> {
+        match self {
+            Item::String(s) => Some(s.as_str().get().to_string()),
+            Item::List(_) => {
+                let mut buf = String::new();
+                for element in self.as_proper_list()? {
+                    buf.push(element.integer()?.to_char()?);
+                }
+                Some(buf)
+            }
+            _ => None,
         }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Literal {
-    Atom(Ident),
-    Bool(Ident),
-    Int(SourceSpan, Integer),
-    Float(SourceSpan, Float),
-    String(Ident),
+fn string_to_chars(s: &Ident) -> Vec<Item> {
+    let span = s.span;
+    s.as_str()
+        .get()
+        .chars()
+        .map(|c| Item::Int(span, Integer::Small(c as i64)))
+        .collect()
 }
 
-#[derive(Debug)]
-pub struct Tuple(Vec<Item>);
+#[derive(Debug, Clone, Spanned)]
+pub struct Tuple {
+    #[span]
+    pub span: SourceSpan,
+    pub elements: Vec<Item>,
+}
+impl Tuple {
+    /// Returns the tag of this tuple, i.e. the first element, if it is an atom
+    pub fn tag(&self) -> Option<Ident> {
+        self.elements.first().and_then(|e| e.atom())
+    }
 
-#[derive(Debug)]
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+}
+
+#[derive(Debug, Clone, Spanned)]
 pub struct List {
+    #[span]
     pub span: SourceSpan,
     pub head: Vec<Item>,
     pub tail: Option<Box<Item>>,
 }
 
-pub struct ListIterator<'a> {
-    curr: Option<&'a List>,
-    idx: usize,
-}
-impl<'a> Iterator for ListIterator<'a> {
-    type Item = &'a Item;
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(list) = self.curr {
-            if let Some(result) = list.heads.get(self.idx) {
-                self.idx += 1;
-                return Some(result);
-            } else {
-                self.idx = 0;
-                if let Some(tail) = &list.tail {
-                    if let Some(tail_list) = tail.list() {
-                        self.curr = Some(tail_list);
-                        return self.next();
-                    } else {
-                        unimplemented!()
-                    }
-                } else {
-                    self.curr = None;
-                    return None;
-                }
-            }
-        } else {
-            None
-        }
-    }
+#[derive(Debug, Clone, Spanned)]
+pub struct Map {
+    #[span]
+    pub span: SourceSpan,
+    pub fields: Vec<(Item, Item)>,
 }
@@ -0,0 +1,125 @@
+//! Lowers the abstract code stored in the debug info of a BEAM file to the AST used by
+//! `firefly_syntax_erl`.
+//!
+//! The forms are decoded from the External Term Format, so they have no textual representation
+//! to derive source spans from; every term is instead given the span provided by the caller,
+//! which is expected to refer to the BEAM file itself.
+use std::sync::Arc;
+
+use firefly_beam::serialization::etf::Term;
+use firefly_beam::AbstractCode;
+use firefly_binary::BitVec;
+use firefly_diagnostics::{CodeMap, Reporter, SourceSpan};
+use firefly_intern::{Ident, Symbol};
+use firefly_number::{Float, Integer};
+use firefly_syntax_erl::Module;
+
+use crate::ast::{self, Item};
+use crate::parser::ParseError;
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+/// Lowers the abstract code of a module, as read from a BEAM file, to a `Module`
+pub fn lower_abstract_code(
+    reporter: &Reporter,
+    codemap: Arc<CodeMap>,
+    span: SourceSpan,
+    code: &AbstractCode,
+) -> Result<Module> {
+    let items = code
+        .forms()
+        .iter()
+        .map(|form| item(form, span))
+        .collect::<Result<Vec<_>>>()?;
+    crate::lower_module(reporter, codemap, &ast::Root { items })
+}
+
+fn item(term: &Term, span: SourceSpan) -> Result<Item> {
+    let items = |terms: &[Term]| {
+        terms
+            .iter()
+            .map(|t| item(t, span))
+            .collect::<Result<Vec<_>>>()
+    };
+    match term {
+        Term::Atom(a) => Ok(Item::Atom(Ident::new(Symbol::intern(&a.name), span))),
+        Term::FixInteger(i) => Ok(Item::Int(span, Integer::Small(i.value as i64))),
+        Term::BigInteger(i) => {
+            let value = Integer::from_string_radix(&i.value.to_string(), 10).unwrap();
+            Ok(Item::Int(span, value))
+        }
+        Term::Float(f) => match Float::new(f.value) {
+            Ok(value) => Ok(Item::Float(span, value)),
+            Err(_) => Err(ParseError::invalid_form(span, "invalid float")),
+        },
+        Term::Binary(b) => {
+            let mut bin = BitVec::new();
+            bin.push_bytes(b.bytes.as_slice());
+            Ok(Item::Binary(span, bin))
+        }
+        Term::BitBinary(b) => {
+            // The trailing bits are stored in the low bits of the last byte
+            let mut bin = BitVec::new();
+            match b.bytes.split_last() {
+                Some((last, bytes)) if b.tail_bits_size > 0 && b.tail_bits_size < 8 => {
+                    let size = b.tail_bits_size;
+                    bin.push_bytes(bytes);
+                    bin.push_bits(&[*last << (8 - size)], size as usize);
+                }
+                _ => bin.push_bytes(b.bytes.as_slice()),
+            }
+            Ok(Item::Binary(span, bin))
+        }
+        Term::List(list) => Ok(Item::List(ast::List {
+            span,
+            head: items(list.elements.as_slice())?,
+            tail: None,
+        })),
+        Term::ImproperList(list) => Ok(Item::List(ast::List {
+            span,
+            head: items(list.elements.as_slice())?,
+            tail: Some(Box::new(item(&list.last, span)?)),
+        })),
+        Term::Tuple(tuple) => Ok(Item::Tuple(ast::Tuple {
+            span,
+            elements: items(tuple.elements.as_slice())?,
+        })),
+        Term::Map(map) => {
+            let fields = map
+                .entries
+                .iter()
+                .map(|(k, v)| Ok((item(k, span)?, item(v, span)?)))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Item::Map(ast::Map { span, fields }))
+        }
+        // Pids, ports, references and funs cannot be written as literals
+        other => Err(ParseError::invalid_form(
+            span,
+            format!("unexpected term in abstract code: {}", other),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use firefly_beam::AbstractCode;
+    use firefly_diagnostics::*;
+
+    #[test]
+    fn lower_beam_debug_info() {
+        let path = "../../library/beam/tests/testdata/reader/test.beam";
+        let code = AbstractCode::from_beam_file(path).unwrap();
+
+        let codemap = Arc::new(CodeMap::new());
+        let id = codemap.add(FileName::Virtual(path.into()), String::new());
+        let span = codemap.get(id).unwrap().source_span();
+
+        let reporter = Reporter::new();
+        let module = super::lower_abstract_code(&reporter, codemap, span, &code).unwrap();
+        assert_eq!(module.name.as_str().get(), "test");
+        assert_eq!(module.exports.len(), 1);
+        assert_eq!(module.functions.len(), 1);
+    }
+}
@@ -1,5 +1,13 @@
+//! This crate provides a parser for Erlang Abstract Format, as printed by the Erlang
+//! compiler when given the `-P`/`+to_pp` options (`.abstr` files), and lowers it to the
+//! AST provided by `firefly_syntax_erl`. The abstract code found in the debug info of BEAM
+//! files is lowered the same way.
 mod ast;
+mod beam;
+mod lower;
 mod parser;
 
 pub use self::ast::*;
+pub use self::beam::lower_abstract_code;
+pub use self::lower::lower_module;
 pub use self::parser::*;
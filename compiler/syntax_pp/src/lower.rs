@@ -0,0 +1,1169 @@
+//! Lowers Erlang Abstract Format, as produced by `epp`/`erl_parse`, to the AST used by `firefly_syntax_erl`.
+//!
+//! The Abstract Format is documented [here](https://www.erlang.org/doc/apps/erts/absform.html).
+//! Annotations (line/column information) contained in the forms are ignored, source spans are instead
+//! derived from the location of each term in the file being lowered.
+use std::sync::Arc;
+
+use firefly_binary::BinaryEntrySpecifier;
+use firefly_diagnostics::{CodeMap, Reporter, SourceSpan, Span, Spanned};
+use firefly_intern::{symbols, Ident, Symbol};
+use firefly_number::Integer;
+use firefly_syntax_base::{BinaryOp, DeprecatedFlag, Deprecation, FunctionName, UnaryOp};
+use firefly_syntax_erl::binary::specifier_from_parsed;
+use firefly_syntax_erl::{
+    After, Arity, Attribute, Begin, Binary, BinaryComprehension, BinaryElement, BinaryExpr,
+    BitType, Callback, Case, Catch, Clause, Cons, Expr, Fun, Function, FunctionVar, Generator,
+    GeneratorType, Guard, If, ListComprehension, Literal, Map, MapField, MapUpdate, Match, Module,
+    Name, Receive, Record, RecordAccess, RecordField, RecordIndex, RecordUpdate, Remote, TopLevel,
+    Try, Tuple, Type, TypeDef, TypeGuard, TypeSig, TypeSpec, UnaryExpr, UserAttribute, Var,
+};
+
+use crate::ast::{self, Item};
+use crate::parser::ParseError;
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+/// Lowers a file containing forms in Abstract Format to a `Module`
+pub fn lower_module(
+    reporter: &Reporter,
+    codemap: Arc<CodeMap>,
+    root: &ast::Root,
+) -> Result<Module> {
+    let span = root.span();
+    let lower = Lower { reporter };
+    let mut forms = Vec::with_capacity(root.items.len());
+    for item in root.items.iter() {
+        if let Some(form) = lower.form(item)? {
+            forms.push(form);
+        }
+    }
+    Module::new_from_pp(reporter, codemap, span, forms)
+        .map_err(|err| ParseError::invalid_form(span, err.to_string()))
+}
+
+struct Lower<'a> {
+    reporter: &'a Reporter,
+}
+impl<'a> Lower<'a> {
+    fn form(&self, item: &Item) -> Result<Option<TopLevel>> {
+        let (tag, span, e) = tagged(item, "form")?;
+        match (tag.as_str().get(), e.len()) {
+            ("attribute", 4) => self.attribute(span, &e[2], &e[3]),
+            ("function", 5) => {
+                let name = atom(&e[2])?;
+                let clauses = list(&e[4])?;
+                if clauses.is_empty() {
+                    return Err(ParseError::invalid_form(
+                        span,
+                        "expected function to have at least one clause",
+                    ));
+                }
+                let clauses = clauses
+                    .iter()
+                    .map(|c| self.clause(c).map(|c| (Some(Name::Atom(name)), c)))
+                    .collect::<Result<Vec<_>>>()?;
+                let fun = Function::new(self.reporter, span, clauses)?;
+                if fun.arity as usize != arity(&e[3])? as usize {
+                    return Err(ParseError::invalid_form(
+                        span,
+                        "function arity does not match its clauses",
+                    ));
+                }
+                Ok(Some(TopLevel::Function(fun)))
+            }
+            ("error", 2) => Err(ParseError::invalid_form(
+                span,
+                "the abstract code contains an error form",
+            )),
+            ("warning", 2) | ("eof", 2) => Ok(None),
+            _ => Err(ParseError::invalid_form(span, "unrecognized form")),
+        }
+    }
+
+    fn attribute(&self, span: SourceSpan, name: &Item, value: &Item) -> Result<Option<TopLevel>> {
+        let name = atom(name)?;
+        let attr = match name.as_str().get() {
+            "module" => match value {
+                Item::Tuple(t) if t.len() == 2 => {
+                    return Ok(Some(TopLevel::Module(atom(&t.elements[0])?)))
+                }
+                _ => return Ok(Some(TopLevel::Module(atom(value)?))),
+            },
+            // File/line information is ignored, as spans refer to the abstract code itself
+            "file" => return Ok(None),
+            "record" => {
+                let (_, e) = tuple(value, 2)?;
+                let name = atom(&e[0])?;
+                let fields = list(&e[1])?
+                    .iter()
+                    .map(|f| self.record_field_def(f))
+                    .collect::<Result<Vec<_>>>()?;
+                return Ok(Some(TopLevel::Record(Record {
+                    span,
+                    name,
+                    fields,
+                    default: None,
+                })));
+            }
+            "export" => Attribute::Export(span, self.function_names(value)?),
+            "export_type" => Attribute::ExportType(span, self.function_names(value)?),
+            "nifs" => Attribute::Nifs(span, self.function_names(value)?),
+            "import" => {
+                let (_, e) = tuple(value, 2)?;
+                Attribute::Import(span, atom(&e[0])?, self.function_names(&e[1])?)
+            }
+            "on_load" => Attribute::OnLoad(span, self.function_name(value)?),
+            "behaviour" | "behavior" => Attribute::Behaviour(span, atom(value)?),
+            "vsn" => Attribute::Vsn(span, term(value)),
+            "author" => Attribute::Author(span, term(value)),
+            "compile" => Attribute::Compile(span, term(value)),
+            "type" | "opaque" => {
+                let (_, e) = tuple(value, 3)?;
+                let params = list(&e[2])?
+                    .iter()
+                    .map(|p| self.var(p).map(Name::Var))
+                    .collect::<Result<Vec<_>>>()?;
+                Attribute::Type(TypeDef {
+                    span,
+                    opaque: name.name == symbols::Opaque,
+                    name: atom(&e[0])?,
+                    params,
+                    ty: self.ty(&e[1])?,
+                })
+            }
+            "spec" | "callback" => {
+                let (_, e) = tuple(value, 2)?;
+                let (_, mfa) = tuple_any(&e[0])?;
+                let (module, function) = match mfa.len() {
+                    2 => (None, atom(&mfa[0])?),
+                    3 => (Some(atom(&mfa[0])?), atom(&mfa[1])?),
+                    _ => {
+                        return Err(ParseError::invalid_form(
+                            e[0].span(),
+                            "expected {Name, Arity} or {Module, Name, Arity}",
+                        ))
+                    }
+                };
+                let sigs = list(&e[1])?
+                    .iter()
+                    .map(|sig| self.type_sig(sig))
+                    .collect::<Result<Vec<_>>>()?;
+                if name.as_str().get() == "spec" {
+                    Attribute::Spec(TypeSpec {
+                        span,
+                        module,
+                        function,
+                        sigs,
+                    })
+                } else {
+                    Attribute::Callback(Callback {
+                        span,
+                        optional: false,
+                        module,
+                        function,
+                        sigs,
+                    })
+                }
+            }
+            "deprecated" => {
+                let deprecations = match value.as_proper_list() {
+                    Some(items) => items
+                        .iter()
+                        .map(|d| self.deprecation(d))
+                        .collect::<Result<Vec<_>>>()?,
+                    None => vec![self.deprecation(value)?],
+                };
+                Attribute::Deprecation(deprecations)
+            }
+            "removed" => {
+                let removed = match value.as_proper_list() {
+                    Some(items) => items,
+                    None => vec![value.clone()],
+                };
+                let removed = removed
+                    .iter()
+                    .map(|r| {
+                        let (span, e) = tuple(r, 3)?;
+                        let name = FunctionName::new_local(atom(&e[0])?.name, arity(&e[1])?);
+                        let descr = string(&e[2])?;
+                        Ok((Span::new(span, name), descr))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Attribute::Removed(span, removed)
+            }
+            _ => Attribute::Custom(UserAttribute {
+                span,
+                name,
+                value: term(value),
+            }),
+        };
+        Ok(Some(TopLevel::Attribute(attr)))
+    }
+
+    fn function_names(&self, item: &Item) -> Result<Vec<Span<FunctionName>>> {
+        list(item)?.iter().map(|f| self.function_name(f)).collect()
+    }
+
+    fn function_name(&self, item: &Item) -> Result<Span<FunctionName>> {
+        let (span, e) = tuple(item, 2)?;
+        let name = FunctionName::new_local(atom(&e[0])?.name, arity(&e[1])?);
+        Ok(Span::new(span, name))
+    }
+
+    fn deprecation(&self, item: &Item) -> Result<Deprecation> {
+        let span = item.span();
+        if item.is_atom("module") {
+            return Ok(Deprecation::Module {
+                span,
+                flag: DeprecatedFlag::Eventually,
+            });
+        }
+        let (_, e) = tuple_any(item)?;
+        let (function, flag) = match e.len() {
+            2 => (&e[0..2], None),
+            3 => (&e[0..2], Some(&e[2])),
+            _ => {
+                return Err(ParseError::invalid_form(
+                    span,
+                    "expected {Name, Arity} or {Name, Arity, Flag}",
+                ))
+            }
+        };
+        let flag = match flag {
+            None => DeprecatedFlag::Eventually,
+            Some(Item::Atom(a)) => match a.as_str().get() {
+                "eventually" => DeprecatedFlag::Eventually,
+                "next_version" => DeprecatedFlag::NextVersion,
+                "next_major_release" => DeprecatedFlag::NextMajorRelease,
+                _ => {
+                    return Err(ParseError::invalid_form(
+                        a.span,
+                        "expected one of 'eventually', 'next_version', or 'next_major_release'",
+                    ))
+                }
+            },
+            Some(descr) => DeprecatedFlag::Description(string(descr)?),
+        };
+        if function[0].is_atom("_") && function[1].is_atom("_") {
+            return Ok(Deprecation::Module { span, flag });
+        }
+        let name = FunctionName::new_local(atom(&function[0])?.name, arity(&function[1])?);
+        Ok(Deprecation::Function {
+            span,
+            function: Span::new(span, name),
+            flag,
+        })
+    }
+
+    fn record_field_def(&self, item: &Item) -> Result<RecordField> {
+        let (tag, span, e) = tagged(item, "record field")?;
+        match (tag.as_str().get(), e.len()) {
+            ("record_field", 3) | ("record_field", 4) => Ok(RecordField {
+                span,
+                name: self.atom_lit(&e[2])?,
+                value: e.get(3).map(|v| self.expr(v)).transpose()?,
+                ty: None,
+                is_default: false,
+            }),
+            ("typed_record_field", 3) => {
+                let mut field = self.record_field_def(&e[1])?;
+                field.span = span;
+                field.ty = Some(self.ty(&e[2])?);
+                Ok(field)
+            }
+            _ => Err(ParseError::invalid_form(span, "invalid record field")),
+        }
+    }
+
+    fn clauses(&self, item: &Item) -> Result<Vec<Clause>> {
+        list(item)?.iter().map(|c| self.clause(c)).collect()
+    }
+
+    fn clause(&self, item: &Item) -> Result<Clause> {
+        let (span, e) = tagged_n(item, "clause", 5)?;
+        let patterns = self.exprs(&e[2])?;
+        let guards = self.guards(&e[3])?;
+        let body = self.exprs(&e[4])?;
+        Ok(Clause::new(span, patterns, guards, body, false))
+    }
+
+    fn if_clause(&self, item: &Item) -> Result<Clause> {
+        let (span, e) = tagged_n(item, "clause", 5)?;
+        let guards = self.guards(&e[3])?;
+        let body = self.exprs(&e[4])?;
+        Ok(Clause::for_if(span, guards, body, false))
+    }
+
+    fn catch_clause(&self, item: &Item) -> Result<Clause> {
+        let (span, e) = tagged_n(item, "clause", 5)?;
+        let patterns = list(&e[2])?;
+        let (kind, error, trace) = match patterns.as_slice() {
+            [pattern] => {
+                let (_, e) = tagged_n(pattern, "tuple", 3)?;
+                match list(&e[2])?.as_slice() {
+                    [kind, error, trace] => {
+                        (self.expr(kind)?, self.expr(error)?, self.expr(trace)?)
+                    }
+                    _ => {
+                        return Err(ParseError::invalid_form(
+                            pattern.span(),
+                            "expected catch pattern of the form {Class, Reason, Stacktrace}",
+                        ))
+                    }
+                }
+            }
+            _ => {
+                return Err(ParseError::invalid_form(
+                    span,
+                    "expected catch clause to have exactly one pattern",
+                ))
+            }
+        };
+        let guards = self.guards(&e[3])?;
+        let body = self.exprs(&e[4])?;
+        Ok(Clause::for_catch(
+            span,
+            kind,
+            error,
+            Some(trace),
+            guards,
+            body,
+        ))
+    }
+
+    fn guards(&self, item: &Item) -> Result<Vec<Guard>> {
+        list(item)?
+            .iter()
+            .map(|g| {
+                Ok(Guard {
+                    span: g.span(),
+                    conditions: self.exprs(g)?,
+                })
+            })
+            .collect()
+    }
+
+    fn exprs(&self, item: &Item) -> Result<Vec<Expr>> {
+        list(item)?.iter().map(|e| self.expr(e)).collect()
+    }
+
+    fn boxed(&self, item: &Item) -> Result<Box<Expr>> {
+        self.expr(item).map(Box::new)
+    }
+
+    /// Lowers an expression or pattern, as both share the same representation
+    fn expr(&self, item: &Item) -> Result<Expr> {
+        let (tag, span, e) = tagged(item, "expression")?;
+        let expr = match (tag.as_str().get(), e.len()) {
+            ("var", 3) => Expr::Var(Var(self.var(item)?)),
+            ("atom", 3) => Expr::Literal(Literal::Atom(self.atom_lit(item)?)),
+            ("integer", 3) => Expr::Literal(Literal::Integer(span, integer(&e[2])?.clone())),
+            ("char", 3) => {
+                let c = integer(&e[2])?.to_char().ok_or_else(|| {
+                    ParseError::invalid_form(e[2].span(), "invalid character code")
+                })?;
+                Expr::Literal(Literal::Char(span, c))
+            }
+            ("float", 3) => match &e[2] {
+                Item::Float(_, f) => Expr::Literal(Literal::Float(span, *f)),
+                other => return Err(ParseError::invalid_form(other.span(), "expected float")),
+            },
+            ("string", 3) => {
+                let s = string(&e[2])?;
+                Expr::Literal(Literal::String(Ident::new(s.name, span)))
+            }
+            ("nil", 2) => Expr::Literal(Literal::Nil(span)),
+            ("cons", 4) => Expr::Cons(Cons {
+                span,
+                head: self.boxed(&e[2])?,
+                tail: self.boxed(&e[3])?,
+            }),
+            ("tuple", 3) => Expr::Tuple(Tuple {
+                span,
+                elements: self.exprs(&e[2])?,
+            }),
+            ("bin", 3) => Expr::Binary(Binary {
+                span,
+                elements: list(&e[2])?
+                    .iter()
+                    .map(|be| self.bin_element(be))
+                    .collect::<Result<Vec<_>>>()?,
+            }),
+            ("map", 3) => Expr::Map(Map {
+                span,
+                fields: self.map_fields(&e[2])?,
+            }),
+            ("map", 4) => Expr::MapUpdate(MapUpdate {
+                span,
+                map: self.boxed(&e[2])?,
+                updates: self.map_fields(&e[3])?,
+            }),
+            ("record", 4) => {
+                let mut fields = self.record_fields(&e[3])?;
+                let default = match fields.iter().position(|f| f.is_default) {
+                    None => None,
+                    Some(pos) => fields.remove(pos).value.map(Box::new),
+                };
+                Expr::Record(Record {
+                    span,
+                    name: atom(&e[2])?,
+                    fields,
+                    default,
+                })
+            }
+            ("record", 5) => Expr::RecordUpdate(RecordUpdate {
+                span,
+                record: self.boxed(&e[2])?,
+                name: atom(&e[3])?,
+                updates: self.record_fields(&e[4])?,
+            }),
+            ("record_field", 5) => Expr::RecordAccess(RecordAccess {
+                span,
+                record: self.boxed(&e[2])?,
+                name: atom(&e[3])?,
+                field: self.atom_lit(&e[4])?,
+            }),
+            ("record_index", 4) => Expr::RecordIndex(RecordIndex {
+                span,
+                name: atom(&e[2])?,
+                field: self.atom_lit(&e[3])?,
+            }),
+            ("match", 4) => Expr::Match(Match {
+                span,
+                pattern: self.boxed(&e[2])?,
+                expr: self.boxed(&e[3])?,
+            }),
+            ("op", 5) => Expr::BinaryExpr(BinaryExpr {
+                span,
+                op: binary_op(&e[2])?,
+                lhs: self.boxed(&e[3])?,
+                rhs: self.boxed(&e[4])?,
+            }),
+            ("op", 4) => Expr::UnaryExpr(UnaryExpr {
+                span,
+                op: unary_op(&e[2])?,
+                operand: self.boxed(&e[3])?,
+            }),
+            ("call", 4) => {
+                let args = self.exprs(&e[3])?;
+                let callee = match tagged_n(&e[2], "remote", 4) {
+                    Ok((remote_span, r)) => Expr::Remote(Remote {
+                        span: remote_span,
+                        module: self.boxed(&r[2])?,
+                        function: self.boxed(&r[3])?,
+                    }),
+                    Err(_) => self.expr(&e[2])?,
+                };
+                Expr::try_resolve_apply(span, callee, args)
+            }
+            ("lc", 4) => Expr::ListComprehension(ListComprehension {
+                span,
+                body: self.boxed(&e[2])?,
+                qualifiers: self.exprs(&e[3])?,
+            }),
+            ("bc", 4) => Expr::BinaryComprehension(BinaryComprehension {
+                span,
+                body: self.boxed(&e[2])?,
+                qualifiers: self.exprs(&e[3])?,
+            }),
+            ("generate", 4) | ("b_generate", 4) => Expr::Generator(Generator {
+                span,
+                ty: if tag.as_str().get() == "generate" {
+                    GeneratorType::Default
+                } else {
+                    GeneratorType::Bitstring
+                },
+                pattern: self.boxed(&e[2])?,
+                expr: self.boxed(&e[3])?,
+            }),
+            ("block", 3) => Expr::Begin(Begin {
+                span,
+                body: self.exprs(&e[2])?,
+            }),
+            ("if", 3) => Expr::If(If {
+                span,
+                clauses: list(&e[2])?
+                    .iter()
+                    .map(|c| self.if_clause(c))
+                    .collect::<Result<Vec<_>>>()?,
+            }),
+            ("case", 4) => Expr::Case(Case {
+                span,
+                expr: self.boxed(&e[2])?,
+                clauses: self.clauses(&e[3])?,
+            }),
+            ("receive", 3) => Expr::Receive(Receive {
+                span,
+                clauses: Some(self.clauses(&e[2])?),
+                after: None,
+            }),
+            ("receive", 5) => {
+                let clauses = self.clauses(&e[2])?;
+                Expr::Receive(Receive {
+                    span,
+                    clauses: if clauses.is_empty() {
+                        None
+                    } else {
+                        Some(clauses)
+                    },
+                    after: Some(After {
+                        span: SourceSpan::new(e[3].span().start(), e[4].span().end()),
+                        timeout: self.boxed(&e[3])?,
+                        body: self.exprs(&e[4])?,
+                    }),
+                })
+            }
+            ("try", 6) => {
+                let clauses = self.clauses(&e[3])?;
+                let catch_clauses = list(&e[4])?
+                    .iter()
+                    .map(|c| self.catch_clause(c))
+                    .collect::<Result<Vec<_>>>()?;
+                let after = self.exprs(&e[5])?;
+                Expr::Try(Try {
+                    span,
+                    exprs: self.exprs(&e[2])?,
+                    clauses: if clauses.is_empty() {
+                        None
+                    } else {
+                        Some(clauses)
+                    },
+                    catch_clauses: if catch_clauses.is_empty() {
+                        None
+                    } else {
+                        Some(catch_clauses)
+                    },
+                    after: if after.is_empty() { None } else { Some(after) },
+                })
+            }
+            ("catch", 3) => Expr::Catch(Catch {
+                span,
+                expr: self.boxed(&e[2])?,
+            }),
+            ("fun", 3) => self.fun(span, &e[2])?,
+            ("named_fun", 4) => {
+                let name = Name::Var(atom(&e[2])?);
+                let clauses = self
+                    .clauses(&e[3])?
+                    .drain(..)
+                    .map(|c| (Some(name), c))
+                    .collect::<Vec<_>>();
+                self.fun_from_clauses(span, clauses)?
+            }
+            _ => return Err(ParseError::invalid_form(span, "unrecognized expression")),
+        };
+        Ok(expr)
+    }
+
+    fn fun(&self, span: SourceSpan, item: &Item) -> Result<Expr> {
+        let (tag, _, e) = tagged(item, "fun")?;
+        match (tag.as_str().get(), e.len()) {
+            ("function", 3) => {
+                let name = FunctionName::new_local(atom(&e[1])?.name, arity(&e[2])?);
+                Ok(Expr::FunctionVar(FunctionVar::PartiallyResolved(
+                    Span::new(span, name),
+                )))
+            }
+            ("function", 4) => {
+                let module = self.name(&e[1])?;
+                let function = self.name(&e[2])?;
+                let arity = match &e[3] {
+                    Item::Int(_, _) => Arity::Int(arity(&e[3])?),
+                    other => match self.expr(other)? {
+                        Expr::Literal(Literal::Integer(_, i)) => {
+                            Arity::Int(i.to_usize().and_then(|i| i.try_into().ok()).ok_or_else(
+                                || ParseError::invalid_form(other.span(), "invalid arity"),
+                            )?)
+                        }
+                        Expr::Var(Var(v)) => Arity::Var(v),
+                        _ => return Err(ParseError::invalid_form(other.span(), "invalid arity")),
+                    },
+                };
+                Ok(Expr::FunctionVar(FunctionVar::detect(
+                    span,
+                    Some(module),
+                    function,
+                    arity,
+                )))
+            }
+            ("clauses", 2) => {
+                let clauses = self
+                    .clauses(&e[1])?
+                    .drain(..)
+                    .map(|c| (None, c))
+                    .collect::<Vec<_>>();
+                self.fun_from_clauses(span, clauses)
+            }
+            _ => Err(ParseError::invalid_form(
+                span,
+                "unrecognized fun expression",
+            )),
+        }
+    }
+
+    fn fun_from_clauses(
+        &self,
+        span: SourceSpan,
+        clauses: Vec<(Option<Name>, Clause)>,
+    ) -> Result<Expr> {
+        if clauses.is_empty() {
+            return Err(ParseError::invalid_form(
+                span,
+                "expected fun to have at least one clause",
+            ));
+        }
+        Ok(Expr::Fun(Fun::new(self.reporter, span, clauses)?))
+    }
+
+    /// Handles the module/function components of `fun M:F/A`, which are either
+    /// bare atoms (prior to OTP R15), or `atom`/`var` expressions.
+    fn name(&self, item: &Item) -> Result<Name> {
+        match item {
+            Item::Atom(a) => Ok(Name::Atom(*a)),
+            other => match self.expr(other)? {
+                Expr::Literal(Literal::Atom(a)) => Ok(Name::Atom(a)),
+                Expr::Var(Var(v)) => Ok(Name::Var(v)),
+                _ => Err(ParseError::invalid_form(
+                    other.span(),
+                    "expected an atom or variable",
+                )),
+            },
+        }
+    }
+
+    fn map_fields(&self, item: &Item) -> Result<Vec<MapField>> {
+        list(item)?
+            .iter()
+            .map(|field| {
+                let (tag, span, e) = tagged(field, "map field")?;
+                match (tag.as_str().get(), e.len()) {
+                    ("map_field_assoc", 4) => Ok(MapField::Assoc {
+                        span,
+                        key: self.expr(&e[2])?,
+                        value: self.expr(&e[3])?,
+                    }),
+                    ("map_field_exact", 4) => Ok(MapField::Exact {
+                        span,
+                        key: self.expr(&e[2])?,
+                        value: self.expr(&e[3])?,
+                    }),
+                    _ => Err(ParseError::invalid_form(span, "invalid map field")),
+                }
+            })
+            .collect()
+    }
+
+    fn record_fields(&self, item: &Item) -> Result<Vec<RecordField>> {
+        list(item)?
+            .iter()
+            .map(|field| {
+                let (span, e) = tagged_n(field, "record_field", 4)?;
+                let (is_default, name) = match tagged(&e[2], "record field name")? {
+                    (tag, _, n) if tag.as_str().get() == "var" && n.len() == 3 => {
+                        let var = self.var(&e[2])?;
+                        if var.name != symbols::Underscore {
+                            return Err(ParseError::invalid_form(
+                                var.span,
+                                "expected an atom, or '_'",
+                            ));
+                        }
+                        (true, var)
+                    }
+                    _ => (false, self.atom_lit(&e[2])?),
+                };
+                Ok(RecordField {
+                    span,
+                    name,
+                    value: Some(self.expr(&e[3])?),
+                    ty: None,
+                    is_default,
+                })
+            })
+            .collect()
+    }
+
+    fn bin_element(&self, item: &Item) -> Result<BinaryElement> {
+        let (span, e) = tagged_n(item, "bin_element", 5)?;
+        let bit_expr = self.expr(&e[2])?;
+        let bit_size = if e[3].is_atom("default") {
+            None
+        } else {
+            Some(self.expr(&e[3])?)
+        };
+        let specifier = if e[4].is_atom("default") {
+            None
+        } else {
+            let types = list(&e[4])?
+                .iter()
+                .map(|ty| match ty {
+                    Item::Atom(name) => Ok(BitType::Name(name.span, *name)),
+                    other => {
+                        let (span, e) = tuple(other, 2)?;
+                        let size = integer(&e[1])?.to_usize().ok_or_else(|| {
+                            ParseError::invalid_form(e[1].span(), "invalid type specifier size")
+                        })?;
+                        Ok(BitType::Sized(span, atom(&e[0])?, size))
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+            match specifier_from_parsed(&types, bit_size.is_some()) {
+                Ok(spec) => Some(spec),
+                Err(err) => {
+                    self.reporter.error(err);
+                    Some(BinaryEntrySpecifier::default())
+                }
+            }
+        };
+        Ok(BinaryElement {
+            span,
+            bit_expr,
+            bit_size,
+            specifier,
+        })
+    }
+
+    fn type_sig(&self, item: &Item) -> Result<TypeSig> {
+        let (tag, span, e) = tagged(item, "type signature")?;
+        let name = e.get(2).and_then(|n| n.atom());
+        match (
+            tag.as_str().get(),
+            name.as_ref().map(|n| n.as_str().get()),
+            e.len(),
+        ) {
+            ("type", Some("fun"), 4) => match list(&e[3])?.as_slice() {
+                [params, ret] => {
+                    let (_, p) = tagged_n(params, "type", 4)?;
+                    let params = list(&p[3])?
+                        .iter()
+                        .map(|t| self.ty(t))
+                        .collect::<Result<Vec<_>>>()?;
+                    Ok(TypeSig {
+                        span,
+                        params,
+                        ret: Box::new(self.ty(ret)?),
+                        guards: None,
+                    })
+                }
+                _ => Err(ParseError::invalid_form(span, "invalid function type")),
+            },
+            ("type", Some("bounded_fun"), 4) => match list(&e[3])?.as_slice() {
+                [fun, constraints] => {
+                    let mut sig = self.type_sig(fun)?;
+                    let guards = list(constraints)?
+                        .iter()
+                        .map(|c| self.type_guard(c))
+                        .collect::<Result<Vec<_>>>()?;
+                    sig.span = span;
+                    sig.guards = Some(guards);
+                    Ok(sig)
+                }
+                _ => Err(ParseError::invalid_form(
+                    span,
+                    "invalid bounded function type",
+                )),
+            },
+            _ => Err(ParseError::invalid_form(span, "expected function type")),
+        }
+    }
+
+    fn type_guard(&self, item: &Item) -> Result<TypeGuard> {
+        let (span, e) = tagged_n(item, "type", 4)?;
+        match list(&e[3])?.as_slice() {
+            [_is_subtype, args] if e[2].is_atom("constraint") => match list(args)?.as_slice() {
+                [var, ty] => Ok(TypeGuard {
+                    span,
+                    var: Name::Var(self.var(var)?),
+                    ty: self.ty(ty)?,
+                }),
+                _ => Err(ParseError::invalid_form(span, "invalid type constraint")),
+            },
+            _ => Err(ParseError::invalid_form(span, "invalid type constraint")),
+        }
+    }
+
+    fn types(&self, item: &Item) -> Result<Vec<Type>> {
+        list(item)?.iter().map(|t| self.ty(t)).collect()
+    }
+
+    fn boxed_ty(&self, item: &Item) -> Result<Box<Type>> {
+        self.ty(item).map(Box::new)
+    }
+
+    fn ty(&self, item: &Item) -> Result<Type> {
+        let (tag, span, e) = tagged(item, "type")?;
+        let ty = match (tag.as_str().get(), e.len()) {
+            ("ann_type", 3) => match list(&e[2])?.as_slice() {
+                [var, ty] => Type::Annotated {
+                    span,
+                    name: Name::Var(self.var(var)?),
+                    ty: self.boxed_ty(ty)?,
+                },
+                _ => return Err(ParseError::invalid_form(span, "invalid annotated type")),
+            },
+            ("atom", 3) => Type::Name(Name::Atom(self.atom_lit(item)?)),
+            ("var", 3) => Type::Name(Name::Var(self.var(item)?)),
+            ("integer", 3) => Type::Integer(span, integer(&e[2])?.clone()),
+            ("char", 3) => {
+                let c = integer(&e[2])?.to_char().ok_or_else(|| {
+                    ParseError::invalid_form(e[2].span(), "invalid character code")
+                })?;
+                Type::Char(span, c)
+            }
+            ("op", 5) => Type::BinaryOp {
+                span,
+                op: binary_op(&e[2])?,
+                lhs: self.boxed_ty(&e[3])?,
+                rhs: self.boxed_ty(&e[4])?,
+            },
+            ("op", 4) => Type::UnaryOp {
+                span,
+                op: unary_op(&e[2])?,
+                rhs: self.boxed_ty(&e[3])?,
+            },
+            ("remote_type", 3) => match list(&e[2])?.as_slice() {
+                [module, fun, args] => Type::Remote {
+                    span,
+                    module: self.atom_lit(module)?,
+                    fun: self.atom_lit(fun)?,
+                    args: self.types(args)?,
+                },
+                _ => return Err(ParseError::invalid_form(span, "invalid remote type")),
+            },
+            ("user_type", 4) => Type::Generic {
+                span,
+                fun: atom(&e[2])?,
+                params: self.types(&e[3])?,
+            },
+            ("type", 4) => {
+                let name = atom(&e[2])?;
+                let args = &e[3];
+                match name.as_str().get() {
+                    "nil" => Type::Nil(span),
+                    "binary" => match list(args)?.as_slice() {
+                        [m, n] => Type::Binary(span, self.boxed_ty(m)?, self.boxed_ty(n)?),
+                        _ => return Err(ParseError::invalid_form(span, "invalid binary type")),
+                    },
+                    "range" => match list(args)?.as_slice() {
+                        [start, end] => Type::Range {
+                            span,
+                            start: self.boxed_ty(start)?,
+                            end: self.boxed_ty(end)?,
+                        },
+                        _ => return Err(ParseError::invalid_form(span, "invalid range type")),
+                    },
+                    "union" => Type::Union {
+                        span,
+                        types: self.types(args)?,
+                    },
+                    "fun" => match list(args)?.as_slice() {
+                        [] => Type::AnyFun { span, ret: None },
+                        [params, ret] if is_type(params, "any") => Type::AnyFun {
+                            span,
+                            ret: Some(self.boxed_ty(ret)?),
+                        },
+                        [_, _] => {
+                            let sig = self.type_sig(item)?;
+                            Type::Fun {
+                                span,
+                                params: sig.params,
+                                ret: sig.ret,
+                            }
+                        }
+                        _ => return Err(ParseError::invalid_form(span, "invalid fun type")),
+                    },
+                    "tuple" if !args.is_atom("any") => Type::Tuple(span, self.types(args)?),
+                    "map" if !args.is_atom("any") => {
+                        let fields = list(args)?
+                            .iter()
+                            .map(|field| {
+                                let (span, f) = tagged_n(field, "type", 4)?;
+                                match list(&f[3])?.as_slice() {
+                                    [k, v] => Ok(Type::KeyValuePair(
+                                        span,
+                                        self.boxed_ty(k)?,
+                                        self.boxed_ty(v)?,
+                                    )),
+                                    _ => Err(ParseError::invalid_form(
+                                        span,
+                                        "invalid map field type",
+                                    )),
+                                }
+                            })
+                            .collect::<Result<Vec<_>>>()?;
+                        Type::Map(span, fields)
+                    }
+                    "record" => match list(args)?.split_first() {
+                        Some((name, fields)) => {
+                            let fields = fields
+                                .iter()
+                                .map(|field| {
+                                    let (span, f) = tagged_n(field, "type", 4)?;
+                                    match list(&f[3])?.as_slice() {
+                                        [name, ty] => Ok(Type::Field(
+                                            span,
+                                            self.atom_lit(name)?,
+                                            self.boxed_ty(ty)?,
+                                        )),
+                                        _ => Err(ParseError::invalid_form(
+                                            span,
+                                            "invalid record field type",
+                                        )),
+                                    }
+                                })
+                                .collect::<Result<Vec<_>>>()?;
+                            Type::Record(span, self.atom_lit(name)?, fields)
+                        }
+                        None => return Err(ParseError::invalid_form(span, "invalid record type")),
+                    },
+                    _ if args.is_atom("any") => Type::Generic {
+                        span,
+                        fun: name,
+                        params: vec![],
+                    },
+                    _ => Type::Generic {
+                        span,
+                        fun: name,
+                        params: self.types(args)?,
+                    },
+                }
+            }
+            _ => return Err(ParseError::invalid_form(span, "unrecognized type")),
+        };
+        Ok(ty)
+    }
+
+    /// Extracts the name from `{var, Anno, Name}`
+    fn var(&self, item: &Item) -> Result<Ident> {
+        let (span, e) = tagged_n(item, "var", 3)?;
+        Ok(Ident::new(atom(&e[2])?.name, span))
+    }
+
+    /// Extracts the value from `{atom, Anno, Value}`
+    fn atom_lit(&self, item: &Item) -> Result<Ident> {
+        let (span, e) = tagged_n(item, "atom", 3)?;
+        Ok(Ident::new(atom(&e[2])?.name, span))
+    }
+}
+
+/// Converts a literal term to its equivalent expression, as used for attribute values
+fn term(item: &Item) -> Expr {
+    match item {
+        Item::Atom(a) => Expr::Literal(Literal::Atom(*a)),
+        Item::String(s) => Expr::Literal(Literal::String(*s)),
+        Item::Int(span, i) => Expr::Literal(Literal::Integer(*span, i.clone())),
+        Item::Float(span, f) => Expr::Literal(Literal::Float(*span, *f)),
+        Item::Binary(span, bin) => Expr::Literal(Literal::Binary(*span, bin.clone())),
+        Item::Tuple(t) => Expr::Tuple(Tuple {
+            span: t.span,
+            elements: t.elements.iter().map(term).collect(),
+        }),
+        Item::List(l) => {
+            let tail = match l.tail.as_deref() {
+                None => Expr::Literal(Literal::Nil(l.span)),
+                Some(tail) => term(tail),
+            };
+            l.head.iter().rev().fold(tail, |tail, head| {
+                Expr::Cons(Cons {
+                    span: l.span,
+                    head: Box::new(term(head)),
+                    tail: Box::new(tail),
+                })
+            })
+        }
+        Item::Map(m) => Expr::Map(Map {
+            span: m.span,
+            fields: m
+                .fields
+                .iter()
+                .map(|(k, v)| MapField::Assoc {
+                    span: SourceSpan::new(k.span().start(), v.span().end()),
+                    key: term(k),
+                    value: term(v),
+                })
+                .collect(),
+        }),
+    }
+}
+
+fn binary_op(item: &Item) -> Result<BinaryOp> {
+    let op = atom(item)?;
+    let op = match op.as_str().get() {
+        "+" => BinaryOp::Add,
+        "-" => BinaryOp::Sub,
+        "*" => BinaryOp::Multiply,
+        "/" => BinaryOp::Divide,
+        "div" => BinaryOp::Div,
+        "rem" => BinaryOp::Rem,
+        "band" => BinaryOp::Band,
+        "bor" => BinaryOp::Bor,
+        "bxor" => BinaryOp::Bxor,
+        "bsl" => BinaryOp::Bsl,
+        "bsr" => BinaryOp::Bsr,
+        "and" => BinaryOp::And,
+        "or" => BinaryOp::Or,
+        "xor" => BinaryOp::Xor,
+        "andalso" => BinaryOp::AndAlso,
+        "orelse" => BinaryOp::OrElse,
+        "++" => BinaryOp::Append,
+        "--" => BinaryOp::Remove,
+        "==" => BinaryOp::Equal,
+        "/=" => BinaryOp::NotEqual,
+        "=<" => BinaryOp::Lte,
+        "<" => BinaryOp::Lt,
+        ">=" => BinaryOp::Gte,
+        ">" => BinaryOp::Gt,
+        "=:=" => BinaryOp::StrictEqual,
+        "=/=" => BinaryOp::StrictNotEqual,
+        "!" => BinaryOp::Send,
+        _ => {
+            return Err(ParseError::invalid_form(
+                op.span,
+                "unrecognized binary operator",
+            ))
+        }
+    };
+    Ok(op)
+}
+
+fn unary_op(item: &Item) -> Result<UnaryOp> {
+    let op = atom(item)?;
+    let op = match op.as_str().get() {
+        "+" => UnaryOp::Plus,
+        "-" => UnaryOp::Minus,
+        "bnot" => UnaryOp::Bnot,
+        "not" => UnaryOp::Not,
+        _ => {
+            return Err(ParseError::invalid_form(
+                op.span,
+                "unrecognized unary operator",
+            ))
+        }
+    };
+    Ok(op)
+}
+
+/// Returns true if `item` is of the form `{type, Anno, Name}`
+fn is_type(item: &Item, name: &str) -> bool {
+    match item.tuple() {
+        Some(t) => t.len() == 3 && t.elements[0].is_atom("type") && t.elements[2].is_atom(name),
+        None => false,
+    }
+}
+
+/// Expects `item` to be a tuple whose first element is an atom, returning the tag, span and elements
+fn tagged<'i>(item: &'i Item, expected: &str) -> Result<(Ident, SourceSpan, &'i [Item])> {
+    match item.tuple() {
+        Some(t) if t.len() >= 2 => match t.tag() {
+            Some(tag) => Ok((tag, t.span, t.elements.as_slice())),
+            None => Err(ParseError::invalid_form(
+                t.span,
+                format!("expected {}, but the tuple tag is not an atom", expected),
+            )),
+        },
+        _ => Err(ParseError::invalid_form(
+            item.span(),
+            format!("expected {}", expected),
+        )),
+    }
+}
+
+/// Expects `item` to be a tuple of size `arity` tagged with `tag`
+fn tagged_n<'i>(item: &'i Item, tag: &str, arity: usize) -> Result<(SourceSpan, &'i [Item])> {
+    match item.tuple() {
+        Some(t) if t.len() == arity && t.elements[0].is_atom(tag) => {
+            Ok((t.span, t.elements.as_slice()))
+        }
+        _ => Err(ParseError::invalid_form(
+            item.span(),
+            format!("expected {} tuple of size {}", tag, arity),
+        )),
+    }
+}
+
+fn tuple_any(item: &Item) -> Result<(SourceSpan, &[Item])> {
+    match item.tuple() {
+        Some(t) => Ok((t.span, t.elements.as_slice())),
+        None => Err(ParseError::invalid_form(item.span(), "expected tuple")),
+    }
+}
+
+fn tuple(item: &Item, arity: usize) -> Result<(SourceSpan, &[Item])> {
+    match item.tuple() {
+        Some(t) if t.len() == arity => Ok((t.span, t.elements.as_slice())),
+        _ => Err(ParseError::invalid_form(
+            item.span(),
+            format!("expected tuple of size {}", arity),
+        )),
+    }
+}
+
+fn list(item: &Item) -> Result<Vec<Item>> {
+    item.as_proper_list()
+        .ok_or_else(|| ParseError::invalid_form(item.span(), "expected proper list"))
+}
+
+fn atom(item: &Item) -> Result<Ident> {
+    item.atom()
+        .ok_or_else(|| ParseError::invalid_form(item.span(), "expected atom"))
+}
+
+fn integer(item: &Item) -> Result<&Integer> {
+    item.integer()
+        .ok_or_else(|| ParseError::invalid_form(item.span(), "expected integer"))
+}
+
+fn arity(item: &Item) -> Result<u8> {
+    integer(item)?
+        .to_usize()
+        .and_then(|i| i.try_into().ok())
+        .ok_or_else(|| ParseError::invalid_form(item.span(), "invalid arity"))
+}
+
+fn string(item: &Item) -> Result<Ident> {
+    match item.as_string() {
+        Some(s) => Ok(Ident::new(Symbol::intern(&s), item.span())),
+        None => Err(ParseError::invalid_form(item.span(), "expected string")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use firefly_diagnostics::*;
+    use firefly_parser::Parser;
+
+    use crate::ast::Root;
+
+    #[test]
+    fn lower_basic_module() {
+        let codemap = Arc::new(CodeMap::new());
+        let parser = Parser::new((), codemap.clone());
+        let reporter = Reporter::new();
+        let root = parser
+            .parse_string::<Root, _, _>(
+                reporter.clone(),
+                "
+{attribute,1,file,{\"woo.erl\",1}}.
+{attribute,1,module,woo}.
+{attribute,3,export,[{foo,2},{bar,1}]}.
+{function,5,foo,2,
+    [{clause,5,
+    [{var,5,'A'},{var,5,'B'}],
+    [],
+    [{op,5,'+',{var,5,'A'},{var,5,'B'}}]}]}.
+{function,7,bar,1,
+    [{clause,7,[{integer,7,1}],[],[{integer,7,2}]},
+    {clause,9,[{var,9,'N'}],[[{call,9,{atom,9,is_integer},[{var,9,'N'}]}]],
+     [{call,9,{remote,9,{atom,9,erlang},{atom,9,abs}},[{var,9,'N'}]}]}]}.
+{eof,10}.
+",
+            )
+            .unwrap();
+        let module = super::lower_module(&reporter, codemap.clone(), &root).unwrap();
+        assert_eq!(module.name.as_str().get(), "woo");
+        assert_eq!(module.exports.len(), 2);
+        assert_eq!(module.functions.len(), 2);
+    }
+}
@@ -1,4 +1,7 @@
 use firefly_diagnostics::*;
+use firefly_syntax_erl::{LexicalError, ParserError};
+
+use super::Token;
 
 #[derive(thiserror::Error, Debug)]
 pub enum ParseError {
@@ -8,8 +11,30 @@ pub enum ParseError {
         source: std::io::Error,
         path: std::path::PathBuf,
     },
+    #[error(transparent)]
+    Lexer(#[from] LexicalError),
     #[error("parsing failed: {0}")]
-    LalrPop(#[from] lalrpop_util::ParseError<SourceIndex, Token, ()>),
+    LalrPop(lalrpop_util::ParseError<SourceIndex, Token, LexicalError>),
+    #[error("invalid abstract form: {message}")]
+    InvalidForm { span: SourceSpan, message: String },
+    #[error(transparent)]
+    Syntax(#[from] ParserError),
+}
+impl ParseError {
+    pub(crate) fn invalid_form<S: Into<String>>(span: SourceSpan, message: S) -> Self {
+        Self::InvalidForm {
+            span,
+            message: message.into(),
+        }
+    }
+}
+impl From<lalrpop_util::ParseError<SourceIndex, Token, LexicalError>> for ParseError {
+    fn from(err: lalrpop_util::ParseError<SourceIndex, Token, LexicalError>) -> Self {
+        match err {
+            lalrpop_util::ParseError::User { error } => Self::Lexer(error),
+            err => Self::LalrPop(err),
+        }
+    }
 }
 impl ToDiagnostic for ParseError {
     fn to_diagnostic(&self) -> Diagnostic {
@@ -19,6 +44,7 @@ impl ToDiagnostic for ParseError {
                 "error occurred while reading {:?}: {}",
                 path, source
             )),
+            Self::Lexer(err) => err.to_diagnostic(),
             Self::LalrPop(InvalidToken { location }) => {
                 let source_id = location.source_id();
                 let index = *location;
@@ -51,7 +77,13 @@ impl ToDiagnostic for ParseError {
                 .with_message("unexpected token")
                 .with_labels(vec![Label::primary(l.source_id(), SourceSpan::new(*l, *r))
                     .with_message("did not expect this token")]),
-            Self::LalrPop(User { .. }) => Diagnostic::error().with_message("parsing failed"),
+            Self::LalrPop(User { error }) => error.to_diagnostic(),
+            Self::InvalidForm { span, message } => Diagnostic::error()
+                .with_message("invalid abstract form")
+                .with_labels(vec![
+                    Label::primary(span.source_id(), *span).with_message(message)
+                ]),
+            Self::Syntax(err) => err.to_diagnostic(),
         }
     }
 }
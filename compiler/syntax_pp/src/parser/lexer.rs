@@ -2,12 +2,13 @@ use std::str::FromStr;
 
 use firefly_diagnostics::*;
 use firefly_intern::Symbol;
-use firefly_number::{Float, Integer};
+use firefly_number::{Float, FloatError, Integer};
 use firefly_parser::{Scanner, Source};
 use firefly_syntax_erl::LexicalError;
 
 use super::{LexicalToken, Token};
 
+/// A lexer for the subset of Erlang syntax used to write terms, i.e. the output of `io:format("~p.~n", [Term])`
 pub struct Lexer<S> {
     scanner: Scanner<S>,
     token: Token,
@@ -40,15 +41,19 @@ where
         }
 
         let token = std::mem::replace(&mut self.token, Token::EOF);
-        let result = Some(Ok(LexicalToken(
-            self.token_start.clone(),
-            token,
-            self.token_end.clone(),
-        )));
+        let result = if let Token::Error(err) = token {
+            Some(Err(err))
+        } else {
+            Some(Ok(LexicalToken(
+                self.token_start.clone(),
+                token,
+                self.token_end.clone(),
+            )))
+        };
 
         self.advance();
 
-        result
+        result.map(|r| r.map(|t| t.into()))
     }
 
     fn advance(&mut self) {
@@ -72,10 +77,22 @@ where
                 continue;
             }
 
+            // Comments run to the end of the line
+            if c == '%' {
+                loop {
+                    match self.scanner.read().1 {
+                        '\0' | '\n' => break,
+                        _ => self.scanner.advance(),
+                    }
+                }
+                continue;
+            }
+
             break;
         }
 
         self.token_start = position;
+        self.token_end = position;
     }
 
     fn pop(&mut self) -> char {
@@ -104,64 +121,58 @@ where
         self.scanner.slice(self.span())
     }
 
-    fn skip_whitespace(&mut self) {
-        while self.read().is_whitespace() {
-            self.skip();
-        }
-    }
-
     fn lex_unquoted_atom(&mut self) -> Token {
         let c = self.pop();
         debug_assert!(c.is_ascii_lowercase());
 
         loop {
             match self.read() {
-                '_' => self.skip(),
-                '@' => self.skip(),
-                '0'..='9' => self.skip(),
+                '_' | '@' => self.skip(),
                 c if c.is_alphanumeric() => self.skip(),
                 _ => break,
             }
         }
 
-        Token::from_bare_atom(self.slice())
+        Token::AtomLiteral(Symbol::intern(self.slice()))
     }
 
     fn lex_quoted_atom(&mut self) -> Token {
         let c = self.pop();
         debug_assert!(c == '\'');
 
-        self.buffer.clear();
-
-        loop {
-            match self.read() {
-                '\\' => unimplemented!(),
-                '\'' => {
-                    self.skip();
-                    break;
-                }
-                c => {
-                    self.skip();
-                    self.buffer.push(c);
-                }
-            }
+        match self.lex_quoted('\'') {
+            Ok(()) => Token::AtomLiteral(Symbol::intern(&self.buffer)),
+            Err(()) => Token::Error(LexicalError::UnclosedAtom { span: self.span() }),
         }
-
-        Token::from_bare_atom(self.buffer.as_str())
     }
 
     fn lex_string(&mut self) -> Token {
         let c = self.pop();
         debug_assert!(c == '"');
 
+        match self.lex_quoted('"') {
+            Ok(()) => Token::StringLiteral(Symbol::intern(&self.buffer)),
+            Err(()) => Token::Error(LexicalError::UnclosedString { span: self.span() }),
+        }
+    }
+
+    /// Reads the contents of a quoted atom or string into `self.buffer`, handling escapes.
+    ///
+    /// Returns `Err` if the closing delimiter is never found.
+    fn lex_quoted(&mut self, delimiter: char) -> Result<(), ()> {
         self.buffer.clear();
 
         loop {
             match self.read() {
-                '\\' => unimplemented!(),
-                '"' => {
+                '\0' => return Err(()),
+                '\\' => {
                     self.skip();
-                    break;
+                    let c = self.lex_escape();
+                    self.buffer.push(c);
+                }
+                c if c == delimiter => {
+                    self.skip();
+                    return Ok(());
                 }
                 c => {
                     self.skip();
@@ -169,18 +180,60 @@ where
                 }
             }
         }
+    }
 
-        Token::StringLiteral(Symbol::intern(&self.buffer))
+    /// Handles the escape sequences the term printer may produce, called after consuming the `\`
+    fn lex_escape(&mut self) -> char {
+        match self.pop() {
+            'b' => '\x08',
+            'd' => '\x7f',
+            'e' => '\x1b',
+            'f' => '\x0c',
+            'n' => '\n',
+            'r' => '\r',
+            's' => ' ',
+            't' => '\t',
+            'v' => '\x0b',
+            '^' => {
+                let c = self.pop();
+                char::from_u32((c as u32) % 32).unwrap_or(c)
+            }
+            'x' => {
+                let mut code = String::new();
+                if self.read() == '{' {
+                    self.skip();
+                    while self.read().is_digit(16) {
+                        code.push(self.pop());
+                    }
+                    if self.read() == '}' {
+                        self.skip();
+                    }
+                } else {
+                    while code.len() < 2 && self.read().is_digit(16) {
+                        code.push(self.pop());
+                    }
+                }
+                u32::from_str_radix(&code, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .unwrap_or('\u{fffd}')
+            }
+            c @ '0'..='7' => {
+                let mut code = c.to_digit(8).unwrap();
+                let mut n = 1;
+                while n < 3 && self.read().is_digit(8) {
+                    code = code * 8 + self.pop().to_digit(8).unwrap();
+                    n += 1;
+                }
+                char::from_u32(code).unwrap_or('\u{fffd}')
+            }
+            c => c,
+        }
     }
 
     #[inline]
-    fn lex_digits(
-        &mut self,
-        radix: u32,
-        allow_leading_underscore: bool,
-        num: &mut String,
-    ) -> Result<(), LexicalError> {
-        let mut last_underscore = !allow_leading_underscore;
+    fn lex_digits(&mut self, radix: u32, num: &mut String) -> Result<(), LexicalError> {
+        let mut last_underscore = true;
         let mut c = self.read();
         loop {
             match c {
@@ -210,7 +263,7 @@ where
         let mut num = String::new();
         let mut c;
 
-        // Expect the first character to be either a sign on digit
+        // Expect the first character to be either a sign or digit
         c = self.read();
         debug_assert!(c == '-' || c == '+' || c.is_digit(10), "got {}", c);
 
@@ -219,7 +272,6 @@ where
         // -10
         // ^
         //
-        let negative = c == '-';
         if c == '-' || c == '+' {
             num.push(self.pop());
         }
@@ -232,20 +284,42 @@ where
         // 10e10
         // ^^
         //
-        if let Err(err) = self.lex_digits(10, false, &mut num) {
+        if let Err(err) = self.lex_digits(10, &mut num) {
             return Token::Error(err);
         }
 
+        // Integers in another radix, e.g. 16#FF
+        c = self.read();
+        if c == '#' {
+            let radix = match num.trim_start_matches(&['-', '+'][..]).parse::<u32>() {
+                Ok(radix) if radix >= 2 && radix <= 36 => radix,
+                _ => {
+                    return Token::Error(LexicalError::InvalidRadix {
+                        span: self.span(),
+                        reason: "invalid radix, expected a value between 2 and 36".to_string(),
+                    })
+                }
+            };
+            self.skip();
+            let mut digits = String::new();
+            if num.starts_with('-') {
+                digits.push('-');
+            }
+            if let Err(err) = self.lex_digits(radix, &mut digits) {
+                return Token::Error(err);
+            }
+            return to_integer_literal(&digits, radix);
+        }
+
         // If we have a dot with a trailing number, we lex a float.
         // Otherwise we return consumed digits as an integer token.
         //
         // 10.0
         //   ^ lex_float()
         //
-        // fn() -> 10 + 10.
-        //                ^ return integer token
+        // {integer,1,10}.
+        //               ^ return integer token
         //
-        c = self.read();
         if c == '.' {
             if self.peek().is_digit(10) {
                 // Pushes .
@@ -277,20 +351,6 @@ where
         to_integer_literal(&num, 10)
     }
 
-    fn lex_float(&mut self) -> Token {
-        let c = self.pop();
-        debug_assert!(c.is_digit(10));
-
-        while self.read().is_digit(10) {
-            self.pop();
-        }
-
-        match f64::from_str(self.slice()) {
-            Ok(f) => Token::FloatLiteral(Float::new(f)),
-            Err(e) => panic!("unhandled float parsing error: {}", &e),
-        }
-    }
-
     // Called after consuming a number up to and including the '.'
     #[inline]
     fn lex_float(&mut self, num: String, seen_e: bool) -> Token {
@@ -300,7 +360,7 @@ where
         debug_assert!(c.is_digit(10), "got {}", c);
         num.push(c);
 
-        if let Err(err) = self.lex_digits(10, true, &mut num) {
+        if let Err(err) = self.lex_digits(10, &mut num) {
             return Token::Error(err);
         }
 
@@ -326,7 +386,7 @@ where
                 });
             }
 
-            if let Err(err) = self.lex_digits(10, false, &mut num) {
+            if let Err(err) = self.lex_digits(10, &mut num) {
                 return Token::Error(err);
             }
         }
@@ -337,7 +397,7 @@ where
     fn to_float_literal(&self, num: String) -> Token {
         let reason = match f64::from_str(&num) {
             Ok(f) => match Float::new(f) {
-                Ok(f) => return Token::Float(f),
+                Ok(f) => return Token::FloatLiteral(f),
                 Err(FloatError::Nan) => "float cannot be NaN".to_string(),
                 Err(FloatError::Infinite) => "float cannot be -Inf or Inf".to_string(),
             },
@@ -361,6 +421,14 @@ macro_rules! pop {
     }};
 }
 
+macro_rules! pop2 {
+    ($lex:ident, $code:expr) => {{
+        $lex.skip();
+        $lex.skip();
+        $code
+    }};
+}
+
 impl<S> Lexer<S>
 where
     S: Source,
@@ -373,11 +441,7 @@ where
             return Token::EOF;
         }
 
-        if c.is_whitespace() {
-            self.skip_whitespace();
-        }
-
-        match self.read() {
+        match c {
             '{' => pop!(self, Token::CurlyOpen),
             '}' => pop!(self, Token::CurlyClose),
             '[' => pop!(self, Token::SquareOpen),
@@ -385,11 +449,21 @@ where
             ',' => pop!(self, Token::Comma),
             '.' => pop!(self, Token::Dot),
             '|' => pop!(self, Token::Pipe),
-            'a'..='z' | 'A'..='Z' => self.lex_unquoted_atom(),
+            ':' => pop!(self, Token::Colon),
+            '#' => pop!(self, Token::Pound),
+            '=' if self.peek() == '>' => pop2!(self, Token::RightArrow),
+            '<' if self.peek() == '<' => pop2!(self, Token::BinaryStart),
+            '>' if self.peek() == '>' => pop2!(self, Token::BinaryEnd),
+            '-' | '+' if self.peek().is_digit(10) => self.lex_number(),
+            'a'..='z' => self.lex_unquoted_atom(),
             '0'..='9' => self.lex_number(),
             '\'' => self.lex_quoted_atom(),
             '"' => self.lex_string(),
-            c => unimplemented!("{}", c),
+            c => {
+                let start = self.span().start();
+                self.skip();
+                Token::Error(LexicalError::UnexpectedCharacter { start, found: c })
+            }
         }
     }
 }
@@ -398,7 +472,7 @@ impl<S> Iterator for Lexer<S>
 where
     S: Source,
 {
-    type Item = Result<(SourceIndex, Token, SourceIndex), ()>;
+    type Item = Result<(SourceIndex, Token, SourceIndex), LexicalError>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.lex()
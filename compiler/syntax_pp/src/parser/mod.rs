@@ -5,22 +5,31 @@ mod lexer;
 pub use self::lexer::Lexer;
 
 mod token;
-pub use self::token::Token;
+pub use self::token::{LexicalToken, Token};
+
+use std::sync::Arc;
+
+use firefly_diagnostics::{CodeMap, Reporter, SourceIndex};
+use firefly_intern::Symbol;
+use firefly_parser::{Parse, Parser, Scanner, Source};
+use firefly_syntax_erl::LexicalError;
+
+use crate::ast;
 
 /// Used in the grammar for easy span creation
 macro_rules! span {
     ($l:expr, $r:expr) => {
-        firefly_diagnostics::SourceSpan::new($l, $r)
+        SourceSpan::new($l, $r)
     };
     ($i:expr) => {
-        firefly_diagnostics::SourceSpan::new($i, $i)
+        SourceSpan::new($i, $i)
     };
 }
 
 #[cfg_attr(rustfmt, rustfmt_skip)]
 #[allow(unknown_lints)]
 #[allow(clippy)]
-#[allow(unused)]
+#[allow(unused_parens)]
 pub(crate) mod grammar {
     // During the build step, `build.rs` will output the generated parser to `OUT_DIR` to avoid
     // adding it to the source directory, so we just directly include the generated parser here.
@@ -31,60 +40,45 @@ pub(crate) mod grammar {
     // in that cached source directory because of https://github.com/lalrpop/lalrpop/issues/280.
     // Later runs of `cargo vendor` then copy the source from that directory, including the
     // generated file.
-    include!(concat!(env!("OUT_DIR"), "/grammar.rs"));
+    include!(concat!(env!("OUT_DIR"), "/parser/grammar.rs"));
 }
 
-use firefly_beam::beam::AbstractCode;
-use firefly_diagnostics::{ToDiagnostic, Diagnostic, Label, Reporter, SourceSpan};
-use firefly_parser::{Parse, Parser, Scanner, Source};
-
-use crate::ast::{self, Form};
-
-/// Parses forms from the given AbstractCode object
-pub from_abstract_code(code: &AbstractCode) -> anyhow::Result<Form> {
-    use firefly_beam::serialization::etf::pattern::VarList;
-
-    let (_, form) = code
-        .code()
-        .as_match(("raw_abstract_v1", VarList(FormPattern)))?;
-
-    Ok(form)
+/// A segment of a binary literal, as printed by `io_lib`
+pub(crate) enum BinarySegment {
+    String(Symbol),
+    Byte(u8),
+    /// A trailing segment of less than 8 bits, i.e. `Value:Size`
+    Bits(u8, u8),
 }
 
 impl Parse for ast::Root {
     type Parser = grammar::RootParser;
     type Error = ParseError;
     type Config = ();
-    type Token = Result<(SourceIndex, Token, SourceIndex), ()>;
+    type Token = Result<(SourceIndex, Token, SourceIndex), LexicalError>;
 
     fn root_file_error(source: std::io::Error, path: std::path::PathBuf) -> Self::Error {
         ParseError::RootFileError { source, path }
     }
 
-    fn parse<S>(
-        _parser: &Parser<Self::Config>,
-        reporter: Reporter,
-        source: S,
-    ) -> Result<Self, ()>
+    fn parse<S>(parser: &Parser<()>, reporter: Reporter, source: S) -> Result<Self, Self::Error>
     where
         S: Source,
     {
         let scanner = Scanner::new(source);
         let lexer = Lexer::new(scanner);
-        Self::parse_tokens(errors, lexer)
+        Self::parse_tokens(reporter, parser.codemap.clone(), lexer)
     }
 
-    fn parse_tokens<S>(reporter: Reporter, tokens: S) -> Result<Self, ()>
+    fn parse_tokens<S>(
+        _reporter: Reporter,
+        _codemap: Arc<CodeMap>,
+        tokens: S,
+    ) -> Result<Self, Self::Error>
     where
         S: IntoIterator<Item = Self::Token>,
     {
-        match Self::Parser::new().parse(tokens) {
-            Ok(inner) => Ok(inner),
-            Err(err) => {
-                errors.error(err.into());
-                Err(())
-            }
-        }
+        Self::Parser::new().parse(tokens).map_err(ParseError::from)
     }
 }
 
@@ -93,46 +87,46 @@ mod test {
     use std::sync::Arc;
 
     use firefly_diagnostics::*;
-    use firefly_parser::{Parse, Parser};
+    use firefly_parser::Parser;
 
-    use super::*;
+    use crate::ast::*;
 
-    fn fail_with(reporter: &Reporter, codemap: &CodeMap) -> ! {
-        use term::termcolor::{ColorChoice, StandardStream};
-
-        let config = term::Config::default();
-        let mut out = StandardStream::stderr(ColorChoice::Always);
-        for diagnostic in reporter.diagnostics() {
-            term::emit(&mut out, &config, codemap, &diagnostic).unwrap();
+    fn parse(input: &str) -> Root {
+        let codemap = Arc::new(CodeMap::new());
+        let parser = Parser::new((), codemap.clone());
+        let reporter = Reporter::new();
+        match parser.parse_string::<Root, _, _>(reporter.clone(), input) {
+            Ok(root) => root,
+            Err(err) => {
+                reporter.diagnostic(err.to_diagnostic());
+                reporter.print(&codemap);
+                panic!("parsing failed");
+            }
         }
-        panic!();
     }
 
-    fn parse<T, S>(input: S) -> T
-    where
-        T: Parse<T, Config = (), Error = ParseError>,
-        S: AsRef<str>,
-    {
-        let parser = Parser::new((), Arc::new(CodeMap::new()));
-        let mut errors = Errors::new();
-        match parser.parse_string::<T, S>(&mut errors, input) {
-            Ok(ast) => return ast,
-            Err(()) => fail_with(&errors, &parser.codemap),
-        };
+    #[test]
+    fn simple() {
+        let root = parse("{woo, '123fwoo', {}}.\n");
+        assert_eq!(root.items.len(), 1);
+        let tuple = root.items[0].tuple().unwrap();
+        assert_eq!(tuple.len(), 3);
+        assert!(tuple.elements[0].is_atom("woo"));
+        assert!(tuple.elements[1].is_atom("123fwoo"));
     }
 
     #[test]
-    fn simple() {
-        let _: Root = parse(
-            "
-{woo, '123fwoo', {}}.
-",
-        );
+    fn terms() {
+        let root = parse("[1, -2, 3.5, \"abc\" \"def\", [a | b], #{k => v}, <<\"ab\",99,1:1>>].\n");
+        let elements = root.items[0].as_proper_list().unwrap();
+        assert_eq!(elements.len(), 7);
+        assert_eq!(elements[3].as_string().as_deref(), Some("abcdef"));
+        assert!(elements[4].as_proper_list().is_none());
     }
 
     #[test]
     fn basic_ast() {
-        let _: Root = parse(
+        let root = parse(
             "
 {attribute,1,file,{\"woo.erl\",1}}.
 {attribute,1,module,woo}.
@@ -146,9 +140,6 @@ mod test {
     [{clause,7,[{integer,7,1}],[],[{integer,7,2}]},
     {clause,8,[{integer,8,2}],[],[{integer,8,4}]},
     {clause,9,[{var,9,'N'}],[],[{var,9,'N'}]}]}.
-{function,11,barr,1,
-    [{clause,11,[{integer,11,1}],[],[{integer,11,2}]},
-    {clause,12,[{integer,12,2}],[],[{integer,12,4}]}]}.
 {function,14,binary,0,
     [{clause,14,[],[],
     [{bin,14,[{bin_element,14,{string,14,\"woo\"},default,default}]}]}]}.
@@ -156,5 +147,6 @@ mod test {
 {eof,17}.
 ",
         );
+        assert_eq!(root.items.len(), 7);
     }
 }
@@ -1,11 +1,11 @@
 use std::fmt;
 
-use firefly_diagnostics::SourceIndex;
+use firefly_diagnostics::{SourceIndex, SourceSpan};
 use firefly_intern::Symbol;
 use firefly_number::{Float, Integer};
 use firefly_syntax_erl::LexicalError;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct LexicalToken(pub SourceIndex, pub Token, pub SourceIndex);
 impl LexicalToken {
     #[inline]
@@ -34,7 +34,8 @@ impl From<(SourceIndex, Token, SourceIndex)> for LexicalToken {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// The tokens of the Erlang term syntax, which is all that is needed to read the Abstract Format
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     EOF,
     Error(LexicalError),
@@ -43,111 +44,43 @@ pub enum Token {
     Comma,
     Dot,
     Pipe,
+    Colon,
+    Pound,
+    RightArrow,
     SquareOpen,
     SquareClose,
     CurlyOpen,
     CurlyClose,
+    BinaryStart,
+    BinaryEnd,
 
     // Literals
     AtomLiteral(Symbol),
     StringLiteral(Symbol),
     IntegerLiteral(Integer),
     FloatLiteral(Float),
-
-    // Keywords
-    Atom,
-    Attribute,
-    Bin,
-    BinElement,
-    BitComprehension, // bc
-    BitGenerator,     // bc_generate
-    Call,
-    Callback,
-    Case,
-    Catch,
-    Char,
-    Clauses,
-    Clause,
-    Cons,
-    Error,
-    Export,
-    ExportType,
-    File,
-    Function,
-    Fun,
-    If,
-    Import,
-    Integer,
-    ListGenerator,     // generate
-    ListComprehension, // lc
-    Match,
-    Map,
-    MapFieldAssoc,
-    MapFieldExact,
-    Module,
-    Nil,
-    Op,
-    Opaque,
-    OptionalCallbacks,
-    Receive,
-    Record,
-    RecordField,
-    Spec,
-    String,
-    Try,
-    Tuple,
-    Type,
-    Var,
-    Warning,
 }
-impl Token {
-    pub fn from_bare_atom(atom: &str) -> Self {
-        match atom {
-            "atom" => Token::Atom,
-            "attribute" => Token::Attribute,
-            "bin" => Token::Bin,
-            "bin_element" => Token::BinElement,
-            "bc" => Token::BitComprehension,
-            "bc_generate" => Token::BitGenerator,
-            "call" => Token::Call,
-            "callback" => Token::Callback,
-            "case" => Token::Case,
-            "catch" => Token::Catch,
-            "char" => Token::Char,
-            "clauses" => Token::Clauses,
-            "clause" => Token::Clause,
-            "cons" => Token::Cons,
-            "error" => Token::Error,
-            "export" => Token::Export,
-            "export_type" => Token::ExportType,
-            "file" => Token::File,
-            "function" => Token::Function,
-            "fun" => Token::Fun,
-            "if" => Token::If,
-            "import" => Token::Import,
-            "integer" => Token::Integer,
-            "generate" => Token::ListGenerator,
-            "lc" => Token::ListComprehension,
-            "match" => Token::Match,
-            "map" => Token::Map,
-            "map_field_assoc" => Token::MapFieldAssoc,
-            "map_field_exact" => Token::MapFieldExact,
-            "module" => Token::Module,
-            "nil" => Token::Nil,
-            "op" => Token::Op,
-            "opaque" => Token::Opaque,
-            "optional_callbacks" => Token::OptionalCallbacks,
-            "receive" => Token::Receive,
-            "record" => Token::Record,
-            "record_field" => Token::RecordField,
-            "spec" => Token::Spec,
-            "string" => Token::String,
-            "try" => Token::Try,
-            "tuple" => Token::Tuple,
-            "type" => Token::Type,
-            "var" => Token::Var,
-            "warning" => Token::Warning,
-            _ => Token::AtomLiteral(Symbol::intern(atom)),
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EOF => f.write_str("EOF"),
+            Self::Error(_) => f.write_str("ERROR"),
+            Self::Comma => f.write_str(","),
+            Self::Dot => f.write_str("."),
+            Self::Pipe => f.write_str("|"),
+            Self::Colon => f.write_str(":"),
+            Self::Pound => f.write_str("#"),
+            Self::RightArrow => f.write_str("=>"),
+            Self::SquareOpen => f.write_str("["),
+            Self::SquareClose => f.write_str("]"),
+            Self::CurlyOpen => f.write_str("{"),
+            Self::CurlyClose => f.write_str("}"),
+            Self::BinaryStart => f.write_str("<<"),
+            Self::BinaryEnd => f.write_str(">>"),
+            Self::AtomLiteral(a) => write!(f, "'{}'", a),
+            Self::StringLiteral(s) => write!(f, "{:?}", s.as_str().get()),
+            Self::IntegerLiteral(i) => write!(f, "{}", i),
+            Self::FloatLiteral(flt) => write!(f, "{}", flt),
         }
     }
 }
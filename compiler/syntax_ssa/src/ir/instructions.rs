@@ -1,5 +1,6 @@
 use std::fmt;
 use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
 
 use cranelift_entity::entity_impl;
 use intrusive_collections::{intrusive_adapter, LinkedListLink, UnsafeRef};
@@ -532,6 +533,100 @@ impl fmt::Display for Opcode {
         }
     }
 }
+impl FromStr for Opcode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "const.int" => Ok(Self::ImmInt),
+            "const.float" => Ok(Self::ImmFloat),
+            "const.bool" => Ok(Self::ImmBool),
+            "const.atom" => Ok(Self::ImmAtom),
+            "const.nil" => Ok(Self::ImmNil),
+            "const.none" => Ok(Self::ImmNone),
+            "null" => Ok(Self::ImmNull),
+            "const.bigint" => Ok(Self::ConstBigInt),
+            "const.binary" => Ok(Self::ConstBinary),
+            "is_null" => Ok(Self::IsNull),
+            "cast" => Ok(Self::Cast),
+            "trunc" => Ok(Self::Trunc),
+            "zext" => Ok(Self::Zext),
+            "cond.br" => Ok(Self::CondBr),
+            "br" => Ok(Self::Br),
+            "br.if" => Ok(Self::BrIf),
+            "br.unless" => Ok(Self::BrUnless),
+            "switch" => Ok(Self::Switch),
+            "call" => Ok(Self::Call),
+            "tail call" => Ok(Self::Enter),
+            "call.indirect" => Ok(Self::CallIndirect),
+            "tail call.indirect" => Ok(Self::EnterIndirect),
+            "ret" => Ok(Self::Ret),
+            "add" => Ok(Self::Add),
+            "sub" => Ok(Self::Sub),
+            "mul" => Ok(Self::Mul),
+            "idiv" => Ok(Self::Div),
+            "fdiv" => Ok(Self::Fdiv),
+            "rem" => Ok(Self::Rem),
+            "neg" => Ok(Self::Neg),
+            "and" => Ok(Self::And),
+            "band" => Ok(Self::Band),
+            "andalso" => Ok(Self::AndAlso),
+            "or" => Ok(Self::Or),
+            "bor" => Ok(Self::Bor),
+            "orelse" => Ok(Self::OrElse),
+            "xor" => Ok(Self::Xor),
+            "bxor" => Ok(Self::Bxor),
+            "bsl" => Ok(Self::Bsl),
+            "bsr" => Ok(Self::Bsr),
+            "icmp.eq" => Ok(Self::IcmpEq),
+            "icmp.neq" => Ok(Self::IcmpNeq),
+            "icmp.gt" => Ok(Self::IcmpGt),
+            "icmp.gte" => Ok(Self::IcmpGte),
+            "icmp.lt" => Ok(Self::IcmpLt),
+            "icmp.lte" => Ok(Self::IcmpLte),
+            "eq" => Ok(Self::Eq),
+            "eq.exact" => Ok(Self::EqExact),
+            "neq" => Ok(Self::Neq),
+            "neq.exact" => Ok(Self::NeqExact),
+            "gt" => Ok(Self::Gt),
+            "gte" => Ok(Self::Gte),
+            "lt" => Ok(Self::Lt),
+            "lte" => Ok(Self::Lte),
+            "not" => Ok(Self::Not),
+            "bnot" => Ok(Self::Bnot),
+            "is_type" => Ok(Self::IsType),
+            "cons" => Ok(Self::Cons),
+            "list.hd" => Ok(Self::Head),
+            "list.tl" => Ok(Self::Tail),
+            "list.concat" => Ok(Self::ListConcat),
+            "list.subtract" => Ok(Self::ListSubtract),
+            "tuple" => Ok(Self::Tuple),
+            "tuple.is_tagged" => Ok(Self::IsTaggedTuple),
+            "tuple.get" => Ok(Self::GetElement),
+            "tuple.set" => Ok(Self::SetElement),
+            "tuple.set.mut" => Ok(Self::SetElementMut),
+            "fun.make" => Ok(Self::MakeFun),
+            "fun.env.get" => Ok(Self::UnpackEnv),
+            "recv.start" => Ok(Self::RecvStart),
+            "recv.next" => Ok(Self::RecvNext),
+            "recv.peek" => Ok(Self::RecvPeek),
+            "recv.pop" => Ok(Self::RecvPop),
+            "recv.wait" => Ok(Self::RecvWait),
+            "recv.done" => Ok(Self::RecvDone),
+            "bs.match.start" => Ok(Self::BitsMatchStart),
+            "bs.match" => Ok(Self::BitsMatch),
+            "bs.match.skip" => Ok(Self::BitsMatchSkip),
+            "bs.push" => Ok(Self::BitsPush),
+            "bs.test.tail" => Ok(Self::BitsTestTail),
+            "raise" => Ok(Self::Raise),
+            "nif.start" => Ok(Self::NifStart),
+            "exception.class" => Ok(Self::ExceptionClass),
+            "exception.reason" => Ok(Self::ExceptionReason),
+            "exception.trace" => Ok(Self::ExceptionTrace),
+            _ => Err(()),
+        }
+    }
+}
 impl From<BinaryOpType> for Opcode {
     fn from(op: BinaryOpType) -> Self {
         match op {
@@ -53,13 +53,7 @@ impl Emit for Module {
     }
 
     fn emit(&self, f: &mut std::fs::File) -> anyhow::Result<()> {
-        use std::io::Write;
-        write!(f, "module {}\n", &self.name)?;
-        for function in self.functions.iter() {
-            writeln!(f)?;
-            crate::write::write_function(f, function)?;
-        }
-
+        crate::write::write_module(f, self)?;
         Ok(())
     }
 }
@@ -1,5 +1,6 @@
 #![deny(warnings)]
 pub mod ir;
+pub mod parser;
 pub mod write;
 
 pub use self::ir::*;
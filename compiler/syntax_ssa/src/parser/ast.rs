@@ -0,0 +1,133 @@
+///! This module contains the syntax tree produced by the parser.
+///!
+///! The tree closely mirrors the text, and is only used as an intermediate step
+///! before being lowered to an SSA IR [`Module`](crate::Module), at which point
+///! names, types, opcodes and operands are validated.
+use firefly_binary::BitVec;
+use firefly_diagnostics::{SourceSpan, Span, Spanned};
+use firefly_intern::{Ident, Symbol};
+use firefly_number::Integer;
+use firefly_syntax_base::FunctionName;
+
+pub struct Module {
+    pub span: SourceSpan,
+    pub name: Ident,
+    pub functions: Vec<Function>,
+}
+
+pub struct Function {
+    pub span: SourceSpan,
+    pub name: Ident,
+    pub is_public: bool,
+    pub is_nif: bool,
+    pub is_closure: bool,
+    pub params: Vec<Type>,
+    pub results: Vec<Type>,
+    pub blocks: Vec<Block>,
+}
+
+pub struct Block {
+    pub span: SourceSpan,
+    pub id: Span<u32>,
+    pub params: Vec<BlockParam>,
+    pub insts: Vec<Inst>,
+}
+
+pub struct BlockParam {
+    pub span: SourceSpan,
+    pub value: Span<u32>,
+    pub ty: Type,
+}
+
+/// An instruction of the form `v1, v2 = opcode.spec(unit) operands  : t1, t2`
+pub struct Inst {
+    pub span: SourceSpan,
+    pub results: Vec<Span<u32>>,
+    pub opcode: Ident,
+    pub unit: Option<Span<Integer>>,
+    pub operands: Vec<Operand>,
+    pub types: Vec<Type>,
+}
+
+pub enum Operand {
+    /// `v1`
+    Value(Span<u32>),
+    /// `v1[2]`, as used by `tuple.set`
+    Index(SourceSpan, Span<u32>, Integer),
+    /// `v1(v2, v3)` or `m:f/1(v2)`
+    Apply(SourceSpan, Callee, Vec<Span<u32>>),
+    /// `block1` or `block1(v2, v3)`
+    Block(SourceSpan, Span<u32>, Vec<Span<u32>>),
+    /// `1 => block1`, as used by `switch`
+    Arm(SourceSpan, Integer, Span<u32>),
+    Integer(SourceSpan, Integer),
+    Float(SourceSpan, f64),
+    /// A quoted atom
+    Atom(SourceSpan, Symbol),
+    /// An unquoted name, either a type, an atom, or one of `true`, `false` or `none`
+    Ident(Ident),
+    String(SourceSpan, Symbol),
+    Bytes(SourceSpan, Vec<u8>),
+    Binary(SourceSpan, BitVec),
+    Nil(SourceSpan),
+    /// A type which can't be mistaken for a name, e.g. `tuple<term>`
+    Type(Type),
+}
+impl Operand {
+    pub fn span(&self) -> SourceSpan {
+        match self {
+            Self::Value(v) => v.span(),
+            Self::Ident(id) => id.span,
+            Self::Type(ty) => ty.span(),
+            Self::Index(span, _, _)
+            | Self::Apply(span, _, _)
+            | Self::Block(span, _, _)
+            | Self::Arm(span, _, _)
+            | Self::Integer(span, _)
+            | Self::Float(span, _)
+            | Self::Atom(span, _)
+            | Self::String(span, _)
+            | Self::Bytes(span, _)
+            | Self::Binary(span, _)
+            | Self::Nil(span) => *span,
+        }
+    }
+}
+
+pub enum Callee {
+    Value(Span<u32>),
+    Function(FunctionName),
+}
+
+pub enum Type {
+    /// `?`
+    Unknown(SourceSpan),
+    /// `!`
+    NoReturn(SourceSpan),
+    /// `term`, `i32`, etc.
+    Named(Ident),
+    /// `ptr<i8>`, `list<term>`, `tuple<term, term>`
+    Generic(SourceSpan, Ident, Vec<Type>),
+    /// `{i1, term}`
+    Struct(SourceSpan, Vec<Type>),
+    /// `[i8; 4]`
+    Array(SourceSpan, Box<Type>, Integer),
+    /// `(term) -> (i1, term)`
+    Function(SourceSpan, Vec<Type>, Vec<Type>),
+    /// `fun(term) -> (i1, term)`
+    Fun(SourceSpan, Vec<Type>, Vec<Type>),
+}
+impl Type {
+    pub fn span(&self) -> SourceSpan {
+        match self {
+            Self::Named(id) => id.span,
+            Self::Unknown(span)
+            | Self::NoReturn(span)
+            | Self::Generic(span, _, _)
+            | Self::Struct(span, _)
+            | Self::Array(span, _, _)
+            | Self::Function(span, _, _)
+            | Self::Fun(span, _, _) => *span,
+        }
+    }
+}
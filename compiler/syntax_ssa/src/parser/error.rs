@@ -0,0 +1,145 @@
+use firefly_diagnostics::*;
+
+use super::Token;
+
+/// An enum of possible errors that can occur when lexing the textual SSA IR
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum LexicalError {
+    #[error("{reason}")]
+    InvalidFloat { span: SourceSpan, reason: String },
+
+    #[error("{reason}")]
+    InvalidRadix { span: SourceSpan, reason: String },
+
+    /// Occurs when a string literal is not closed before the end of the input
+    #[error("Unclosed string literal")]
+    UnclosedString { span: SourceSpan },
+
+    /// Like UnclosedString, but for quoted atoms
+    #[error("Unclosed atom literal")]
+    UnclosedAtom { span: SourceSpan },
+
+    /// Occurs when we encounter an unexpected character
+    #[error("Encountered unexpected character '{found}'")]
+    UnexpectedCharacter { start: SourceIndex, found: char },
+}
+impl LexicalError {
+    /// Return the source span for this error
+    pub fn span(&self) -> SourceSpan {
+        match self {
+            Self::InvalidFloat { span, .. }
+            | Self::InvalidRadix { span, .. }
+            | Self::UnclosedString { span }
+            | Self::UnclosedAtom { span } => *span,
+            Self::UnexpectedCharacter { start, .. } => SourceSpan::new(*start, *start),
+        }
+    }
+}
+impl ToDiagnostic for LexicalError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let span = self.span();
+        let msg = self.to_string();
+        match self {
+            Self::InvalidFloat { .. } => Diagnostic::error()
+                .with_message("invalid float literal")
+                .with_labels(vec![
+                    Label::primary(span.source_id(), span).with_message(msg)
+                ]),
+            Self::InvalidRadix { .. } => Diagnostic::error()
+                .with_message("invalid radix value for integer literal")
+                .with_labels(vec![
+                    Label::primary(span.source_id(), span).with_message(msg)
+                ]),
+            Self::UnexpectedCharacter { .. } => Diagnostic::error()
+                .with_message("unexpected character")
+                .with_labels(vec![
+                    Label::primary(span.source_id(), span).with_message(msg)
+                ]),
+            _ => Diagnostic::error()
+                .with_message(msg)
+                .with_labels(vec![Label::primary(span.source_id(), span)]),
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ParseError {
+    #[error("error occurred when reading {path:?}: {source}")]
+    RootFileError {
+        #[source]
+        source: std::io::Error,
+        path: std::path::PathBuf,
+    },
+    #[error(transparent)]
+    Lexer(#[from] LexicalError),
+    #[error("parsing failed: {0}")]
+    LalrPop(lalrpop_util::ParseError<SourceIndex, Token, LexicalError>),
+    #[error("invalid ssa: {message}")]
+    Invalid { span: SourceSpan, message: String },
+}
+impl ParseError {
+    pub(crate) fn invalid<S: Into<String>>(span: SourceSpan, message: S) -> Self {
+        Self::Invalid {
+            span,
+            message: message.into(),
+        }
+    }
+}
+impl From<lalrpop_util::ParseError<SourceIndex, Token, LexicalError>> for ParseError {
+    fn from(err: lalrpop_util::ParseError<SourceIndex, Token, LexicalError>) -> Self {
+        match err {
+            lalrpop_util::ParseError::User { error } => Self::Lexer(error),
+            err => Self::LalrPop(err),
+        }
+    }
+}
+impl ToDiagnostic for ParseError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        use lalrpop_util::ParseError::*;
+        match self {
+            Self::RootFileError { source, path } => Diagnostic::error().with_message(format!(
+                "error occurred while reading {:?}: {}",
+                path, source
+            )),
+            Self::Lexer(err) => err.to_diagnostic(),
+            Self::LalrPop(InvalidToken { location }) => {
+                let source_id = location.source_id();
+                let index = *location;
+                Diagnostic::error()
+                    .with_message("invalid token")
+                    .with_labels(vec![Label::primary(
+                        source_id,
+                        SourceSpan::new(index, index),
+                    )
+                    .with_message("invalid token encountered here")])
+            }
+            Self::LalrPop(UnrecognizedEOF { location, expected }) => {
+                let source_id = location.source_id();
+                let index = *location;
+                Diagnostic::error()
+                    .with_message("unexpected end of file")
+                    .with_labels(vec![Label::primary(
+                        source_id,
+                        SourceSpan::new(index, index),
+                    )
+                    .with_message(&format!("expected one of: {}", expected.join(", ")))])
+            }
+            Self::LalrPop(ExtraToken { token: (l, _, r) }) => Diagnostic::error()
+                .with_message("extra token")
+                .with_labels(vec![Label::primary(l.source_id(), SourceSpan::new(*l, *r))
+                    .with_message("did not expect this token")]),
+            Self::LalrPop(UnrecognizedToken {
+                token: (l, _, r), ..
+            }) => Diagnostic::error()
+                .with_message("unexpected token")
+                .with_labels(vec![Label::primary(l.source_id(), SourceSpan::new(*l, *r))
+                    .with_message("did not expect this token")]),
+            Self::LalrPop(User { error }) => error.to_diagnostic(),
+            Self::Invalid { span, message } => Diagnostic::error()
+                .with_message("invalid ssa")
+                .with_labels(vec![
+                    Label::primary(span.source_id(), *span).with_message(message)
+                ]),
+        }
+    }
+}
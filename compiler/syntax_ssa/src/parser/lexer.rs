@@ -0,0 +1,501 @@
+use std::str::FromStr;
+
+use firefly_diagnostics::*;
+use firefly_intern::Symbol;
+use firefly_number::Integer;
+use firefly_parser::{Scanner, Source};
+use firefly_syntax_base::FunctionName;
+
+use super::{LexicalError, LexicalToken, Token};
+
+/// A lexer for the textual SSA IR, i.e. the output of `firefly_syntax_ssa::write`
+pub struct Lexer<S> {
+    scanner: Scanner<S>,
+    token: Token,
+    token_start: SourceIndex,
+    token_end: SourceIndex,
+    eof: bool,
+    /// Set when a line break was skipped since the last token
+    newline: bool,
+    /// True if the last token produced was a newline, or if no tokens have been produced yet
+    after_newline: bool,
+    buffer: String,
+}
+impl<S> Lexer<S>
+where
+    S: Source,
+{
+    pub fn new(scanner: Scanner<S>) -> Self {
+        let start = scanner.start();
+        let mut lexer = Self {
+            scanner,
+            token: Token::EOF,
+            token_start: start,
+            token_end: start,
+            eof: false,
+            newline: false,
+            after_newline: true,
+            buffer: String::new(),
+        };
+        lexer.advance();
+        lexer
+    }
+
+    pub fn lex(&mut self) -> Option<<Self as Iterator>::Item> {
+        if self.eof && self.token == Token::EOF {
+            return None;
+        }
+
+        let token = std::mem::replace(&mut self.token, Token::EOF);
+        let result = if let Token::Error(err) = token {
+            Some(Err(err))
+        } else {
+            Some(Ok(LexicalToken(
+                self.token_start.clone(),
+                token,
+                self.token_end.clone(),
+            )))
+        };
+
+        self.advance();
+
+        result.map(|r| r.map(|t| t.into()))
+    }
+
+    fn advance(&mut self) {
+        self.advance_start();
+        // A line break terminates the current line, unless it is empty, so the input
+        // always ends with a newline token if it contains anything at all
+        self.token = if (self.newline || self.eof) && !self.after_newline {
+            Token::Newline
+        } else {
+            self.tokenize()
+        };
+        self.after_newline = self.token == Token::Newline;
+    }
+
+    fn advance_start(&mut self) {
+        let mut position: SourceIndex;
+        self.newline = false;
+        loop {
+            let (pos, c) = self.scanner.read();
+            position = pos;
+
+            if c == '\0' {
+                self.eof = true;
+                break;
+            }
+
+            if c == '\n' {
+                // The newline token is positioned at the first line break
+                if !self.newline {
+                    self.newline = true;
+                    self.token_start = position;
+                    self.token_end = position;
+                }
+                self.scanner.advance();
+                continue;
+            }
+
+            if c.is_whitespace() {
+                self.scanner.advance();
+                continue;
+            }
+
+            // Comments run to the end of the line
+            if c == '/' && self.scanner.peek().1 == '/' {
+                loop {
+                    match self.scanner.read().1 {
+                        '\0' | '\n' => break,
+                        _ => self.scanner.advance(),
+                    }
+                }
+                continue;
+            }
+
+            break;
+        }
+
+        if !self.newline || self.after_newline {
+            self.token_start = position;
+            self.token_end = position;
+        }
+    }
+
+    fn pop(&mut self) -> char {
+        let (pos, c) = self.scanner.pop();
+        self.token_end = pos + ByteOffset::from_char_len(c);
+        c
+    }
+
+    fn peek(&mut self) -> char {
+        self.scanner.peek().1
+    }
+
+    fn read(&mut self) -> char {
+        self.scanner.read().1
+    }
+
+    fn skip(&mut self) {
+        self.pop();
+    }
+
+    pub fn span(&self) -> SourceSpan {
+        SourceSpan::new(self.token_start, self.token_end)
+    }
+
+    fn slice(&self) -> &str {
+        self.scanner.slice(self.span())
+    }
+
+    /// Consumes the remainder of an unquoted name, returning the slice of input it spans
+    fn lex_name(&mut self, start: SourceIndex) -> &str {
+        loop {
+            match self.read() {
+                '_' | '@' | '.' => self.skip(),
+                c if c.is_ascii_alphanumeric() => self.skip(),
+                _ => break,
+            }
+        }
+        self.scanner.slice(SourceSpan::new(start, self.token_end))
+    }
+
+    fn lex_identifier(&mut self) -> Token {
+        let c = self.pop();
+        debug_assert!(c == '_' || c.is_ascii_lowercase());
+
+        self.lex_name(self.token_start);
+        // Used by the `list?` type
+        if self.read() == '?' {
+            self.skip();
+        }
+
+        let name = self.slice();
+        if let Some(index) = entity_index(name, "v") {
+            return Token::Value(index);
+        }
+        if let Some(index) = entity_index(name, "block") {
+            return Token::Block(index);
+        }
+
+        let name = Symbol::intern(name);
+        if self.is_function_name_start() {
+            return self.lex_function_name(name);
+        }
+
+        match name.as_str().get() {
+            "module" => Token::Module,
+            "function" => Token::Function,
+            "pub" => Token::Pub,
+            "nif" => Token::Nif,
+            "closure" => Token::Closure,
+            "tail" => Token::Tail,
+            _ => Token::Ident(name),
+        }
+    }
+
+    fn lex_quoted_atom(&mut self) -> Token {
+        let c = self.pop();
+        debug_assert!(c == '\'');
+
+        match self.lex_quoted('\'') {
+            Ok(()) => {
+                let name = Symbol::intern(&self.buffer);
+                if self.is_function_name_start() {
+                    self.lex_function_name(name)
+                } else {
+                    Token::Atom(name)
+                }
+            }
+            Err(()) => Token::Error(LexicalError::UnclosedAtom { span: self.span() }),
+        }
+    }
+
+    /// Returns true if the name just lexed is immediately followed by `:Name` or `/Arity`
+    fn is_function_name_start(&mut self) -> bool {
+        match (self.read(), self.peek()) {
+            (':', c) => c == '\'' || c == '_' || c.is_ascii_lowercase(),
+            ('/', c) => c.is_ascii_digit(),
+            _ => false,
+        }
+    }
+
+    /// Lexes the remainder of a function name after its first component, i.e. `[:Function]/Arity`
+    fn lex_function_name(&mut self, first: Symbol) -> Token {
+        let (module, function) = if self.read() == ':' {
+            self.skip();
+            let start = self.token_end;
+            let function = match self.pop() {
+                '\'' => match self.lex_quoted('\'') {
+                    Ok(()) => Symbol::intern(&self.buffer),
+                    Err(()) => {
+                        return Token::Error(LexicalError::UnclosedAtom { span: self.span() })
+                    }
+                },
+                _ => Symbol::intern(self.lex_name(start)),
+            };
+            (Some(first), function)
+        } else {
+            (None, first)
+        };
+
+        let c = self.read();
+        if c != '/' || !self.peek().is_ascii_digit() {
+            return Token::Error(LexicalError::UnexpectedCharacter {
+                start: self.token_end,
+                found: c,
+            });
+        }
+        self.skip();
+
+        let mut digits = String::new();
+        while self.read().is_ascii_digit() {
+            digits.push(self.pop());
+        }
+        match digits.parse::<u8>() {
+            Ok(arity) => Token::FunctionName(FunctionName {
+                module,
+                function,
+                arity,
+            }),
+            Err(_) => Token::Error(LexicalError::InvalidRadix {
+                span: self.span(),
+                reason: "invalid function arity".to_string(),
+            }),
+        }
+    }
+
+    fn lex_string(&mut self) -> Token {
+        let c = self.pop();
+        debug_assert!(c == '"');
+
+        match self.lex_quoted('"') {
+            Ok(()) => Token::String(Symbol::intern(&self.buffer)),
+            Err(()) => Token::Error(LexicalError::UnclosedString { span: self.span() }),
+        }
+    }
+
+    /// Reads the contents of a quoted atom or string into `self.buffer`, handling escapes.
+    ///
+    /// Returns `Err` if the closing delimiter is never found.
+    fn lex_quoted(&mut self, delimiter: char) -> Result<(), ()> {
+        self.buffer.clear();
+
+        loop {
+            match self.read() {
+                '\0' => return Err(()),
+                '\\' => {
+                    self.skip();
+                    let c = self.lex_escape();
+                    self.buffer.push(c);
+                }
+                c if c == delimiter => {
+                    self.skip();
+                    return Ok(());
+                }
+                c => {
+                    self.skip();
+                    self.buffer.push(c);
+                }
+            }
+        }
+    }
+
+    /// Handles the escape sequences produced by `char::escape_debug`/`char::escape_default`,
+    /// called after consuming the `\`
+    fn lex_escape(&mut self) -> char {
+        match self.pop() {
+            '0' => '\0',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' if self.read() == '{' => {
+                self.skip();
+                let mut code = String::new();
+                while self.read().is_digit(16) {
+                    code.push(self.pop());
+                }
+                if self.read() == '}' {
+                    self.skip();
+                }
+                u32::from_str_radix(&code, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .unwrap_or('\u{fffd}')
+            }
+            c => c,
+        }
+    }
+
+    fn lex_number(&mut self) -> Token {
+        let mut num = String::new();
+
+        // Expect the first character to be either a sign or digit
+        let c = self.read();
+        debug_assert!(c == '-' || c.is_digit(10), "got {}", c);
+
+        if c == '-' {
+            num.push(self.pop());
+        } else if c == '0' && self.peek() == 'x' {
+            return self.lex_bytes();
+        }
+
+        while self.read().is_digit(10) {
+            num.push(self.pop());
+        }
+
+        // Floats are printed using the `Debug` format of `f64`, so they either contain a
+        // fractional part, an exponent, or both, e.g. `1.0`, `1e-7`, `1.5e20`
+        let mut is_float = false;
+        if self.read() == '.' && self.peek().is_digit(10) {
+            is_float = true;
+            num.push(self.pop());
+            while self.read().is_digit(10) {
+                num.push(self.pop());
+            }
+        }
+        if self.read() == 'e' || self.read() == 'E' {
+            let c2 = self.peek();
+            if c2.is_digit(10) || c2 == '-' || c2 == '+' {
+                is_float = true;
+                num.push(self.pop());
+                if c2 == '-' || c2 == '+' {
+                    num.push(self.pop());
+                }
+                if !self.read().is_digit(10) {
+                    return Token::Error(LexicalError::InvalidFloat {
+                        span: self.span(),
+                        reason: "expected digits after scientific notation".to_string(),
+                    });
+                }
+                while self.read().is_digit(10) {
+                    num.push(self.pop());
+                }
+            }
+        }
+
+        if is_float {
+            match f64::from_str(&num) {
+                Ok(f) => Token::Float(f),
+                Err(e) => Token::Error(LexicalError::InvalidFloat {
+                    span: self.span(),
+                    reason: e.to_string(),
+                }),
+            }
+        } else {
+            match Integer::from_string_radix(&num, 10) {
+                Some(i) => Token::Integer(i),
+                None => Token::Error(LexicalError::InvalidRadix {
+                    span: self.span(),
+                    reason: "invalid integer literal".to_string(),
+                }),
+            }
+        }
+    }
+
+    /// Lexes constant data printed in big-endian hexadecimal, e.g. `0xdeadbeef`
+    fn lex_bytes(&mut self) -> Token {
+        self.skip();
+        self.skip();
+
+        let mut digits = String::new();
+        while self.read().is_digit(16) {
+            digits.push(self.pop());
+        }
+
+        // Empty data is printed as `0x0`
+        if digits.is_empty() || digits == "0" {
+            return Token::Bytes(vec![]);
+        }
+        if digits.len() % 2 != 0 {
+            digits.insert(0, '0');
+        }
+        let mut bytes = Vec::with_capacity(digits.len() / 2);
+        for i in (0..digits.len()).step_by(2) {
+            bytes.push(u8::from_str_radix(&digits[i..(i + 2)], 16).unwrap());
+        }
+        bytes.reverse();
+        Token::Bytes(bytes)
+    }
+}
+
+macro_rules! pop {
+    ($lex:ident) => {{
+        $lex.skip();
+    }};
+    ($lex:ident, $code:expr) => {{
+        $lex.skip();
+        $code
+    }};
+}
+
+macro_rules! pop2 {
+    ($lex:ident, $code:expr) => {{
+        $lex.skip();
+        $lex.skip();
+        $code
+    }};
+}
+
+impl<S> Lexer<S>
+where
+    S: Source,
+{
+    fn tokenize(&mut self) -> Token {
+        let c = self.read();
+
+        if c == '\0' {
+            self.eof = true;
+            return Token::EOF;
+        }
+
+        match c {
+            '(' => pop!(self, Token::ParenOpen),
+            ')' => pop!(self, Token::ParenClose),
+            '[' => pop!(self, Token::SquareOpen),
+            ']' => pop!(self, Token::SquareClose),
+            '{' => pop!(self, Token::CurlyOpen),
+            '}' => pop!(self, Token::CurlyClose),
+            '<' => pop!(self, Token::AngleOpen),
+            '>' => pop!(self, Token::AngleClose),
+            ',' => pop!(self, Token::Comma),
+            ':' => pop!(self, Token::Colon),
+            ';' => pop!(self, Token::Semicolon),
+            '!' => pop!(self, Token::Bang),
+            '?' => pop!(self, Token::Question),
+            '=' if self.peek() == '>' => pop2!(self, Token::FatArrow),
+            '=' => pop!(self, Token::Equal),
+            '-' if self.peek() == '>' => pop2!(self, Token::RightArrow),
+            '-' if self.peek().is_digit(10) => self.lex_number(),
+            '0'..='9' => self.lex_number(),
+            'a'..='z' | '_' => self.lex_identifier(),
+            '\'' => self.lex_quoted_atom(),
+            '"' => self.lex_string(),
+            c => {
+                let start = self.span().start();
+                self.skip();
+                Token::Error(LexicalError::UnexpectedCharacter { start, found: c })
+            }
+        }
+    }
+}
+
+impl<S> Iterator for Lexer<S>
+where
+    S: Source,
+{
+    type Item = Result<(SourceIndex, Token, SourceIndex), LexicalError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lex()
+    }
+}
+
+/// Parses the index out of an entity name such as `v1` or `block2`
+fn entity_index(name: &str, prefix: &str) -> Option<u32> {
+    let digits = name.strip_prefix(prefix)?;
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
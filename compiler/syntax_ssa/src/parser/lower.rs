@@ -0,0 +1,824 @@
+///! Lowers the syntax tree produced by the parser into an SSA IR [`Module`].
+///!
+///! Block and value numbers are preserved, so that printing the resulting module
+///! reproduces the input, modulo whitespace and comments.
+use std::collections::{BTreeMap, BTreeSet};
+
+use cranelift_entity::EntityRef;
+
+use firefly_binary::{BinaryEntrySpecifier, Endianness};
+use firefly_diagnostics::{SourceSpan, Span, Spanned};
+use firefly_intern::Ident;
+use firefly_number::{Integer, ToPrimitive};
+use firefly_syntax_base::*;
+
+use crate::ir::*;
+
+use super::ast;
+use super::ParseError;
+
+pub(super) fn lower_module(ast: ast::Module) -> Result<Module, ParseError> {
+    let mut module = Module::new(ast.name);
+
+    // Declare all functions up front, so that calls can refer to functions defined later in the module
+    let mut declared = BTreeSet::new();
+    let mut functions = Vec::with_capacity(ast.functions.len());
+    for function in ast.functions.iter() {
+        let params = lower_types(&function.params)?;
+        let results = lower_types(&function.results)?;
+        let mut visibility = Visibility::DEFAULT;
+        if function.is_public {
+            visibility |= Visibility::PUBLIC;
+        }
+        if function.is_nif {
+            visibility |= Visibility::NIF;
+        }
+        let signature = Signature {
+            visibility,
+            cc: CallConv::Erlang,
+            module: module.name(),
+            name: function.name.name,
+            ty: FunctionType::new(params, results),
+        };
+        if !declared.insert(signature.mfa()) {
+            return Err(ParseError::invalid(
+                function.name.span,
+                format!("{} is defined more than once", signature.mfa()),
+            ));
+        }
+        let id = if function.is_closure {
+            module.declare_closure(signature.clone())
+        } else {
+            module.declare_function(signature.clone())
+        };
+        functions.push((id, signature));
+    }
+
+    for (function, (id, signature)) in ast.functions.into_iter().zip(functions.into_iter()) {
+        let function =
+            FunctionLowering::new(&mut module, id, signature, function.span).lower(function)?;
+        module.define_function(function);
+    }
+
+    Ok(module)
+}
+
+struct FunctionLowering<'m> {
+    module: &'m mut Module,
+    function: Function,
+    /// The types of all values defined in the function, by value number
+    types: BTreeMap<u32, Type>,
+    /// The block numbers defined in the function
+    blocks: BTreeSet<u32>,
+}
+impl<'m> FunctionLowering<'m> {
+    fn new(module: &'m mut Module, id: FuncRef, signature: Signature, span: SourceSpan) -> Self {
+        let function = Function::new(
+            id,
+            span,
+            signature,
+            module.signatures.clone(),
+            module.callees.clone(),
+            module.constants.clone(),
+        );
+        Self {
+            module,
+            function,
+            types: BTreeMap::new(),
+            blocks: BTreeSet::new(),
+        }
+    }
+
+    fn lower(mut self, function: ast::Function) -> Result<Function, ParseError> {
+        // Gather all block and value definitions, so that instructions can refer to blocks
+        // and values which appear later in the function
+        let entry = match function.blocks.first() {
+            Some(block) => block,
+            None => {
+                return Err(ParseError::invalid(
+                    function.name.span,
+                    "functions must have at least one block",
+                ))
+            }
+        };
+        if entry.params.len() != self.function.signature.arity() {
+            return Err(ParseError::invalid(
+                entry.span,
+                format!(
+                    "expected the entry block to have {} parameters, matching the function signature",
+                    self.function.signature.arity()
+                ),
+            ));
+        }
+        for block in function.blocks.iter() {
+            if !self.blocks.insert(block.id.item) {
+                return Err(ParseError::invalid(
+                    block.id.span(),
+                    format!("block{} is defined more than once", block.id.item),
+                ));
+            }
+            for param in block.params.iter() {
+                let ty = lower_type(&param.ty)?;
+                self.define_value(param.value, ty)?;
+            }
+            for inst in block.insts.iter() {
+                if inst.results.len() != inst.types.len() {
+                    return Err(ParseError::invalid(
+                        inst.span,
+                        format!(
+                            "expected {} result types, but got {}",
+                            inst.results.len(),
+                            inst.types.len()
+                        ),
+                    ));
+                }
+                for (value, ty) in inst.results.iter().zip(inst.types.iter()) {
+                    let ty = lower_type(ty)?;
+                    self.define_value(*value, ty)?;
+                }
+            }
+        }
+
+        // Allocate all of the block and value entities, so that their numbering matches the input
+        let dfg = &mut self.function.dfg;
+        let max_block = self.blocks.iter().next_back().copied().unwrap();
+        for _ in 0..=max_block {
+            dfg.blocks.create();
+        }
+        if let Some(max_value) = self.types.keys().next_back().copied() {
+            let placeholder = Block::new(entry.id.item as usize);
+            for _ in 0..=max_value {
+                dfg.values.push(ValueData::Param {
+                    ty: Type::Invalid,
+                    num: 0,
+                    block: placeholder,
+                    span: SourceSpan::UNKNOWN,
+                });
+            }
+        }
+        for block in function.blocks.iter() {
+            let id = Block::new(block.id.item as usize);
+            dfg.blocks.append(id, BlockData::new());
+            for param in block.params.iter() {
+                let value = Value::new(param.value.item as usize);
+                let num = dfg.blocks[id].params.push(value, &mut dfg.value_lists);
+                dfg.values[value] = ValueData::Param {
+                    ty: self.types[&param.value.item].clone(),
+                    num: num as u16,
+                    block: id,
+                    span: param.span,
+                };
+            }
+        }
+
+        for block in function.blocks.iter() {
+            let id = Block::new(block.id.item as usize);
+            for inst in block.insts.iter() {
+                let data = self.lower_inst(inst)?;
+                let dfg = &mut self.function.dfg;
+                let inst_id = dfg.push_inst(id, data, inst.span);
+                for value in inst.results.iter() {
+                    let v = Value::new(value.item as usize);
+                    let num = dfg.results[inst_id].push(v, &mut dfg.value_lists);
+                    dfg.values[v] = ValueData::Inst {
+                        ty: self.types[&value.item].clone(),
+                        num: num as u16,
+                        inst: inst_id,
+                    };
+                }
+            }
+        }
+
+        Ok(self.function)
+    }
+
+    fn define_value(&mut self, value: Span<u32>, ty: Type) -> Result<(), ParseError> {
+        if self.types.insert(value.item, ty).is_some() {
+            return Err(ParseError::invalid(
+                value.span(),
+                format!("v{} is defined more than once", value.item),
+            ));
+        }
+        Ok(())
+    }
+
+    fn value(&self, value: &Span<u32>) -> Result<Value, ParseError> {
+        if self.types.contains_key(&value.item) {
+            Ok(Value::new(value.item as usize))
+        } else {
+            Err(ParseError::invalid(
+                value.span(),
+                format!("v{} is not defined", value.item),
+            ))
+        }
+    }
+
+    fn value_type(&self, value: &Span<u32>) -> Type {
+        self.types.get(&value.item).cloned().unwrap_or_default()
+    }
+
+    fn values(&mut self, values: &[Span<u32>]) -> Result<ValueList, ParseError> {
+        let mut list = ValueList::default();
+        for value in values.iter() {
+            let value = self.value(value)?;
+            list.push(value, &mut self.function.dfg.value_lists);
+        }
+        Ok(list)
+    }
+
+    fn block(&self, block: &Span<u32>) -> Result<Block, ParseError> {
+        if self.blocks.contains(&block.item) {
+            Ok(Block::new(block.item as usize))
+        } else {
+            Err(ParseError::invalid(
+                block.span(),
+                format!("block{} is not defined", block.item),
+            ))
+        }
+    }
+
+    fn lower_inst(&mut self, inst: &ast::Inst) -> Result<InstData, ParseError> {
+        use ast::Operand;
+
+        let (op, spec) = lower_opcode(inst)?;
+        let operands = inst.operands.as_slice();
+        let result_type = inst
+            .types
+            .first()
+            .map(lower_type)
+            .transpose()?
+            .unwrap_or_default();
+
+        let data = match (op, operands) {
+            (
+                Opcode::Call | Opcode::Enter,
+                [Operand::Apply(span, ast::Callee::Function(name), args)],
+            ) => {
+                let callee = self.callee(*span, name)?;
+                self.check_arity(*span, name, args.len())?;
+                let args = self.values(args)?;
+                InstData::Call(Call { op, callee, args })
+            }
+            (
+                Opcode::CallIndirect | Opcode::EnterIndirect,
+                [Operand::Apply(_, ast::Callee::Value(callee), args)],
+            ) => {
+                let callee = self.value(callee)?;
+                let args = self.values(args)?;
+                InstData::CallIndirect(CallIndirect { op, callee, args })
+            }
+            (Opcode::MakeFun, [Operand::Apply(span, ast::Callee::Function(name), env)]) => {
+                let callee = self.callee(*span, name)?;
+                let env = self.values(env)?;
+                InstData::MakeFun(MakeFun { callee, env })
+            }
+            (Opcode::Br, [Operand::Block(_, dest, args)]) => {
+                let destination = self.block(dest)?;
+                let args = self.values(args)?;
+                InstData::Br(Br {
+                    op,
+                    destination,
+                    args,
+                })
+            }
+            (
+                Opcode::BrIf | Opcode::BrUnless,
+                [Operand::Value(cond), Operand::Block(_, dest, args)],
+            ) => {
+                let cond = self.value(cond)?;
+                let destination = self.block(dest)?;
+                let mut list = self.values(args)?;
+                list.insert(0, cond, &mut self.function.dfg.value_lists);
+                InstData::Br(Br {
+                    op,
+                    destination,
+                    args: list,
+                })
+            }
+            (
+                Opcode::CondBr,
+                [Operand::Value(cond), Operand::Block(_, then_dest, then_args), Operand::Block(_, else_dest, else_args)],
+            ) => {
+                let cond = self.value(cond)?;
+                let then_dest = (self.block(then_dest)?, self.values(then_args)?);
+                let else_dest = (self.block(else_dest)?, self.values(else_args)?);
+                InstData::CondBr(CondBr {
+                    cond,
+                    then_dest,
+                    else_dest,
+                })
+            }
+            (
+                Opcode::Switch,
+                [Operand::Value(arg), arms @ .., Operand::Block(_, default, default_args)],
+            ) if default_args.is_empty() => {
+                let arg = self.value(arg)?;
+                let arms = arms
+                    .iter()
+                    .map(|arm| match arm {
+                        Operand::Arm(span, value, dest) => {
+                            let value = value.to_u32().ok_or_else(|| {
+                                ParseError::invalid(*span, "invalid switch value")
+                            })?;
+                            Ok((value, self.block(dest)?))
+                        }
+                        other => Err(ParseError::invalid(
+                            other.span(),
+                            "expected a switch arm, e.g. `0 => block1`",
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let default = self.block(default)?;
+                InstData::Switch(Switch {
+                    op,
+                    arg,
+                    arms,
+                    default,
+                })
+            }
+            (Opcode::Ret, [Operand::Value(a), Operand::Value(b)]) => InstData::Ret(Ret {
+                op,
+                args: [self.value(a)?, self.value(b)?],
+            }),
+            (Opcode::Ret, [imm, Operand::Value(arg)]) => InstData::RetImm(RetImm {
+                op,
+                imm: self.immediate(imm, &Type::Primitive(PrimitiveType::I1))?,
+                arg: self.value(arg)?,
+            }),
+            (Opcode::IsType, [Operand::Value(arg), ty]) => {
+                let ty = match ty {
+                    Operand::Ident(name) => lower_named_type(name)?,
+                    Operand::Type(ty) => lower_type(ty)?,
+                    other => return Err(ParseError::invalid(other.span(), "expected a type")),
+                };
+                InstData::IsType(IsType {
+                    arg: self.value(arg)?,
+                    ty,
+                })
+            }
+            (Opcode::BitsMatch | Opcode::BitsPush, args) => {
+                let spec = spec.unwrap();
+                let args = args
+                    .iter()
+                    .map(|arg| self.operand_value(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let args = self.values(&args)?;
+                if op == Opcode::BitsMatch {
+                    InstData::BitsMatch(BitsMatch { spec, args })
+                } else {
+                    InstData::BitsPush(BitsPush { spec, args })
+                }
+            }
+            (Opcode::BitsMatchSkip, [args @ .., value]) => {
+                let spec = spec.unwrap();
+                let args = args
+                    .iter()
+                    .map(|arg| self.operand_value(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let args = self.values(&args)?;
+                let value = self.immediate(value, &Type::Primitive(PrimitiveType::I64))?;
+                InstData::BitsMatchSkip(BitsMatchSkip { spec, args, value })
+            }
+            (
+                Opcode::SetElement | Opcode::SetElementMut,
+                [Operand::Index(span, tuple, index), value],
+            ) => {
+                let index = self.immediate(
+                    &Operand::Integer(*span, index.clone()),
+                    &Type::Primitive(PrimitiveType::Isize),
+                )?;
+                let tuple = self.value(tuple)?;
+                match value {
+                    Operand::Value(value) => InstData::SetElement(SetElement {
+                        op,
+                        index,
+                        args: [tuple, self.value(value)?],
+                    }),
+                    imm => InstData::SetElementImm(SetElementImm {
+                        op,
+                        arg: tuple,
+                        index,
+                        value: self.immediate(imm, &Type::Unknown)?,
+                    }),
+                }
+            }
+            (Opcode::ConstBigInt | Opcode::ConstBinary, [constant]) => {
+                let constant = self.constant(constant)?;
+                InstData::UnaryOpConst(UnaryOpConst {
+                    op,
+                    imm: self.function.dfg.make_constant(constant),
+                })
+            }
+            (
+                Opcode::RecvStart
+                | Opcode::RecvNext
+                | Opcode::RecvPeek
+                | Opcode::RecvPop
+                | Opcode::RecvWait
+                | Opcode::RecvDone
+                | Opcode::BitsMatchStart
+                | Opcode::Raise
+                | Opcode::NifStart
+                | Opcode::ExceptionClass
+                | Opcode::ExceptionReason
+                | Opcode::ExceptionTrace,
+                args,
+            ) => match args {
+                [imm, args @ ..] if !matches!(imm, Operand::Value(_)) => {
+                    let imm = self.immediate(imm, &Type::Unknown)?;
+                    let args = args
+                        .iter()
+                        .map(|arg| self.operand_value(arg))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let args = self.values(&args)?;
+                    InstData::PrimOpImm(PrimOpImm { op, imm, args })
+                }
+                args => {
+                    let args = args
+                        .iter()
+                        .map(|arg| self.operand_value(arg))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let args = self.values(&args)?;
+                    InstData::PrimOp(PrimOp { op, args })
+                }
+            },
+            (
+                Opcode::Call
+                | Opcode::Enter
+                | Opcode::CallIndirect
+                | Opcode::EnterIndirect
+                | Opcode::MakeFun
+                | Opcode::Br
+                | Opcode::BrIf
+                | Opcode::BrUnless
+                | Opcode::CondBr
+                | Opcode::Switch
+                | Opcode::Ret
+                | Opcode::IsType
+                | Opcode::BitsMatchSkip
+                | Opcode::SetElement
+                | Opcode::SetElementMut
+                | Opcode::ConstBigInt
+                | Opcode::ConstBinary,
+                _,
+            ) => {
+                return Err(ParseError::invalid(
+                    inst.span,
+                    format!("invalid operands for {}", op),
+                ))
+            }
+            (_, [Operand::Value(arg)]) => InstData::UnaryOp(UnaryOp {
+                op,
+                arg: self.value(arg)?,
+            }),
+            (_, [imm]) => {
+                // The type of an immediate is the type of the value it produces, with the exception
+                // of `null` which always uses `none`, and `tuple` which takes the arity of the tuple
+                let ty = match op {
+                    Opcode::ImmNull => Type::Unknown,
+                    Opcode::Tuple => Type::Primitive(PrimitiveType::Isize),
+                    _ => result_type,
+                };
+                InstData::UnaryOpImm(UnaryOpImm {
+                    op,
+                    imm: self.immediate(imm, &ty)?,
+                })
+            }
+            (_, [Operand::Value(a), Operand::Value(b)]) => InstData::BinaryOp(BinaryOp {
+                op,
+                args: [self.value(a)?, self.value(b)?],
+            }),
+            (_, [Operand::Value(arg), imm]) => {
+                let ty = match op {
+                    Opcode::GetElement | Opcode::UnpackEnv | Opcode::BitsTestTail => {
+                        Type::Primitive(PrimitiveType::Isize)
+                    }
+                    Opcode::IsTaggedTuple => Type::Unknown,
+                    _ => self.value_type(arg),
+                };
+                InstData::BinaryOpImm(BinaryOpImm {
+                    op,
+                    arg: self.value(arg)?,
+                    imm: self.immediate(imm, &ty)?,
+                })
+            }
+            _ => {
+                return Err(ParseError::invalid(
+                    inst.span,
+                    format!("invalid operands for {}", op),
+                ))
+            }
+        };
+
+        Ok(data)
+    }
+
+    /// Resolves a function reference to a callee, registering builtins and natives on demand
+    fn callee(&mut self, span: SourceSpan, name: &FunctionName) -> Result<FuncRef, ParseError> {
+        match name.module {
+            Some(module) if module == self.module.name() => {
+                self.module.get_callee(*name).ok_or_else(|| {
+                    ParseError::invalid(span, format!("{} is not defined in this module", name))
+                })
+            }
+            Some(_) if bifs::get(name).is_some() => Ok(self.module.get_or_register_builtin(*name)),
+            Some(_) => Ok(self.function.dfg.register_callee(*name)),
+            None if nifs::get(&name.function).is_some() => {
+                Ok(self.module.get_or_register_native(name.function))
+            }
+            None => Err(ParseError::invalid(
+                span,
+                format!("{} is not a known native function", name),
+            )),
+        }
+    }
+
+    fn check_arity(
+        &self,
+        span: SourceSpan,
+        name: &FunctionName,
+        num_args: usize,
+    ) -> Result<(), ParseError> {
+        if name.arity as usize == num_args {
+            Ok(())
+        } else {
+            Err(ParseError::invalid(
+                span,
+                format!(
+                    "{} expects {} arguments, but got {}",
+                    name, name.arity, num_args
+                ),
+            ))
+        }
+    }
+
+    fn operand_value(&self, operand: &ast::Operand) -> Result<Span<u32>, ParseError> {
+        match operand {
+            ast::Operand::Value(value) => Ok(*value),
+            other => Err(ParseError::invalid(other.span(), "expected a value")),
+        }
+    }
+
+    /// Converts an operand to an immediate of the given type.
+    ///
+    /// If the type is not primitive, the immediate is a term, and its type is inferred from the syntax.
+    fn immediate(&self, operand: &ast::Operand, ty: &Type) -> Result<Immediate, ParseError> {
+        use ast::Operand;
+
+        let span = operand.span();
+        let invalid = || ParseError::invalid(span, format!("expected an immediate of type {}", ty));
+        let imm = match (ty, operand) {
+            (Type::Primitive(PrimitiveType::I1), Operand::Ident(id)) => match id.as_str().get() {
+                "true" => Immediate::I1(true),
+                "false" => Immediate::I1(false),
+                _ => return Err(invalid()),
+            },
+            (Type::Primitive(prim), Operand::Integer(_, i)) => {
+                let i = i.to_i64().ok_or_else(invalid)?;
+                match prim {
+                    PrimitiveType::I8 => Immediate::I8(i.try_into().map_err(|_| invalid())?),
+                    PrimitiveType::I16 => Immediate::I16(i.try_into().map_err(|_| invalid())?),
+                    PrimitiveType::I32 => Immediate::I32(i.try_into().map_err(|_| invalid())?),
+                    PrimitiveType::I64 => Immediate::I64(i),
+                    PrimitiveType::Isize => Immediate::Isize(i.try_into().map_err(|_| invalid())?),
+                    _ => return Err(invalid()),
+                }
+            }
+            (Type::Primitive(PrimitiveType::F64), Operand::Float(_, f)) => Immediate::F64(*f),
+            (Type::Primitive(_), _) => return Err(invalid()),
+            (_, Operand::Integer(_, i)) => match i {
+                Integer::Small(i) => Immediate::Term(ImmediateTerm::Integer(*i)),
+                Integer::Big(_) => {
+                    return Err(ParseError::invalid(
+                        span,
+                        "integer is too large for an immediate, use const.bigint",
+                    ))
+                }
+            },
+            (_, Operand::Float(_, f)) => Immediate::Term(ImmediateTerm::Float(*f)),
+            (_, Operand::Atom(_, a)) => Immediate::Term(ImmediateTerm::Atom(*a)),
+            (_, Operand::Nil(_)) => Immediate::Term(ImmediateTerm::Nil),
+            (_, Operand::Ident(id)) => match id.as_str().get() {
+                "true" => Immediate::Term(ImmediateTerm::Bool(true)),
+                "false" => Immediate::Term(ImmediateTerm::Bool(false)),
+                "none" => Immediate::Term(ImmediateTerm::None),
+                _ => Immediate::Term(ImmediateTerm::Atom(id.name)),
+            },
+            _ => return Err(ParseError::invalid(span, "expected an immediate")),
+        };
+        Ok(imm)
+    }
+
+    fn constant(&self, operand: &ast::Operand) -> Result<ConstantItem, ParseError> {
+        use ast::Operand;
+
+        match operand {
+            Operand::Integer(_, i) => Ok(ConstantItem::Integer(i.clone())),
+            Operand::Float(_, f) => Ok(ConstantItem::Float(*f)),
+            Operand::Atom(_, a) => Ok(ConstantItem::Atom(*a)),
+            Operand::Ident(id) => match id.as_str().get() {
+                "true" => Ok(ConstantItem::Bool(true)),
+                "false" => Ok(ConstantItem::Bool(false)),
+                _ => Ok(ConstantItem::Atom(id.name)),
+            },
+            Operand::String(_, s) => Ok(ConstantItem::String(s.as_str().get().to_string())),
+            Operand::Bytes(_, bytes) => Ok(ConstantItem::Bytes(bytes.clone().into())),
+            Operand::Binary(_, bin) => Ok(ConstantItem::Bitstring(bin.clone())),
+            other => Err(ParseError::invalid(other.span(), "expected a constant")),
+        }
+    }
+}
+
+/// Parses an opcode, along with the binary entry specifier which is part of the name of the
+/// `bs.match`, `bs.match.skip` and `bs.push` instructions, e.g. `bs.push.uint.big(8)`
+fn lower_opcode(inst: &ast::Inst) -> Result<(Opcode, Option<BinaryEntrySpecifier>), ParseError> {
+    let name = inst.opcode.as_str().get();
+    let unit = match inst.unit.as_ref() {
+        None => None,
+        Some(unit) => match unit.to_u8() {
+            Some(u) if u > 0 => Some(u),
+            _ => return Err(ParseError::invalid(unit.span(), "invalid unit")),
+        },
+    };
+
+    if let Ok(op) = name.parse::<Opcode>() {
+        if unit.is_some() {
+            return Err(ParseError::invalid(
+                inst.opcode.span,
+                format!("{} does not accept a unit", op),
+            ));
+        }
+        if let Opcode::BitsMatch | Opcode::BitsMatchSkip | Opcode::BitsPush = op {
+            return Err(ParseError::invalid(
+                inst.opcode.span,
+                format!("{} requires a binary entry specifier", op),
+            ));
+        }
+        return Ok((op, None));
+    }
+
+    for (prefix, op) in [
+        ("bs.match.skip.", Opcode::BitsMatchSkip),
+        ("bs.match.", Opcode::BitsMatch),
+        ("bs.push.", Opcode::BitsPush),
+    ] {
+        if let Some(spec) = name.strip_prefix(prefix) {
+            let spec = lower_spec(spec, unit).ok_or_else(|| {
+                ParseError::invalid(inst.opcode.span, "invalid binary entry specifier")
+            })?;
+            return Ok((op, Some(spec)));
+        }
+    }
+
+    Err(ParseError::invalid(
+        inst.opcode.span,
+        format!("unknown opcode '{}'", name),
+    ))
+}
+
+fn lower_spec(spec: &str, unit: Option<u8>) -> Option<BinaryEntrySpecifier> {
+    let (ty, endianness) = match spec.split_once('.') {
+        None => (spec, None),
+        Some((ty, endianness)) => {
+            let endianness = match endianness {
+                "big" => Endianness::Big,
+                "little" => Endianness::Little,
+                "native" => Endianness::Native,
+                _ => return None,
+            };
+            (ty, Some(endianness))
+        }
+    };
+
+    match (ty, endianness) {
+        ("sint" | "uint", Some(endianness)) => Some(BinaryEntrySpecifier::Integer {
+            signed: ty == "sint",
+            endianness,
+            unit: unit.unwrap_or(1),
+        }),
+        ("float", Some(endianness)) => Some(BinaryEntrySpecifier::Float {
+            endianness,
+            unit: unit.unwrap_or(1),
+        }),
+        ("bytes", None) if unit.is_none() => Some(BinaryEntrySpecifier::Binary { unit: 8 }),
+        ("bits", None) => Some(BinaryEntrySpecifier::Binary {
+            unit: unit.unwrap_or(1),
+        }),
+        ("utf8", None) if unit.is_none() => Some(BinaryEntrySpecifier::Utf8),
+        ("utf16", Some(endianness)) if unit.is_none() => {
+            Some(BinaryEntrySpecifier::Utf16 { endianness })
+        }
+        ("utf32", Some(endianness)) if unit.is_none() => {
+            Some(BinaryEntrySpecifier::Utf32 { endianness })
+        }
+        _ => None,
+    }
+}
+
+fn lower_types(types: &[ast::Type]) -> Result<Vec<Type>, ParseError> {
+    types.iter().map(lower_type).collect()
+}
+
+fn lower_type(ty: &ast::Type) -> Result<Type, ParseError> {
+    match ty {
+        ast::Type::Unknown(_) => Ok(Type::Unknown),
+        ast::Type::NoReturn(_) => Ok(Type::NoReturn),
+        ast::Type::Named(name) => lower_named_type(name),
+        ast::Type::Generic(span, name, params) => match (name.as_str().get(), params.as_slice()) {
+            ("ptr", [pointee]) => Ok(Type::Primitive(PrimitiveType::Ptr(Box::new(
+                lower_primitive_type(pointee)?,
+            )))),
+            ("list", [element]) => Ok(Type::Term(TermType::List(Some(Box::new(lower_term_type(
+                element,
+            )?))))),
+            ("tuple", elements) => Ok(Type::Term(TermType::Tuple(Some(
+                elements
+                    .iter()
+                    .map(lower_term_type)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )))),
+            _ => Err(ParseError::invalid(*span, "invalid type")),
+        },
+        ast::Type::Struct(_, fields) => Ok(Type::Primitive(PrimitiveType::Struct(
+            fields
+                .iter()
+                .map(lower_primitive_type)
+                .collect::<Result<Vec<_>, _>>()?,
+        ))),
+        ast::Type::Array(span, element, len) => {
+            let len = len
+                .to_usize()
+                .ok_or_else(|| ParseError::invalid(*span, "invalid array length"))?;
+            Ok(Type::Primitive(PrimitiveType::Array(
+                Box::new(lower_primitive_type(element)?),
+                len,
+            )))
+        }
+        ast::Type::Function(_, params, results) => Ok(Type::Function(FunctionType::new(
+            lower_types(params)?,
+            lower_types(results)?,
+        ))),
+        ast::Type::Fun(_, params, results) => Ok(Type::Term(TermType::Fun(Some(Box::new(
+            FunctionType::new(lower_types(params)?, lower_types(results)?),
+        ))))),
+    }
+}
+
+fn lower_primitive_type(ty: &ast::Type) -> Result<PrimitiveType, ParseError> {
+    match lower_type(ty)? {
+        Type::Primitive(prim) => Ok(prim),
+        _ => Err(ParseError::invalid(ty.span(), "expected a primitive type")),
+    }
+}
+
+fn lower_term_type(ty: &ast::Type) -> Result<TermType, ParseError> {
+    match lower_type(ty)? {
+        Type::Term(term) => Ok(term),
+        _ => Err(ParseError::invalid(ty.span(), "expected a term type")),
+    }
+}
+
+fn lower_named_type(name: &Ident) -> Result<Type, ParseError> {
+    let ty = match name.as_str().get() {
+        "invalid" => Type::Invalid,
+        "exception" => Type::Exception,
+        "trace" => Type::ExceptionTrace,
+        "recv_context" => Type::RecvContext,
+        "recv_state" => Type::RecvState,
+        "binary_builder" => Type::BinaryBuilder,
+        "match_context" => Type::MatchContext,
+        "void" => Type::Primitive(PrimitiveType::Void),
+        "i1" => Type::Primitive(PrimitiveType::I1),
+        "i8" => Type::Primitive(PrimitiveType::I8),
+        "i16" => Type::Primitive(PrimitiveType::I16),
+        "i32" => Type::Primitive(PrimitiveType::I32),
+        "i64" => Type::Primitive(PrimitiveType::I64),
+        "isize" => Type::Primitive(PrimitiveType::Isize),
+        "f64" => Type::Primitive(PrimitiveType::F64),
+        "term" => Type::Term(TermType::Any),
+        "bool" => Type::Term(TermType::Bool),
+        "int" => Type::Term(TermType::Integer),
+        "float" => Type::Term(TermType::Float),
+        "number" => Type::Term(TermType::Number),
+        "atom" => Type::Term(TermType::Atom),
+        "bits" => Type::Term(TermType::Bitstring),
+        "bytes" => Type::Term(TermType::Binary),
+        "nil" => Type::Term(TermType::Nil),
+        "cons" => Type::Term(TermType::Cons),
+        "list" => Type::Term(TermType::List(None)),
+        "list?" => Type::Term(TermType::MaybeImproperList),
+        "tuple" => Type::Term(TermType::Tuple(None)),
+        "map" => Type::Term(TermType::Map),
+        "reference" => Type::Term(TermType::Reference),
+        "port" => Type::Term(TermType::Port),
+        "pid" => Type::Term(TermType::Pid),
+        "fun" => Type::Term(TermType::Fun(None)),
+        other => {
+            return Err(ParseError::invalid(
+                name.span,
+                format!("unknown type '{}'", other),
+            ))
+        }
+    };
+    Ok(ty)
+}
@@ -0,0 +1,236 @@
+///! This module provides a parser for the textual form of the SSA IR, as produced by
+///! `firefly_syntax_ssa::write`, so that modules can be written or edited by hand and
+///! fed back into the compiler, e.g. for testing codegen in isolation.
+pub mod ast;
+
+mod error;
+pub use self::error::{LexicalError, ParseError};
+
+mod lexer;
+pub use self::lexer::Lexer;
+
+mod lower;
+
+mod token;
+pub use self::token::{LexicalToken, Token};
+
+use std::sync::Arc;
+
+use firefly_diagnostics::{CodeMap, Reporter, SourceIndex};
+use firefly_intern::Symbol;
+use firefly_parser::{Parse, Parser, Scanner, Source};
+
+use crate::Module;
+
+/// Used in the grammar for easy span creation
+macro_rules! span {
+    ($l:expr, $r:expr) => {
+        SourceSpan::new($l, $r)
+    };
+    ($i:expr) => {
+        SourceSpan::new($i, $i)
+    };
+}
+
+#[cfg_attr(rustfmt, rustfmt_skip)]
+#[allow(unknown_lints)]
+#[allow(clippy)]
+#[allow(unused_parens)]
+pub(crate) mod grammar {
+    // During the build step, `build.rs` will output the generated parser to `OUT_DIR` to avoid
+    // adding it to the source directory, so we just directly include the generated parser here.
+    //
+    // Even with `.gitignore` and the `exclude` in the `Cargo.toml`, the generated parser can still
+    // end up in the source directory. This could happen when `cargo build` builds the file out of
+    // the Cargo cache (`$HOME/.cargo/registrysrc`), and the build script would then put its output
+    // in that cached source directory because of https://github.com/lalrpop/lalrpop/issues/280.
+    // Later runs of `cargo vendor` then copy the source from that directory, including the
+    // generated file.
+    include!(concat!(env!("OUT_DIR"), "/parser/grammar.rs"));
+}
+
+/// A segment of a binary constant, as printed by `BitVec::display`
+pub(crate) enum BinarySegment {
+    String(Symbol),
+    Byte(u8),
+}
+
+impl Parse for Module {
+    type Parser = grammar::ModuleParser;
+    type Error = ParseError;
+    type Config = ();
+    type Token = Result<(SourceIndex, Token, SourceIndex), LexicalError>;
+
+    fn root_file_error(source: std::io::Error, path: std::path::PathBuf) -> Self::Error {
+        ParseError::RootFileError { source, path }
+    }
+
+    fn parse<S>(parser: &Parser<()>, reporter: Reporter, source: S) -> Result<Self, Self::Error>
+    where
+        S: Source,
+    {
+        let scanner = Scanner::new(source);
+        let lexer = Lexer::new(scanner);
+        Self::parse_tokens(reporter, parser.codemap.clone(), lexer)
+    }
+
+    fn parse_tokens<S>(
+        _reporter: Reporter,
+        _codemap: Arc<CodeMap>,
+        tokens: S,
+    ) -> Result<Self, Self::Error>
+    where
+        S: IntoIterator<Item = Self::Token>,
+    {
+        let module = Self::Parser::new().parse(tokens)?;
+        lower::lower_module(module)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use firefly_diagnostics::*;
+    use firefly_parser::Parser;
+
+    use crate::Module;
+
+    use super::{LexicalError, ParseError};
+
+    fn parse(input: &str) -> Module {
+        let codemap = Arc::new(CodeMap::new());
+        let parser = Parser::new((), codemap.clone());
+        let reporter = Reporter::new();
+        match parser.parse_string::<Module, _, _>(reporter.clone(), input) {
+            Ok(module) => module,
+            Err(err) => {
+                reporter.diagnostic(err.to_diagnostic());
+                reporter.print(&codemap);
+                panic!("parsing failed");
+            }
+        }
+    }
+
+    fn print(module: &Module) -> String {
+        let mut out = vec![];
+        crate::write::write_module(&mut out, module).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Parses the given input, and asserts that printing the result reproduces it exactly
+    fn roundtrip(input: &str) {
+        let module = parse(input);
+        assert_eq!(print(&module), input);
+    }
+
+    #[test]
+    fn simple() {
+        let module = parse(
+            "module foo
+
+pub function bar(term) -> i1, term  {
+block0(v0: term):
+    ret false, v0
+}
+",
+        );
+        assert_eq!(module.name().as_str().get(), "foo");
+        assert_eq!(module.functions.len(), 1);
+        let function = &module.functions[0];
+        assert!(function.signature.visibility.is_public());
+        assert_eq!(function.signature.arity(), 1);
+        assert_eq!(function.dfg.blocks().count(), 1);
+    }
+
+    #[test]
+    fn calls_and_control_flow() {
+        roundtrip(
+            "module foo
+
+pub function bar(term, term) -> i1, term  {
+block0(v0: term, v1: term):
+    v2, v3 = call erlang:'+'/2(v0, v1)  : i1, term
+    cond.br v2, block1, block2(v3)
+
+block1:
+    v4 = exception.class v3  : atom
+    v5 = tuple.is_tagged v3, 'EXIT'  : i1
+    br.if v5, block3(v4)
+    tail call foo:baz/1(v4)
+
+block2(v6: term):
+    v7 = call __firefly_map_empty/0()  : map
+    v8 = fun.make foo:'-bar/2-fun-0-'/1(v6)  : fun
+    ret false, v8
+
+block3(v9: term):
+    v10 = const.atom 'true'  : atom
+    v11 = const.int 42  : int
+    v12 = const.float -1e-7  : float
+    switch v11, 0 => block1, 1 => block3, block1
+}
+
+function baz(term) -> i1, term  {
+block0(v0: term):
+    v1 = const.bigint 100000000000000000000000  : int
+    v2 = const.binary \"hello\\n\"  : bytes
+    v3 = const.binary <<255,1>>  : bytes
+    v4 = tuple 2  : tuple
+    v5 = tuple.set v4[0], v1  : tuple
+    v6 = tuple.set.mut v5[1], []  : tuple
+    v7 = is_type v6, tuple<term, term>  : i1
+    v8 = bs.push.uint.big(8) v0, v1  : i1, term
+    ret v7, v6
+}
+
+closure function '-bar/2-fun-0-'(term) -> i1, term  {
+block0(v0: term):
+    v1 = fun.env.get v0, 0  : term
+    v2 = call.indirect v1(v0)  : i1, term
+    v3 = const.none none  : term
+    ret v2, v3
+}
+",
+        );
+    }
+
+    #[test]
+    fn undefined_block() {
+        let codemap = Arc::new(CodeMap::new());
+        let parser = Parser::new((), codemap.clone());
+        let reporter = Reporter::new();
+        let result = parser.parse_string::<Module, _, _>(
+            reporter,
+            "module foo
+
+function bar() -> i1, term  {
+block0:
+    br block1
+}
+",
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn unclosed_string() {
+        let codemap = Arc::new(CodeMap::new());
+        let parser = Parser::new((), codemap.clone());
+        let reporter = Reporter::new();
+        let result = parser.parse_string::<Module, _, _>(
+            reporter,
+            "module foo
+
+function bar() -> i1, term  {
+block0:
+    v0 = const.binary \"hello  : bytes
+}
+",
+        );
+        assert!(matches!(
+            result,
+            Err(ParseError::Lexer(LexicalError::UnclosedString { .. }))
+        ));
+    }
+}
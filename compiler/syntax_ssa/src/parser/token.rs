@@ -0,0 +1,140 @@
+use std::fmt;
+
+use firefly_diagnostics::{SourceIndex, SourceSpan};
+use firefly_intern::Symbol;
+use firefly_number::Integer;
+use firefly_syntax_base::FunctionName;
+
+use super::LexicalError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexicalToken(pub SourceIndex, pub Token, pub SourceIndex);
+impl LexicalToken {
+    #[inline]
+    pub fn token(&self) -> Token {
+        self.1.clone()
+    }
+
+    #[inline]
+    pub fn span(&self) -> SourceSpan {
+        SourceSpan::new(self.0, self.2)
+    }
+}
+impl fmt::Display for LexicalToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.token())
+    }
+}
+impl Into<(SourceIndex, Token, SourceIndex)> for LexicalToken {
+    fn into(self) -> (SourceIndex, Token, SourceIndex) {
+        (self.0, self.1, self.2)
+    }
+}
+impl From<(SourceIndex, Token, SourceIndex)> for LexicalToken {
+    fn from(triple: (SourceIndex, Token, SourceIndex)) -> LexicalToken {
+        LexicalToken(triple.0, triple.1, triple.2)
+    }
+}
+
+/// The tokens of the textual SSA IR, as produced by `firefly_syntax_ssa::write`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    EOF,
+    Error(LexicalError),
+
+    /// Line breaks are significant, as they terminate block headers and instructions.
+    ///
+    /// Consecutive line breaks are collapsed into a single token
+    Newline,
+
+    // Keywords
+    Module,
+    Function,
+    Pub,
+    Nif,
+    Closure,
+    Tail,
+
+    // Punctuation
+    Comma,
+    Colon,
+    Semicolon,
+    Equal,
+    RightArrow,
+    FatArrow,
+    Bang,
+    Question,
+    ParenOpen,
+    ParenClose,
+    SquareOpen,
+    SquareClose,
+    CurlyOpen,
+    CurlyClose,
+    AngleOpen,
+    AngleClose,
+
+    // Identifiers
+    /// An unquoted name, e.g. an opcode, type, or atom
+    Ident(Symbol),
+    /// A quoted atom
+    Atom(Symbol),
+    /// A value reference, e.g. `v1`
+    Value(u32),
+    /// A block reference, e.g. `block1`
+    Block(u32),
+    /// A function reference, e.g. `erlang:'+'/2` or `__firefly_map_put/3`
+    FunctionName(FunctionName),
+
+    // Literals
+    Integer(Integer),
+    Float(f64),
+    String(Symbol),
+    /// Hex-encoded constant data, e.g. `0xdeadbeef`, stored in the order used by `ConstantData`
+    Bytes(Vec<u8>),
+}
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EOF => f.write_str("EOF"),
+            Self::Error(_) => f.write_str("ERROR"),
+            Self::Newline => f.write_str("NEWLINE"),
+            Self::Module => f.write_str("module"),
+            Self::Function => f.write_str("function"),
+            Self::Pub => f.write_str("pub"),
+            Self::Nif => f.write_str("nif"),
+            Self::Closure => f.write_str("closure"),
+            Self::Tail => f.write_str("tail"),
+            Self::Comma => f.write_str(","),
+            Self::Colon => f.write_str(":"),
+            Self::Semicolon => f.write_str(";"),
+            Self::Equal => f.write_str("="),
+            Self::RightArrow => f.write_str("->"),
+            Self::FatArrow => f.write_str("=>"),
+            Self::Bang => f.write_str("!"),
+            Self::Question => f.write_str("?"),
+            Self::ParenOpen => f.write_str("("),
+            Self::ParenClose => f.write_str(")"),
+            Self::SquareOpen => f.write_str("["),
+            Self::SquareClose => f.write_str("]"),
+            Self::CurlyOpen => f.write_str("{"),
+            Self::CurlyClose => f.write_str("}"),
+            Self::AngleOpen => f.write_str("<"),
+            Self::AngleClose => f.write_str(">"),
+            Self::Ident(id) => write!(f, "{}", id),
+            Self::Atom(a) => write!(f, "'{}'", a),
+            Self::Value(v) => write!(f, "v{}", v),
+            Self::Block(b) => write!(f, "block{}", b),
+            Self::FunctionName(name) => write!(f, "{}", name),
+            Self::Integer(i) => write!(f, "{}", i),
+            Self::Float(flt) => write!(f, "{:?}", flt),
+            Self::String(s) => write!(f, "{:?}", s.as_str().get()),
+            Self::Bytes(bytes) => {
+                f.write_str("0x")?;
+                for b in bytes.iter().rev() {
+                    write!(f, "{:02x}", b)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
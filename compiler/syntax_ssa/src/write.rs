@@ -1,13 +1,31 @@
 use std::fmt;
 use std::io::{self, Write};
 
-use super::{Block, DataFlowGraph, Function, Immediate, Inst, Value};
+use firefly_intern::Symbol;
+use firefly_syntax_base::FunctionName;
+
+use super::{Block, DataFlowGraph, Function, Immediate, ImmediateTerm, Inst, Module, Value};
+
+pub fn write_module(w: &mut dyn Write, module: &Module) -> io::Result<()> {
+    writeln!(w, "module {}", DisplayAtom(module.name()))?;
+    for function in module.functions.iter() {
+        writeln!(w)?;
+        if module.is_closure(&function.signature.mfa().to_local()) {
+            write!(w, "closure ")?;
+        }
+        write_function(w, function)?;
+    }
+    Ok(())
+}
 
 pub fn write_function(w: &mut dyn Write, func: &Function) -> io::Result<()> {
     let is_public = func.signature.visibility.is_public();
     if is_public {
         write!(w, "pub ")?;
     }
+    if func.signature.visibility.is_nif() {
+        write!(w, "nif ")?;
+    }
     write!(w, "function ")?;
     write_spec(w, func)?;
     if func.signature.visibility.is_externally_defined() {
@@ -31,7 +49,7 @@ pub fn write_function(w: &mut dyn Write, func: &Function) -> io::Result<()> {
 }
 
 fn write_spec(w: &mut dyn Write, func: &Function) -> io::Result<()> {
-    write!(w, "{}(", &func.signature.name)?;
+    write!(w, "{}(", DisplayAtom(func.signature.name))?;
     let args = func
         .signature
         .params()
@@ -128,18 +146,26 @@ fn write_operands(w: &mut dyn Write, dfg: &DataFlowGraph, inst: Inst) -> io::Res
     let pool = &dfg.value_lists;
     match dfg[inst].as_ref() {
         InstData::BinaryOp(BinaryOp { args, .. }) => write!(w, " {}, {}", args[0], args[1]),
-        InstData::BinaryOpImm(BinaryOpImm { arg, imm, .. }) => write!(w, " {}, {}", arg, imm),
+        InstData::BinaryOpImm(BinaryOpImm { arg, imm, .. }) => {
+            write!(w, " {}, {}", arg, DisplayImmediate(*imm))
+        }
         InstData::UnaryOp(UnaryOp { arg, .. }) => write!(w, " {}", arg),
-        InstData::UnaryOpImm(UnaryOpImm { imm, .. }) => write!(w, " {}", imm),
-        InstData::UnaryOpConst(UnaryOpConst { imm, .. }) => write!(w, " {}", dfg.constant(*imm)),
+        InstData::UnaryOpImm(UnaryOpImm { imm, .. }) => write!(w, " {}", DisplayImmediate(*imm)),
+        InstData::UnaryOpConst(UnaryOpConst { imm, .. }) => match &*dfg.constant(*imm) {
+            ConstantItem::Float(f) => write!(w, " {:?}", f),
+            ConstantItem::Atom(a) => write!(w, " {}", DisplayAtom(*a)),
+            constant => write!(w, " {}", constant),
+        },
         InstData::Ret(Ret { args, .. }) => write!(w, " {}", DisplayValues(args.as_slice())),
-        InstData::RetImm(RetImm { arg, imm, .. }) => write!(w, " {}, {}", imm, arg),
+        InstData::RetImm(RetImm { arg, imm, .. }) => {
+            write!(w, " {}, {}", DisplayImmediate(*imm), arg)
+        }
         InstData::Call(Call { args, .. }) => {
             let func_data = dfg.call_signature(inst).unwrap();
             write!(
                 w,
                 " {}({})",
-                &func_data.mfa(),
+                DisplayFunctionName(func_data.mfa()),
                 DisplayValues(args.as_slice(pool))
             )
         }
@@ -149,7 +175,12 @@ fn write_operands(w: &mut dyn Write, dfg: &DataFlowGraph, inst: Inst) -> io::Res
         InstData::MakeFun(MakeFun { callee, env, .. }) => {
             let sig = dfg.callee_signature(*callee);
             let mfa = sig.mfa();
-            write!(w, " {}({})", &mfa, DisplayValues(env.as_slice(pool)))
+            write!(
+                w,
+                " {}({})",
+                DisplayFunctionName(mfa),
+                DisplayValues(env.as_slice(pool))
+            )
         }
         InstData::CondBr(CondBr {
             cond,
@@ -192,7 +223,12 @@ fn write_operands(w: &mut dyn Write, dfg: &DataFlowGraph, inst: Inst) -> io::Res
             write!(w, " {}", DisplayValues(args.as_slice(pool)))
         }
         InstData::PrimOpImm(PrimOpImm { imm, args, .. }) => {
-            write!(w, " {}, {}", imm, DisplayValues(args.as_slice(pool)))
+            write!(
+                w,
+                " {}, {}",
+                DisplayImmediate(*imm),
+                DisplayValues(args.as_slice(pool))
+            )
         }
         InstData::IsType(IsType { ty, arg, .. }) => {
             write!(w, " {}, {}", arg, ty)
@@ -201,12 +237,14 @@ fn write_operands(w: &mut dyn Write, dfg: &DataFlowGraph, inst: Inst) -> io::Res
             let values = DisplayValues(args.as_slice(pool));
             match spec {
                 BinaryEntrySpecifier::Integer {
-                    endianness, signed, ..
+                    endianness,
+                    signed,
+                    unit,
                 } => {
                     if *signed {
-                        write!(w, ".sint.{} {}", endianness, values)
+                        write!(w, ".sint.{}({}) {}", endianness, unit, values)
                     } else {
-                        write!(w, ".uint.{} {}", endianness, values)
+                        write!(w, ".uint.{}({}) {}", endianness, unit, values)
                     }
                 }
                 BinaryEntrySpecifier::Float {
@@ -237,12 +275,14 @@ fn write_operands(w: &mut dyn Write, dfg: &DataFlowGraph, inst: Inst) -> io::Res
             let values = DisplayValuesWithImmediate(args.as_slice(pool), *value);
             match spec {
                 BinaryEntrySpecifier::Integer {
-                    endianness, signed, ..
+                    endianness,
+                    signed,
+                    unit,
                 } => {
                     if *signed {
-                        write!(w, ".sint.{} {}", endianness, values)
+                        write!(w, ".sint.{}({}) {}", endianness, unit, values)
                     } else {
-                        write!(w, ".uint.{} {}", endianness, values)
+                        write!(w, ".uint.{}({}) {}", endianness, unit, values)
                     }
                 }
                 BinaryEntrySpecifier::Float {
@@ -306,11 +346,17 @@ fn write_operands(w: &mut dyn Write, dfg: &DataFlowGraph, inst: Inst) -> io::Res
         }
         InstData::SetElement(SetElement { index, args, .. }) => {
             let argv = args.as_slice();
-            write!(w, " {}[{}], {}", argv[0], index, argv[1])
+            write!(w, " {}[{}], {}", argv[0], DisplayImmediate(*index), argv[1])
         }
         InstData::SetElementImm(SetElementImm {
             arg, index, value, ..
-        }) => write!(w, " {}[{}], {}", arg, index, value),
+        }) => write!(
+            w,
+            " {}[{}], {}",
+            arg,
+            DisplayImmediate(*index),
+            DisplayImmediate(*value)
+        ),
     }
 }
 
@@ -347,9 +393,76 @@ impl<'a> fmt::Display for DisplayValuesWithImmediate<'a> {
             }
         }
         if self.0.is_empty() {
-            write!(f, "{}", &self.1)
+            write!(f, "{}", DisplayImmediate(self.1))
         } else {
-            write!(f, ", {}", &self.1)
+            write!(f, ", {}", DisplayImmediate(self.1))
+        }
+    }
+}
+
+/// Displays an immediate such that it can be read back by the parser, i.e. atoms are quoted
+/// when necessary, and floats always have a fractional part or exponent
+struct DisplayImmediate(Immediate);
+impl fmt::Display for DisplayImmediate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Immediate::Term(ImmediateTerm::Atom(a)) => write!(f, "{}", DisplayAtom(a)),
+            Immediate::Term(ImmediateTerm::Float(n)) | Immediate::F64(n) => write!(f, "{:?}", n),
+            imm => write!(f, "{}", imm),
         }
     }
 }
+
+/// Displays a function name, quoting its components when necessary
+struct DisplayFunctionName(FunctionName);
+impl fmt::Display for DisplayFunctionName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(module) = self.0.module {
+            write!(f, "{}:", DisplayAtom(module))?;
+        }
+        write!(f, "{}/{}", DisplayAtom(self.0.function), self.0.arity)
+    }
+}
+
+/// Displays an atom, quoting it if it would otherwise be read back as something else,
+/// e.g. a keyword, boolean, value or block
+struct DisplayAtom(Symbol);
+impl fmt::Display for DisplayAtom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = self.0.as_str().get();
+        if needs_quotes(name) {
+            write!(f, "'")?;
+            for c in name.chars() {
+                match c {
+                    '\'' => write!(f, "\\'")?,
+                    c => write!(f, "{}", c.escape_debug())?,
+                }
+            }
+            write!(f, "'")
+        } else {
+            write!(f, "{}", name)
+        }
+    }
+}
+
+fn needs_quotes(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c == '_' || c.is_ascii_lowercase() => (),
+        _ => return true,
+    }
+    if !chars.all(|c| c == '_' || c == '@' || c.is_ascii_alphanumeric()) {
+        return true;
+    }
+    let is_entity = |prefix: &str| match name.strip_prefix(prefix) {
+        Some(digits) => !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()),
+        None => false,
+    };
+    if is_entity("v") || is_entity("block") {
+        return true;
+    }
+    matches!(
+        name,
+        "module" | "function" | "pub" | "nif" | "closure" | "tail" | "true" | "false" | "none"
+    )
+}
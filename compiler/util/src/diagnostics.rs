@@ -1,3 +1,4 @@
+use std::cell::RefCell;
 use std::io::Write;
 use std::ops::Deref;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -196,6 +197,38 @@ impl<'h> InFlightDiagnostic<'h> {
     }
 }
 
+thread_local! {
+    static CAPTURED: RefCell<Option<Vec<Buffer>>> = RefCell::new(None);
+}
+
+/// Output printed by diagnostics handlers on a thread while it was being captured
+pub struct CapturedOutput(Vec<Buffer>);
+
+/// Runs `f`, capturing all output printed by diagnostics handlers on the current thread
+/// in the meantime, rather than printing it
+///
+/// This allows the output of tasks run concurrently to be printed in a deterministic order
+/// via `DiagnosticsHandler::print_captured`.
+pub fn capture<F, R>(f: F) -> (R, CapturedOutput)
+where
+    F: FnOnce() -> R,
+{
+    // Restores any outer capture, even if `f` panics
+    struct Restore(Option<Vec<Buffer>>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let outer = self.0.take();
+            CAPTURED.with(|captured| *captured.borrow_mut() = outer);
+        }
+    }
+
+    let outer = CAPTURED.with(|captured| captured.borrow_mut().replace(vec![]));
+    let _restore = Restore(outer);
+    let result = f();
+    let buffers = CAPTURED.with(|captured| captured.borrow_mut().take().unwrap_or_default());
+    (result, CapturedOutput(buffers))
+}
+
 pub struct DiagnosticsHandler {
     emitter: Arc<dyn Emitter>,
     codemap: Arc<CodeMap>,
@@ -276,7 +309,7 @@ impl DiagnosticsHandler {
         write!(&mut buffer, ": {}", message.into()).unwrap();
         buffer.reset().ok();
         write!(&mut buffer, "\n").unwrap();
-        self.emitter.print(&buffer).unwrap();
+        self.print(buffer);
     }
 
     /// Emits a debug message
@@ -290,7 +323,7 @@ impl DiagnosticsHandler {
         write!(&mut buffer, ": {}", message.into()).unwrap();
         buffer.reset().ok();
         write!(&mut buffer, "\n").unwrap();
-        self.emitter.print(&buffer).unwrap();
+        self.print(buffer);
     }
 
     /// Emits a note
@@ -328,7 +361,7 @@ impl DiagnosticsHandler {
         write!(&mut buffer, "{:>12} ", prefix).unwrap();
         buffer.reset().ok();
         writeln!(&mut buffer, "{}", message.into()).unwrap();
-        self.emitter.print(&buffer).unwrap();
+        self.print(buffer);
     }
 
     /// Generates an in-flight diagnostic for more complex diagnostics use cases
@@ -338,14 +371,41 @@ impl DiagnosticsHandler {
         InFlightDiagnostic::new(self, severity)
     }
 
+    /// Prints output previously captured by `capture`
+    pub fn print_captured(&self, output: CapturedOutput) {
+        for buffer in output.0.iter() {
+            self.emitter.print(buffer).unwrap();
+        }
+    }
+
+    fn print(&self, buffer: Buffer) {
+        let buffer = CAPTURED.with(|captured| match captured.borrow_mut().as_mut() {
+            Some(captured) => {
+                captured.push(buffer);
+                None
+            }
+            None => Some(buffer),
+        });
+        if let Some(buffer) = buffer {
+            self.emitter.print(&buffer).unwrap();
+        }
+    }
+
     /// Emits the given diagnostic
     #[inline(always)]
     pub fn emit(&self, diagnostic: &Diagnostic) {
         use firefly_diagnostics::term;
 
         let mut buffer = self.emitter.buffer();
-        term::emit(&mut buffer, &self.display, self.codemap.deref(), diagnostic).unwrap();
-        self.emitter.print(&buffer).unwrap();
+        let diagnostic = self.codemap.remap_diagnostic(diagnostic);
+        term::emit(
+            &mut buffer,
+            &self.display,
+            self.codemap.deref(),
+            &diagnostic,
+        )
+        .unwrap();
+        self.print(buffer);
     }
 }
 
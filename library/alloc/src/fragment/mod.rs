@@ -1,5 +1,6 @@
 use alloc::alloc::{AllocError, Allocator, Global, Layout};
 use alloc::boxed::Box;
+use core::cell::Cell;
 use core::cmp;
 use core::ops::Range;
 use core::ptr::{self, NonNull};
@@ -48,7 +49,7 @@ pub struct HeapFragment {
     raw: RawFragment,
     /// A pointer to the top of the allocated region of this fragment,
     /// e.g. when the fragment is unused, `top == raw.base`
-    top: *mut u8,
+    top: Cell<*mut u8>,
     /// An optional destructor for this fragment
     destructor: Option<Box<dyn Fn(NonNull<u8>)>>,
 }
@@ -76,7 +77,7 @@ impl HeapFragment {
             header.write(Self {
                 link: LinkedListLink::new(),
                 raw: RawFragment { layout, base },
-                top: base.as_ptr(),
+                top: Cell::new(base.as_ptr()),
                 destructor,
             });
             Ok(NonNull::new_unchecked(header))
@@ -106,14 +107,16 @@ unsafe impl Allocator for HeapFragment {
 
         // Calculate the base pointer of the allocation at the desired alignment,
         // then offset that pointer by the desired size to give us the new top
-        let top = self.top;
+        let top = self.top.get();
         let offset = top.align_offset(layout.align());
         let base = unsafe { top.add(offset) };
         let new_top = unsafe { base.add(size) };
 
-        // Make sure the requested allocation fits within the fragment
+        // Make sure the requested allocation fits within the fragment, note that
+        // an allocation which exactly fills the fragment ends at `range.end`
         let range = self.raw.as_ptr_range();
-        if range.contains(&new_top) {
+        if new_top <= range.end {
+            self.top.set(new_top);
             Ok(unsafe { NonNull::new_unchecked(ptr::from_raw_parts_mut(base.cast(), size)) })
         } else {
             Err(AllocError)
@@ -156,7 +159,7 @@ impl Heap for HeapFragment {
 
     #[inline]
     fn heap_top(&self) -> *mut u8 {
-        self.top
+        self.top.get()
     }
 
     #[inline]
@@ -164,3 +167,24 @@ impl Heap for HeapFragment {
         self.raw.as_ptr_range().end
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heap_fragment_allocate_test() {
+        let layout = Layout::from_size_align(32, 8).unwrap();
+        let fragment = HeapFragment::new(layout, None).unwrap();
+        let frag = unsafe { fragment.as_ref() };
+
+        let word = Layout::from_size_align(16, 8).unwrap();
+        let a = frag.allocate(word).unwrap();
+        let b = frag.allocate(word).unwrap();
+        assert_ne!(a.as_mut_ptr(), b.as_mut_ptr());
+        assert_eq!(frag.heap_top(), frag.heap_end());
+        assert!(frag.allocate(word).is_err());
+
+        unsafe { ptr::drop_in_place(fragment.as_ptr()) }
+    }
+}
@@ -2,6 +2,7 @@ mod boxed;
 
 pub use self::boxed::*;
 
+use alloc::alloc::AllocError;
 use core::fmt;
 
 /// Represents the types of errors that can occur during garbage collection.
@@ -31,5 +32,10 @@ impl fmt::Display for GcError {
         }
     }
 }
+impl From<AllocError> for GcError {
+    fn from(_: AllocError) -> Self {
+        Self::AllocError
+    }
+}
 #[cfg(feature = "std")]
 impl std::error::Error for GcError {}
@@ -19,6 +19,12 @@ pub enum FromBeamError {
     #[error("debug info is required but not present")]
     NoDebugInfo,
 
+    #[error("unsupported debug info backend '{0}', only erl_abstract_code is supported")]
+    UnsupportedDebugInfo(String),
+
+    #[error("invalid debug info: {0}")]
+    InvalidDebugInfo(etf::Term),
+
     #[error("missing module attribute")]
     NoModuleAttribute,
 
@@ -55,7 +61,7 @@ impl fmt::Display for Unmatched {
 
 #[derive(Debug)]
 pub struct UnmatchedTerms(Vec<Unmatched>);
-impl Display for UnmatchedTerms {
+impl fmt::Display for UnmatchedTerms {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "[")?;
         let limit = 3;
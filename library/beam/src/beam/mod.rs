@@ -22,8 +22,9 @@
 //!   com/KronicDeth/intellij-elixir/blob/master/src/org/elixir_lang/beam/Beam.kt) in Kotlin
 mod code;
 mod errors;
-mod reader;
+pub mod reader;
 
 pub use self::code::AbstractCode;
 pub use self::errors::*;
+pub use self::reader::chunk;
 pub use self::reader::*;
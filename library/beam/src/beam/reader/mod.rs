@@ -25,7 +25,7 @@
 //!     beam.push_chunk(chunk);
 //!     beam.to_file("my.beam").unwrap();
 mod beam_file;
-mod chunk;
+pub mod chunk;
 mod parts;
 #[cfg(test)]
 mod test;
@@ -11,6 +11,7 @@ use crate::beam::reader::BeamFile;
 use crate::beam::reader::RawBeamFile;
 use crate::beam::reader::Result;
 use crate::beam::reader::StandardBeamFile;
+use crate::beam::{AbstractCode, FromBeamError};
 
 #[test]
 fn raw_chunks() {
@@ -228,6 +229,29 @@ fn encode_chunks() {
     assert_eq!(original, encoded);
 }
 
+#[test]
+fn abstract_code() {
+    // Pre-OTP 20 "Abst" chunk
+    let code = AbstractCode::from_beam_file(test_file("test.beam")).unwrap();
+    let rendered = code.to_string();
+    assert!(rendered.starts_with("{'attribute',1,'file',{[116,101,115,116,46,101,114,108],1}}.\n"));
+    assert!(rendered.ends_with("{'eof',11}.\n"));
+
+    // Debug info from a backend other than erl_abstract_code
+    match AbstractCode::from_beam_file(test_file("Elixir.Unicode.beam")) {
+        Err(FromBeamError::UnsupportedDebugInfo(backend)) => assert_eq!(backend, "elixir_erl"),
+        Err(err) => panic!("unexpected error: {}", err),
+        Ok(_) => panic!("expected elixir debug info to be rejected"),
+    }
+
+    // Compiled without debug_info
+    match AbstractCode::from_beam_file("tests/testdata/simple.beam") {
+        Err(FromBeamError::NoDebugInfo) => (),
+        Err(err) => panic!("unexpected error: {}", err),
+        Ok(_) => panic!("expected missing debug info to be reported"),
+    }
+}
+
 fn test_file(name: &str) -> PathBuf {
     let mut path = PathBuf::from("tests/testdata/reader");
     path.push(name);
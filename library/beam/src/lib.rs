@@ -1,6 +1,5 @@
 pub mod beam;
 pub mod serialization;
-pub mod syntax;
 
 pub use self::beam::reader::ReadError;
-pub use self::syntax::ast::error::FromBeamError;
+pub use self::beam::FromBeamError;
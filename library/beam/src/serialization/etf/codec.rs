@@ -10,28 +10,26 @@ use libflate::zlib;
 
 use num::bigint::BigInt;
 
-use failure::Fail;
-
 use self::convert::TryAsRef;
 use self::convert::TryInto;
 use super::*;
 
 /// Errors which can occur when decoding a term
-#[derive(Fail, Debug)]
+#[derive(thiserror::Error, Debug)]
 pub enum DecodeError {
-    #[fail(display = "decoding failed, i/o error: {}", _0)]
-    IO(#[fail(cause)] std::io::Error),
+    #[error("decoding failed, i/o error: {0}")]
+    IO(#[source] std::io::Error),
 
-    #[fail(display = "unsupported version '{}'", version)]
+    #[error("unsupported version '{version}'")]
     UnsupportedVersion { version: u8 },
 
-    #[fail(display = "unknown tag: '{}'", tag)]
+    #[error("unknown tag: '{tag}'")]
     UnknownTag { tag: u8 },
 
-    #[fail(display = "unexpected type! {} is not a {}", value, expected)]
+    #[error("unexpected type! {value} is not a {expected}")]
     UnexpectedType { value: Term, expected: String },
 
-    #[fail(display = "{} is out of range {:?}", value, range)]
+    #[error("{value} is out of range {range:?}")]
     OutOfRange {
         value: i32,
         range: std::ops::Range<i32>,
@@ -44,21 +42,21 @@ impl std::convert::From<std::io::Error> for DecodeError {
 }
 
 /// Errors which can occur when encoding a term
-#[derive(Fail, Debug)]
+#[derive(thiserror::Error, Debug)]
 pub enum EncodeError {
-    #[fail(display = "encoding failed, i/o error: {}", _0)]
-    IO(#[fail(cause)] std::io::Error),
+    #[error("encoding failed, i/o error: {0}")]
+    IO(#[source] std::io::Error),
 
     // a.name.len()
-    #[fail(display = "atom exceeds maximum byte size: {}", _0)]
+    #[error("atom exceeds maximum byte size: {0}")]
     TooLongAtomName(Atom),
 
     // i.value.to_bytes_le().1.len()
-    #[fail(display = "integer is too large, exceeds maximum byte size")]
+    #[error("integer is too large, exceeds maximum byte size")]
     TooLargeInteger(BigInteger),
 
     // r.id.len() * 4
-    #[fail(display = "reference is too large, exceeds maximum byte size")]
+    #[error("reference is too large, exceeds maximum byte size")]
     TooLargeReferenceId(Reference),
 }
 impl std::convert::From<std::io::Error> for EncodeError {
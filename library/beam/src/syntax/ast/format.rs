@@ -1 +0,0 @@
-pub mod raw_abstract_v1;
@@ -1,5 +0,0 @@
-mod ast;
-mod parser;
-
-pub use self::ast::*;
-pub use self::parser::*;
@@ -1,3 +0,0 @@
-pub(crate) mod grammar {
-    include!(concat!(env!("OUT_DIR"), "/grammar.rs"));
-}
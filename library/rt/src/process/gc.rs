@@ -0,0 +1,512 @@
+use alloc::alloc::AllocError;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cmp;
+use core::ops::Range;
+use core::ptr::{self, NonNull};
+
+use intrusive_collections::UnsafeRef;
+
+use firefly_alloc::gc::{GcBox, GcError};
+use firefly_alloc::heap::{GenerationalHeap, Heap};
+use firefly_binary::Bitstring;
+
+use crate::term::{BinaryData, BitSlice, Closure, Cons, Map, OpaqueTerm, Term, Tuple};
+
+use super::{Process, ProcessHeap};
+
+/// The fraction of the immature heap which may be used before a collection is requested
+const GC_THRESHOLD: f64 = 0.75;
+
+impl Process {
+    /// Returns true if this process should be garbage collected before allocating again
+    ///
+    /// This is the case once the immature heap is mostly full, allocations have started
+    /// spilling over into heap fragments, or received messages are holding on to theirs.
+    pub fn should_collect(&self) -> bool {
+        let fragments = unsafe { &*self.fragments.get() };
+        let mailbox = unsafe { self.mailbox() };
+        self.heap().should_collect(GC_THRESHOLD)
+            || !fragments.is_empty()
+            || mailbox.received_fragments().next().is_some()
+    }
+
+    /// Returns the size in bytes of the immature heap of this process
+    pub fn heap_size(&self) -> usize {
+        self.heap().heap_size()
+    }
+
+    /// Returns the size in bytes of all memory used to hold the terms of this process, i.e. both
+    /// generations of its heap, and any heap fragments, including those of received messages
+    pub fn total_heap_size(&self) -> usize {
+        let heap = self.heap();
+        let fragments: usize = self
+            .fragment_ranges()
+            .iter()
+            .map(|range| range.end as usize - range.start as usize)
+            .sum();
+        heap.immature().heap_size() + heap.mature().heap_size() + fragments
+    }
+
+    /// Performs a garbage collection of this process, ensuring there is room for at least `need`
+    /// bytes on the process heap afterwards.
+    ///
+    /// This is a copying, generational collector. Live data is found by tracing from `roots`,
+    /// and moved out of the immature heap, any overflow heap fragments, and the fragments of
+    /// messages which have been received. A minor collection promotes data which already
+    /// survived a previous collection to the mature heap, and moves the rest to a new immature
+    /// heap. If the mature heap does not have room for the promoted data, a major collection
+    /// is performed instead, which moves everything live, mature data included, to a new heap.
+    ///
+    /// Returns the number of bytes of live data which were moved.
+    ///
+    /// # Safety
+    ///
+    /// This must only be called by the owning scheduler, or by the process itself, and `roots`
+    /// must contain every location outside of the process heap which refers to a term on it.
+    /// Any such reference which is not a root will be left dangling.
+    pub unsafe fn garbage_collect(
+        &self,
+        need: usize,
+        roots: &mut [NonNull<OpaqueTerm>],
+    ) -> Result<usize, GcError> {
+        let heap = &mut *self.heap.get();
+        let young = heap.immature();
+        let promoted = high_water_mark(young) as usize - young.heap_start() as usize;
+
+        // The mature heap cannot grow in place, so if it has no room for the data being
+        // promoted, either replace it, if it is unused, or fall back to a major collection
+        if promoted * 2 > heap.mature().heap_available() {
+            if heap.mature().heap_used() > 0 {
+                return self.major_collection(need, roots);
+            }
+            heap.swap_mature(ProcessHeap::with_capacity(heap_size_for(promoted)));
+        }
+
+        self.minor_collection(need, roots)
+    }
+
+    fn minor_collection(
+        &self,
+        need: usize,
+        roots: &mut [NonNull<OpaqueTerm>],
+    ) -> Result<usize, GcError> {
+        let heap = unsafe { &mut *self.heap.get() };
+        let young = heap.immature();
+        let mature = heap.mature();
+        let high_water_mark = high_water_mark(young);
+
+        let mut sources = self.fragment_ranges();
+        let fragments_used = self.fragments_used();
+        sources.push(young.as_ptr_range());
+        let unpromoted = young.heap_top() as usize - high_water_mark as usize;
+
+        let used = unpromoted + fragments_used;
+        let new_young = ProcessHeap::with_capacity(heap_size_for(used + need));
+        let mature_used = mature.heap_used();
+        {
+            let mut collector = Collector {
+                sources,
+                promote: (young.heap_start() as *const u8)..high_water_mark,
+                young: &new_young,
+                mature,
+                forwarded: BTreeMap::new(),
+                pending: Vec::new(),
+            };
+            collector.collect(roots)?;
+        }
+        let moved = new_young.heap_used() + (mature.heap_used() - mature_used);
+
+        new_young.set_high_water_mark();
+        heap.swap_immature(new_young);
+        unsafe {
+            self.free_fragments();
+        }
+
+        Ok(moved)
+    }
+
+    fn major_collection(
+        &self,
+        need: usize,
+        roots: &mut [NonNull<OpaqueTerm>],
+    ) -> Result<usize, GcError> {
+        let heap = unsafe { &mut *self.heap.get() };
+        let young = heap.immature();
+        let mature = heap.mature();
+
+        let mut sources = self.fragment_ranges();
+        let fragments_used = self.fragments_used();
+        sources.push(young.as_ptr_range());
+        sources.push(mature.as_ptr_range());
+        let used = young.heap_used() + mature.heap_used() + fragments_used;
+
+        // Everything live ends up on the new immature heap, below its high water mark, so
+        // that it is promoted to the mature heap by the next minor collection
+        let new_young = ProcessHeap::with_capacity(heap_size_for(used + need));
+        {
+            let mut collector = Collector {
+                sources,
+                promote: ptr::null()..ptr::null(),
+                young: &new_young,
+                mature: &new_young,
+                forwarded: BTreeMap::new(),
+                pending: Vec::new(),
+            };
+            collector.collect(roots)?;
+        }
+        let moved = new_young.heap_used();
+
+        new_young.set_high_water_mark();
+        heap.swap_immature(new_young);
+        heap.swap_mature(ProcessHeap::empty());
+        unsafe {
+            self.free_fragments();
+        }
+
+        Ok(moved)
+    }
+
+    /// Returns the address ranges of all heap fragments owned by this process
+    fn fragment_ranges(&self) -> Vec<Range<*const u8>> {
+        let fragments = unsafe { &*self.fragments.get() };
+        let mailbox = unsafe { self.mailbox() };
+        fragments
+            .iter()
+            .chain(mailbox.received_fragments())
+            .map(|fragment| fragment.as_ptr_range())
+            .collect()
+    }
+
+    /// Returns the number of bytes allocated in all heap fragments owned by this process
+    fn fragments_used(&self) -> usize {
+        let fragments = unsafe { &*self.fragments.get() };
+        let mailbox = unsafe { self.mailbox() };
+        fragments
+            .iter()
+            .chain(mailbox.received_fragments())
+            .map(|fragment| fragment.heap_used())
+            .sum()
+    }
+
+    /// Frees all heap fragments owned by this process
+    ///
+    /// # Safety
+    ///
+    /// This must only be called at the end of a collection, as any data in them is lost.
+    unsafe fn free_fragments(&self) {
+        let fragments = &mut *self.fragments.get();
+        while let Some(fragment) = fragments.pop_front() {
+            UnsafeRef::into_raw(fragment).drop_in_place();
+        }
+        self.mailbox().free_received_fragments();
+    }
+}
+
+/// Returns the high water mark of `heap`, or the start of the heap if it has none
+fn high_water_mark(heap: &ProcessHeap) -> *const u8 {
+    heap.high_water_mark()
+        .map(|hwm| hwm.as_ptr() as *const u8)
+        .unwrap_or(heap.heap_start())
+}
+
+/// Returns the size of heap to allocate in order to hold `used` bytes of live data
+///
+/// The heap is sized with enough headroom that alignment padding can never cause the
+/// copied data to exceed it, and so that the process can make progress before the next
+/// collection is required.
+fn heap_size_for(used: usize) -> usize {
+    cmp::max(
+        ProcessHeap::DEFAULT_HEAP_SIZE,
+        (used * 2).next_power_of_two(),
+    )
+}
+
+/// The state of a single collection
+///
+/// Objects are moved using a worklist, rather than recursively, so that arbitrarily long lists
+/// and deeply nested terms can be collected without exhausting the stack. As `Cons` cells and
+/// tuples have no header in which to store a forwarding pointer, the new location of each object
+/// is instead recorded by the address it was moved from.
+struct Collector<'a> {
+    /// The address ranges being collected, live objects in these ranges are moved
+    sources: Vec<Range<*const u8>>,
+    /// The address range of objects which are promoted to the mature heap
+    promote: Range<*const u8>,
+    /// The heap to which unpromoted objects are moved
+    young: &'a ProcessHeap,
+    /// The heap to which promoted objects are moved
+    mature: &'a ProcessHeap,
+    /// The new location of each object moved so far, by its original address
+    forwarded: BTreeMap<usize, OpaqueTerm>,
+    /// Objects which have been moved, but whose fields have not yet been visited
+    pending: Vec<Term>,
+}
+impl<'a> Collector<'a> {
+    fn collect(&mut self, roots: &mut [NonNull<OpaqueTerm>]) -> Result<(), AllocError> {
+        for root in roots.iter_mut() {
+            let root = unsafe { root.as_mut() };
+            *root = self.evacuate(*root, false)?;
+        }
+
+        while let Some(term) = self.pending.pop() {
+            self.scan(term)?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves the object referenced by `opaque` out of the regions being collected, if it is
+    /// in one of them, returning the term which refers to its new location.
+    ///
+    /// Objects are promoted if they are in the promoted range, or if `promote` is set, as is the
+    /// case when they are referenced from an object which was itself promoted. This ensures
+    /// there are never references from the mature heap to the immature heap.
+    fn evacuate(&mut self, opaque: OpaqueTerm, promote: bool) -> Result<OpaqueTerm, AllocError> {
+        let term: Term = opaque.into();
+        let addr = match term {
+            Term::Cons(ptr) => ptr.as_ptr() as *const u8,
+            Term::Tuple(ptr) => ptr.as_ptr() as *const u8,
+            Term::BigInt(boxed) => GcBox::as_ptr(&boxed) as *const u8,
+            Term::Map(boxed) => GcBox::as_ptr(&boxed) as *const u8,
+            Term::Closure(boxed) => GcBox::as_ptr(&boxed) as *const u8,
+            Term::Pid(boxed) => GcBox::as_ptr(&boxed) as *const u8,
+            Term::Port(boxed) => GcBox::as_ptr(&boxed) as *const u8,
+            Term::Reference(boxed) => GcBox::as_ptr(&boxed) as *const u8,
+            Term::HeapBinary(boxed) => GcBox::as_ptr(&boxed) as *const u8,
+            Term::RefBinary(boxed) => GcBox::as_ptr(&boxed) as *const u8,
+            // Immediates, reference-counted and constant binaries are never on a process heap
+            _ => return Ok(opaque),
+        };
+        if !self.sources.iter().any(|source| source.contains(&addr)) {
+            return Ok(opaque);
+        }
+        if let Some(forwarded) = self.forwarded.get(&(addr as usize)) {
+            return Ok(*forwarded);
+        }
+
+        let target = if promote || self.promote.contains(&addr) {
+            self.mature
+        } else {
+            self.young
+        };
+        let moved: OpaqueTerm = self.copy(term, target)?.into();
+        self.forwarded.insert(addr as usize, moved);
+        Ok(moved)
+    }
+
+    /// Moves `term` to `target`, without visiting any of the terms it references
+    ///
+    /// Objects with fields are queued to be visited by `scan` once moved, with the exception
+    /// of maps, which are rebuilt with their keys and values moved eagerly.
+    fn copy(&mut self, term: Term, target: &ProcessHeap) -> Result<Term, AllocError> {
+        let moved = match term {
+            Term::BigInt(boxed) => {
+                Term::BigInt(GcBox::new_in(unsafe { ptr::read(&*boxed) }, target)?)
+            }
+            Term::Cons(ptr) => {
+                let mut cell = Cons::new_in(target)?;
+                unsafe {
+                    cell.as_uninit_mut().write(ptr::read(ptr.as_ptr()));
+                }
+                self.pending.push(Term::Cons(cell));
+                Term::Cons(cell)
+            }
+            Term::Tuple(ptr) => {
+                let tuple = Tuple::from_slice(unsafe { ptr.as_ref() }.as_slice(), target)?;
+                self.pending.push(Term::Tuple(tuple));
+                Term::Tuple(tuple)
+            }
+            Term::Map(boxed) => {
+                let promote = ptr::eq(target, self.mature);
+                let items = boxed
+                    .iter()
+                    .map(|(k, v)| {
+                        let k = self.evacuate((*k).into(), promote)?;
+                        let v = self.evacuate((*v).into(), promote)?;
+                        Ok((k.into(), v.into()))
+                    })
+                    .collect::<Result<Vec<(Term, Term)>, AllocError>>()?;
+                let map = GcBox::new_in(Map::new_from_iter(items.into_iter()), target)?;
+                // The original map is no longer reachable, so release the storage it holds
+                unsafe {
+                    ptr::drop_in_place(GcBox::into_raw(boxed));
+                }
+                Term::Map(map)
+            }
+            Term::Closure(boxed) => {
+                let closure = Closure::new_in(
+                    boxed.module,
+                    boxed.name,
+                    boxed.arity as u8,
+                    boxed.callee(),
+                    boxed.env(),
+                    target,
+                )?;
+                self.pending.push(Term::Closure(closure));
+                Term::Closure(closure)
+            }
+            Term::Pid(boxed) => Term::Pid(GcBox::new_in(unsafe { ptr::read(&*boxed) }, target)?),
+            Term::Port(boxed) => Term::Port(GcBox::new_in(unsafe { ptr::read(&*boxed) }, target)?),
+            Term::Reference(boxed) => {
+                Term::Reference(GcBox::new_in(unsafe { ptr::read(&*boxed) }, target)?)
+            }
+            Term::HeapBinary(boxed) => {
+                let bytes = boxed.as_bytes();
+                let mut moved = GcBox::<BinaryData>::with_capacity_in(bytes.len(), target)?;
+                unsafe {
+                    moved.set_flags(boxed.flags());
+                }
+                moved.copy_from_slice(bytes);
+                Term::HeapBinary(moved)
+            }
+            Term::RefBinary(boxed) => {
+                let slice = GcBox::<BitSlice>::new_in(unsafe { ptr::read(&*boxed) }, target)?;
+                self.pending.push(Term::RefBinary(slice));
+                Term::RefBinary(slice)
+            }
+            term => term,
+        };
+        Ok(moved)
+    }
+
+    /// Visits the fields of an object which has been moved, moving anything they reference
+    fn scan(&mut self, term: Term) -> Result<(), AllocError> {
+        match term {
+            Term::Cons(mut ptr) => {
+                let promote = self.is_promoted(ptr.as_ptr());
+                let cell = unsafe { ptr.as_mut() };
+                cell.head = self.evacuate(cell.head, promote)?;
+                cell.tail = self.evacuate(cell.tail, promote)?;
+            }
+            Term::Tuple(mut ptr) => {
+                let promote = self.is_promoted(ptr.as_ptr());
+                for element in unsafe { ptr.as_mut() }.as_mut_slice() {
+                    *element = self.evacuate(*element, promote)?;
+                }
+            }
+            Term::Closure(mut boxed) => {
+                let promote = self.is_promoted(GcBox::as_ptr(&boxed));
+                for element in boxed.env_mut() {
+                    *element = self.evacuate(*element, promote)?;
+                }
+            }
+            Term::RefBinary(mut boxed) => {
+                let promote = self.is_promoted(GcBox::as_ptr(&boxed));
+                let owner = boxed.owner();
+                let moved = self.evacuate(owner, promote)?;
+                // The selection borrows the data of its owner, so must follow it when moved
+                let terms: (Term, Term) = (owner.into(), moved.into());
+                if let (Term::HeapBinary(old), Term::HeapBinary(new)) = terms {
+                    if moved != owner {
+                        let old_base = old.as_bytes().as_ptr();
+                        let new_base = new.as_bytes().as_ptr();
+                        unsafe {
+                            boxed.relocate(moved, old_base, new_base);
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Returns true if `ptr` is an object which has been moved to the mature heap
+    #[inline]
+    fn is_promoted<T: ?Sized>(&self, ptr: *const T) -> bool {
+        self.mature.contains(ptr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::term::{ListBuilder, ProcessId};
+
+    fn make_list(process: &Process, len: usize) -> Term {
+        let mut builder = ListBuilder::new(process);
+        for i in (0..len).rev() {
+            builder.push(Term::Int(i as i64)).unwrap();
+        }
+        Term::Cons(builder.finish().unwrap())
+    }
+
+    fn assert_list(term: Term, len: usize) {
+        let Term::Cons(ptr) = term else { panic!("expected list, got {:?}", term); };
+        let list = unsafe { ptr.as_ref() };
+        let mut count = 0;
+        for (i, element) in list.iter().enumerate() {
+            assert_eq!(element.ok(), Some(Term::Int(i as i64)));
+            count += 1;
+        }
+        assert_eq!(count, len);
+    }
+
+    #[test]
+    fn garbage_collect_moves_live_terms() {
+        let process = Process::new(None, ProcessId::next(), "root:init/0".parse().unwrap());
+        let list = make_list(&process, 4);
+        let tuple = Tuple::from_slice(&[Term::Int(1).into(), list.into()], &process).unwrap();
+        // Garbage which should not survive the collection
+        make_list(&process, 10);
+
+        let mut root: OpaqueTerm = Term::Tuple(tuple).into();
+        let moved = unsafe {
+            process
+                .garbage_collect(0, &mut [NonNull::from(&mut root)])
+                .unwrap()
+        };
+
+        let Term::Tuple(ptr) = root.into() else { panic!("expected tuple"); };
+        assert_ne!(ptr.as_ptr() as *const u8, tuple.as_ptr() as *const u8);
+        assert!(process.contains(ptr.as_ptr()));
+        assert_eq!(moved, process.heap_used());
+        let tuple = unsafe { ptr.as_ref() };
+        assert_eq!(tuple.get_element(0usize).unwrap(), Term::Int(1));
+        assert_list(tuple.get_element(1usize).unwrap(), 4);
+    }
+
+    #[test]
+    fn garbage_collect_promotes_survivors() {
+        let process = Process::new(None, ProcessId::next(), "root:init/0".parse().unwrap());
+        let mut root: OpaqueTerm = make_list(&process, 4).into();
+
+        unsafe {
+            process
+                .garbage_collect(0, &mut [NonNull::from(&mut root)])
+                .unwrap();
+        }
+        let Term::Cons(ptr) = root.into() else { panic!("expected list"); };
+        assert!(!process.heap().mature().contains(ptr.as_ptr()));
+
+        unsafe {
+            process
+                .garbage_collect(0, &mut [NonNull::from(&mut root)])
+                .unwrap();
+        }
+        let Term::Cons(ptr) = root.into() else { panic!("expected list"); };
+        assert!(process.heap().mature().contains(ptr.as_ptr()));
+        assert_eq!(process.heap().heap_used(), 0);
+        assert_list(root.into(), 4);
+    }
+
+    #[test]
+    fn garbage_collect_frees_heap_fragments() {
+        let process = Process::new(None, ProcessId::next(), "root:init/0".parse().unwrap());
+        let len = ProcessHeap::DEFAULT_HEAP_SIZE;
+        let mut root: OpaqueTerm = make_list(&process, len).into();
+        assert!(process.should_collect());
+
+        unsafe {
+            process
+                .garbage_collect(0, &mut [NonNull::from(&mut root)])
+                .unwrap();
+        }
+
+        assert!(!process.should_collect());
+        assert!(unsafe { &*process.fragments.get() }.is_empty());
+        assert_list(root.into(), len);
+    }
+}
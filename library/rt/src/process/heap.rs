@@ -1,5 +1,5 @@
 use alloc::alloc::{AllocError, Allocator, Global, Layout};
-use core::cell::UnsafeCell;
+use core::cell::{Cell, UnsafeCell};
 use core::mem;
 use core::ptr::{self, NonNull};
 
@@ -10,19 +10,38 @@ use crate::term::Term;
 pub struct ProcessHeap {
     range: *mut [u8],
     top: UnsafeCell<*mut u8>,
+    /// The top of the heap at the end of the last garbage collection which produced this heap,
+    /// everything allocated below this point has survived at least one collection.
+    high_water_mark: Cell<Option<NonNull<u8>>>,
 }
 impl ProcessHeap {
-    const DEFAULT_HEAP_SIZE: usize = 4 * 1024;
+    pub const DEFAULT_HEAP_SIZE: usize = 4 * 1024;
 
     pub fn new() -> Self {
-        let layout =
-            Layout::from_size_align(Self::DEFAULT_HEAP_SIZE, mem::align_of::<Term>()).unwrap();
+        Self::with_capacity(Self::DEFAULT_HEAP_SIZE)
+    }
+
+    /// Creates a new heap which can hold `size` bytes
+    pub fn with_capacity(size: usize) -> Self {
+        let layout = Layout::from_size_align(size, mem::align_of::<Term>()).unwrap();
         let nonnull = Global.allocate(layout).unwrap();
         Self {
             range: nonnull.as_ptr(),
             top: UnsafeCell::new(nonnull.as_non_null_ptr().as_ptr()),
+            high_water_mark: Cell::new(None),
         }
     }
+
+    /// Creates a heap with no capacity, used as a placeholder for the mature heap of a process
+    /// until its first collection.
+    pub fn empty() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Marks everything currently allocated on this heap as having survived a collection
+    pub fn set_high_water_mark(&self) {
+        self.high_water_mark.set(NonNull::new(self.heap_top()));
+    }
 }
 impl Drop for ProcessHeap {
     fn drop(&mut self) {
@@ -43,11 +62,9 @@ unsafe impl Allocator for ProcessHeap {
         let base = unsafe { top.add(offset) };
         let new_top = unsafe { base.add(size) } as *const u8;
 
-        // Make sure the requested allocation fits within the fragment
-        let start = self.range.as_mut_ptr() as *const u8;
-        let heap_size = self.range.len();
-        let range = start..(unsafe { start.add(heap_size) });
-        if range.contains(&new_top) {
+        // Make sure the requested allocation fits within the heap, note that an
+        // allocation which exactly fills the heap ends at `heap_end`
+        if new_top <= self.heap_end() as *const u8 {
             unsafe {
                 self.top.get().write(new_top as *mut u8);
             }
@@ -98,4 +115,9 @@ impl Heap for ProcessHeap {
     fn heap_end(&self) -> *mut u8 {
         unsafe { self.heap_start().add(self.range.len()) }
     }
+
+    #[inline]
+    fn high_water_mark(&self) -> Option<NonNull<u8>> {
+        self.high_water_mark.get()
+    }
 }
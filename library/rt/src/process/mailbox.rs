@@ -0,0 +1,239 @@
+use alloc::alloc::AllocError;
+use alloc::boxed::Box;
+use core::ptr::{self, NonNull};
+
+use intrusive_collections::intrusive_adapter;
+use intrusive_collections::{LinkedList, LinkedListLink, UnsafeRef};
+
+use firefly_alloc::fragment::{HeapFragment, HeapFragmentAdapter};
+
+use crate::term::{OpaqueTerm, Term};
+
+intrusive_adapter!(pub MessageAdapter = Box<Message>: Message { link: LinkedListLink });
+
+/// Describes where the term of a message is stored
+///
+/// NOTE: The layout of this type is relied upon by the compiler, which generates code to
+/// read the message term directly when peeking a message during a receive.
+#[derive(Debug, Copy, Clone)]
+#[repr(C, u32)]
+pub enum MessageData {
+    /// The message term was allocated on the receiving process heap
+    Process(OpaqueTerm),
+    /// The message term was allocated in a heap fragment owned by the message
+    HeapFragment(OpaqueTerm),
+}
+impl MessageData {
+    #[inline]
+    pub fn term(&self) -> OpaqueTerm {
+        match self {
+            Self::Process(term) | Self::HeapFragment(term) => *term,
+        }
+    }
+}
+
+/// A message which has been delivered to a process mailbox
+///
+/// Each message is an entry in an intrusive, doubly-linked list which forms the queue of
+/// the mailbox. Pointers to messages are handed out to receive state machines as cursors
+/// into that queue, so a message must not move while it is in the mailbox.
+///
+/// NOTE: The layout of this type is relied upon by the compiler, see `MessageData`.
+#[repr(C)]
+pub struct Message {
+    link: LinkedListLink,
+    pub data: MessageData,
+    fragment: Option<NonNull<HeapFragment>>,
+}
+impl Message {
+    /// Returns the message term
+    #[inline]
+    pub fn term(&self) -> OpaqueTerm {
+        self.data.term()
+    }
+}
+impl Drop for Message {
+    fn drop(&mut self) {
+        if let Some(fragment) = self.fragment.take() {
+            unsafe {
+                fragment.as_ptr().drop_in_place();
+            }
+        }
+    }
+}
+
+/// The message queue of a process
+///
+/// Messages are received in the order they were delivered, oldest first, but may be removed
+/// from anywhere in the queue, as is the case for a selective receive.
+pub struct Mailbox {
+    len: usize,
+    messages: LinkedList<MessageAdapter>,
+    /// The save pointer of the current receive, i.e. the last message which was inspected
+    /// and rejected, or null if no message has been inspected yet.
+    save: *const Message,
+    /// Heap fragments of messages which have been removed from the mailbox.
+    ///
+    /// Once received, a message term may be referenced from the process heap, so the
+    /// fragments are kept alive here until the process is garbage collected.
+    fragments: LinkedList<HeapFragmentAdapter>,
+}
+impl Mailbox {
+    /// Create a new, empty mailbox
+    pub fn new() -> Self {
+        Self {
+            len: 0,
+            messages: LinkedList::new(MessageAdapter::new()),
+            save: ptr::null(),
+            fragments: LinkedList::new(HeapFragmentAdapter::new()),
+        }
+    }
+
+    /// Returns true if the mailbox is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Returns the number of messages in the mailbox
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns an iterator over the messages in the mailbox, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &Message> + '_ {
+        self.messages.iter()
+    }
+
+    /// Appends a copy of `term` to the back of the queue.
+    ///
+    /// The term is cloned into a new heap fragment which is owned by the message, as the
+    /// receiving process heap may not be safely allocated on by the sender.
+    pub fn push(&mut self, term: Term) -> Result<(), AllocError> {
+        let message = match term {
+            // Immediates don't require any storage, and constant binaries are never freed
+            Term::None
+            | Term::Nil
+            | Term::Bool(_)
+            | Term::Atom(_)
+            | Term::Int(_)
+            | Term::Float(_)
+            | Term::ConstantBinary(_) => Message {
+                link: LinkedListLink::new(),
+                data: MessageData::Process(term.into()),
+                fragment: None,
+            },
+            term => {
+                let (term, fragment) = term.clone_to_fragment()?;
+                Message {
+                    link: LinkedListLink::new(),
+                    data: MessageData::HeapFragment(term.into()),
+                    fragment: Some(fragment),
+                }
+            }
+        };
+        self.messages.push_back(Box::new(message));
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Returns the oldest message in the mailbox
+    #[inline]
+    pub fn front(&self) -> Option<&Message> {
+        self.messages.front().get()
+    }
+
+    /// Returns the message following `message` in the queue, if there is one
+    ///
+    /// # Safety
+    ///
+    /// The given pointer must refer to a message which is still in this mailbox
+    pub unsafe fn next(&self, message: *const Message) -> Option<&Message> {
+        let mut cursor = self.messages.cursor_from_ptr(message);
+        cursor.move_next();
+        cursor.get()
+    }
+
+    /// Removes the given message from the mailbox
+    ///
+    /// # Safety
+    ///
+    /// The given pointer must refer to a message which is still in this mailbox
+    pub unsafe fn remove(&mut self, message: *const Message) {
+        if self.save == message {
+            self.save = ptr::null();
+        }
+        let mut cursor = self.messages.cursor_mut_from_ptr(message);
+        let mut message = cursor.remove().unwrap();
+        self.len -= 1;
+        if let Some(fragment) = message.fragment.take() {
+            self.fragments
+                .push_back(UnsafeRef::from_raw(fragment.as_ptr()));
+        }
+    }
+
+    /// Returns the message which the current receive should inspect next, if available
+    pub fn peek(&self) -> Option<&Message> {
+        if self.save.is_null() {
+            self.front()
+        } else {
+            unsafe { self.next(self.save) }
+        }
+    }
+
+    /// Moves the save pointer past the message returned by `peek`, if there was one
+    pub fn recv_next(&mut self) {
+        if let Some(message) = self.peek() {
+            self.save = message as *const Message;
+        }
+    }
+
+    /// Removes the message returned by `peek`, and resets the save pointer so that the next
+    /// receive starts from the oldest message in the mailbox
+    pub fn remove_message(&mut self) {
+        if let Some(message) = self.peek() {
+            let message = message as *const Message;
+            unsafe {
+                self.remove(message);
+            }
+        }
+        self.save = ptr::null();
+    }
+
+    /// Resets the save pointer so that the next receive starts from the oldest message
+    #[inline]
+    pub fn reset_save(&mut self) {
+        self.save = ptr::null();
+    }
+
+    /// Returns an iterator over the heap fragments of messages which have been received
+    pub(super) fn received_fragments(&self) -> impl Iterator<Item = &HeapFragment> + '_ {
+        self.fragments.iter()
+    }
+
+    /// Frees the heap fragments of messages which have been received
+    ///
+    /// # Safety
+    ///
+    /// This must only be called by the garbage collector, once any live data in these
+    /// fragments has been moved to the process heap.
+    pub(super) unsafe fn free_received_fragments(&mut self) {
+        while let Some(fragment) = self.fragments.pop_front() {
+            UnsafeRef::into_raw(fragment).drop_in_place();
+        }
+    }
+}
+impl Default for Mailbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Drop for Mailbox {
+    fn drop(&mut self) {
+        self.messages.clear();
+        unsafe {
+            self.free_received_fragments();
+        }
+    }
+}
@@ -1,17 +1,25 @@
+mod gc;
 mod heap;
+mod mailbox;
 mod stack;
 
 use alloc::alloc::{AllocError, Allocator, Layout};
-use core::cell::UnsafeCell;
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::cell::{Cell, UnsafeCell};
+use core::cmp;
 use core::ptr::NonNull;
 
-use firefly_alloc::heap::Heap;
+use intrusive_collections::{LinkedList, UnsafeRef};
+
+use firefly_alloc::fragment::{HeapFragment, HeapFragmentAdapter};
+use firefly_alloc::heap::{Heap, SemispaceHeap};
 
 use crate::error::ErlangException;
 use crate::function::ModuleFunctionArity;
-use crate::term::ProcessId;
+use crate::term::{ProcessId, ReferenceId, Term};
 
 pub use self::heap::ProcessHeap;
+pub use self::mailbox::{Mailbox, Message, MessageData};
 pub use self::stack::ProcessStack;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -39,8 +47,24 @@ pub struct Process {
     /// In both cases access is exclusive, and special care is taken to guarantee
     /// that when a GC takes place, that live references held by the suspended process
     /// are properly updated so that the aliasing in that case is safe.
-    heap: UnsafeCell<ProcessHeap>,
+    heap: UnsafeCell<SemispaceHeap<ProcessHeap, ProcessHeap>>,
+    /// Heap fragments used for allocations which did not fit on the process heap, these
+    /// are freed by the next garbage collection, once their live contents have been moved.
+    fragments: UnsafeCell<LinkedList<HeapFragmentAdapter>>,
     stack: UnsafeCell<ProcessStack>,
+    /// The mailbox is only ever accessed by the owning scheduler, either on behalf of the
+    /// process itself when receiving, or when delivering a message sent by another process
+    /// on the same scheduler.
+    mailbox: UnsafeCell<Mailbox>,
+    /// The set of processes linked to this one, only ever accessed by the owning scheduler
+    links: UnsafeCell<BTreeSet<ProcessId>>,
+    /// The monitors held by this process, i.e. the processes it is monitoring, by reference
+    monitors: UnsafeCell<BTreeMap<ReferenceId, ProcessId>>,
+    /// The monitors held on this process by other processes, by reference
+    monitored_by: UnsafeCell<BTreeMap<ReferenceId, ProcessId>>,
+    /// When set, exit signals received by this process are converted to `{'EXIT', From, Reason}`
+    /// messages, rather than terminating the process, except for untrappable `kill` signals.
+    trap_exit: Cell<bool>,
 }
 impl Process {
     pub fn new(parent: Option<ProcessId>, pid: ProcessId, mfa: ModuleFunctionArity) -> Self {
@@ -49,8 +73,14 @@ impl Process {
             pid,
             mfa,
             status: UnsafeCell::new(ProcessStatus::Waiting),
-            heap: UnsafeCell::new(ProcessHeap::new()),
+            heap: UnsafeCell::new(SemispaceHeap::new(ProcessHeap::new(), ProcessHeap::empty())),
+            fragments: UnsafeCell::new(LinkedList::new(HeapFragmentAdapter::new())),
             stack: UnsafeCell::new(ProcessStack::new(32).unwrap()),
+            mailbox: UnsafeCell::new(Mailbox::new()),
+            links: UnsafeCell::new(BTreeSet::new()),
+            monitors: UnsafeCell::new(BTreeMap::new()),
+            monitored_by: UnsafeCell::new(BTreeMap::new()),
+            trap_exit: Cell::new(false),
         }
     }
 
@@ -94,16 +124,109 @@ impl Process {
         self.status.get().write(status);
     }
 
+    /// Returns a mutable reference to the process mailbox
+    ///
+    /// # Safety
+    ///
+    /// This function must be called with exclusive access to the mailbox, i.e. by the owning
+    /// scheduler, or from within the process itself while it is executing. The returned
+    /// reference must not outlive that exclusive access.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn mailbox(&self) -> &mut Mailbox {
+        &mut *self.mailbox.get()
+    }
+
+    /// Returns a mutable reference to the set of processes linked to this one
+    ///
+    /// # Safety
+    ///
+    /// The same rules apply as for `mailbox`, this must only be called with exclusive access
+    /// to the process, i.e. by the owning scheduler or by the process itself while executing.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn links(&self) -> &mut BTreeSet<ProcessId> {
+        &mut *self.links.get()
+    }
+
+    /// Returns a mutable reference to the monitors held by this process
+    ///
+    /// # Safety
+    ///
+    /// The same rules apply as for `links`.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn monitors(&self) -> &mut BTreeMap<ReferenceId, ProcessId> {
+        &mut *self.monitors.get()
+    }
+
+    /// Returns a mutable reference to the monitors held on this process by other processes
+    ///
+    /// # Safety
+    ///
+    /// The same rules apply as for `links`.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn monitored_by(&self) -> &mut BTreeMap<ReferenceId, ProcessId> {
+        &mut *self.monitored_by.get()
+    }
+
+    /// Returns true if this process is trapping exits
+    #[inline]
+    pub fn trap_exit(&self) -> bool {
+        self.trap_exit.get()
+    }
+
+    /// Sets whether this process traps exits, returning the previous value
+    #[inline]
+    pub fn set_trap_exit(&self, trap_exit: bool) -> bool {
+        self.trap_exit.replace(trap_exit)
+    }
+
+    /// Delivers a copy of `message` to the mailbox of this process
+    ///
+    /// This does not wake the process if it is waiting, that is the responsibility of the
+    /// scheduler which owns it.
+    pub fn send(&self, message: Term) -> Result<(), AllocError> {
+        unsafe { self.mailbox().push(message) }
+    }
+
     #[inline(always)]
-    fn heap(&self) -> &ProcessHeap {
+    fn heap(&self) -> &SemispaceHeap<ProcessHeap, ProcessHeap> {
         unsafe { &*self.heap.get() }
     }
+
+    /// Allocates from the most recent heap fragment, or a new fragment if it has no room
+    ///
+    /// This is used when the process heap is full, so that allocation never fails just
+    /// because a garbage collection could not be performed at that point.
+    fn allocate_fragment(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let fragments = unsafe { &mut *self.fragments.get() };
+        if let Some(fragment) = fragments.back().get() {
+            if let Ok(ptr) = fragment.allocate(layout) {
+                return Ok(ptr);
+            }
+        }
+        let size = cmp::max(layout.pad_to_align().size(), ProcessHeap::DEFAULT_HEAP_SIZE);
+        let fragment_layout = Layout::from_size_align(size, layout.align()).unwrap();
+        let fragment = HeapFragment::new(fragment_layout, None)?;
+        fragments.push_back(unsafe { UnsafeRef::from_raw(fragment.as_ptr()) });
+        unsafe { fragment.as_ref() }.allocate(layout)
+    }
+}
+impl Drop for Process {
+    fn drop(&mut self) {
+        let fragments = self.fragments.get_mut();
+        while let Some(fragment) = fragments.pop_front() {
+            unsafe {
+                UnsafeRef::into_raw(fragment).drop_in_place();
+            }
+        }
+    }
 }
 
 unsafe impl Allocator for Process {
     #[inline]
     fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
-        self.heap().allocate(layout)
+        self.heap()
+            .allocate(layout)
+            .or_else(|_| self.allocate_fragment(layout))
     }
 
     #[inline]
@@ -166,5 +289,8 @@ impl Heap for Process {
     #[inline]
     fn contains<T: ?Sized>(&self, ptr: *const T) -> bool {
         self.heap().contains(ptr)
+            || unsafe { &*self.fragments.get() }
+                .iter()
+                .any(|fragment| fragment.contains(ptr))
     }
 }
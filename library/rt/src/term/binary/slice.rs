@@ -1,6 +1,7 @@
 use core::any::TypeId;
 use core::fmt;
 use core::hash::{Hash, Hasher};
+use core::slice;
 
 use firefly_binary::{Bitstring, Selection};
 
@@ -55,6 +56,51 @@ impl BitSlice {
     pub fn as_selection(&self) -> Selection<'static> {
         self.selection
     }
+
+    /// Returns the term which owns the data referenced by this slice
+    #[inline]
+    pub fn owner(&self) -> OpaqueTerm {
+        self.owner
+    }
+
+    /// Rewrites this slice to borrow from `owner`, a copy of the original owner whose data
+    /// has been moved from `old_base` to `new_base` by the garbage collector.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the data of the original owner was copied as-is from
+    /// `old_base` to `new_base`, so that the selected bytes are at the same offset in both.
+    pub unsafe fn relocate(&mut self, owner: OpaqueTerm, old_base: *const u8, new_base: *const u8) {
+        unsafe fn relocate_bytes(
+            bytes: &'static [u8],
+            old_base: *const u8,
+            new_base: *const u8,
+        ) -> &'static [u8] {
+            // Empty slices do not necessarily point into the original data
+            if bytes.is_empty() {
+                return bytes;
+            }
+            let offset = bytes.as_ptr().offset_from(old_base);
+            slice::from_raw_parts(new_base.offset(offset), bytes.len())
+        }
+
+        self.owner = owner;
+        self.selection = match self.selection {
+            Selection::AlignedBinary(bytes) => {
+                Selection::AlignedBinary(relocate_bytes(bytes, old_base, new_base))
+            }
+            Selection::Binary(l, bytes, r) => {
+                Selection::Binary(l, relocate_bytes(bytes, old_base, new_base), r)
+            }
+            Selection::AlignedBitstring(bytes, r) => {
+                Selection::AlignedBitstring(relocate_bytes(bytes, old_base, new_base), r)
+            }
+            Selection::Bitstring(l, bytes, r) => {
+                Selection::Bitstring(l, relocate_bytes(bytes, old_base, new_base), r)
+            }
+            selection @ (Selection::Empty | Selection::Byte(_)) => selection,
+        };
+    }
 }
 impl Bitstring for BitSlice {
     #[inline]
@@ -99,6 +99,10 @@ impl Closure {
         &self.env
     }
 
+    pub fn env_mut(&mut self) -> &mut [OpaqueTerm] {
+        &mut self.env
+    }
+
     pub fn callee(&self) -> *const () {
         self.fun
     }
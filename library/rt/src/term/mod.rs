@@ -28,6 +28,7 @@ pub use firefly_number::{BigInt, Float, Integer, Number};
 use firefly_number::{DivisionError, InvalidArithmeticError, Sign, ToPrimitive};
 
 use alloc::alloc::{AllocError, Layout};
+use alloc::vec::Vec;
 use core::convert::AsRef;
 use core::fmt;
 use core::ptr::NonNull;
@@ -94,7 +95,14 @@ impl Term {
         Ok((term, frag))
     }
 
+    /// Clones this term, and any terms it references, to the given heap
+    ///
+    /// Terms which are already allocated on `heap` are not cloned, nor is anything they reference.
     pub fn clone_to_heap<H: Heap>(self, heap: H) -> Result<Self, AllocError> {
+        self.deep_clone_to_heap(&heap)
+    }
+
+    fn deep_clone_to_heap<H: Heap>(self, heap: &H) -> Result<Self, AllocError> {
         let cloned = match self {
             Self::None => Self::None,
             Self::Nil => Self::Nil,
@@ -115,12 +123,34 @@ impl Term {
                 if heap.contains(ptr.as_ptr()) {
                     Self::Cons(ptr)
                 } else {
-                    let old = unsafe { ptr.as_ref() };
-                    let cons = Cons::new_in(heap)?;
-                    unsafe {
-                        cons.as_uninit_mut().write(*old);
+                    // Lists can be arbitrarily long, so the spine is cloned iteratively
+                    let first = Cons::new_in(heap)?;
+                    let mut cell = first;
+                    let mut old = unsafe { ptr.as_ref() };
+                    loop {
+                        let head = old.head().deep_clone_to_heap(heap)?;
+                        unsafe {
+                            cell.as_uninit_mut().write(Cons::cons(head, Term::Nil));
+                        }
+                        match old.tail() {
+                            Self::Cons(next) if !heap.contains(next.as_ptr()) => {
+                                let next_cell = Cons::new_in(heap)?;
+                                unsafe {
+                                    cell.as_mut().tail = Self::Cons(next_cell).into();
+                                    old = next.as_ref();
+                                }
+                                cell = next_cell;
+                            }
+                            tail => {
+                                let tail = tail.deep_clone_to_heap(heap)?;
+                                unsafe {
+                                    cell.as_mut().tail = tail.into();
+                                }
+                                break;
+                            }
+                        }
                     }
-                    Self::Cons(cons)
+                    Self::Cons(first)
                 }
             }
             Self::Tuple(ptr) => {
@@ -128,22 +158,42 @@ impl Term {
                     Self::Tuple(ptr)
                 } else {
                     let tuple = unsafe { ptr.as_ref() };
-                    Self::Tuple(Tuple::from_slice(tuple.as_slice(), heap)?)
+                    let elements = tuple
+                        .iter()
+                        .map(|element| element.deep_clone_to_heap(heap).map(OpaqueTerm::from))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Self::Tuple(Tuple::from_slice(elements.as_slice(), heap)?)
                 }
             }
             Self::Map(boxed) => {
                 if heap.contains(GcBox::as_ptr(&boxed)) {
                     Self::Map(boxed)
                 } else {
-                    Self::Map(GcBox::new_in((&*boxed).clone(), heap)?)
+                    let items = boxed
+                        .iter()
+                        .map(|(k, v)| {
+                            Ok((k.deep_clone_to_heap(heap)?, v.deep_clone_to_heap(heap)?))
+                        })
+                        .collect::<Result<Vec<_>, AllocError>>()?;
+                    Self::Map(GcBox::new_in(Map::new_from_iter(items.into_iter()), heap)?)
                 }
             }
             Self::Closure(boxed) => {
                 if heap.contains(GcBox::as_ptr(&boxed)) {
                     Self::Closure(boxed)
                 } else {
-                    let mut cloned = GcBox::<Closure>::with_capacity_in(boxed.env_size(), heap)?;
-                    cloned.copy_from(&boxed);
+                    let mut cloned = Closure::new_in(
+                        boxed.module,
+                        boxed.name,
+                        boxed.arity as u8,
+                        boxed.callee(),
+                        boxed.env(),
+                        heap,
+                    )?;
+                    for (i, opaque) in boxed.env().iter().copied().enumerate() {
+                        let term: Term = opaque.into();
+                        cloned.env_mut()[i] = term.deep_clone_to_heap(heap)?.into();
+                    }
                     Self::Closure(cloned)
                 }
             }
@@ -312,7 +362,25 @@ impl Term {
                     .unwrap();
                 base.pad_to_align()
             }
-            Self::Cons(_) => Layout::new::<Cons>(),
+            Self::Cons(ptr) => {
+                let mut layout = Layout::new::<Cons>();
+                let mut cons = unsafe { ptr.as_ref() };
+                loop {
+                    let (extended, _) = layout.extend(cons.head().layout()).unwrap();
+                    layout = extended.pad_to_align();
+                    match cons.tail() {
+                        Self::Cons(next) => {
+                            let (extended, _) = layout.extend(Layout::new::<Cons>()).unwrap();
+                            layout = extended.pad_to_align();
+                            cons = unsafe { next.as_ref() };
+                        }
+                        tail => {
+                            let (extended, _) = layout.extend(tail.layout()).unwrap();
+                            break extended.pad_to_align();
+                        }
+                    }
+                }
+            }
             Self::Tuple(t) => {
                 let tuple = unsafe { t.as_ref() };
                 let base = Layout::for_value(tuple);
@@ -0,0 +1,68 @@
+use std::ops::Deref;
+use std::ptr::NonNull;
+
+use firefly_alloc::gc::GcBox;
+use firefly_rt::backtrace::Trace;
+use firefly_rt::error::ErlangException;
+use firefly_rt::function::ErlangResult;
+use firefly_rt::term::*;
+
+use crate::scheduler;
+
+use super::{badarg, make_reason, system_limit};
+
+#[export_name = "maps:from_list/1"]
+#[allow(improper_ctypes_definitions)]
+pub extern "C-unwind" fn from_list(list: OpaqueTerm) -> ErlangResult {
+    // When a key occurs more than once, the last value for that key is the one kept
+    let map = match list.into() {
+        Term::Nil => Map::new(),
+        Term::Cons(cons) => match Map::from_keyword_list(unsafe { cons.as_ref() }) {
+            Ok(map) => map,
+            Err(_) => return badarg(Trace::capture()),
+        },
+        _ => return badarg(Trace::capture()),
+    };
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        let proc = arc_proc.deref();
+        match GcBox::new_in(map, proc) {
+            Ok(boxed) => ErlangResult::Ok(Term::Map(boxed).into()),
+            Err(_) => system_limit(Trace::capture()),
+        }
+    })
+}
+
+#[export_name = "maps:to_list/1"]
+#[allow(improper_ctypes_definitions)]
+pub extern "C-unwind" fn to_list(map: OpaqueTerm) -> ErlangResult {
+    let Term::Map(boxed) = map.into() else { return ErlangResult::Err(badmap(map)); };
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        let proc = arc_proc.deref();
+        // Pairs are returned in key order, like BEAM does for small maps, rather than in the
+        // arbitrary order of the underlying hash trie. `ListBuilder` prepends, so the list is
+        // built from the last pair to the first.
+        let mut pairs = boxed.iter().collect::<Vec<_>>();
+        pairs.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        let mut builder = ListBuilder::new(proc);
+        for (key, value) in pairs.into_iter().rev() {
+            let Ok(pair) = Tuple::from_slice(&[(*key).into(), (*value).into()], proc) else {
+                return system_limit(Trace::capture());
+            };
+            if builder.push(Term::Tuple(pair)).is_err() {
+                return system_limit(Trace::capture());
+            }
+        }
+        match builder.finish() {
+            None => ErlangResult::Ok(OpaqueTerm::NIL),
+            Some(cons) => ErlangResult::Ok(Term::Cons(cons).into()),
+        }
+    })
+}
+
+fn badmap(term: OpaqueTerm) -> NonNull<ErlangException> {
+    let reason = make_reason(atoms::Badmap, term);
+    let err = ErlangException::new(atoms::Error, reason.into(), Trace::capture());
+    unsafe { NonNull::new_unchecked(Box::into_raw(err)) }
+}
@@ -1,8 +1,10 @@
 pub mod file;
 pub mod lists;
+pub mod maps;
 pub mod unicode;
 
 use std::io::Write;
+use std::mem;
 use std::ops::Deref;
 use std::ptr::NonNull;
 use std::sync::Arc;
@@ -13,9 +15,10 @@ use firefly_alloc::gc::GcBox;
 use firefly_rt::backtrace::Trace;
 use firefly_rt::error::ErlangException;
 use firefly_rt::function::{self, ErlangResult, ModuleFunctionArity};
+use firefly_rt::process::{Message, Process};
 use firefly_rt::term::*;
 
-use crate::scheduler;
+use crate::scheduler::{self, SpawnEntry, TimerKind};
 
 macro_rules! handle_arith_result {
     ($math:expr) => {
@@ -446,6 +449,463 @@ pub extern "C-unwind" fn raise2(reason: OpaqueTerm, trace: NonNull<Trace>) -> Er
     ErlangResult::Err(unsafe { NonNull::new_unchecked(Box::into_raw(err)) })
 }
 
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:self/0"]
+pub extern "C-unwind" fn self0() -> ErlangResult {
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        let proc = arc_proc.deref();
+        ErlangResult::Ok(make_pid(proc.pid(), proc))
+    })
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:spawn/1"]
+pub extern "C-unwind" fn spawn1(fun: OpaqueTerm) -> ErlangResult {
+    spawn_fun(fun, false)
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:spawn_link/1"]
+pub extern "C-unwind" fn spawn_link1(fun: OpaqueTerm) -> ErlangResult {
+    spawn_fun(fun, true)
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:spawn/3"]
+pub extern "C-unwind" fn spawn3(
+    module: OpaqueTerm,
+    function: OpaqueTerm,
+    arglist: OpaqueTerm,
+) -> ErlangResult {
+    spawn_mfa(module, function, arglist, false)
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:spawn_link/3"]
+pub extern "C-unwind" fn spawn_link3(
+    module: OpaqueTerm,
+    function: OpaqueTerm,
+    arglist: OpaqueTerm,
+) -> ErlangResult {
+    spawn_mfa(module, function, arglist, true)
+}
+
+fn spawn_fun(fun: OpaqueTerm, link: bool) -> ErlangResult {
+    let Term::Closure(closure) = fun.into() else { return badarg(Trace::capture()); };
+    if closure.arity != 0 {
+        return badarg(Trace::capture());
+    }
+    let mfa = ModuleFunctionArity::new(closure.module, closure.name, 0);
+    spawn_process(mfa, apply_fun, fun.into(), link)
+}
+
+fn spawn_mfa(
+    module: OpaqueTerm,
+    function: OpaqueTerm,
+    arglist: OpaqueTerm,
+    link: bool,
+) -> ErlangResult {
+    let (Term::Atom(m), Term::Atom(f)) = (module.into(), function.into()) else { return badarg(Trace::capture()); };
+    let arity = match arglist.into() {
+        Term::Nil => 0,
+        Term::Cons(ptr) => {
+            let mut arity = 0;
+            for element in unsafe { ptr.as_ref().iter() } {
+                if element.is_err() {
+                    return badarg(Trace::capture());
+                }
+                arity += 1;
+            }
+            arity
+        }
+        _ => return badarg(Trace::capture()),
+    };
+    let mfa = ModuleFunctionArity::new(m, f, arity);
+    let init = scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        let proc = arc_proc.deref();
+        Tuple::from_slice(&[module, function, arglist], proc)
+    });
+    match init {
+        Ok(init) => spawn_process(mfa, apply_mfa, Term::Tuple(init), link),
+        Err(_) => system_limit(Trace::capture()),
+    }
+}
+
+/// Spawns a new process which will start in `entry` with a copy of `arg`, returning its pid
+fn spawn_process(
+    mfa: ModuleFunctionArity,
+    entry: SpawnEntry,
+    arg: Term,
+    link: bool,
+) -> ErlangResult {
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        let proc = arc_proc.deref();
+        match scheduler.spawn(mfa, entry, arg, link) {
+            Ok(spawned) => ErlangResult::Ok(make_pid(spawned.pid(), proc)),
+            Err(_) => system_limit(Trace::capture()),
+        }
+    })
+}
+
+/// The entry point of a process spawned with a fun
+#[allow(improper_ctypes_definitions)]
+extern "C-unwind" fn apply_fun(fun: OpaqueTerm) -> ErlangResult {
+    let Term::Closure(fun) = fun.into() else { unreachable!() };
+    fun.apply(&[])
+}
+
+/// The entry point of a process spawned with a module, function and argument list
+#[allow(improper_ctypes_definitions)]
+extern "C-unwind" fn apply_mfa(init: OpaqueTerm) -> ErlangResult {
+    let Term::Tuple(ptr) = init.into() else { unreachable!() };
+    let init = unsafe { ptr.as_ref().as_slice() };
+    apply3(init[0], init[1], init[2])
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:send/2"]
+pub extern "C-unwind" fn send2(dest: OpaqueTerm, message: OpaqueTerm) -> ErlangResult {
+    // There is no process registry yet, so only pids are valid destinations
+    let Some(id) = local_pid(dest) else { return badarg(Trace::capture()); };
+    scheduler::with_current(|scheduler| match scheduler.send(id, message.into()) {
+        Ok(_) => ErlangResult::Ok(message),
+        Err(_) => system_limit(Trace::capture()),
+    })
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:!/2"]
+pub extern "C-unwind" fn bang2(dest: OpaqueTerm, message: OpaqueTerm) -> ErlangResult {
+    send2(dest, message)
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:link/1"]
+pub extern "C-unwind" fn link1(pid: OpaqueTerm) -> ErlangResult {
+    let Some(id) = local_pid(pid) else { return badarg(Trace::capture()); };
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        if id == arc_proc.pid() {
+            return ErlangResult::Ok(true.into());
+        }
+        match scheduler.find_process(id) {
+            Some(target) => unsafe {
+                arc_proc.links().insert(id);
+                target.links().insert(arc_proc.pid());
+            },
+            // Linking to a process which doesn't exist raises `noproc`, unless trapping exits,
+            // in which case it behaves as if the process exited with `noproc`
+            None if arc_proc.trap_exit() => {
+                let noproc = atoms::Noproc.into();
+                if scheduler.exit_signal(id, &arc_proc, noproc, true).is_err() {
+                    return system_limit(Trace::capture());
+                }
+            }
+            None => return noproc(Trace::capture()),
+        }
+        ErlangResult::Ok(true.into())
+    })
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:unlink/1"]
+pub extern "C-unwind" fn unlink1(pid: OpaqueTerm) -> ErlangResult {
+    let Some(id) = local_pid(pid) else { return badarg(Trace::capture()); };
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        unsafe {
+            arc_proc.links().remove(&id);
+        }
+        if let Some(target) = scheduler.find_process(id) {
+            unsafe {
+                target.links().remove(&arc_proc.pid());
+            }
+        }
+        ErlangResult::Ok(true.into())
+    })
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:monitor/2"]
+pub extern "C-unwind" fn monitor2(kind: OpaqueTerm, pid: OpaqueTerm) -> ErlangResult {
+    // Only process monitors are supported, as there are no ports or nodes to monitor
+    match kind.into() {
+        Term::Atom(kind) if kind == atoms::Process => (),
+        _ => return badarg(Trace::capture()),
+    }
+    let Some(id) = local_pid(pid) else { return badarg(Trace::capture()); };
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        let proc = arc_proc.deref();
+        let reference = scheduler.next_reference_id();
+        match scheduler.find_process(id) {
+            Some(target) => unsafe {
+                proc.monitors().insert(reference, id);
+                target.monitored_by().insert(reference, proc.pid());
+            },
+            // Monitoring a process which doesn't exist results in an immediate 'DOWN' message
+            None => {
+                let noproc = atoms::Noproc.into();
+                if scheduler.send_down(proc, reference, id, noproc).is_err() {
+                    return system_limit(Trace::capture());
+                }
+            }
+        }
+        ErlangResult::Ok(make_reference(reference, proc))
+    })
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:demonitor/1"]
+pub extern "C-unwind" fn demonitor1(reference: OpaqueTerm) -> ErlangResult {
+    demonitor(reference, false, false)
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:demonitor/2"]
+pub extern "C-unwind" fn demonitor2(reference: OpaqueTerm, options: OpaqueTerm) -> ErlangResult {
+    let mut flush = false;
+    let mut info = false;
+    match options.into() {
+        Term::Nil => (),
+        Term::Cons(ptr) => {
+            for option in unsafe { ptr.as_ref().iter() } {
+                match option {
+                    Ok(Term::Atom(a)) if a == atoms::Flush => flush = true,
+                    Ok(Term::Atom(a)) if a == atoms::Info => info = true,
+                    _ => return badarg(Trace::capture()),
+                }
+            }
+        }
+        _ => return badarg(Trace::capture()),
+    }
+    demonitor(reference, flush, info)
+}
+
+/// Removes the monitor identified by `reference`, if it is still active
+///
+/// If `flush` is set, any 'DOWN' message for the monitor is removed from the mailbox. If `info`
+/// is set, the result indicates whether the monitor was still active, otherwise it is `true`.
+fn demonitor(reference: OpaqueTerm, flush: bool, info: bool) -> ErlangResult {
+    let Term::Reference(reference) = reference.into() else { return badarg(Trace::capture()); };
+    let id = reference.id();
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        let removed = unsafe { arc_proc.monitors().remove(&id) };
+        if let Some(target) = removed.and_then(|pid| scheduler.find_process(pid)) {
+            unsafe {
+                target.monitored_by().remove(&id);
+            }
+        }
+
+        if flush {
+            let mailbox = unsafe { arc_proc.mailbox() };
+            let down = mailbox
+                .iter()
+                .filter(|message| is_down_message(message.term(), id))
+                .map(|message| message as *const Message)
+                .collect::<SmallVec<[_; 1]>>();
+            for message in down {
+                unsafe {
+                    mailbox.remove(message);
+                }
+            }
+        }
+
+        ErlangResult::Ok((!info || removed.is_some()).into())
+    })
+}
+
+/// Returns true if `message` is a 'DOWN' message for the monitor identified by `id`
+fn is_down_message(message: OpaqueTerm, id: ReferenceId) -> bool {
+    let Term::Tuple(ptr) = message.into() else { return false; };
+    match unsafe { ptr.as_ref().as_slice() } {
+        [tag, reference, _, _, _] => match ((*tag).into(), (*reference).into()) {
+            (Term::Atom(tag), Term::Reference(reference)) => {
+                tag == atoms::DOWN && reference.id() == id
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:process_flag/2"]
+pub extern "C-unwind" fn process_flag2(flag: OpaqueTerm, value: OpaqueTerm) -> ErlangResult {
+    // Only `trap_exit` is supported for now
+    match (flag.into(), value.into()) {
+        (Term::Atom(flag), Term::Bool(value)) if flag == atoms::TrapExit => {
+            scheduler::with_current_process(|proc| {
+                ErlangResult::Ok(proc.set_trap_exit(value).into())
+            })
+        }
+        _ => badarg(Trace::capture()),
+    }
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:process_info/2"]
+pub extern "C-unwind" fn process_info2(pid: OpaqueTerm, item: OpaqueTerm) -> ErlangResult {
+    let Some(id) = local_pid(pid) else { return badarg(Trace::capture()); };
+    // Only the heap sizes are supported for now
+    let item = match item.into() {
+        Term::Atom(a) if a == atoms::HeapSize || a == atoms::TotalHeapSize => a,
+        _ => return badarg(Trace::capture()),
+    };
+    scheduler::with_current(|scheduler| {
+        let Some(target) = scheduler.find_process(id) else {
+            return ErlangResult::Ok(atoms::Undefined.into());
+        };
+        let bytes = if item == atoms::HeapSize {
+            target.heap_size()
+        } else {
+            target.total_heap_size()
+        };
+        let words = (bytes / mem::size_of::<OpaqueTerm>()) as i64;
+        let arc_proc = scheduler.current_process();
+        match Tuple::from_slice(&[item.into(), Term::Int(words).into()], arc_proc.deref()) {
+            Ok(tuple) => ErlangResult::Ok(tuple.into()),
+            Err(_) => system_limit(Trace::capture()),
+        }
+    })
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:exit/2"]
+pub extern "C-unwind" fn exit2(pid: OpaqueTerm, reason: OpaqueTerm) -> ErlangResult {
+    let Some(id) = local_pid(pid) else { return badarg(Trace::capture()); };
+    let reason: Term = reason.into();
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        if id == arc_proc.pid() {
+            // Unlike when sent by another process, `normal` terminates the sender when sent to
+            // itself, unless it is trapping exits
+            let normal = matches!(reason, Term::Atom(a) if a == atoms::Normal);
+            if normal && !arc_proc.trap_exit() {
+                scheduler.terminate(&arc_proc, reason);
+            } else if scheduler.exit_signal(id, &arc_proc, reason, false).is_err() {
+                return system_limit(Trace::capture());
+            }
+        } else if let Some(target) = scheduler.find_process(id) {
+            let from = arc_proc.pid();
+            if scheduler.exit_signal(from, &target, reason, false).is_err() {
+                return system_limit(Trace::capture());
+            }
+        }
+        // The signal may have terminated this process, either directly, or by being
+        // propagated back to it via links
+        drop(arc_proc);
+        scheduler.yield_if_exiting();
+        ErlangResult::Ok(true.into())
+    })
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:send_after/3"]
+pub extern "C-unwind" fn send_after3(
+    time: OpaqueTerm,
+    dest: OpaqueTerm,
+    message: OpaqueTerm,
+) -> ErlangResult {
+    start_timer(time, dest, message, TimerKind::Message)
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:start_timer/3"]
+pub extern "C-unwind" fn start_timer3(
+    time: OpaqueTerm,
+    dest: OpaqueTerm,
+    message: OpaqueTerm,
+) -> ErlangResult {
+    start_timer(time, dest, message, TimerKind::Timeout)
+}
+
+/// Starts a timer which sends `message` to `dest` in `time` milliseconds, returning its reference
+fn start_timer(
+    time: OpaqueTerm,
+    dest: OpaqueTerm,
+    message: OpaqueTerm,
+    kind: TimerKind,
+) -> ErlangResult {
+    let time = match time.into() {
+        Term::Int(time) if time >= 0 => time as u64,
+        _ => return badarg(Trace::capture()),
+    };
+    // There is no process registry yet, so only pids are valid destinations
+    let Some(id) = local_pid(dest) else { return badarg(Trace::capture()); };
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        let proc = arc_proc.deref();
+        match scheduler.start_timer(time, kind, id, message.into()) {
+            Ok(reference) => ErlangResult::Ok(make_reference(reference, proc)),
+            Err(_) => system_limit(Trace::capture()),
+        }
+    })
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:cancel_timer/1"]
+pub extern "C-unwind" fn cancel_timer1(timer: OpaqueTerm) -> ErlangResult {
+    let Term::Reference(reference) = timer.into() else { return badarg(Trace::capture()); };
+    scheduler::with_current(|scheduler| match scheduler.cancel_timer(reference.id()) {
+        Some(remaining) => ErlangResult::Ok(Term::Int(remaining as i64).into()),
+        None => ErlangResult::Ok(false.into()),
+    })
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:monotonic_time/0"]
+pub extern "C-unwind" fn monotonic_time0() -> ErlangResult {
+    let time = scheduler::with_current(|scheduler| scheduler.monotonic_time_native());
+    ErlangResult::Ok(Term::Int(time as i64).into())
+}
+
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:monotonic_time/1"]
+pub extern "C-unwind" fn monotonic_time1(unit: OpaqueTerm) -> ErlangResult {
+    // Native time units are nanoseconds, so this is the number of units per second
+    const NATIVE: u128 = 1_000_000_000;
+
+    let units_per_second = match unit.into() {
+        Term::Atom(a) if a == atoms::Second => 1,
+        Term::Atom(a) if a == atoms::Millisecond => 1_000,
+        Term::Atom(a) if a == atoms::Microsecond => 1_000_000,
+        Term::Atom(a) if a == atoms::Nanosecond => NATIVE,
+        Term::Atom(a) if a == atoms::Native || a == atoms::PerfCounter => NATIVE,
+        Term::Int(n) if n > 0 => n as u128,
+        _ => return badarg(Trace::capture()),
+    };
+    let time = scheduler::with_current(|scheduler| scheduler.monotonic_time_native());
+    let time = (time as u128 * units_per_second) / NATIVE;
+    match i64::try_from(time) {
+        Ok(time) => ErlangResult::Ok(Term::Int(time).into()),
+        Err(_) => system_limit(Trace::capture()),
+    }
+}
+
+/// Returns the process id of `term`, if it is a local pid
+fn local_pid(term: OpaqueTerm) -> Option<ProcessId> {
+    match term.into() {
+        Term::Pid(pid) => match pid.deref() {
+            Pid::Local { id } => Some(*id),
+            Pid::External { .. } => None,
+        },
+        _ => None,
+    }
+}
+
+fn make_pid(id: ProcessId, proc: &Process) -> OpaqueTerm {
+    Term::Pid(GcBox::new_in(Pid::Local { id }, proc).unwrap()).into()
+}
+
+fn make_reference(id: ReferenceId, proc: &Process) -> OpaqueTerm {
+    Term::Reference(GcBox::new_in(Reference::Local { id }, proc).unwrap()).into()
+}
+
 fn make_reason<R: Into<OpaqueTerm>>(tag: Atom, reason: R) -> OpaqueTerm {
     scheduler::with_current(|scheduler| {
         let arc_proc = scheduler.current_process();
@@ -466,6 +926,16 @@ pub(self) fn badarg(trace: Arc<Trace>) -> ErlangResult {
     ErlangResult::Err(badarg_err(trace))
 }
 
+pub(self) fn system_limit(trace: Arc<Trace>) -> ErlangResult {
+    let err = ErlangException::new(atoms::Error, atoms::SystemLimit.into(), trace);
+    ErlangResult::Err(unsafe { NonNull::new_unchecked(Box::into_raw(err)) })
+}
+
+pub(self) fn noproc(trace: Arc<Trace>) -> ErlangResult {
+    let err = ErlangException::new(atoms::Error, atoms::Noproc.into(), trace);
+    ErlangResult::Err(unsafe { NonNull::new_unchecked(Box::into_raw(err)) })
+}
+
 pub(self) fn badarg_err(trace: Arc<Trace>) -> NonNull<ErlangException> {
     let err = ErlangException::new(atoms::Error, atoms::Badarg.into(), trace);
     unsafe { NonNull::new_unchecked(Box::into_raw(err)) }
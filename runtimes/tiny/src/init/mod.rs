@@ -19,7 +19,7 @@ extern "C-unwind" {
 ///
 /// NOTE: When this function is invoked, it is on the stack of the new process, not the scheduler.
 #[allow(improper_ctypes_definitions)]
-pub(crate) extern "C-unwind" fn start() -> ErlangResult {
+pub(crate) extern "C-unwind" fn start(_arg: OpaqueTerm) -> ErlangResult {
     scheduler::with_current_process(|process| {
         let argv = env::argv();
         let args = {
@@ -0,0 +1,94 @@
+use core::ptr::NonNull;
+
+use firefly_rt::process::Process;
+use firefly_rt::term::{atoms, OpaqueTerm};
+use firefly_stackmaps::StackMap;
+
+use crate::scheduler::Scheduler;
+
+/// Garbage collects the current process if its heap is due for collection, leaving room for at
+/// least `need` bytes to be allocated afterwards.
+///
+/// This must only be called from code generated by the compiler, i.e. an intrinsic called
+/// directly by the process, as only those frames have stack maps describing where their live
+/// terms are. Any terms held by runtime frames would not be updated when moved.
+///
+/// If the live data of the process can't be fit in a new heap, the process exits with
+/// `system_limit`, and this function does not return.
+///
+/// If the executable contains no stack maps, e.g. because the statepoints were never generated,
+/// the live terms can't be found, so the heap is never collected.
+pub fn maybe_collect(scheduler: &Scheduler, need: usize) {
+    let stack_map = StackMap::get();
+    if stack_map.is_empty() {
+        return;
+    }
+
+    let result = {
+        let process = scheduler.current_process();
+        if !process.should_collect() {
+            return;
+        }
+        let mut roots = stack_roots(stack_map, &process);
+        unsafe { process.garbage_collect(need, roots.as_mut_slice()) }
+    };
+
+    if result.is_err() {
+        let process = scheduler.current_process();
+        scheduler.terminate(&process, atoms::SystemLimit.into());
+        drop(process);
+        scheduler.yield_if_exiting();
+    }
+}
+
+/// Walks the stack of `process`, returning the location of every live term on it
+///
+/// For each frame which has an entry in the stack map, i.e. one suspended at a statepoint,
+/// the stack map describes where the live terms of that frame are, as offsets from the
+/// stack pointer at the call site, which is the canonical frame address of its callee.
+///
+/// Derived pointers are not visited, as generated code never offsets a term.
+///
+/// # Panics
+///
+/// Panics if a frame of a function which has stack maps is suspended at a call site which
+/// has none, as any terms held by that frame would be left dangling by a collection.
+fn stack_roots(stack_map: &StackMap, process: &Process) -> Vec<NonNull<OpaqueTerm>> {
+    let stack = process.stack();
+    let stack_range = (stack.bottom as usize)..(stack.top as usize);
+
+    let mut roots = Vec::new();
+    let mut callsite_sp = None;
+    backtrace::trace(|frame| {
+        if let Some(sp) = callsite_sp {
+            let ip = frame.ip() as *const u8;
+            match stack_map.find_frame(ip) {
+                Some(frame_info) => {
+                    for slot in frame_info.iter_base() {
+                        let root = (sp + slot.offset as usize) as *mut OpaqueTerm;
+                        roots.push(unsafe { NonNull::new_unchecked(root) });
+                    }
+                }
+                None => {
+                    let function = frame.symbol_address() as usize;
+                    let has_stack_map = stack_map
+                        .functions()
+                        .iter()
+                        .any(|info| { info.address } == function);
+                    assert!(
+                        !has_stack_map,
+                        "missing stack map for the call site at {:p}, its live terms cannot be found",
+                        ip
+                    );
+                }
+            }
+        }
+
+        // Stop once we've walked off the end of the process stack
+        let sp = frame.sp() as usize;
+        callsite_sp = Some(sp);
+        stack_range.contains(&sp)
+    });
+
+    roots
+}
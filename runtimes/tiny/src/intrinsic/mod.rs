@@ -1,3 +1,4 @@
+use core::mem;
 use core::ops::Deref;
 use core::ptr::NonNull;
 use std::sync::Arc;
@@ -26,10 +27,16 @@ macro_rules! err {
     };
 }
 
+mod gc;
+mod receive;
+
 /// Constructs a new empty term of the given type and size on the current process heap
 #[export_name = "__firefly_builtin_malloc"]
 pub extern "C-unwind" fn malloc(kind: TermType, size: usize) -> *mut () {
     scheduler::with_current(|scheduler| {
+        // This is an upper bound on the size of the allocation, including any header
+        let need = (size + 8) * mem::size_of::<OpaqueTerm>();
+        gc::maybe_collect(scheduler, need);
         let arc_proc = scheduler.current_process();
         let proc = arc_proc.deref();
         match kind {
@@ -717,3 +724,10 @@ pub(self) fn badarg(trace: Arc<Trace>) -> NonNull<ErlangException> {
     })
     .unwrap_err()
 }
+
+pub(self) fn timeout_value(trace: Arc<Trace>) -> NonNull<ErlangException> {
+    crate::erlang::raise2(atoms::TimeoutValue.into(), unsafe {
+        NonNull::new_unchecked(Trace::into_raw(trace))
+    })
+    .unwrap_err()
+}
@@ -0,0 +1,267 @@
+use core::ptr;
+
+use firefly_rt::backtrace::Trace;
+use firefly_rt::function::ErlangResult;
+use firefly_rt::process::Message;
+use firefly_rt::term::{atoms, OpaqueTerm, Term};
+
+use crate::scheduler::{self, Scheduler};
+
+use super::timeout_value;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReceiveState {
+    // Indicates to the caller that a message is available to peek
+    Peek = 0,
+    // Indicates to the caller that no message was available and the process
+    // was transitioned to the waiting state.
+    Wait = 1,
+    // Indicates to the caller that the receive timed out
+    Timeout = 2,
+}
+
+/// This structure manages the context for a receive state machine.
+///
+/// It is critical that the layout for this structure be carefully
+/// maintained, as the compiler generates code which accesses it. Any
+/// changes that modify the layout or semantics of this structure must
+/// be synchronized with codegen.
+#[repr(C)]
+pub struct ReceiveContext {
+    /// The absolute deadline, in milliseconds of scheduler monotonic time, after which
+    /// this receive times out. A value of `u64::MAX` indicates that it never times out.
+    timeout: u64,
+    /// Reserved for a timer reference used to wake the process if a message is never
+    /// received. Timeouts are currently handled by the scheduler directly, so this is
+    /// always `OpaqueTerm::NONE`.
+    #[allow(dead_code)]
+    timer_reference: OpaqueTerm,
+    /// This field is a pointer to the last Message in the mailbox which was peeked. Each
+    /// Message is an entry in an intrusive, doubly-linked list which forms the queue of the
+    /// mailbox, so this is used to resume the search for a matching message from where it
+    /// left off.
+    ///
+    /// NOTE: The only valid values for this pointer are null, or a pointer to a Message
+    /// which is still in the mailbox. For this reason, garbage collection must not occur
+    /// while a receive context is live.
+    message: *const Message,
+}
+impl ReceiveContext {
+    fn new(timeout: u64) -> Self {
+        Self {
+            timeout,
+            timer_reference: OpaqueTerm::NONE,
+            message: ptr::null(),
+        }
+    }
+
+    #[inline]
+    fn deadline(&self) -> Option<u64> {
+        if self.timeout == u64::MAX {
+            None
+        } else {
+            Some(self.timeout)
+        }
+    }
+}
+
+/// Converts a receive timeout term to an absolute deadline in milliseconds of monotonic time
+///
+/// Returns `Err` if the timeout is neither `infinity`, nor a non-negative integer, `Ok(None)`
+/// if the receive should never time out, and `Ok(Some(deadline))` otherwise.
+fn timeout_deadline(scheduler: &Scheduler, timeout: OpaqueTerm) -> Result<Option<u64>, ()> {
+    let timeout: Term = timeout.into();
+    match timeout {
+        Term::Atom(a) if a == atoms::Infinity => Ok(None),
+        Term::Int(ms) if ms >= 0 => Ok(Some(scheduler.monotonic_time().saturating_add(ms as u64))),
+        _ => Err(()),
+    }
+}
+
+/// This function is called to construct a receive state machine, it performs no other work
+/// beyond setting up the context for the state machine itself. The following state transition
+/// graph summarizes how the caller is expected to manage the state machine.
+///
+/// start -> next <---------<-------- yield
+///           |             |           |
+///           |             |           |
+///           v             |           |
+///           received --> peek         |
+///           |             |           |
+///           |             v           |
+///           |            pop          |
+///           |             |           |
+///           |             v           |
+///           timeout ---> done         |
+///           |                         |
+///           wait ---------------------^
+///
+/// When `next` transitions to the wait state, the process has been marked as waiting, and the
+/// caller is expected to yield. The scheduler will not resume the process until either a
+/// message is delivered to it, or the timeout expires, at which point `next` is called again.
+///
+/// NOTE: `peek` is implemented via generated code that directly accesses the message data.
+///
+/// The context is returned directly, so an invalid timeout can't be raised as an exception to
+/// the caller, instead the process exits with `timeout_value` as if it had been raised.
+#[export_name = "__firefly_builtin_receive_start"]
+pub extern "C-unwind" fn receive_start(timeout: OpaqueTerm) -> ReceiveContext {
+    scheduler::with_current(|scheduler| match timeout_deadline(scheduler, timeout) {
+        Ok(deadline) => ReceiveContext::new(deadline.unwrap_or(u64::MAX)),
+        Err(_) => scheduler.exit_with_error(atoms::TimeoutValue.into()),
+    })
+}
+
+/// This function is called upon entering the receive state machine, after a peeked message
+/// did not match, and after being woken up while waiting. It determines whether a message is
+/// available, and which state to transition to next.
+#[export_name = "__firefly_builtin_receive_next"]
+pub extern "C-unwind" fn receive_next(context: &mut ReceiveContext) -> ReceiveState {
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        let mailbox = unsafe { arc_proc.mailbox() };
+        let next = if context.message.is_null() {
+            mailbox.front()
+        } else {
+            unsafe { mailbox.next(context.message) }
+        };
+
+        if let Some(message) = next {
+            context.message = message as *const Message;
+            return ReceiveState::Peek;
+        }
+
+        match context.deadline() {
+            Some(deadline) if deadline <= scheduler.monotonic_time() => ReceiveState::Timeout,
+            deadline => {
+                scheduler.set_wait_deadline(deadline);
+                scheduler.wait();
+                ReceiveState::Wait
+            }
+        }
+    })
+}
+
+/// This function is called after a peeked message matched, and the receive state machine is
+/// exiting. The message is removed from the mailbox, but its heap fragment, if it has one, is
+/// kept alive by the process until the next garbage collection, which is performed here if
+/// the process is due one, so that the fragments of received messages don't pile up.
+#[export_name = "__firefly_builtin_receive_pop"]
+pub extern "C-unwind" fn receive_pop(context: &mut ReceiveContext) {
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        unsafe {
+            arc_proc.mailbox().remove(context.message);
+        }
+        context.message = ptr::null();
+        drop(arc_proc);
+        super::gc::maybe_collect(scheduler, 0);
+    })
+}
+
+/// This function is called when the receive state machine is exiting, and cleans up any
+/// state associated with the receive which should not be left dangling.
+#[export_name = "__firefly_builtin_receive_done"]
+pub extern "C-unwind" fn receive_done(context: &mut ReceiveContext) {
+    scheduler::with_current(|scheduler| scheduler.set_wait_deadline(None));
+    context.message = ptr::null();
+}
+
+/// The result of `erlang:recv_peek_message/0`
+#[repr(C)]
+#[allow(dead_code)]
+pub struct PeekResult {
+    /// A boolean term indicating whether a message was available
+    available: OpaqueTerm,
+    /// The message, or `OpaqueTerm::NONE` if no message was available
+    message: OpaqueTerm,
+}
+
+/// Returns the message at the save pointer of the current process mailbox, if available
+#[export_name = "erlang:recv_peek_message/0"]
+pub extern "C-unwind" fn recv_peek_message() -> PeekResult {
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        match unsafe { arc_proc.mailbox().peek() } {
+            Some(message) => PeekResult {
+                available: true.into(),
+                message: message.term(),
+            },
+            None => PeekResult {
+                available: false.into(),
+                message: OpaqueTerm::NONE,
+            },
+        }
+    })
+}
+
+/// Advances the save pointer of the current process mailbox past the last peeked message
+#[export_name = "erlang:recv_next/0"]
+pub extern "C-unwind" fn recv_next() {
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        unsafe {
+            arc_proc.mailbox().recv_next();
+        }
+    })
+}
+
+/// Removes the last peeked message from the current process mailbox, ending the receive
+#[export_name = "erlang:remove_message/0"]
+pub extern "C-unwind" fn remove_message() {
+    scheduler::with_current(|scheduler| {
+        let arc_proc = scheduler.current_process();
+        unsafe {
+            arc_proc.mailbox().remove_message();
+        }
+        scheduler.set_wait_deadline(None);
+    })
+}
+
+/// Waits for a message to be delivered to the current process, or for `timeout` to expire.
+///
+/// Returns `true` if the timeout expired, in which case the receive is over, or `false` if
+/// a new message may be available to peek.
+#[allow(improper_ctypes_definitions)]
+#[export_name = "erlang:recv_wait_timeout/1"]
+pub extern "C-unwind" fn recv_wait_timeout(timeout: OpaqueTerm) -> ErlangResult {
+    scheduler::with_current(|scheduler| {
+        // The deadline is set the first time we wait during a given receive, and is
+        // cleared when the receive completes, one way or the other
+        let deadline = match scheduler.wait_deadline() {
+            Some(deadline) => Some(deadline),
+            None => match timeout_deadline(scheduler, timeout) {
+                Ok(deadline) => deadline,
+                Err(_) => return err!(timeout_value(Trace::capture())),
+            },
+        };
+
+        let arc_proc = scheduler.current_process();
+        if deadline
+            .map(|d| d <= scheduler.monotonic_time())
+            .unwrap_or(false)
+        {
+            unsafe {
+                arc_proc.mailbox().reset_save();
+            }
+            scheduler.set_wait_deadline(None);
+            return ok!(true.into());
+        }
+
+        scheduler.set_wait_deadline(deadline);
+        scheduler.wait();
+        scheduler.process_yield();
+
+        // We were woken either due to a message being delivered, or the deadline expiring
+        if unsafe { arc_proc.mailbox().peek().is_some() } {
+            ok!(false.into())
+        } else {
+            unsafe {
+                arc_proc.mailbox().reset_save();
+            }
+            scheduler.set_wait_deadline(None);
+            ok!(true.into())
+        }
+    })
+}
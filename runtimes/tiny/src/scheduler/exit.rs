@@ -1,8 +1,9 @@
+use std::alloc::AllocError;
 use std::ptr::NonNull;
 
 use firefly_rt::error::{self, ErlangException};
 use firefly_rt::process::Process;
-use firefly_rt::term::{atoms, Term};
+use firefly_rt::term::{atoms, Term, Tuple};
 
 pub fn log_exit(process: &Process, ptr: NonNull<ErlangException>) -> bool {
     let exception = unsafe { ptr.as_ref() };
@@ -16,6 +17,27 @@ pub fn log_exit(process: &Process, ptr: NonNull<ErlangException>) -> bool {
     }
 }
 
+/// Returns the reason a process which raised `exception` exits with, as seen by the processes
+/// linked to, or monitoring, it
+///
+/// Like in BEAM, errors carry the stacktrace, i.e. `{Reason, Stack}`, and an uncaught throw
+/// becomes `{{nocatch, Reason}, Stack}`. Any terms needed are allocated on `process`.
+pub fn exit_reason(process: &Process, exception: &ErlangException) -> Result<Term, AllocError> {
+    let reason = exception.reason();
+    let kind = exception.kind();
+    if kind == atoms::Exit {
+        return Ok(reason);
+    }
+    let reason = if kind == atoms::Throw {
+        let nocatch = Tuple::from_slice(&[atoms::Nocatch.into(), reason.into()], process)?;
+        Term::Tuple(nocatch)
+    } else {
+        reason
+    };
+    let stack = exception.trace().as_term()?;
+    Tuple::from_slice(&[reason.into(), stack.into()], process).map(Term::Tuple)
+}
+
 fn is_expected_exit_reason(reason: Term) -> bool {
     match reason {
         Term::Atom(a) if a == atoms::Normal => true,
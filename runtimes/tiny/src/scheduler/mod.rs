@@ -1,21 +1,32 @@
 mod exit;
 mod queue;
+mod timer;
 
+use std::alloc::{AllocError, Layout};
 use std::arch::global_asm;
-use std::cell::{OnceCell, UnsafeCell};
+use std::cell::{Cell, OnceCell, UnsafeCell};
+use std::collections::BTreeMap;
 use std::mem;
-use std::ptr;
+use std::ptr::{self, NonNull, Pointee};
 use std::sync::{
     atomic::{AtomicI32, AtomicU64, Ordering},
     Arc,
 };
 use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
 
-use firefly_rt::function::{DynamicCallee, ModuleFunctionArity};
+use firefly_alloc::fragment::HeapFragment;
+use firefly_alloc::gc::{self, GcBox};
+use firefly_rt::backtrace::Trace;
+use firefly_rt::error::ErlangException;
+use firefly_rt::function::{ErlangResult, ModuleFunctionArity};
 use firefly_rt::process::{Process, ProcessStatus};
-use firefly_rt::term::{OpaqueTerm, Pid, ProcessId};
+use firefly_rt::term::{atoms, OpaqueTerm, Pid, ProcessId, Reference, ReferenceId, Term, Tuple};
 
 use self::queue::RunQueue;
+use self::timer::Timers;
+
+pub use self::timer::TimerKind;
 
 #[thread_local]
 pub static CURRENT_PROCESS: UnsafeCell<Option<Arc<Process>>> = UnsafeCell::new(None);
@@ -23,6 +34,12 @@ pub static CURRENT_PROCESS: UnsafeCell<Option<Arc<Process>>> = UnsafeCell::new(N
 #[thread_local]
 pub static CURRENT_SCHEDULER: OnceCell<Scheduler> = OnceCell::new();
 
+/// The type of function which acts as the entry point for a newly spawned process
+///
+/// The sole argument is the term given when spawning, allocated on the new process heap.
+#[allow(improper_ctypes_definitions)]
+pub type SpawnEntry = extern "C-unwind" fn(OpaqueTerm) -> ErlangResult;
+
 /// Returns a reference to the scheduler for the current thread
 pub fn with_current<F, R>(fun: F) -> R
 where
@@ -50,12 +67,16 @@ where
 struct SchedulerData {
     process: Arc<Process>,
     registers: UnsafeCell<CalleeSavedRegisters>,
+    /// When the process is waiting, this is the time (in milliseconds of scheduler monotonic
+    /// time) at which it should be woken even if no message has arrived, if ever.
+    deadline: Cell<Option<u64>>,
 }
 impl SchedulerData {
     fn new(process: Arc<Process>) -> Self {
         Self {
             process,
             registers: UnsafeCell::new(Default::default()),
+            deadline: Cell::new(None),
         }
     }
 
@@ -80,13 +101,23 @@ unsafe impl Sync for SchedulerData {}
 pub struct Scheduler {
     pub id: ThreadId,
     // References are always 64-bits even on 32-bit platforms
-    #[allow(dead_code)]
     next_reference_id: AtomicU64,
     // In this runtime, we aren't doing work-stealing, so the run queue
     // is never accessed by any other thread
     run_queue: UnsafeCell<RunQueue>,
+    // Processes which are parked waiting on a message or timeout, these are
+    // moved back to the run queue when woken
+    waiting: UnsafeCell<BTreeMap<ProcessId, Arc<SchedulerData>>>,
+    // All live processes owned by this scheduler, used to resolve pids
+    processes: UnsafeCell<BTreeMap<ProcessId, Arc<Process>>>,
+    // Timers started by processes on this scheduler, which are fired by the scheduler loop
+    timers: UnsafeCell<Timers>,
+    // The point in time from which the monotonic time of this scheduler is measured
+    epoch: Instant,
     prev: UnsafeCell<Option<Arc<SchedulerData>>>,
     current: UnsafeCell<Arc<SchedulerData>>,
+    // The pid of the init process, whose exit determines the exit status of the program
+    init: OnceCell<ProcessId>,
     halt_code: AtomicI32,
 }
 // This guarantee holds as long as `init` and `current` are only
@@ -116,6 +147,7 @@ impl Scheduler {
             Arc::new(SchedulerData {
                 process,
                 registers: UnsafeCell::new(registers),
+                deadline: Cell::new(None),
             })
         };
 
@@ -124,8 +156,13 @@ impl Scheduler {
             id,
             next_reference_id: AtomicU64::new(0),
             run_queue: UnsafeCell::new(RunQueue::default()),
+            waiting: UnsafeCell::new(BTreeMap::new()),
+            processes: UnsafeCell::new(BTreeMap::new()),
+            timers: UnsafeCell::new(Timers::default()),
+            epoch: Instant::now(),
             prev: UnsafeCell::new(None),
             current: UnsafeCell::new(root),
+            init: OnceCell::new(),
             halt_code: AtomicI32::new(0),
         })
     }
@@ -163,6 +200,167 @@ impl Scheduler {
         self.current().process.clone()
     }
 
+    /// Returns the number of milliseconds elapsed since this scheduler was started
+    pub fn monotonic_time(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+
+    /// Returns the time elapsed since this scheduler was started in native time units,
+    /// which for this runtime are nanoseconds
+    pub fn monotonic_time_native(&self) -> u64 {
+        self.epoch.elapsed().as_nanos() as u64
+    }
+
+    /// Starts a timer which sends a copy of `message` to `destination` in `timeout` milliseconds
+    ///
+    /// Returns the reference which identifies the timer.
+    pub fn start_timer(
+        &self,
+        timeout: u64,
+        kind: TimerKind,
+        destination: ProcessId,
+        message: Term,
+    ) -> Result<ReferenceId, AllocError> {
+        let reference = self.next_reference_id();
+        let deadline = self.monotonic_time().saturating_add(timeout);
+        let timers = unsafe { &mut *self.timers.get() };
+        timers.start(deadline, reference, kind, destination, message)?;
+        Ok(reference)
+    }
+
+    /// Cancels the timer identified by `reference`
+    ///
+    /// Returns the number of milliseconds remaining until the timer would have expired, or
+    /// `None` if the timer has already expired or been cancelled.
+    pub fn cancel_timer(&self, reference: ReferenceId) -> Option<u64> {
+        let timers = unsafe { &mut *self.timers.get() };
+        let deadline = timers.cancel(reference)?;
+        Some(deadline.saturating_sub(self.monotonic_time()))
+    }
+
+    /// Sends the messages of all timers which have expired
+    ///
+    /// Returns the earliest deadline among the timers still active, if any
+    fn timeout_timers(&self) -> Option<u64> {
+        let now = self.monotonic_time();
+        loop {
+            let timers = unsafe { &mut *self.timers.get() };
+            let Some(timer) = timers.pop_expired(now) else { break; };
+            let Some(process) = self.find_process(timer.destination) else { continue; };
+            let delivered = match timer.kind {
+                TimerKind::Message => self.deliver(&process, timer.message),
+                TimerKind::Timeout => {
+                    let layout = signal_layout(3, &[boxed_layout::<Reference>()]);
+                    self.send_signal(&process, layout, |heap| {
+                        let id = timer.reference;
+                        let reference = GcBox::new_in(Reference::Local { id }, heap)?;
+                        let message = [
+                            atoms::Timeout.into(),
+                            Term::Reference(reference).into(),
+                            timer.message.into(),
+                        ];
+                        Tuple::from_slice(&message, heap).map(Term::Tuple)
+                    })
+                }
+            };
+            if delivered.is_err() {
+                self.undeliverable(&process);
+            }
+        }
+        let timers = unsafe { &*self.timers.get() };
+        timers.next_deadline()
+    }
+
+    /// Marks the current process as waiting for a message
+    ///
+    /// The process is parked once it yields to the scheduler, and will not run again until
+    /// either a message is delivered to it, or its wait deadline (if set) has passed.
+    pub fn wait(&self) {
+        unsafe {
+            self.current().process.set_status(ProcessStatus::Waiting);
+        }
+    }
+
+    /// Returns the wait deadline of the current process, if set
+    pub fn wait_deadline(&self) -> Option<u64> {
+        self.current().deadline.get()
+    }
+
+    /// Sets the time, in milliseconds of monotonic time, at which the current process
+    /// will be woken while waiting, or `None` to wait indefinitely
+    pub fn set_wait_deadline(&self, deadline: Option<u64>) {
+        self.current().deadline.set(deadline);
+    }
+
+    /// Returns a new reference id, unique to this scheduler
+    pub fn next_reference_id(&self) -> ReferenceId {
+        ReferenceId::new(0, self.next_reference_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Returns the live process with the given id, if it exists
+    pub fn find_process(&self, pid: ProcessId) -> Option<Arc<Process>> {
+        let processes = unsafe { &*self.processes.get() };
+        processes.get(&pid).cloned()
+    }
+
+    /// Sends a copy of `message` to the process with the given id
+    ///
+    /// Like in BEAM, sending to a process which does not exist (anymore) is not an error,
+    /// the message is simply dropped.
+    pub fn send(&self, pid: ProcessId, message: Term) -> Result<(), AllocError> {
+        match self.find_process(pid) {
+            Some(process) => self.deliver(&process, message),
+            None => Ok(()),
+        }
+    }
+
+    /// Delivers a copy of `message` to the mailbox of `process`, waking it if it is waiting
+    pub fn deliver(&self, process: &Process, message: Term) -> Result<(), AllocError> {
+        process.send(message)?;
+        self.wake(process.pid());
+        Ok(())
+    }
+
+    /// Wakes the process with the given id, if it is waiting, and schedules it to run
+    pub fn wake(&self, pid: ProcessId) {
+        let waiting = unsafe { &mut *self.waiting.get() };
+        if let Some(data) = waiting.remove(&pid) {
+            unsafe {
+                data.process.set_status(ProcessStatus::Runnable);
+            }
+            self.schedule(data);
+        }
+    }
+
+    /// Parks a process which yielded while waiting
+    fn park(&self, data: Arc<SchedulerData>) {
+        let waiting = unsafe { &mut *self.waiting.get() };
+        waiting.insert(data.process.pid(), data);
+    }
+
+    /// Wakes all waiting processes whose deadline has passed
+    ///
+    /// Returns the earliest deadline among the processes still waiting, if any
+    fn wake_expired(&self) -> Option<u64> {
+        let now = self.monotonic_time();
+        let waiting = unsafe { &*self.waiting.get() };
+        let mut next_deadline = None;
+        let mut expired = vec![];
+        for (pid, data) in waiting.iter() {
+            match data.deadline.get() {
+                Some(deadline) if deadline <= now => expired.push(*pid),
+                Some(deadline) => {
+                    next_deadline = Some(next_deadline.map_or(deadline, |d: u64| d.min(deadline)))
+                }
+                None => continue,
+            }
+        }
+        for pid in expired {
+            self.wake(pid);
+        }
+        next_deadline
+    }
+
     /// Swaps the prev and current scheduler data in-place and updates CURRENT_PROCESS
     ///
     /// This is intended for use when yielding to the scheduler
@@ -206,14 +404,252 @@ impl Scheduler {
         // If this process exits, the scheduler terminates
         let mfa: ModuleFunctionArity = "init:start/0".parse().unwrap();
         //let init_fn = function::find_symbol(&mfa).expect("unable to locate init:start/0 function!");
-        let init_fn = crate::init::start as DynamicCallee;
+        let init_fn = crate::init::start as SpawnEntry;
         let process = Arc::new(Process::new(Some(self.parent()), ProcessId::next(), mfa));
+        if self.init.set(process.pid()).is_err() {
+            anyhow::bail!("the init process has already been spawned");
+        }
+
+        let data = Arc::new(SchedulerData::new(process));
+
+        Self::runnable(&data, init_fn, OpaqueTerm::NONE);
+
+        Ok(self.register(data))
+    }
+
+    /// Spawns a new process as a child of the current process
+    ///
+    /// The new process begins executing in `entry`, which is given a copy of `arg` allocated
+    /// on the heap of the new process. If `link` is true, the new process is linked to the
+    /// current process before it is scheduled.
+    pub fn spawn(
+        &self,
+        mfa: ModuleFunctionArity,
+        entry: SpawnEntry,
+        arg: Term,
+        link: bool,
+    ) -> Result<Arc<Process>, AllocError> {
+        let parent = self.current_process();
+        let process = Arc::new(Process::new(Some(parent.pid()), ProcessId::next(), mfa));
+        let arg = arg.clone_to_heap(&*process)?;
+
+        if link {
+            unsafe {
+                parent.links().insert(process.pid());
+                process.links().insert(parent.pid());
+            }
+        }
 
         let data = Arc::new(SchedulerData::new(process));
 
-        Self::runnable(&data, init_fn);
+        Self::runnable(&data, entry, arg.into());
+
+        Ok(self.register(data))
+    }
+
+    /// Makes the given process known to this scheduler, and schedules it for execution
+    fn register(&self, data: Arc<SchedulerData>) -> Arc<Process> {
+        let processes = unsafe { &mut *self.processes.get() };
+        processes.insert(data.process.pid(), data.process.clone());
+        self.schedule(data)
+    }
+
+    /// Removes an exited process from this scheduler
+    fn unregister(&self, process: &Process) {
+        let processes = unsafe { &mut *self.processes.get() };
+        processes.remove(&process.pid());
+    }
+
+    /// Removes an exited process from this scheduler, and notifies the processes linked to,
+    /// or monitoring, it of its exit
+    fn exited(&self, process: &Process) {
+        // The process must be unregistered first, so that signals sent back to it by the
+        // processes it takes down with it are ignored
+        self.unregister(process);
+
+        let pid = process.pid();
+        let timers = unsafe { &mut *self.timers.get() };
+        timers.cancel_all(pid);
+        let reason = match process.status() {
+            ProcessStatus::Errored(exception) => {
+                let exception = unsafe { exception.as_ref() };
+                // Without the memory to build the full exit reason, the raw reason will have to do
+                exit::exit_reason(process, exception).unwrap_or_else(|_| exception.reason())
+            }
+            _ => atoms::Normal.into(),
+        };
+        let (links, monitors, monitored_by) = unsafe {
+            (
+                mem::take(process.links()),
+                mem::take(process.monitors()),
+                mem::take(process.monitored_by()),
+            )
+        };
+
+        for (reference, target) in monitors.iter() {
+            if let Some(target) = self.find_process(*target) {
+                unsafe {
+                    target.monitored_by().remove(reference);
+                }
+            }
+        }
+        for (reference, watcher) in monitored_by.iter() {
+            let Some(watcher) = self.find_process(*watcher) else { continue; };
+            unsafe {
+                watcher.monitors().remove(reference);
+            }
+            if self.send_down(&watcher, *reference, pid, reason).is_err() {
+                self.undeliverable(&watcher);
+            }
+        }
+        for linked in links.iter() {
+            let Some(target) = self.find_process(*linked) else { continue; };
+            unsafe {
+                target.links().remove(&pid);
+            }
+            if self.exit_signal(pid, &target, reason, true).is_err() {
+                self.undeliverable(&target);
+            }
+        }
+    }
+
+    /// Terminates `process` with `system_limit`, as a signal could not be delivered to it
+    ///
+    /// A process which silently misses an exit signal or 'DOWN' message could wait forever,
+    /// so this is the only safe option when no memory is left for the message.
+    fn undeliverable(&self, process: &Process) {
+        self.terminate(process, atoms::SystemLimit.into());
+    }
+
+    /// Sends an exit signal with `reason` from the process `from` to `to`
+    ///
+    /// If `to` is trapping exits, the signal is converted to an `{'EXIT', From, Reason}` message,
+    /// otherwise `to` is terminated, unless the reason is `normal`. A `kill` signal can't be
+    /// trapped, and terminates `to` with reason `killed`, but only when sent explicitly, i.e.
+    /// not when `linked` is true, which indicates the signal is due to a linked process exiting.
+    pub fn exit_signal(
+        &self,
+        from: ProcessId,
+        to: &Process,
+        reason: Term,
+        linked: bool,
+    ) -> Result<(), AllocError> {
+        let kill = !linked && matches!(reason, Term::Atom(a) if a == atoms::Kill);
+        if kill {
+            self.terminate(to, atoms::Killed.into());
+        } else if to.trap_exit() {
+            let layout = signal_layout(3, &[boxed_layout::<Pid>()]);
+            return self.send_signal(to, layout, |heap| {
+                let from = GcBox::new_in(Pid::Local { id: from }, heap)?;
+                let message = [atoms::EXIT.into(), Term::Pid(from).into(), reason.into()];
+                Tuple::from_slice(&message, heap).map(Term::Tuple)
+            });
+        } else if !matches!(reason, Term::Atom(a) if a == atoms::Normal) {
+            self.terminate(to, reason);
+        }
+        Ok(())
+    }
+
+    /// Delivers `{'DOWN', Ref, process, Pid, Reason}` to `watcher`, for the monitor `reference`
+    /// on `pid`, which has exited with `reason`
+    pub fn send_down(
+        &self,
+        watcher: &Process,
+        reference: ReferenceId,
+        pid: ProcessId,
+        reason: Term,
+    ) -> Result<(), AllocError> {
+        let layout = signal_layout(5, &[boxed_layout::<Reference>(), boxed_layout::<Pid>()]);
+        self.send_signal(watcher, layout, |heap| {
+            let reference = GcBox::new_in(Reference::Local { id: reference }, heap)?;
+            let pid = GcBox::new_in(Pid::Local { id: pid }, heap)?;
+            let message = [
+                atoms::DOWN.into(),
+                Term::Reference(reference).into(),
+                atoms::Process.into(),
+                Term::Pid(pid).into(),
+                reason.into(),
+            ];
+            Tuple::from_slice(&message, heap).map(Term::Tuple)
+        })
+    }
+
+    /// Delivers a signal message, constructed by `f`, to `process`
+    ///
+    /// Signals are usually sent on behalf of a process which has exited, or is the scheduler
+    /// itself, so the message is constructed in a temporary heap fragment of the given layout,
+    /// from which it is copied when delivered. Terms the message refers to, rather than
+    /// constructs, e.g. an exit reason, don't need to fit in the fragment.
+    fn send_signal<F>(&self, process: &Process, layout: Layout, f: F) -> Result<(), AllocError>
+    where
+        F: FnOnce(&HeapFragment) -> Result<Term, AllocError>,
+    {
+        let fragment = HeapFragment::new(layout, None)?;
+        let result =
+            f(unsafe { fragment.as_ref() }).and_then(|message| self.deliver(process, message));
+        unsafe {
+            fragment.as_ptr().drop_in_place();
+        }
+        result
+    }
+
+    /// Terminates `process`, as if it had exited with `reason`
+    ///
+    /// If `process` is the current process, it is only marked as exiting, and is cleaned up by
+    /// the scheduler once it yields, see `yield_if_exiting`. Otherwise, it is descheduled and
+    /// its exit is propagated immediately.
+    pub fn terminate(&self, process: &Process, reason: Term) {
+        let exception = ErlangException::new(atoms::Exit, reason, Trace::capture());
+        let exception = unsafe { NonNull::new_unchecked(Box::into_raw(exception)) };
+        process.exit_error(exception);
+
+        let pid = process.pid();
+        if pid == self.current().process.pid() {
+            return;
+        }
+
+        let rq = unsafe { &mut *self.run_queue.get() };
+        let waiting = unsafe { &mut *self.waiting.get() };
+        rq.remove(pid);
+        waiting.remove(&pid);
+        self.errored(process, exception);
+    }
+
+    /// Cleans up after `process`, which exited due to `exception`
+    ///
+    /// Like `exited`, but the exit is also reported, and if `process` is the init process, the
+    /// system will halt with a non-zero status.
+    fn errored(&self, process: &Process, exception: NonNull<ErlangException>) {
+        self.exited(process);
+        exit::log_exit(process, exception);
+        // Only the exit of the init process determines the exit status,
+        // crashes of other processes are reported but otherwise ignored
+        if self.init.get() == Some(&process.pid()) {
+            self.halt_code.store(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Terminates the current process as if it had raised an error with `reason`, and not caught it
+    ///
+    /// This is for intrinsics which have no way to return an exception to their caller. Like
+    /// `yield_if_exiting`, this does not return, so callers must not be holding a reference to
+    /// the current process when calling this.
+    pub fn exit_with_error(&self, reason: Term) -> ! {
+        let exception = ErlangException::new(atoms::Error, reason, Trace::capture());
+        let process = &self.current().process;
+        process.exit_error(unsafe { NonNull::new_unchecked(Box::into_raw(exception)) });
+        self.process_yield();
+        unreachable!("exited process was resumed")
+    }
 
-        Ok(self.schedule(data))
+    /// Yields to the scheduler if the current process was terminated by an exit signal
+    ///
+    /// An exited process is never resumed, so this does not return in that case, and callers
+    /// must not be holding a reference to the current process when calling this.
+    pub fn yield_if_exiting(&self) {
+        if let ProcessStatus::Errored(_) = self.current().process.status() {
+            self.process_yield();
+        }
     }
 
     fn schedule(&self, data: Arc<SchedulerData>) -> Arc<Process> {
@@ -229,7 +665,7 @@ impl Scheduler {
         self.scheduler_yield()
     }
 
-    fn runnable(scheduler: &SchedulerData, init_fn: DynamicCallee) {
+    fn runnable(scheduler: &SchedulerData, init_fn: SpawnEntry, arg: OpaqueTerm) {
         #[derive(Copy, Clone)]
         struct StackPointer(*mut u64);
         impl StackPointer {
@@ -266,12 +702,11 @@ impl Scheduler {
             registers.set_stack_pointer(sp.0 as u64);
             registers.set_frame_pointer(sp.0 as u64);
 
-            // TODO: Set up for closures
-            // If the init function is a closure, place it in
-            // the first callee-save register, which will be moved to
-            // the first argument register (e.g. %rsi) by swap_stack for
-            // the call to the entry point
-            registers.set(0, OpaqueTerm::NONE);
+            // The argument to the init function is placed in the first
+            // callee-save register, which will be moved to the first
+            // argument register (e.g. %rdi) by swap_stack for the call
+            // to the entry point
+            registers.set(0, arg);
 
             // This is used to indicate to swap_stack that this process
             // is being swapped to for the first time, which allows the
@@ -329,6 +764,11 @@ impl Scheduler {
     /// swap in a new process.
     fn scheduler_yield(&self) -> bool {
         loop {
+            // Timers are fired first, as the messages they send may wake waiting processes
+            let next_deadline = match (self.timeout_timers(), self.wake_expired()) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
             let next = {
                 let rq = unsafe { &mut *self.run_queue.get() };
                 rq.next()
@@ -354,17 +794,20 @@ impl Scheduler {
                     // At this point, `prev` is the process which just yielded
                     let prev = self.take_prev();
                     match prev.process.status() {
-                        ProcessStatus::Running => {
+                        ProcessStatus::Running | ProcessStatus::Runnable => {
                             let rq = unsafe { &mut *self.run_queue.get() };
                             rq.reschedule(prev);
                         }
+                        ProcessStatus::Waiting => {
+                            // The process will be rescheduled when woken
+                            self.park(prev);
+                        }
                         ProcessStatus::Exiting => {
-                            self.halt_code.store(0, Ordering::Relaxed);
+                            self.exited(&prev.process);
                             // Process has exited normally, we're done with it
                         }
                         ProcessStatus::Errored(exception) => {
-                            exit::log_exit(&prev.process, exception);
-                            self.halt_code.store(1, Ordering::Relaxed);
+                            self.errored(&prev.process, exception);
                         }
                         other => assert_eq!(other, ProcessStatus::Running),
                     }
@@ -374,10 +817,17 @@ impl Scheduler {
                     // returning to the main scheduler loop to check for signals, etc.
                     break true;
                 }
-                None => {
+                None => match next_deadline {
+                    // Nothing is runnable, but a timer will expire or a waiting process will
+                    // time out, so sleep until then rather than spinning
+                    Some(deadline) => {
+                        let now = self.monotonic_time();
+                        thread::sleep(Duration::from_millis(deadline.saturating_sub(now)));
+                        continue;
+                    }
                     // No more processes to schedule, we're done
-                    break false;
-                }
+                    None => break false,
+                },
             }
         }
     }
@@ -507,3 +957,28 @@ global_asm!(include_str!("swap_stack/swap_stack_linux_x86_64.s"));
 global_asm!(include_str!("swap_stack/swap_stack_macos_x86_64.s"));
 #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
 global_asm!(include_str!("swap_stack/swap_stack_macos_aarch64.s"));
+
+/// Returns the layout of a signal message, i.e. a tuple of `arity` elements, followed by the
+/// boxed terms it constructs, whose layouts are given by `boxed`
+fn signal_layout(arity: usize, boxed: &[Layout]) -> Layout {
+    let (tuple, _) = Layout::new::<usize>()
+        .align_to(16)
+        .unwrap()
+        .extend(Layout::array::<OpaqueTerm>(arity).unwrap())
+        .unwrap();
+    boxed.iter().fold(tuple.pad_to_align(), |layout, boxed| {
+        let (extended, _) = layout.extend(*boxed).unwrap();
+        extended.pad_to_align()
+    })
+}
+
+/// Returns the layout of a `T` allocated in a `GcBox`
+fn boxed_layout<T: 'static>() -> Layout
+where
+    gc::PtrMetadata: From<<T as Pointee>::Metadata> + TryInto<<T as Pointee>::Metadata>,
+{
+    let (layout, _) = Layout::new::<GcBox<T>>()
+        .extend(Layout::new::<T>())
+        .unwrap();
+    layout.pad_to_align()
+}
@@ -2,6 +2,8 @@ use std::collections::VecDeque;
 use std::mem;
 use std::sync::Arc;
 
+use firefly_rt::term::ProcessId;
+
 use super::SchedulerData;
 
 /// Just about the simplest of run queues, but it makes an attempt to ensure
@@ -53,4 +55,14 @@ impl RunQueue {
     pub fn reschedule(&mut self, process: Arc<SchedulerData>) {
         self.visited.push_back(process);
     }
+
+    /// Removes the process with the given id from the queue, if present
+    pub fn remove(&mut self, pid: ProcessId) -> Option<Arc<SchedulerData>> {
+        for queue in [&mut self.scheduled, &mut self.visited] {
+            if let Some(index) = queue.iter().position(|data| data.process.pid() == pid) {
+                return queue.remove(index);
+            }
+        }
+        None
+    }
 }
@@ -0,0 +1,116 @@
+use std::alloc::AllocError;
+use std::collections::BTreeMap;
+use std::ptr::NonNull;
+
+use firefly_alloc::fragment::HeapFragment;
+use firefly_rt::term::{ProcessId, ReferenceId, Term};
+
+/// Determines the shape of the message sent when a timer expires
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimerKind {
+    /// The message is sent as-is, as with `erlang:send_after/3`
+    Message,
+    /// The message is sent as `{timeout, TimerRef, Msg}`, as with `erlang:start_timer/3`
+    Timeout,
+}
+
+/// A timer which sends a message to a process when it expires
+pub(super) struct Timer {
+    pub reference: ReferenceId,
+    pub kind: TimerKind,
+    pub destination: ProcessId,
+    /// The message to send, which is a copy of the term given when the timer was started,
+    /// as the process which started the timer may no longer exist when it expires.
+    pub message: Term,
+    fragment: Option<NonNull<HeapFragment>>,
+}
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if let Some(fragment) = self.fragment.take() {
+            unsafe {
+                fragment.as_ptr().drop_in_place();
+            }
+        }
+    }
+}
+
+/// The active timers of a scheduler, ordered by deadline
+///
+/// Deadlines are in milliseconds of scheduler monotonic time.
+#[derive(Default)]
+pub(super) struct Timers {
+    timers: BTreeMap<(u64, ReferenceId), Timer>,
+    deadlines: BTreeMap<ReferenceId, u64>,
+}
+impl Timers {
+    /// Starts a new timer which sends `message` to `destination` at `deadline`
+    pub fn start(
+        &mut self,
+        deadline: u64,
+        reference: ReferenceId,
+        kind: TimerKind,
+        destination: ProcessId,
+        message: Term,
+    ) -> Result<(), AllocError> {
+        let (message, fragment) = match message {
+            // Immediates don't require any storage, and constant binaries are never freed
+            Term::None
+            | Term::Nil
+            | Term::Bool(_)
+            | Term::Atom(_)
+            | Term::Int(_)
+            | Term::Float(_)
+            | Term::ConstantBinary(_) => (message, None),
+            message => {
+                let (message, fragment) = message.clone_to_fragment()?;
+                (message, Some(fragment))
+            }
+        };
+        let timer = Timer {
+            reference,
+            kind,
+            destination,
+            message,
+            fragment,
+        };
+        self.timers.insert((deadline, reference), timer);
+        self.deadlines.insert(reference, deadline);
+        Ok(())
+    }
+
+    /// Cancels the timer with the given reference, returning its deadline if it was active
+    pub fn cancel(&mut self, reference: ReferenceId) -> Option<u64> {
+        let deadline = self.deadlines.remove(&reference)?;
+        self.timers.remove(&(deadline, reference));
+        Some(deadline)
+    }
+
+    /// Cancels all timers which would send a message to `destination`
+    pub fn cancel_all(&mut self, destination: ProcessId) {
+        let deadlines = &mut self.deadlines;
+        self.timers.retain(|(_, reference), timer| {
+            if timer.destination == destination {
+                deadlines.remove(reference);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    /// Returns the earliest deadline of all active timers, if any
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.timers.keys().next().map(|(deadline, _)| *deadline)
+    }
+
+    /// Removes and returns the earliest timer if it expired at or before `now`
+    pub fn pop_expired(&mut self, now: u64) -> Option<Timer> {
+        let entry = self.timers.first_entry()?;
+        if entry.key().0 > now {
+            return None;
+        }
+        let timer = entry.remove();
+        self.deadlines.remove(&timer.reference);
+        Some(timer)
+    }
+}
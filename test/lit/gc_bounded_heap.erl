@@ -0,0 +1,25 @@
+%% RUN: @firefly compile -o @tempfile @file && @tempfile
+
+%% CHECK: true
+-module(init).
+
+-export([boot/1]).
+
+%% Allocates far more garbage than fits on the heap, which must be collected
+%% for the heap to stay bounded, while the terms still live on the stack must
+%% survive each collection intact
+boot(_Args) ->
+    Live = garbage(7),
+    ok = loop(100000),
+    [7, {7}, [7], 7] = Live,
+    {total_heap_size, Words} = erlang:process_info(self(), total_heap_size),
+    erlang:display(Words < 65536).
+
+loop(0) ->
+    ok;
+loop(N) ->
+    4 = length(garbage(N)),
+    loop(N - 1).
+
+garbage(N) ->
+    [N, {N}, [N], N].
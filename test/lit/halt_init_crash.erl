@@ -0,0 +1,18 @@
+%% RUN: @firefly compile -o @tempfile @file && @tempfile; echo "exit: $?"
+
+%% CHECK: spawned
+%% CHECK: exit: 1
+-module(init).
+
+-export([boot/1]).
+
+%% A process exiting normally before init crashes must not mask the failure of init
+boot(_Args) ->
+    spawn(fun spawned/0),
+    receive
+    after 10 ->
+        erlang:error(boom)
+    end.
+
+spawned() ->
+    erlang:display(spawned).
@@ -0,0 +1,20 @@
+%% RUN: @firefly compile -o @tempfile @file && @tempfile; echo "exit: $?"
+
+%% CHECK: boom
+%% CHECK: boom
+%% CHECK: exit: 1
+-module(init).
+
+-export([boot/1]).
+
+%% When init is taken down by the crash of a process it is linked to, the exit
+%% of init must be reported after that of the crashed process, and the failure
+%% reflected in the exit status
+boot(_Args) ->
+    spawn_link(fun crash/0),
+    receive
+        never -> ok
+    end.
+
+crash() ->
+    erlang:error(boom).
@@ -0,0 +1,18 @@
+%% RUN: @firefly compile -o @tempfile @file && @tempfile; echo "exit: $?"
+
+%% CHECK: done
+%% CHECK: exit: 0
+-module(init).
+
+-export([boot/1]).
+
+%% A process which crashes after init has returned must not change the exit status
+boot(_Args) ->
+    spawn(fun crash/0),
+    erlang:display(done).
+
+crash() ->
+    receive
+    after 10 ->
+        erlang:error(boom)
+    end.
@@ -0,0 +1,40 @@
+%% RUN: @firefly compile -o @tempfile @file && @tempfile
+
+%% CHECK: noproc
+%% CHECK: propagated
+%% CHECK: survived
+-module(init).
+
+-export([boot/1]).
+
+boot(_Args) ->
+    %% Linking to a process which doesn't exist raises when not trapping exits
+    Dead = spawn(fun () -> ok end),
+    receive after 10 -> ok end,
+    try link(Dead) of
+        true -> erlang:display(linked)
+    catch
+        error:noproc -> erlang:display(noproc)
+    end,
+    %% A crash takes down linked processes, with the same exit reason
+    Linked = spawn(fun () ->
+        spawn_link(fun () -> erlang:error(boom) end),
+        receive stop -> ok end
+    end),
+    LinkedRef = monitor(process, Linked),
+    receive
+        {'DOWN', LinkedRef, process, Linked, {boom, _Stack}} ->
+            erlang:display(propagated)
+    end,
+    %% A normal exit does not
+    Normal = spawn(fun () ->
+        spawn_link(fun () -> ok end),
+        receive stop -> ok end
+    end),
+    NormalRef = monitor(process, Normal),
+    receive
+        {'DOWN', NormalRef, process, Normal, _} -> erlang:display(died)
+    after 10 ->
+        erlang:display(survived)
+    end,
+    Normal ! stop.
@@ -0,0 +1,20 @@
+%% RUN: @firefly compile -o @tempfile @file && @tempfile
+
+%% CHECK: [{a, 1}, {b, 2}, {c, 3}]
+%% CHECK: [{1, a}, {2, b}, {3, c}]
+%% CHECK: [{1, 1}, {2, 4}, {3, 9}]
+%% CHECK: {bad_generator, not_a_map}
+-module(init).
+
+-export([boot/1]).
+
+boot(_Args) ->
+    Map = #{c => 3, a => 1, b => 2},
+    erlang:display(maps:to_list(Map)),
+    erlang:display(maps:to_list(#{V => K || K := V <- Map})),
+    erlang:display(maps:to_list(#{X => X * X || X <- [3, 1, 2, four], is_integer(X)})),
+    try
+        [K || K := _ <- not_a_map]
+    catch
+        error:Reason -> erlang:display(Reason)
+    end.
@@ -0,0 +1,39 @@
+%% RUN: @firefly compile -o @tempfile @file && @tempfile
+
+%% CHECK: 3
+%% CHECK: {not_found, missing}
+%% CHECK: {error, {bad_value, c}}
+%% CHECK: {else_clause, other}
+-module(init).
+-feature(maybe_expr, enable).
+
+-export([boot/1]).
+
+boot(_Args) ->
+    erlang:display(sum(a, b)),
+    erlang:display(sum(a, missing)),
+    erlang:display(sum(c, a)),
+    try sum(other, a) of
+        _ -> erlang:display(no_error)
+    catch
+        error:Reason -> erlang:display(Reason)
+    end.
+
+%% Each failed match jumps to the same else clauses
+sum(X, Y) ->
+    maybe
+        {ok, A} ?= lookup(X),
+        {ok, B} ?= lookup(Y),
+        A + B
+    else
+        {error, not_found} ->
+            {not_found, Y};
+        {error, _} = Error ->
+            Error
+    end.
+
+lookup(a) -> {ok, 1};
+lookup(b) -> {ok, 2};
+lookup(c) -> {error, {bad_value, c}};
+lookup(missing) -> {error, not_found};
+lookup(other) -> other.
@@ -0,0 +1,18 @@
+%% RUN: rm -rf @tempfile && mkdir @tempfile && @firefly compile --emit=mlir --output-dir @tempfile @file && @firefly compile -o @tempfile/init @tempfile/mlir_input.mlir && @tempfile/init
+
+%% CHECK: <<"hello from mlir">>
+%% CHECK: 6
+%% CHECK: {ok, [a, b]}
+-module(init).
+
+-export([boot/1]).
+
+%% The MLIR emitted for this module is compiled on its own, so that the output
+%% of the executable reflects that the .mlir input was lowered and linked
+boot(_Args) ->
+    erlang:display(<<"hello from mlir">>),
+    erlang:display(sum([1, 2, 3])),
+    erlang:display({ok, [a, b]}).
+
+sum([]) -> 0;
+sum([H | T]) -> H + sum(T).
@@ -0,0 +1,41 @@
+%% RUN: @firefly compile -o @tempfile @file && @tempfile
+
+%% CHECK: shutdown
+%% CHECK: boom
+%% CHECK: noproc
+%% CHECK: true
+-module(init).
+
+-export([boot/1]).
+
+boot(_Args) ->
+    Stopped = spawn(fun () -> receive stop -> exit(shutdown) end end),
+    StoppedRef = monitor(process, Stopped),
+    Stopped ! stop,
+    receive
+        {'DOWN', StoppedRef, process, Stopped, Reason} ->
+            erlang:display(Reason)
+    end,
+    %% Errors carry the stacktrace in the exit reason
+    Crashed = spawn(fun () -> erlang:error(boom) end),
+    CrashedRef = monitor(process, Crashed),
+    receive
+        {'DOWN', CrashedRef, process, Crashed, {boom, _Stack}} ->
+            erlang:display(boom)
+    end,
+    %% Monitoring a process which no longer exists delivers 'DOWN' immediately
+    DeadRef = monitor(process, Stopped),
+    receive
+        {'DOWN', DeadRef, process, Stopped, noproc} ->
+            erlang:display(noproc)
+    end,
+    %% A flushed monitor leaves no 'DOWN' message behind
+    Waiting = spawn(fun () -> receive stop -> ok end end),
+    WaitingRef = monitor(process, Waiting),
+    true = demonitor(WaitingRef, [flush]),
+    Waiting ! stop,
+    receive
+        {'DOWN', WaitingRef, _, _, _} -> erlang:display(false)
+    after 10 ->
+        erlang:display(true)
+    end.
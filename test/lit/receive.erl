@@ -0,0 +1,38 @@
+%% RUN: @firefly compile -o @tempfile @file && @tempfile
+
+%% CHECK: 42
+%% CHECK: {boxed, [1, 2, 3], <<"bin">>}
+%% CHECK: second
+%% CHECK: first
+%% CHECK: timeout
+-module(init).
+
+-export([boot/1]).
+
+boot(_Args) ->
+    Self = self(),
+    %% Immediates are delivered without copying
+    Self ! 42,
+    receive
+        N when is_integer(N) -> erlang:display(N)
+    end,
+    %% Boxed terms are copied to the receiver
+    Self ! {boxed, [1, 2, 3], <<"bin">>},
+    receive
+        {boxed, _, _} = Boxed -> erlang:display(Boxed)
+    end,
+    %% A selective receive leaves unmatched messages in the mailbox
+    Self ! first,
+    Self ! second,
+    receive
+        second -> erlang:display(second)
+    end,
+    receive
+        Msg -> erlang:display(Msg)
+    end,
+    %% The mailbox is now empty, so this times out immediately
+    receive
+        _ -> erlang:display(unexpected)
+    after
+        0 -> erlang:display(timeout)
+    end.
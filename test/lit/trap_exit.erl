@@ -0,0 +1,31 @@
+%% RUN: @firefly compile -o @tempfile @file && @tempfile
+
+%% CHECK: shutdown
+%% CHECK: nocatch
+%% CHECK: normal
+%% CHECK: noproc
+-module(init).
+
+-export([boot/1]).
+
+boot(_Args) ->
+    false = process_flag(trap_exit, true),
+    Exited = spawn_link(fun () -> exit(shutdown) end),
+    receive
+        {'EXIT', Exited, Reason} -> erlang:display(Reason)
+    end,
+    %% An uncaught throw exits with {{nocatch, Value}, Stack}
+    Thrown = spawn_link(fun () -> throw(oops) end),
+    receive
+        {'EXIT', Thrown, {{nocatch, oops}, _Stack}} -> erlang:display(nocatch)
+    end,
+    %% Normal exits are delivered as messages too when trapping exits
+    Normal = spawn_link(fun () -> ok end),
+    receive
+        {'EXIT', Normal, NormalReason} -> erlang:display(NormalReason)
+    end,
+    %% Linking to a process which doesn't exist results in an 'EXIT' message
+    true = link(Exited),
+    receive
+        {'EXIT', Exited, noproc} -> erlang:display(noproc)
+    end.
@@ -471,6 +471,29 @@ pub fn run(config: &Config) -> anyhow::Result<()> {
         }
     }
 
+    // The profiler runtime used by `-C profile-generate` comes from compiler-rt, if LLVM has it
+    let arch = llvm_target.split('-').next().unwrap();
+    let profiler_runtime = if config.is_darwin() {
+        "libclang_rt.profile_osx.a".to_owned()
+    } else {
+        format!("libclang_rt.profile-{}.a", arch)
+    };
+    let walker = WalkDir::new(config.llvm_prefix().join("lib/clang")).into_iter();
+    for entry in walker.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let filename = entry.file_name().to_str().unwrap_or_default();
+        // Newer layouts place runtimes in a per-target directory without the arch suffix
+        let in_target_dir = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|p| p == llvm_target)
+            .unwrap_or(false);
+        if filename == profiler_runtime || (in_target_dir && filename == "libclang_rt.profile.a") {
+            fs::copy(path, install_target_lib_dir.join("libfirefly_rt.profile.a")).unwrap();
+            break;
+        }
+    }
+
     if config.link_dynamic() {
         match env::var_os("LLVM_LINK_LLVM_DYLIB") {
             Some(val) if val == "ON" => {
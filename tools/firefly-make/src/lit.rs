@@ -16,9 +16,9 @@ pub struct Config {
     #[clap(value_parser)]
     tests: PathBuf,
 
-    /// The extension of the files that should be parsed for tests
-    #[clap(long, default_value = "erl", value_parser = clap::builder::NonEmptyStringValueParser::new())]
-    file_type: String,
+    /// The extensions of the files that should be parsed for tests
+    #[clap(long, default_value = "erl,ssa", value_delimiter = ',', value_parser = clap::builder::NonEmptyStringValueParser::new())]
+    file_type: Vec<String>,
 }
 
 pub fn run(config: &Config) -> anyhow::Result<()> {
@@ -39,7 +39,9 @@ pub fn run(config: &Config) -> anyhow::Result<()> {
 
     lit::run::tests(EventHandler::default(), |runner| {
         runner.add_search_path(lit_dir);
-        runner.add_extension(config.file_type.as_str());
+        for file_type in config.file_type.iter() {
+            runner.add_extension(file_type.as_str());
+        }
 
         runner
             .constants